@@ -0,0 +1,379 @@
+use std::sync::Arc;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use winit::window::CursorIcon;
+use crate::ai::{self, Hint};
+use crate::engine::{AuctionLot, Engine, Phase, TurnAction, JAIL_FINE};
+use crate::events::{CommandError, GameCommand, GameEvent, Trade};
+use crate::hints;
+use crate::palette;
+use crate::render::{ColorSource, Model};
+use crate::screens::draft::DraftPicker;
+use crate::ui::{self, Button, ClickKind, Color, ColorBox, Coloring, Component, HoverMode, TextBox, TextOverflow, TextSection, TextStyle};
+use crate::utils::{DARK_GRAY_UI, LIGHT_GRAY_UI};
+use crate::{Game, i18n};
+
+// the buttons are centered at the bottom of the screen, the prompt sits right above them
+const BAR_Y: f32 = 0.02;
+const BUTTON_WIDTH: f32 = 0.12;
+const BUTTON_HEIGHT: f32 = 0.06;
+const BUTTON_GAP: f32 = 0.01;
+const PROMPT_WIDTH: f32 = 0.46;
+const PROMPT_HEIGHT: f32 = 0.05;
+// the hint is shown right above the prompt of a decision
+const HINT_HEIGHT: f32 = 0.04;
+const HINT_SCALE: f32 = 18.0;
+const STRIPE_WIDTH: f32 = 0.006;
+const TEXT_SCALE: f32 = 22.0;
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+const BLACK: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+const ERROR_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.45,
+    b: 0.4,
+    a: 1.0,
+};
+
+#[derive(Clone, PartialEq)]
+enum Entry {
+    Submit(GameCommand),
+    // picking a property takes a whole grid of them, so the draft picker is opened instead
+    OpenDraft,
+}
+
+// everything the bar shows, it only gets rebuilt if any of this changes
+#[derive(Clone, PartialEq, Default)]
+struct BarState {
+    // the local player the buttons act for
+    player: Option<usize>,
+    prompt: String,
+    // the suggestion for the decision the prompt asks for, only if hints are enabled
+    hint: Option<String>,
+    entries: Vec<(String, Entry)>,
+}
+
+/// The buttons for everything the local player who has to act next may do, see `Engine::legal_actions`.
+/// Decisions like buying a property, bidding and answering trades come with a prompt and, if enabled, a hint.
+/// Paying taxes has its own dialog and properties are managed from their cards, so there are no buttons for these.
+pub struct ActionBar {
+    state: BarState,
+    // the length of the event log when the bar looked for rejected commands the last time,
+    // commands which were rejected before the bar was shown are left alone
+    seen: Option<usize>,
+    // why the last command of a local player was rejected, it's shown instead of the prompt until the bar changes
+    error: Option<CommandError>,
+    prompt: Option<TextBox<'static>>,
+    hint: Option<TextBox<'static>>,
+    buttons: Vec<Button<'static, ()>>,
+}
+
+impl ActionBar {
+
+    pub fn new() -> Self {
+        Self {
+            state: BarState::default(),
+            seen: None,
+            error: None,
+            prompt: None,
+            hint: None,
+            buttons: vec![],
+        }
+    }
+
+    // a trade which is offered to a local player is answered first, even if another local player is about to act
+    fn acting_player(game: &Arc<Game>, engine: &Engine) -> Option<usize> {
+        engine.pending_trade.as_ref().map(|trade| trade.to).filter(|player| game.is_local(*player))
+            .or_else(|| Some(engine.next_actor()).filter(|player| game.is_local(*player)))
+            .filter(|_| !matches!(engine.phase, Phase::Lobby | Phase::Finished))
+    }
+
+    fn state(game: &Arc<Game>, engine: &Engine, hints: bool) -> BarState {
+        let player = match Self::acting_player(game, engine) {
+            Some(player) => player,
+            None => return BarState::default(),
+        };
+        let currency = &engine.rules.currency;
+        let property_name = |property: usize| engine.properties[property].frame.name.clone();
+        let mut entries = vec![];
+        for action in engine.legal_actions(player) {
+            if action == TurnAction::Draft {
+                entries.push((i18n::tr("hud.draft"), Entry::OpenDraft));
+                continue;
+            }
+            if action == TurnAction::PayTax {
+                continue;
+            }
+            for command in engine.action_commands(player, action) {
+                let label = match &command {
+                    GameCommand::RollDice { .. } => i18n::tr("hud.roll"),
+                    GameCommand::UseJailFreeCard { .. } => i18n::tr("hud.use_jail_free_card"),
+                    GameCommand::PayBail { .. } => i18n::tr_with("hud.pay_bail", &[("amount", &currency.format(JAIL_FINE))]),
+                    GameCommand::BuyProperty { .. } => i18n::tr("hud.buy"),
+                    GameCommand::DeclineProperty { .. } => i18n::tr("hud.decline"),
+                    GameCommand::Bid { amount, .. } => i18n::tr_with("hud.bid", &[("amount", &currency.format(*amount))]),
+                    GameCommand::PassBid { .. } => i18n::tr("hud.pass"),
+                    GameCommand::AcceptTrade { .. } => i18n::tr("hud.accept_trade"),
+                    GameCommand::DeclineTrade { .. } => i18n::tr("hud.decline_trade"),
+                    GameCommand::EndTurn { .. } => i18n::tr("hud.end_turn"),
+                    _ => continue,
+                };
+                entries.push((label, Entry::Submit(command)));
+            }
+        }
+        let name = engine.players[player].display_name();
+        let prompt = match (&engine.pending_trade, engine.phase) {
+            (Some(trade), _) if trade.to == player => i18n::tr_with("hud.trade_prompt", &[
+                ("player", &engine.players[trade.from].display_name()),
+                ("offered", &trade_side(engine, trade, true)),
+                ("requested", &trade_side(engine, trade, false)),
+            ]),
+            (_, Phase::Buying { property }) => i18n::tr_with("hud.buy_prompt", &[
+                ("player", &name),
+                ("property", &property_name(property)),
+                ("amount", &currency.format(engine.properties[property].frame.buy_price)),
+            ]),
+            (_, Phase::Auction) => {
+                let auction = engine.auction.as_ref().unwrap();
+                let lot = match auction.lot {
+                    AuctionLot::Building { hotel: false } => i18n::tr("hud.lot_house"),
+                    AuctionLot::Building { hotel: true } => i18n::tr("hud.lot_hotel"),
+                    AuctionLot::Property { property } => property_name(property),
+                };
+                let bid = match auction.highest {
+                    Some((bidder, _, amount)) => i18n::tr_with("hud.highest_bid", &[
+                        ("amount", &currency.format(amount)),
+                        ("player", &engine.players[bidder].display_name()),
+                    ]),
+                    None => i18n::tr("hud.no_bids"),
+                };
+                i18n::tr_with("hud.auction_prompt", &[("player", &name), ("lot", &lot), ("bid", &bid)])
+            }
+            _ => i18n::tr_with("hud.turn", &[("player", &name)]),
+        };
+        // the tax dialog shows its hint itself
+        let hint = ai::hint(engine, player).filter(|hint| hints && !matches!(hint, Hint::Tax { .. }))
+            .map(|hint| hints::hint_text(&hint, engine));
+        BarState {
+            player: Some(player),
+            prompt,
+            hint,
+            entries,
+        }
+    }
+
+    fn buttons_width(&self) -> f32 {
+        let count = self.buttons.len() as f32;
+        (count * (BUTTON_WIDTH + BUTTON_GAP) - BUTTON_GAP).max(0.0)
+    }
+
+    fn prompt_pos() -> (f32, f32) {
+        ((1.0 - PROMPT_WIDTH) / 2.0, BAR_Y + BUTTON_HEIGHT + BUTTON_GAP)
+    }
+
+    fn hint_pos() -> (f32, f32) {
+        let (x, y) = Self::prompt_pos();
+        (x, y + PROMPT_HEIGHT)
+    }
+
+    fn build_buttons(&mut self) {
+        self.prompt = None;
+        self.hint = None;
+        self.buttons.clear();
+        if self.state.player.is_none() {
+            return;
+        }
+        let (prompt, color) = match self.error {
+            Some(error) => (i18n::tr(error.message_key()), ERROR_COLOR),
+            None => (self.state.prompt.clone(), WHITE),
+        };
+        self.prompt = Some(TextBox::new(
+            Self::prompt_pos(),
+            PROMPT_WIDTH,
+            PROMPT_HEIGHT,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(prompt, TextStyle::new(TEXT_SCALE).with_color(color)),
+        ).with_overflow(TextOverflow::Ellipsis));
+        self.hint = self.state.hint.as_ref().map(|hint| TextBox::new(
+            Self::hint_pos(),
+            PROMPT_WIDTH,
+            HINT_HEIGHT,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(hint.clone(), TextStyle::new(HINT_SCALE).with_color(LIGHT_GRAY_UI)),
+        ).with_overflow(TextOverflow::Ellipsis));
+        let count = self.state.entries.len() as f32;
+        let start = (1.0 - (count * (BUTTON_WIDTH + BUTTON_GAP) - BUTTON_GAP)) / 2.0;
+        for (idx, (label, entry)) in self.state.entries.iter().enumerate() {
+            let entry = entry.clone();
+            self.buttons.push(Button::new(
+                TextBox::new(
+                    (start + idx as f32 * (BUTTON_WIDTH + BUTTON_GAP), BAR_Y),
+                    BUTTON_WIDTH,
+                    BUTTON_HEIGHT,
+                    Coloring::Color([LIGHT_GRAY_UI; 4]),
+                    TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                        .with_run(label.clone(), TextStyle::new(TEXT_SCALE).with_color(BLACK)),
+                ),
+                Arc::new(Box::new(move |_button: &mut Button<'_, ()>, game| match &entry {
+                    // the bar gets rebuilt once the command changed what the player may do
+                    Entry::Submit(command) => game.submit(command.clone()),
+                    Entry::OpenDraft => game.screen_sys.push_screen(Box::new(DraftPicker::new())),
+                })),
+                None,
+            ));
+        }
+    }
+
+}
+
+// what one side of the trade gives, e.g. "Park Place, $100"
+fn trade_side(engine: &Engine, trade: &Trade, offered: bool) -> String {
+    let (properties, money, jail_free_cards, immunities) = if offered {
+        (&trade.offered_properties, trade.offered_money, trade.offered_jail_free_cards, &trade.offered_immunities)
+    } else {
+        (&trade.requested_properties, trade.requested_money, trade.requested_jail_free_cards, &trade.requested_immunities)
+    };
+    let mut items = properties.iter().map(|property| engine.properties[*property].frame.name.clone()).collect::<Vec<_>>();
+    if money > 0 {
+        items.push(engine.rules.currency.format(money));
+    }
+    if jail_free_cards > 0 {
+        items.push(i18n::tr_with("hud.trade_cards", &[("count", &jail_free_cards.to_string())]));
+    }
+    items.extend(immunities.iter().map(|immunity| i18n::tr_with("hud.trade_immunity", &[
+        ("property", &engine.properties[immunity.property].frame.name),
+        ("count", &immunity.landings.to_string()),
+    ])));
+    if items.is_empty() {
+        return i18n::tr("hud.trade_nothing");
+    }
+    items.join(", ")
+}
+
+impl Component for ActionBar {
+    fn build_model(&self) -> Model {
+        let mut vertices = vec![];
+        if let Some(hint) = &self.hint {
+            vertices.extend(hint.build_model().vertices);
+        }
+        if let (Some(prompt), Some(player)) = (&self.prompt, self.state.player) {
+            vertices.extend(prompt.build_model().vertices);
+            // the acting player's color, like in their row of the HUD
+            vertices.extend(ColorBox {
+                pos: Self::prompt_pos(),
+                width: STRIPE_WIDTH,
+                height: PROMPT_HEIGHT,
+                coloring: Coloring::Color([palette::player_color(player); 4]),
+            }.build_model().vertices);
+        }
+        for button in self.buttons.iter() {
+            vertices.extend(button.build_model().vertices);
+        }
+        Model {
+            vertices,
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn do_render(&self, game: &Arc<Game>) {
+        if let Some(hint) = &self.hint {
+            hint.do_render(game);
+        }
+        if let Some(prompt) = &self.prompt {
+            prompt.do_render(game);
+        }
+        for button in self.buttons.iter() {
+            button.do_render(game);
+        }
+    }
+
+    // only the buttons take clicks, the prompt above them doesn't
+    fn pos(&self) -> (f32, f32) {
+        ((1.0 - self.buttons_width()) / 2.0, BAR_Y)
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (self.buttons_width(), if self.buttons.is_empty() { 0.0 } else { BUTTON_HEIGHT })
+    }
+
+    fn on_click(&mut self, game: &Arc<Game>, click_kind: ClickKind, pos: (f32, f32)) {
+        for button in self.buttons.iter_mut() {
+            if ui::is_inbounds(button.dims(), button.pos(), pos) {
+                button.on_click(game, click_kind, pos);
+            } else {
+                button.on_click_outside(game);
+            }
+        }
+    }
+
+    fn on_click_outside(&mut self, game: &Arc<Game>) {
+        for button in self.buttons.iter_mut() {
+            button.on_click_outside(game);
+        }
+    }
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, game: &Arc<Game>, mode: HoverMode, pos: (f32, f32)) {
+        for button in self.buttons.iter_mut() {
+            let hovered = matches!(mode, HoverMode::Enter) && ui::is_inbounds(button.dims(), button.pos(), pos);
+            button.on_hover(game, if hovered { HoverMode::Enter } else { HoverMode::Exit }, pos);
+        }
+    }
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        if self.buttons.iter().any(|button| button.is_hovered() == Some(HoverMode::Enter)) {
+            Some(HoverMode::Enter)
+        } else {
+            Some(HoverMode::Exit)
+        }
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        CursorIcon::Hand
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        let hints = game.settings.lock().unwrap().hints;
+        let state = Self::state(game, &game.engine.lock().unwrap(), hints);
+        let mut changed = false;
+        if state != self.state {
+            self.state = state;
+            self.error = None;
+            changed = true;
+        }
+        {
+            let event_log = game.event_log.lock().unwrap();
+            for event in event_log[self.seen.unwrap_or(event_log.len())..].iter() {
+                if let GameEvent::CommandRejected { command, error } = event {
+                    // the property card shows why its own commands were rejected
+                    let managing = matches!(command, GameCommand::BuildHouse { .. } | GameCommand::SellHouse { .. }
+                        | GameCommand::Mortgage { .. } | GameCommand::Unmortgage { .. });
+                    if !managing && command.player().map_or(true, |player| game.is_local(player)) {
+                        self.error = Some(*error);
+                        changed = true;
+                    }
+                }
+            }
+            self.seen = Some(event_log.len());
+        }
+        if changed {
+            self.build_buttons();
+        }
+        changed
+    }
+}
@@ -1,62 +1,82 @@
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
+use crate::board::{Tile, TileKind};
+use crate::property::PropertyType;
+use crate::rules::CurrencyRules;
+use crate::utils::{read_config, write_config};
 
-const ACTION_CARDS_PATH: &str = "./config/action_cards.json";
+const ACTION_CARDS_FILE: &str = "action_cards.json";
 
 pub fn load_cards() -> Vec<ActionCard> {
-    if Path::new(ACTION_CARDS_PATH).exists() {
-        let mut file = File::open(ACTION_CARDS_PATH).unwrap();
-        let mut buf = String::new();
-        file.read_to_string(&mut buf).unwrap();
+    if let Some(buf) = read_config(ACTION_CARDS_FILE) {
         serde_json::from_str(&*buf).unwrap()
     } else {
-        let mut file = File::create(ACTION_CARDS_PATH).unwrap();
-        let cards = vec![ActionCard {
-            text: "Go to jail".to_string(),
-            action: Action::GoToJail,
-        }, ActionCard {
-            text: "Pay 2$".to_string(),
-            action: Action::DirectCurrency { amount: -2, },
-        }, ActionCard {
-            text: "Get 2$".to_string(),
-            action: Action::DirectCurrency { amount: 2, },
-        }, ActionCard {
-            text: "Pay everybody 2".to_string(),
-            action: Action::DistributeCurrency { amount: -2 },
-        }, ActionCard {
-            text: "Everybody pays you 2".to_string(),
-            action: Action::DistributeCurrency { amount: 2 },
-        }, ActionCard {
-            text: "Wait 1 round".to_string(),
-            action: Action::Wait { rounds: 1 },
-        }, ActionCard {
-            text: "Go 2 tiles back".to_string(),
-            action: Action::MoveRelative { amount: -2 },
-        }, ActionCard {
-            text: "Go 2 tiles forward".to_string(),
-            action: Action::MoveRelative { amount: 2 },
-        }, ActionCard {
-            text: "Go to the first tile".to_string(),
-            action: Action::MoveAbsolute { tile: 0 },
-        }, ActionCard {
-            text: "Jail free card".to_string(),
-            action: Action::JailFree,
-        },];
-        file.write_all(serde_json::to_string(&cards).unwrap().as_ref()).unwrap();
+        let cards = default_cards();
+        write_config(ACTION_CARDS_FILE, &serde_json::to_string(&cards).unwrap());
         cards
     }
 }
 
-#[derive(Serialize, Deserialize)]
+// the cards which are used if there is no config file yet
+pub fn default_cards() -> Vec<ActionCard> {
+    vec![ActionCard {
+        text: "Go to jail".to_string(),
+        action: Action::GoToJail,
+    }, ActionCard {
+        text: "Pay {amount}".to_string(),
+        action: Action::DirectCurrency { amount: -2, },
+    }, ActionCard {
+        text: "Get {amount}".to_string(),
+        action: Action::DirectCurrency { amount: 2, },
+    }, ActionCard {
+        text: "Pay everybody {amount}".to_string(),
+        action: Action::DistributeCurrency { amount: -2 },
+    }, ActionCard {
+        text: "Everybody pays you {amount}".to_string(),
+        action: Action::DistributeCurrency { amount: 2 },
+    }, ActionCard {
+        text: "Wait 1 round".to_string(),
+        action: Action::Wait { rounds: 1 },
+    }, ActionCard {
+        text: "Go 2 tiles back".to_string(),
+        action: Action::MoveRelative { amount: -2 },
+    }, ActionCard {
+        text: "Go 2 tiles forward".to_string(),
+        action: Action::MoveRelative { amount: 2 },
+    }, ActionCard {
+        text: "Go to the first tile".to_string(),
+        action: Action::MoveAbsolute { tile: 0 },
+    }, ActionCard {
+        text: "Jail free card".to_string(),
+        action: Action::JailFree,
+    },]
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ActionCard {
+    // "{amount}" is replaced with the formatted amount of money the card is about
     pub text: String,
     pub action: Action,
 }
 
-#[derive(Serialize, Deserialize)]
+impl ActionCard {
+
+    pub fn display_text(&self, currency: &CurrencyRules) -> String {
+        match self.action {
+            Action::DirectCurrency { amount } | Action::DistributeCurrency { amount } => {
+                self.text.replace("{amount}", &currency.format(amount.unsigned_abs()))
+            }
+            Action::PayPerHouse { house, hotel } => self.text
+                .replace("{house}", &currency.format(house.unsigned_abs()))
+                .replace("{hotel}", &currency.format(hotel.unsigned_abs())),
+            Action::CollectFromBankPerProperty { amount } => self.text.replace("{amount}", &currency.format(amount)),
+            _ => self.text.clone(),
+        }
+    }
+
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Action {
     // currency is exchanged between the player and the bank
     DirectCurrency {
@@ -77,8 +97,53 @@ pub enum Action {
     },
     GoToJail,
     JailFree,
+    // the player moves forward to the next tile of the kind and passes the start tile like after a roll
+    MoveToNearest {
+        kind: NearestKind,
+    },
+    // the player pays the amounts for every house and hotel they own, negative amounts are paid to them instead
+    PayPerHouse {
+        house: isize,
+        hotel: isize,
+    },
+    // the player receives the amount for every property they own, mortgaged ones included
+    CollectFromBankPerProperty {
+        amount: usize,
+    },
+    // the player advances to the start tile if it's at most `within` tiles ahead of them, otherwise nothing happens
+    AdvanceToStartIf {
+        within: usize,
+    },
+    // a rhai script which decides what happens, see card_script.rs for the functions it can call
+    Script {
+        source: String,
+    },
 }
 
+// the tiles `Action::MoveToNearest` can move a player to
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum NearestKind {
+    Station,
+    Special,
+    Tile {
+        kind: TileKind,
+    },
+}
+
+impl NearestKind {
+
+    pub fn matches(&self, tile: &Tile) -> bool {
+        match (self, tile) {
+            (NearestKind::Station, Tile::Property { property }) => property.ty == PropertyType::Station,
+            (NearestKind::Special, Tile::Property { property }) => property.ty == PropertyType::Special,
+            (NearestKind::Tile { kind }, tile) => tile.kind() == *kind,
+            _ => false,
+        }
+    }
+
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CardStack(Vec<usize>);
 
 impl CardStack {
@@ -88,8 +153,13 @@ impl CardStack {
         Self(cards)
     }
 
-    pub fn draw(&self) -> usize {
-        self.0[rand::thread_rng().gen_range(0..(self.0.len()))]
+    #[inline]
+    pub fn cards(&self) -> &[usize] {
+        &self.0
+    }
+
+    pub fn draw(&self, rng: &mut impl Rng) -> usize {
+        self.0[rng.gen_range(0..(self.0.len()))]
     }
 
 }
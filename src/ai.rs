@@ -0,0 +1,267 @@
+use crate::engine::{AuctionLot, Engine, Phase, JAIL_FINE};
+use crate::events::{GameCommand, Immunity, Trade};
+
+// makes the decisions for a computer controlled player
+pub trait Strategy {
+
+    fn name(&self) -> &'static str;
+
+    // whether the player wants to buy the unowned property they landed on
+    fn buy_property(&self, engine: &Engine, player: usize, property: usize) -> bool;
+
+    // asked for every property the player could build a house on, until it returns false
+    fn build_house(&self, engine: &Engine, player: usize, property: usize) -> bool;
+
+}
+
+// buys and builds everything it can afford
+pub struct Greedy;
+
+impl Strategy for Greedy {
+
+    fn name(&self) -> &'static str {
+        "greedy"
+    }
+
+    fn buy_property(&self, _engine: &Engine, _player: usize, _property: usize) -> bool {
+        true
+    }
+
+    fn build_house(&self, _engine: &Engine, _player: usize, _property: usize) -> bool {
+        true
+    }
+
+}
+
+// only spends money as long as it keeps a reserve to pay rent with
+pub struct Cautious {
+    pub reserve: usize,
+}
+
+impl Strategy for Cautious {
+
+    fn name(&self) -> &'static str {
+        "cautious"
+    }
+
+    fn buy_property(&self, engine: &Engine, player: usize, property: usize) -> bool {
+        engine.players[player].currency >= engine.properties[property].frame.buy_price + self.reserve
+    }
+
+    fn build_house(&self, engine: &Engine, player: usize, property: usize) -> bool {
+        engine.players[player].currency >= engine.house_price(property) + self.reserve
+    }
+
+}
+
+// never spends any money, serves as a baseline for the other strategies
+pub struct Passive;
+
+impl Strategy for Passive {
+
+    fn name(&self) -> &'static str {
+        "passive"
+    }
+
+    fn buy_property(&self, _engine: &Engine, _player: usize, _property: usize) -> bool {
+        false
+    }
+
+    fn build_house(&self, _engine: &Engine, _player: usize, _property: usize) -> bool {
+        false
+    }
+
+}
+
+pub fn strategies() -> Vec<Box<dyn Strategy>> {
+    vec![Box::new(Greedy), Box::new(Cautious { reserve: 150 }), Box::new(Passive)]
+}
+
+// the command the strategy wants to submit next for the player, see `Engine::next_actor`
+pub fn next_command(strategy: &dyn Strategy, engine: &Engine, player: usize) -> GameCommand {
+    match engine.phase {
+        // the most expensive property left is the best pick
+        Phase::Drafting => GameCommand::DraftProperty {
+            player,
+            property: (0..engine.properties.len()).filter(|property| engine.properties[*property].owner.is_none())
+                .max_by_key(|property| engine.properties[*property].frame.buy_price)
+                .unwrap(),
+        },
+        // a jail free card is worth less than the turns spent in jail
+        Phase::Rolling if engine.players[player].is_jailed() && engine.players[player].jail_free_cards > 0 => GameCommand::UseJailFreeCard {
+            player,
+        },
+        Phase::Rolling => GameCommand::RollDice {
+            player,
+        },
+        Phase::Buying { property } => {
+            if engine.players[player].currency >= engine.properties[property].frame.buy_price
+                && strategy.buy_property(engine, player, property) {
+                GameCommand::BuyProperty {
+                    player,
+                }
+            } else {
+                GameCommand::DeclineProperty {
+                    player,
+                }
+            }
+        }
+        Phase::Acting => {
+            let property = engine.buildable_properties(player).into_iter()
+                .find(|property| strategy.build_house(engine, player, *property));
+            match property {
+                Some(property) => GameCommand::BuildHouse {
+                    player,
+                    property,
+                },
+                None => GameCommand::EndTurn {
+                    player,
+                },
+            }
+        }
+        // computer players simply pay whatever is cheaper
+        Phase::ChoosingTax { flat, percentage } => GameCommand::PayTax {
+            player,
+            percentage: percentage < flat,
+        },
+        Phase::Auction => {
+            let auction = engine.auction.as_ref().unwrap();
+            let bid = bid_range(strategy, engine, player).map(|(property, min, max)| {
+                (property, auction.highest.map_or(min, |(_, _, highest)| highest + 1), max)
+            });
+            match bid {
+                Some((property, amount, max)) if amount <= max
+                    && engine.players[player].currency >= amount => GameCommand::Bid {
+                    player,
+                    property,
+                    amount,
+                },
+                _ => GameCommand::PassBid {
+                    player,
+                },
+            }
+        }
+        Phase::Lobby | Phase::Finished => unreachable!("computer players can only act during a game"),
+    }
+}
+
+// the property the player would bid on in the current auction, along with the lowest and highest bid
+fn bid_range(strategy: &dyn Strategy, engine: &Engine, player: usize) -> Option<(usize, usize, usize)> {
+    match engine.auction.as_ref()?.lot {
+        AuctionLot::Building { hotel } => {
+            let property = engine.buildable_properties(player).into_iter()
+                .find(|property| engine.needs_hotel(*property) == hotel && strategy.build_house(engine, player, *property));
+            // computer players never pay more than twice the building's price
+            property.map(|property| (property, engine.house_price(property), engine.house_price(property) * 2))
+        }
+        // nor more than the property's price
+        AuctionLot::Property { property } => Some((property, 1, engine.properties[property].frame.buy_price))
+            .filter(|_| strategy.buy_property(engine, player, property)),
+    }
+}
+
+// the most likely sum of two dice, rents which depend on the roll are estimated with it
+const AVERAGE_ROLL: usize = 7;
+
+// how much the player the trade is offered to gains by accepting it, negative if they lose
+fn trade_value(engine: &Engine, trade: &Trade) -> isize {
+    // an immunity is worth the rent its holder doesn't have to pay
+    let value = |properties: &[usize], money: usize, jail_free_cards: usize, immunities: &[Immunity]| {
+        properties.iter().map(|property| engine.property_value(*property)).sum::<usize>()
+            + money + jail_free_cards * JAIL_FINE
+            + immunities.iter().map(|immunity| immunity.landings * engine.rent(immunity.property, AVERAGE_ROLL)).sum::<usize>()
+    };
+    let mut gained = value(&trade.offered_properties, trade.offered_money, trade.offered_jail_free_cards, &trade.offered_immunities) as isize;
+    let lost = value(&trade.requested_properties, trade.requested_money, trade.requested_jail_free_cards, &trade.requested_immunities) as isize;
+    // completing a group doubles the rent of its properties, so the whole group gains in value
+    for property in trade.offered_properties.iter().copied() {
+        let group = engine.group(property);
+        let completes = group.len() > 1 && group.iter().all(|other| trade.offered_properties.contains(other)
+            || (engine.properties[*other].owner == Some(trade.to) && !trade.requested_properties.contains(other)));
+        if completes {
+            gained += engine.properties[property].frame.buy_price as isize;
+        }
+    }
+    gained - lost
+}
+
+// the strategy whose decisions are suggested to human players
+const ADVISOR: Cautious = Cautious {
+    reserve: 150,
+};
+
+/// A non-binding suggestion for the decision a human player has to take next,
+/// it's what a computer player would decide in their place.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Hint {
+    Buy {
+        property: usize,
+        buy: bool,
+    },
+    // no bid is suggested if `max` is None
+    Bid {
+        property: usize,
+        max: Option<usize>,
+    },
+    Tax {
+        percentage: bool,
+    },
+    Trade {
+        accept: bool,
+    },
+}
+
+// the command a computer player submits next for a player they took over, None if that player doesn't have to decide anything
+pub fn substitute_command(engine: &Engine, player: usize) -> Option<GameCommand> {
+    if let Some(trade) = engine.pending_trade.as_ref() {
+        // the others wait for the answer to the trade
+        return (trade.to == player).then(|| if trade_value(engine, trade) > 0 {
+            GameCommand::AcceptTrade {
+                player,
+            }
+        } else {
+            GameCommand::DeclineTrade {
+                player,
+            }
+        });
+    }
+    match engine.phase {
+        Phase::Lobby | Phase::Finished => None,
+        _ => (engine.next_actor() == player).then(|| next_command(&ADVISOR, engine, player)),
+    }
+}
+
+pub fn hint(engine: &Engine, player: usize) -> Option<Hint> {
+    if let Some(trade) = engine.pending_trade.as_ref().filter(|trade| trade.to == player) {
+        return Some(Hint::Trade {
+            accept: trade_value(engine, trade) > 0,
+        });
+    }
+    if engine.next_actor() != player {
+        return None;
+    }
+    match engine.phase {
+        Phase::Buying { property } => Some(Hint::Buy {
+            property,
+            buy: matches!(next_command(&ADVISOR, engine, player), GameCommand::BuyProperty { .. }),
+        }),
+        Phase::Auction => {
+            let range = bid_range(&ADVISOR, engine, player);
+            let property = match engine.auction.as_ref()?.lot {
+                AuctionLot::Property { property } => property,
+                AuctionLot::Building { .. } => range?.0,
+            };
+            Some(Hint::Bid {
+                property,
+                max: range.map(|(_, _, max)| max.min(engine.players[player].currency)),
+            })
+        }
+        Phase::ChoosingTax { .. } => match next_command(&ADVISOR, engine, player) {
+            GameCommand::PayTax { percentage, .. } => Some(Hint::Tax {
+                percentage,
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use anyhow::Result;
+use cgmath::{Quaternion, Vector3, Zero};
+use gltf::animation::util::ReadOutputs;
+use instant::Instant;
+use crate::render::Instance;
+
+// a keyframed animation which moves and rotates a whole model, relative to where the model is
+pub struct AnimationClip {
+    pub name: String,
+    pub duration: Duration,
+    // keyframes sorted by their time in seconds
+    translations: Vec<(f32, Vector3<f32>)>,
+    rotations: Vec<(f32, Quaternion<f32>)>,
+}
+
+impl AnimationClip {
+
+    // the transform of the animated model at the given point in time, relative to its rest pose
+    pub fn sample(&self, time: f32) -> Instance {
+        Instance {
+            position: sample_keyframes(&self.translations, time, |from, to, t| from + (to - from) * t)
+                .unwrap_or_else(Vector3::zero),
+            rotation: sample_keyframes(&self.rotations, time, |from, to, t| from.slerp(to, t))
+                .unwrap_or_else(identity_rotation),
+        }
+    }
+
+}
+
+fn identity_rotation() -> Quaternion<f32> {
+    Quaternion::new(1.0, 0.0, 0.0, 0.0)
+}
+
+fn sample_keyframes<T: Copy>(keyframes: &[(f32, T)], time: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let next = keyframes.partition_point(|(key_time, _)| *key_time <= time);
+    if next == 0 {
+        return keyframes.first().map(|(_, value)| *value);
+    }
+    if next == keyframes.len() {
+        return keyframes.last().map(|(_, value)| *value);
+    }
+    let (from_time, from) = keyframes[next - 1];
+    let (to_time, to) = keyframes[next];
+    Some(lerp(from, to, (time - from_time) / (to_time - from_time)))
+}
+
+/// Loads all animations of the glTF file, indexed by their names. Every channel of an animation
+/// is applied to the whole model, no matter which node it targets.
+pub fn load_clips(file_name: &str) -> Result<HashMap<String, Arc<AnimationClip>>> {
+    let (document, buffers, _) = gltf::import(file_name)?;
+    let mut clips = HashMap::new();
+    for animation in document.animations() {
+        let mut translations = vec![];
+        let mut rotations = vec![];
+        for channel in animation.channels() {
+            let reader = channel.reader(|buffer| Some(&buffers[buffer.index()]));
+            let times = match reader.read_inputs() {
+                Some(times) => times.collect::<Vec<_>>(),
+                None => continue,
+            };
+            // FIXME: support cubic spline interpolation, for now the tangents are simply skipped
+            let stride = if matches!(channel.sampler().interpolation(), gltf::animation::Interpolation::CubicSpline) {
+                3
+            } else {
+                1
+            };
+            match reader.read_outputs() {
+                Some(ReadOutputs::Translations(values)) => {
+                    let values = values.skip(stride / 2).step_by(stride);
+                    translations.extend(times.iter().copied().zip(values.map(Vector3::from)));
+                }
+                Some(ReadOutputs::Rotations(values)) => {
+                    // glTF stores quaternions as x, y, z, w
+                    let values = values.into_f32().skip(stride / 2).step_by(stride);
+                    rotations.extend(times.iter().copied().zip(values.map(|[x, y, z, w]| Quaternion::new(w, x, y, z))));
+                }
+                // instances can't be scaled and models don't have morph targets
+                _ => {}
+            }
+        }
+        translations.sort_by(|a, b| a.0.total_cmp(&b.0));
+        rotations.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let end = translations.last().map_or(0.0, |(time, _)| *time)
+            .max(rotations.last().map_or(0.0, |(time, _)| *time));
+        let name = animation.name().map_or_else(|| animation.index().to_string(), str::to_string);
+        clips.insert(name.clone(), Arc::new(AnimationClip {
+            name,
+            duration: Duration::from_secs_f32(end),
+            translations,
+            rotations,
+        }));
+    }
+    Ok(clips)
+}
+
+struct PlayingClip {
+    clip: Arc<AnimationClip>,
+    start: Instant,
+    looping: bool,
+    // how long the clip takes to fully replace the clips which were played before it
+    blend: Duration,
+}
+
+impl PlayingClip {
+
+    fn time(&self) -> f32 {
+        let elapsed = self.start.elapsed().as_secs_f32();
+        let duration = self.clip.duration.as_secs_f32();
+        if self.looping && duration > 0.0 {
+            elapsed % duration
+        } else {
+            elapsed.min(duration)
+        }
+    }
+
+    fn weight(&self) -> f32 {
+        if self.blend.is_zero() {
+            return 1.0;
+        }
+        (self.start.elapsed().as_secs_f32() / self.blend.as_secs_f32()).min(1.0)
+    }
+
+    fn is_finished(&self) -> bool {
+        !self.looping && self.start.elapsed() >= self.clip.duration
+    }
+
+}
+
+/// The clips an entity is playing, clips which were played later are blended over the earlier ones.
+/// Once a clip which plays only once is finished, the clips below it take over again.
+#[derive(Default)]
+pub struct AnimationPlayer {
+    playing: Vec<PlayingClip>,
+}
+
+impl AnimationPlayer {
+
+    pub fn play(&mut self, clip: Arc<AnimationClip>, looping: bool, blend: Duration) {
+        // playing a looping clip again would restart it, which is visible
+        if looping && self.playing.last().map_or(false, |playing| playing.looping && Arc::ptr_eq(&playing.clip, &clip)) {
+            return;
+        }
+        self.playing.push(PlayingClip {
+            clip,
+            start: Instant::now(),
+            looping,
+            blend,
+        });
+    }
+
+    pub fn update(&mut self) {
+        self.playing.retain(|playing| !playing.is_finished());
+        // a looping clip which was blended in completely hides all clips below it forever
+        if let Some(idx) = self.playing.iter().rposition(|playing| playing.looping && playing.weight() >= 1.0) {
+            self.playing.drain(..idx);
+        }
+    }
+
+    // the transform relative to the entity's own one
+    pub fn pose(&self) -> Instance {
+        let mut pose = Instance {
+            position: Vector3::zero(),
+            rotation: identity_rotation(),
+        };
+        for playing in self.playing.iter() {
+            let sample = playing.clip.sample(playing.time());
+            let weight = playing.weight();
+            pose = Instance {
+                position: pose.position + (sample.position - pose.position) * weight,
+                rotation: pose.rotation.slerp(sample.rotation, weight),
+            };
+        }
+        pose
+    }
+
+}
@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use image::RgbaImage;
+use wgpu_glyph::ab_glyph;
+use crate::Game;
+use crate::player::Character;
+
+const BOLD_FONT_PATH: &str = "./resources/PlayfairDisplayBold.ttf";
+pub const BOARD_TEXTURE_PATH: &str = "./resources/cube-diffuse.jpg";
+pub const PLAY_BUTTON_PATH: &str = "./resources/play-button_3.jpg";
+
+// the assets which are loaded once at startup, before the first real screen is shown
+pub struct Assets {
+    pub board_texture: Arc<RgbaImage>,
+    pub play_button: Arc<RgbaImage>,
+    // indexed by the character's id
+    pub character_textures: HashMap<usize, Arc<RgbaImage>>,
+}
+
+pub enum LoadEvent {
+    // `step` is the translation key of what's about to be loaded, `done` out of `total` steps were already loaded
+    Progress {
+        step: &'static str,
+        done: usize,
+        total: usize,
+    },
+    Finished(Assets),
+    // the error of the first asset which couldn't be loaded, nothing gets loaded after it
+    Failed(String),
+}
+
+// the number of progress steps, each character counts as its own step
+fn total_steps(characters: &[Character]) -> usize {
+    3 + characters.len()
+}
+
+/// Loads all startup assets on a worker thread, progress is reported through the returned receiver
+/// and the assets are passed along with the final `LoadEvent::Finished`.
+pub fn load_startup_assets(game: &Arc<Game>) -> Receiver<LoadEvent> {
+    let (sender, receiver) = channel();
+    let game = game.clone();
+    thread::spawn(move || {
+        let event = match load(&game, &sender) {
+            Ok(assets) => LoadEvent::Finished(assets),
+            Err(err) => LoadEvent::Failed(err),
+        };
+        let _ = sender.send(event);
+    });
+    receiver
+}
+
+fn open_image(path: &Path) -> Result<RgbaImage, String> {
+    let image = image::open(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(image.into_rgba8())
+}
+
+fn load(game: &Arc<Game>, sender: &Sender<LoadEvent>) -> Result<Assets, String> {
+    let total = total_steps(&game.characters);
+    let mut done = 0;
+    let mut progress = |step: &'static str| {
+        // the loading screen may already be gone, in which case nobody is interested in the progress
+        let _ = sender.send(LoadEvent::Progress {
+            step,
+            done,
+            total,
+        });
+        done += 1;
+    };
+
+    progress("loading.fonts");
+    // the bold font is optional, if it isn't present the regular one stays in its place
+    if let Ok(bold_font) = fs::read(BOLD_FONT_PATH) {
+        game.renderer.set_bold_font(bold_font);
+    }
+
+    progress("loading.board_textures");
+    let board_texture = Arc::new(open_image(Path::new(BOARD_TEXTURE_PATH))?);
+
+    progress("loading.ui_textures");
+    let play_button = Arc::new(open_image(Path::new(PLAY_BUTTON_PATH))?);
+
+    let mut character_textures = HashMap::new();
+    for character in game.characters.iter() {
+        progress("loading.character_textures");
+        let texture = open_image(Path::new(&character.model_path))?;
+        character_textures.insert(character.id, Arc::new(texture));
+    }
+
+    Ok(Assets {
+        board_texture,
+        play_button,
+        character_textures,
+    })
+}
@@ -43,7 +43,7 @@ static ATLAS_ID: AtomicUsize = AtomicUsize::new(0);
 pub struct Atlas {
     alloc: Mutex<AtlasAllocator>,
     alloc_map: RwLock<HashMap<String, Arc<AtlasAlloc>>>,
-    gpu_buffer: SwapArc<TexTriple>, // FIXME: add a way to access the gpu_buffer!
+    gpu_buffer: SwapArc<TexTriple>,
     buffer_size: Size,
     state: Arc<State>,
     write_queue: Mutex<Vec<QueuedWrite>>,
@@ -85,6 +85,7 @@ impl Atlas {
             if let Some(alloc) = alloc.allocate(size2(size.0 as i32, size.1 as i32)) {
                 let alloc = Arc::new(AtlasAlloc {
                     allocation: alloc,
+                    size,
                     atlas: self.clone(),
                 });
                 self.alloc_map.write().unwrap().insert(path, alloc.clone());
@@ -145,7 +146,7 @@ impl Atlas {
                 Extent3d {
                     width,
                     height,
-                    depth_or_array_layers: 0,
+                    depth_or_array_layers: 1,
                 },
             );
             self.gpu_buffer.store(new_tex);
@@ -168,7 +169,8 @@ impl Atlas {
     ) -> TexTriple {
         let tex = state.create_raw_texture(
             RawTextureBuilder::new()
-                .usages(TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST)
+                // the old texture gets copied into the new one when the atlas grows
+                .usages(TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::COPY_SRC)
                 .dimensions(size)
                 .texture_dimension(TextureDimension::D2)
                 .format(texture_format),
@@ -216,8 +218,9 @@ impl Atlas {
             },
             content,
             ImageDataLayout {
-                offset: 1,
-                bytes_per_row: None/*NonZeroU32::new(4 * size.0)*/, // FIXME: can we pass the actual values, so we get more optimizations?
+                offset: 0,
+                // FIXME: this assumes a format with 4 bytes per pixel
+                bytes_per_row: NonZeroU32::new(4 * size.0),
                 rows_per_image: None/*NonZeroU32::new(size.1)*/, // FIXME: can we pass the actual values, so we get more optimizations?
             },
             Extent3d {
@@ -232,10 +235,21 @@ impl Atlas {
     pub fn id(&self) -> AtlasId {
         self.id
     }
+
+    // the size of the atlas' texture in pixels
+    pub fn size(&self) -> (u32, u32) {
+        self.buffer_size.get()
+    }
+
+    pub fn with_texture<R>(&self, f: impl FnOnce(&TexTriple) -> R) -> R {
+        f(&self.gpu_buffer.load())
+    }
 }
 
 pub struct AtlasAlloc {
     allocation: Allocation,
+    // the allocated rectangle may be larger than what was requested
+    size: (u32, u32),
     atlas: Arc<Atlas>,
 }
 
@@ -247,6 +261,17 @@ impl AtlasAlloc {
         )
     }
 
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    // the pixel in the atlas at the relative position inside of the allocation
+    pub fn uv_at(&self, (x, y): (f32, f32)) -> (u32, u32) {
+        let UV(min_x, min_y) = self.uv();
+        let (width, height) = self.size();
+        (min_x + (x * width as f32) as u32, min_y + (y * height as f32) as u32)
+    }
+
     #[inline(always)]
     pub fn atlas(&self) -> &Arc<Atlas> {
         &self.atlas
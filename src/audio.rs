@@ -0,0 +1,394 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use instant::Instant;
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
+use crate::utils::{read_config, write_config};
+
+const AUDIO_MANIFEST_FILE: &str = "audio.json";
+
+pub fn load_audio_manifest() -> AudioManifest {
+    if let Some(buf) = read_config(AUDIO_MANIFEST_FILE) {
+        serde_json::from_str(&*buf).unwrap()
+    } else {
+        let manifest = AudioManifest::default();
+        write_config(AUDIO_MANIFEST_FILE, &serde_json::to_string(&manifest).unwrap());
+        manifest
+    }
+}
+
+// maps the logical sounds to the files which get played for them, so themes and
+// content packs can replace sounds by editing the manifest.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AudioManifest {
+    pub sounds: HashMap<SoundId, String>,
+}
+
+impl Default for AudioManifest {
+    fn default() -> Self {
+        let sounds = [
+            (SoundId::ButtonClick, "button_click.ogg"),
+            (SoundId::DiceRoll, "dice_roll.ogg"),
+            (SoundId::TokenHop, "token_hop.ogg"),
+            (SoundId::MoneyPaid, "money_paid.ogg"),
+            (SoundId::MoneyReceived, "money_received.ogg"),
+            (SoundId::CardDraw, "card_draw.ogg"),
+            (SoundId::BuildingPurchased, "building_purchased.ogg"),
+            (SoundId::GoToJail, "go_to_jail.ogg"),
+            (SoundId::TurnChime, "turn_chime.ogg"),
+            (SoundId::BackgroundMusic, "music.ogg"),
+        ];
+        Self {
+            sounds: sounds.into_iter().map(|(sound, file)| (sound, format!("./resources/sounds/{}", file))).collect(),
+        }
+    }
+}
+
+// logical sounds, which file gets played for each of them is decided by the audio manifest
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SoundId {
+    ButtonClick,
+    DiceRoll,
+    TokenHop,
+    MoneyPaid,
+    MoneyReceived,
+    CardDraw,
+    BuildingPurchased,
+    GoToJail,
+    TurnChime,
+    BackgroundMusic,
+}
+
+impl SoundId {
+
+    pub fn category(self) -> Category {
+        match self {
+            SoundId::BackgroundMusic => Category::Music,
+            _ => Category::Sfx,
+        }
+    }
+
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Category {
+    Music,
+    Sfx,
+    Voice,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SoundHandle(u64);
+
+enum AudioCommand {
+    Play {
+        handle: SoundHandle,
+        sound: SoundId,
+        looped: bool,
+    },
+    PlayFile {
+        handle: SoundHandle,
+        path: String,
+        category: Category,
+    },
+    Stop(SoundHandle),
+    SetMasterVolume(f32),
+    SetCategoryVolume(Category, f32),
+    SetMuted(bool),
+    SetDucked(bool),
+}
+
+/// Plays sounds on a dedicated thread, as the output stream can't be shared between threads.
+/// All methods return immediately, so they can be called from game rules and ui callbacks.
+pub struct Audio {
+    commands: Mutex<Sender<AudioCommand>>,
+    next_handle: AtomicU64,
+}
+
+impl Audio {
+
+    pub fn new(manifest: AudioManifest) -> Self {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            run(receiver, manifest);
+        });
+        Self {
+            commands: Mutex::new(sender),
+            next_handle: AtomicU64::new(0),
+        }
+    }
+
+    pub fn play(&self, sound: SoundId) -> SoundHandle {
+        self.start(sound, false)
+    }
+
+    pub fn play_looped(&self, sound: SoundId) -> SoundHandle {
+        self.start(sound, true)
+    }
+
+    fn start(&self, sound: SoundId, looped: bool) -> SoundHandle {
+        let handle = SoundHandle(self.next_handle.fetch_add(1, Ordering::Relaxed));
+        self.send(AudioCommand::Play {
+            handle,
+            sound,
+            looped,
+        });
+        handle
+    }
+
+    // plays a file which isn't part of the manifest, e.g. a character's voice line
+    pub fn play_file(&self, path: impl Into<String>, category: Category) -> SoundHandle {
+        let handle = SoundHandle(self.next_handle.fetch_add(1, Ordering::Relaxed));
+        self.send(AudioCommand::PlayFile {
+            handle,
+            path: path.into(),
+            category,
+        });
+        handle
+    }
+
+    pub fn stop(&self, handle: SoundHandle) {
+        self.send(AudioCommand::Stop(handle));
+    }
+
+    pub fn set_master_volume(&self, volume: f32) {
+        self.send(AudioCommand::SetMasterVolume(volume));
+    }
+
+    pub fn set_volume(&self, category: Category, volume: f32) {
+        self.send(AudioCommand::SetCategoryVolume(category, volume));
+    }
+
+    pub fn set_muted(&self, muted: bool) {
+        self.send(AudioCommand::SetMuted(muted));
+    }
+
+    // lowers the music's volume while e.g. a dialog is open, the volume fades between both states
+    pub fn set_ducked(&self, ducked: bool) {
+        self.send(AudioCommand::SetDucked(ducked));
+    }
+
+    fn send(&self, command: AudioCommand) {
+        // if the audio thread is gone there's no way to play anything anyways
+        let _ = self.commands.lock().unwrap().send(command);
+    }
+
+}
+
+// the music's volume factor while ducked and how long fading between both states takes
+const DUCKED_VOLUME: f32 = 0.3;
+const DUCK_FADE: Duration = Duration::from_millis(400);
+// how often the volume gets updated while fading
+const DUCK_STEP: Duration = Duration::from_millis(20);
+
+struct Mixer {
+    manifest: AudioManifest,
+    master: f32,
+    categories: HashMap<Category, f32>,
+    muted: bool,
+    playing: HashMap<SoundHandle, (Sink, Category)>,
+    ducked: bool,
+    // the current volume factor of the music, moves towards its target while fading
+    duck_factor: f32,
+    sfx: HashMap<SoundId, DecodedSound>,
+}
+
+struct DecodedSound {
+    channels: u16,
+    sample_rate: u32,
+    samples: Vec<i16>,
+}
+
+impl DecodedSound {
+
+    fn load(path: &str) -> anyhow::Result<Self> {
+        let decoder = Decoder::new(BufReader::new(File::open(path)?))?;
+        Ok(Self {
+            channels: decoder.channels(),
+            sample_rate: decoder.sample_rate(),
+            samples: decoder.collect(),
+        })
+    }
+
+}
+
+impl Mixer {
+
+    fn volume(&self, category: Category) -> f32 {
+        if self.muted {
+            return 0.0;
+        }
+        let duck = if category == Category::Music {
+            self.duck_factor
+        } else {
+            1.0
+        };
+        self.master * self.categories.get(&category).copied().unwrap_or(1.0) * duck
+    }
+
+    // returns whether the volume still has to be faded any further
+    fn fade_duck(&mut self, elapsed: Duration) -> bool {
+        let target = if self.ducked {
+            DUCKED_VOLUME
+        } else {
+            1.0
+        };
+        if self.duck_factor == target {
+            return false;
+        }
+        let step = (1.0 - DUCKED_VOLUME) * (elapsed.as_secs_f32() / DUCK_FADE.as_secs_f32());
+        self.duck_factor = if self.duck_factor < target {
+            (self.duck_factor + step).min(target)
+        } else {
+            (self.duck_factor - step).max(target)
+        };
+        self.apply_volumes();
+        true
+    }
+
+    fn apply_volumes(&self) {
+        for (sink, category) in self.playing.values() {
+            sink.set_volume(self.volume(*category));
+        }
+    }
+
+    fn play(&mut self, stream: &OutputStreamHandle, handle: SoundHandle, sound: SoundId, looped: bool) -> anyhow::Result<()> {
+        if let Some(decoded) = self.sfx.get(&sound) {
+            let sink = Sink::try_new(stream)?;
+            let source = SamplesBuffer::new(decoded.channels, decoded.sample_rate, decoded.samples.to_vec());
+            if looped {
+                sink.append(source.repeat_infinite());
+            } else {
+                sink.append(source);
+            }
+            sink.set_volume(self.volume(sound.category()));
+            self.playing.insert(handle, (sink, sound.category()));
+            return Ok(());
+        }
+        let path = match self.manifest.sounds.get(&sound) {
+            Some(path) => path.clone(),
+            // sounds can be left out of the manifest on purpose to disable them
+            None => return Ok(()),
+        };
+        self.play_file(stream, handle, &path, sound.category(), looped)
+    }
+
+    // sound effects are short and get played often, so they're decoded once up front
+    // while everything else (most importantly music) gets decoded while it's played.
+    fn preload_sfx(&mut self) {
+        for (sound, path) in self.manifest.sounds.iter() {
+            if sound.category() != Category::Sfx {
+                continue;
+            }
+            match DecodedSound::load(path) {
+                Ok(decoded) => {
+                    self.sfx.insert(*sound, decoded);
+                }
+                Err(err) => println!("Couldn't load sound {:?}: {}", sound, err),
+            }
+        }
+    }
+
+    fn play_file(&mut self, stream: &OutputStreamHandle, handle: SoundHandle, path: &str, category: Category, looped: bool) -> anyhow::Result<()> {
+        let sink = Sink::try_new(stream)?;
+        let source = Decoder::new(BufReader::new(File::open(path)?))?;
+        if looped {
+            sink.append(source.repeat_infinite());
+        } else {
+            sink.append(source);
+        }
+        sink.set_volume(self.volume(category));
+        self.playing.insert(handle, (sink, category));
+        Ok(())
+    }
+
+}
+
+fn run(commands: Receiver<AudioCommand>, manifest: AudioManifest) {
+    let (_stream, stream_handle) = match OutputStream::try_default() {
+        Ok(stream) => stream,
+        Err(err) => {
+            println!("Couldn't open audio output: {}", err);
+            // keep on receiving commands so the senders don't notice anything
+            for _ in commands.iter() {}
+            return;
+        }
+    };
+    let mut mixer = Mixer {
+        manifest,
+        master: 1.0,
+        categories: HashMap::new(),
+        muted: false,
+        playing: HashMap::new(),
+        ducked: false,
+        duck_factor: 1.0,
+        sfx: HashMap::new(),
+    };
+    mixer.preload_sfx();
+    let mut fading = false;
+    let mut last_fade = Instant::now();
+    loop {
+        // only wake up regularly while there's something to fade
+        let command = if fading {
+            match commands.recv_timeout(DUCK_STEP) {
+                Ok(command) => Some(command),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        } else {
+            match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return,
+            }
+        };
+        let now = Instant::now();
+        fading = mixer.fade_duck(now.duration_since(last_fade));
+        last_fade = now;
+        let command = match command {
+            Some(command) => command,
+            None => continue,
+        };
+        // get rid of sounds which finished playing
+        mixer.playing.retain(|_, (sink, _)| !sink.empty());
+        match command {
+            AudioCommand::Play { handle, sound, looped } => {
+                if let Err(err) = mixer.play(&stream_handle, handle, sound, looped) {
+                    println!("Couldn't play sound {:?}: {}", sound, err);
+                }
+            }
+            AudioCommand::PlayFile { handle, path, category } => {
+                if let Err(err) = mixer.play_file(&stream_handle, handle, &path, category, false) {
+                    println!("Couldn't play {}: {}", path, err);
+                }
+            }
+            AudioCommand::Stop(handle) => {
+                if let Some((sink, _)) = mixer.playing.remove(&handle) {
+                    sink.stop();
+                }
+            }
+            AudioCommand::SetMasterVolume(volume) => {
+                mixer.master = volume;
+                mixer.apply_volumes();
+            }
+            AudioCommand::SetCategoryVolume(category, volume) => {
+                mixer.categories.insert(category, volume);
+                mixer.apply_volumes();
+            }
+            AudioCommand::SetMuted(muted) => {
+                mixer.muted = muted;
+                mixer.apply_volumes();
+            }
+            AudioCommand::SetDucked(ducked) => {
+                mixer.ducked = ducked;
+                fading = true;
+            }
+        }
+    }
+}
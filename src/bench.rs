@@ -0,0 +1,87 @@
+use std::f32::consts::PI;
+use std::time::Duration;
+use cgmath::{Point3, Rad};
+use instant::Instant;
+use crate::render::Camera;
+
+// frames which are rendered before the measurement starts, so pipeline and texture setup don't skew the results
+const WARMUP_FRAMES: usize = 30;
+// the camera circles around the board once every ORBIT_FRAMES frames
+const ORBIT_FRAMES: f32 = 600.0;
+const ORBIT_RADIUS: f32 = 12.0;
+const ORBIT_HEIGHT: f32 = 6.0;
+// how much the camera's height changes during an orbit
+const ORBIT_BOB: f32 = 3.0;
+
+// renders a fixed number of frames along a scripted camera path and measures how long each one took,
+// the path only depends on the frame index so results are comparable between machines
+pub struct Benchmark {
+    frames: usize,
+    rendered: usize,
+    frame_times: Vec<Duration>,
+    last_frame: Option<Instant>,
+}
+
+impl Benchmark {
+
+    pub fn new(frames: usize) -> Self {
+        Self {
+            frames,
+            rendered: 0,
+            frame_times: Vec::with_capacity(frames),
+            last_frame: None,
+        }
+    }
+
+    // has to be called once per frame, returns false once all frames were measured
+    pub fn frame(&mut self, camera: &mut Camera) -> bool {
+        let now = Instant::now();
+        if self.rendered > WARMUP_FRAMES {
+            if let Some(last_frame) = self.last_frame {
+                self.frame_times.push(now.duration_since(last_frame));
+            }
+        }
+        self.last_frame = Some(now);
+        if self.frame_times.len() >= self.frames {
+            return false;
+        }
+
+        let angle = self.rendered as f32 / ORBIT_FRAMES * 2.0 * PI;
+        let height = ORBIT_HEIGHT + (angle * 2.0).sin() * ORBIT_BOB;
+        // always look at the center of the board
+        *camera = Camera::new(
+            Point3::new(angle.cos() * ORBIT_RADIUS, height, angle.sin() * ORBIT_RADIUS),
+            Rad(angle + PI),
+            Rad(-(height / ORBIT_RADIUS).atan()),
+        );
+        self.rendered += 1;
+        true
+    }
+
+    pub fn report(&self) {
+        if self.frame_times.is_empty() {
+            println!("No frames were measured");
+            return;
+        }
+        let mut sorted = self.frame_times.clone();
+        sorted.sort();
+        let total = sorted.iter().sum::<Duration>();
+        let average = total / sorted.len() as u32;
+        println!("Rendered {} frames in {:.2}s ({:.1} fps on average)", sorted.len(), total.as_secs_f64(), 1.0 / average.as_secs_f64());
+        for percentile in [50.0, 90.0, 95.0, 99.0] {
+            println!("  p{:<4} {:>8.3}ms", percentile, to_millis(percentile_of(&sorted, percentile)));
+        }
+        println!("  max   {:>8.3}ms", to_millis(*sorted.last().unwrap()));
+    }
+
+}
+
+// expects the frame times to be sorted
+fn percentile_of(sorted: &[Duration], percentile: f64) -> Duration {
+    let idx = ((percentile / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[idx]
+}
+
+fn to_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
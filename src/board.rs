@@ -1,37 +1,45 @@
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
-use crate::property::{PropertyFrame, PropertyType};
+use crate::property::{ColorGroup, PropertyFrame, PropertyType};
 use serde::{Deserialize, Serialize};
+use crate::utils::{read_config, write_config};
 
 pub const TILES: usize = 40;
 
+#[derive(Clone)]
 pub struct Board {
     pub tiles: [Tile; TILES],
     pub index: BoardIndex,
 }
 
-const BOARD_PATH: &str = "./config/board.json";
+const BOARD_FILE: &str = "board.json";
 
 pub fn load_board() -> Board {
-    if Path::new(BOARD_PATH).exists() {
-        let mut file = File::open(BOARD_PATH).unwrap();
-        let mut buf = String::new();
-        file.read_to_string(&mut buf).unwrap();
-        let tiles: Vec<Tile> = serde_json::from_str(&*buf).unwrap();
-        let tiles = tiles.try_into().unwrap();
-        let index = BoardIndex::new(&tiles);
-        Board {
-            tiles,
-            index,
+    match read_config(BOARD_FILE) {
+        Some(buf) => {
+            let tiles: Vec<Tile> = serde_json::from_str(&*buf).unwrap();
+            let tiles = tiles.try_into().unwrap();
+            let index = BoardIndex::new(&tiles);
+            Board {
+                tiles,
+                index,
+            }
+        }
+        None => {
+            write_config(BOARD_FILE, &serde_json::to_string(&Vec::from(Board::default().tiles)).unwrap());
+            Board::default()
         }
-    } else {
-        let mut file = File::create(BOARD_PATH).unwrap();
-        file.write_all(serde_json::to_string(&Vec::from(Board::default().tiles)).unwrap().as_ref()).unwrap();
-        Board::default()
     }
 }
 
+impl Board {
+
+    // the tile at `layout[idx]` is moved to `idx`
+    pub fn rearrange(&mut self, layout: &[usize; TILES]) {
+        let tiles = self.tiles.clone();
+        self.tiles = layout.map(|from| tiles[from].clone());
+        self.index = BoardIndex::new(&self.tiles);
+    }
+
+}
 
 struct SerdeBoard {
     tiles: [Tile; TILES],
@@ -45,7 +53,7 @@ impl Default for Board {
                 name: "DarkBlue1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(1), None] },
+                ty: PropertyType::Normal { associates: [Some(1), None], group: Some(ColorGroup::DarkBlue) },
             } },
             Tile::DrawCard {
                 kind: CardKind::Community,
@@ -55,9 +63,9 @@ impl Default for Board {
                 name: "DarkBlue2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(0), None] },
+                ty: PropertyType::Normal { associates: [Some(0), None], group: Some(ColorGroup::DarkBlue) },
             } },
-            Tile::Pay { name: "Pay1".to_string(), amount: 0 },
+            Tile::Pay { name: "Pay1".to_string(), amount: 0, income_tax: true },
             Tile::Property { property: PropertyFrame {
                 id: 2,
                 name: "Station1".to_string(),
@@ -70,7 +78,7 @@ impl Default for Board {
                 name: "LightBlue1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(4), Some(5)] },
+                ty: PropertyType::Normal { associates: [Some(4), Some(5)], group: Some(ColorGroup::LightBlue) },
             } },
             Tile::DrawCard {
                 kind: CardKind::Chance,
@@ -80,14 +88,14 @@ impl Default for Board {
                 name: "LightBlue2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(3), Some(5)] },
+                ty: PropertyType::Normal { associates: [Some(3), Some(5)], group: Some(ColorGroup::LightBlue) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 5,
                 name: "LightBlue3".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(3), Some(4)] },
+                ty: PropertyType::Normal { associates: [Some(3), Some(4)], group: Some(ColorGroup::LightBlue) },
             } },
             Tile::Jail { name: "Jail".to_string() },
             Tile::Property { property: PropertyFrame {
@@ -95,7 +103,7 @@ impl Default for Board {
                 name: "Violet1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(8), Some(9)] },
+                ty: PropertyType::Normal { associates: [Some(8), Some(9)], group: Some(ColorGroup::Violet) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 7,
@@ -109,14 +117,14 @@ impl Default for Board {
                 name: "Violet2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(6), Some(9)] },
+                ty: PropertyType::Normal { associates: [Some(6), Some(9)], group: Some(ColorGroup::Violet) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 9,
                 name: "Violet3".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(6), Some(8)] },
+                ty: PropertyType::Normal { associates: [Some(6), Some(8)], group: Some(ColorGroup::Violet) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 10,
@@ -130,7 +138,7 @@ impl Default for Board {
                 name: "Brown1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(12), Some(13)] },
+                ty: PropertyType::Normal { associates: [Some(12), Some(13)], group: Some(ColorGroup::Brown) },
             } },
             Tile::DrawCard {
                 kind: CardKind::Community,
@@ -140,14 +148,14 @@ impl Default for Board {
                 name: "Brown2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(11), Some(13)] },
+                ty: PropertyType::Normal { associates: [Some(11), Some(13)], group: Some(ColorGroup::Brown) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 13,
                 name: "Brown3".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(11), Some(12)] },
+                ty: PropertyType::Normal { associates: [Some(11), Some(12)], group: Some(ColorGroup::Brown) },
             } },
             Tile::Parking {
                 name: "Parking".to_string(),
@@ -157,7 +165,7 @@ impl Default for Board {
                 name: "Red1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(15), Some(16)] },
+                ty: PropertyType::Normal { associates: [Some(15), Some(16)], group: Some(ColorGroup::Red) },
             } },
             Tile::DrawCard {
                 kind: CardKind::Chance,
@@ -167,14 +175,14 @@ impl Default for Board {
                 name: "Red2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(14), Some(16)] },
+                ty: PropertyType::Normal { associates: [Some(14), Some(16)], group: Some(ColorGroup::Red) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 16,
                 name: "Red3".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(14), Some(15)] },
+                ty: PropertyType::Normal { associates: [Some(14), Some(15)], group: Some(ColorGroup::Red) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 17,
@@ -188,28 +196,28 @@ impl Default for Board {
                 name: "Yellow1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(19), Some(21)] },
+                ty: PropertyType::Normal { associates: [Some(19), Some(21)], group: Some(ColorGroup::Yellow) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 19,
                 name: "Yellow2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(18), Some(21)] },
+                ty: PropertyType::Normal { associates: [Some(18), Some(21)], group: Some(ColorGroup::Yellow) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 20,
                 name: "Special2".to_string(),
                 buy_price: 0,
                 rents: [Some(0), None, None, None, None, None],
-                ty: PropertyType::Normal { associates: [Some(11), Some(12)] },
+                ty: PropertyType::Normal { associates: [Some(11), Some(12)], group: None },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 21,
                 name: "Yellow3".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(22), Some(23)] },
+                ty: PropertyType::Normal { associates: [Some(22), Some(23)], group: Some(ColorGroup::Yellow) },
             } },
             Tile::GoToJail {
                 name: "Go to jail".to_string(),
@@ -219,14 +227,14 @@ impl Default for Board {
                 name: "Green1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(23), Some(24)] },
+                ty: PropertyType::Normal { associates: [Some(23), Some(24)], group: Some(ColorGroup::Green) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 23,
                 name: "Green2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(22), Some(24)] },
+                ty: PropertyType::Normal { associates: [Some(22), Some(24)], group: Some(ColorGroup::Green) },
             } },
             Tile::DrawCard {
                 kind: CardKind::Community,
@@ -236,7 +244,7 @@ impl Default for Board {
                 name: "Green3".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(22), Some(23)] },
+                ty: PropertyType::Normal { associates: [Some(22), Some(23)], group: Some(ColorGroup::Green) },
             } },
             Tile::Property { property: PropertyFrame {
                 id: 25,
@@ -253,18 +261,19 @@ impl Default for Board {
                 name: "OtherBlue1".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(27), None] },
+                ty: PropertyType::Normal { associates: [Some(27), None], group: Some(ColorGroup::OtherBlue) },
             } },
             Tile::Pay {
                 name: "Pay2".to_string(),
                 amount: 0,
+                income_tax: false,
             },
             Tile::Property { property: PropertyFrame {
                 id: 27,
                 name: "OtherBlue2".to_string(),
                 buy_price: 0,
                 rents: [Some(0); 6],
-                ty: PropertyType::Normal { associates: [Some(26), None] },
+                ty: PropertyType::Normal { associates: [Some(26), None], group: Some(ColorGroup::OtherBlue) },
             } },
         ];
         let index = BoardIndex::new(&tiles);
@@ -275,7 +284,7 @@ impl Default for Board {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum Tile {
     Parking {
         name: String,
@@ -295,10 +304,18 @@ pub enum Tile {
     Pay {
         name: String,
         amount: usize,
+        // the player may pay a percentage of their net worth instead of the amount, see `IncomeTaxRules`
+        #[serde(default)]
+        income_tax: bool,
     },
     DrawCard {
         kind: CardKind,
     },
+    // a tile whose behavior is added by a mod, `kind` is the name the mod registered it under
+    Custom {
+        name: String,
+        kind: String,
+    },
 }
 
 impl Tile {
@@ -312,12 +329,13 @@ impl Tile {
             Tile::Property { .. } => TileKind::Property,
             Tile::Pay { .. } => TileKind::Pay,
             Tile::DrawCard { .. } => TileKind::DrawCard,
+            Tile::Custom { .. } => TileKind::Custom,
         }
     }
 
 }
 
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum TileKind {
     Parking,
     Start,
@@ -326,15 +344,17 @@ pub enum TileKind {
     Property,
     Pay,
     DrawCard,
+    Custom,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 #[repr(usize)]
 pub enum CardKind {
     Chance = 0,
     Community = 1,
 }
 
+#[derive(Copy, Clone)]
 pub struct BoardIndex {
     pub jail: usize,
     pub start: usize,
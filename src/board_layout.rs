@@ -0,0 +1,87 @@
+use cgmath::{Deg, EuclideanSpace, Matrix4, Point3, Quaternion, Rotation3, Vector3};
+use crate::board::{Board, TILES};
+use crate::render::Instance;
+
+// half of the length of the board's edges in world units
+pub const BOARD_HALF_SIZE: f32 = 5.0;
+// the height of the board's surface
+const BOARD_TOP: f32 = 2.0;
+// where the board's center rests in the world, everything on the board is attached to it
+pub const BOARD_POSITION: Vector3<f32> = Vector3::new(0.0, 1.0, 0.0);
+
+// the side of the board the tile is on, starting with the side of the start tile
+#[inline]
+pub fn tile_side(tile: usize) -> usize {
+    (tile / (TILES / 4)) % 4
+}
+
+// the point on the board's surface at the center of the tile relative to the board's center,
+// tiles are laid out counter clockwise
+pub fn tile_position(tile: usize) -> Vector3<f32> {
+    const CORNERS: [(f32, f32); 4] = [(1.0, 1.0), (-1.0, 1.0), (-1.0, -1.0), (1.0, -1.0)];
+    let per_side = TILES / 4;
+    let side = tile_side(tile);
+    let progress = (tile % per_side) as f32 / per_side as f32;
+    let from = CORNERS[side];
+    let to = CORNERS[(side + 1) % 4];
+    Vector3::new(
+        (from.0 + (to.0 - from.0) * progress) * BOARD_HALF_SIZE,
+        BOARD_TOP - BOARD_POSITION.y,
+        (from.1 + (to.1 - from.1) * progress) * BOARD_HALF_SIZE,
+    )
+}
+
+// the tiles' local z axis points towards the outer edge of the board, so things which are laid out
+// along a tile (houses, owner markers) line up the same way on every side
+pub fn tile_rotation(tile: usize) -> Quaternion<f32> {
+    Quaternion::from_angle_y(Deg(-90.0 * tile_side(tile) as f32))
+}
+
+// the transform of something placed on the tile, the offset is in the tile's local space
+// and the result is relative to the board's center, as everything on the board is attached to it
+pub fn on_tile(tile: usize, offset: Vector3<f32>) -> Instance {
+    let rotation = tile_rotation(tile);
+    Instance {
+        position: tile_position(tile) + rotation * offset,
+        rotation,
+    }
+}
+
+// the tile under the point on the board's surface relative to the board's center, the tiles are
+// as long as the distance between them and as deep, points inside of the ring of tiles hit none
+pub fn tile_at(point: Vector3<f32>) -> Option<usize> {
+    let half_tile = BOARD_HALF_SIZE / (TILES / 4) as f32;
+    (0..TILES)
+        .map(|tile| {
+            let position = tile_position(tile);
+            (tile, (point.x - position.x).abs().max((point.z - position.z).abs()))
+        })
+        .filter(|(_, distance)| *distance <= half_tile)
+        .min_by(|(_, first), (_, second)| first.total_cmp(second))
+        .map(|(tile, _)| tile)
+}
+
+// the tile the ray hits on the board's surface, the ray is in world space
+pub fn pick_tile(origin: Point3<f32>, direction: Vector3<f32>) -> Option<usize> {
+    // rays that are parallel to the board or point away from it can't hit it
+    if direction.y >= 0.0 {
+        return None;
+    }
+    let distance = (BOARD_TOP - origin.y) / direction.y;
+    if distance < 0.0 {
+        return None;
+    }
+    let hit = origin.to_vec() + direction * distance;
+    tile_at(hit - BOARD_POSITION)
+}
+
+impl Board {
+
+    /// The transform from the tile's local space into world space, its origin is the center of the
+    /// tile on the board's surface.
+    pub fn tile_transform(&self, idx: usize) -> Matrix4<f32> {
+        assert!(idx < self.tiles.len(), "there is no tile {}", idx);
+        Matrix4::from_translation(BOARD_POSITION + tile_position(idx)) * Matrix4::from(tile_rotation(idx))
+    }
+
+}
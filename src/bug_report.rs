@@ -0,0 +1,68 @@
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use winit::event::VirtualKeyCode;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+use crate::engine::Engine;
+use crate::Game;
+
+// pressing this key exports a bug report
+pub const BUG_REPORT_KEY: VirtualKeyCode = VirtualKeyCode::F12;
+const BUG_REPORT_DIR: &str = "./bug_reports/";
+
+/// Bundles everything that's needed to reproduce the current game into a single archive, which can
+/// be attached to a bug report. Returns the path of the archive.
+pub fn export(game: &Game) -> anyhow::Result<PathBuf> {
+    let (seed, snapshot) = {
+        let engine = game.engine.lock().unwrap();
+        (engine.seed, snapshot(&engine))
+    };
+    let events = game.event_log.lock().unwrap().iter()
+        .map(|event| format!("{:?}\n", event))
+        .collect::<String>();
+    let settings = serde_json::to_string_pretty(&*game.settings.lock().unwrap())?;
+
+    fs::create_dir_all(BUG_REPORT_DIR)?;
+    let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = PathBuf::from(BUG_REPORT_DIR).join(format!("bug-report-{}.zip", time));
+    let mut zip = ZipWriter::new(File::create(&path)?);
+    // FIXME: add the log file once the output gets written to one, for now it only goes to stdout
+    for (name, contents) in [
+        ("seed.txt", seed.to_string()),
+        ("events.txt", events),
+        ("snapshot.txt", snapshot),
+        ("settings.json", settings),
+    ] {
+        zip.start_file(name, FileOptions::default())?;
+        zip.write_all(contents.as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(path)
+}
+
+// FIXME: use the save game format once there is one
+fn snapshot(engine: &Engine) -> String {
+    let mut ret = String::new();
+    writeln!(ret, "phase: {:?}", engine.phase).unwrap();
+    writeln!(ret, "turns: {}", engine.turns).unwrap();
+    writeln!(ret, "current player: {}", engine.curr_player).unwrap();
+    writeln!(ret, "parking pot: {}", engine.parking_pot).unwrap();
+    for player in engine.players.iter() {
+        writeln!(
+            ret,
+            "player {} ({}): money {}, position {}, wait {}, jail free cards {}, bankrupt {}",
+            player.id, player.name, player.currency, player.position, player.wait, player.jail_free_cards, player.bankrupt,
+        ).unwrap();
+    }
+    for (idx, property) in engine.properties.iter().enumerate() {
+        writeln!(
+            ret,
+            "property {} ({}): owner {:?}, houses {}, mortgaged {}",
+            idx, property.frame.name, property.owner, property.houses, property.mortgaged,
+        ).unwrap();
+    }
+    ret
+}
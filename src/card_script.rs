@@ -0,0 +1,176 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use rhai::{Dynamic, Engine as ScriptEngine, EvalAltResult};
+use crate::board::TILES;
+use crate::engine::Engine;
+use crate::rules::CurrencyRules;
+
+// keeps scripts with endless loops from freezing the game
+const MAX_OPERATIONS: u64 = 100_000;
+
+// what a script asks the engine to do, the effects are applied in order after the script finished
+#[derive(Clone, PartialEq, Debug)]
+pub enum ScriptEffect {
+    // currency is exchanged between the player and the bank
+    Receive {
+        amount: usize,
+    },
+    Pay {
+        amount: usize,
+    },
+    // currency is exchanged between two players
+    Transfer {
+        from: usize,
+        to: usize,
+        amount: usize,
+    },
+    MoveBy {
+        tiles: isize,
+    },
+    MoveTo {
+        tile: usize,
+    },
+    GoToJail,
+}
+
+// a read only view of the game the script can query
+struct ScriptView {
+    player: usize,
+    owners: Vec<Option<usize>>,
+    currencies: Vec<usize>,
+    positions: Vec<usize>,
+    bankrupt: Vec<bool>,
+    currency: CurrencyRules,
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn to_usize(value: i64, what: &str) -> ScriptResult<usize> {
+    usize::try_from(value).map_err(|_| format!("{} must not be negative, got {}", what, value).into())
+}
+
+fn check_player(view: &ScriptView, value: i64) -> ScriptResult<usize> {
+    let player = to_usize(value, "player")?;
+    if player >= view.currencies.len() {
+        return Err(format!("there is no player {}", player).into());
+    }
+    Ok(player)
+}
+
+fn script_engine() -> ScriptEngine {
+    let mut script = ScriptEngine::new();
+    script.set_max_operations(MAX_OPERATIONS);
+    script
+}
+
+fn view(engine: &Engine, player: usize) -> Rc<ScriptView> {
+    Rc::new(ScriptView {
+        player,
+        owners: engine.properties.iter().map(|property| property.owner).collect(),
+        currencies: engine.players.iter().map(|player| player.currency).collect(),
+        positions: engine.players.iter().map(|player| player.position).collect(),
+        bankrupt: engine.players.iter().map(|player| player.bankrupt).collect(),
+        currency: engine.rules.currency.clone(),
+    })
+}
+
+/// Runs the script of the card the player drew or the custom tile they landed on. The script can
+/// query the state of the game through functions like `owner(property)`, but it can only change
+/// it through the effects it returns, so it's unable to break the rules engine's invariants.
+pub fn run(source: &str, engine: &Engine, player: usize) -> Result<Vec<ScriptEffect>, String> {
+    let view = view(engine, player);
+    let effects = Rc::new(RefCell::new(vec![]));
+    let mut script = script_engine();
+    register_queries(&mut script, &view);
+    register_effects(&mut script, &view, &effects);
+    script.run(source).map_err(|err| err.to_string())?;
+    drop(script);
+    let effects = effects.borrow().clone();
+    Ok(effects)
+}
+
+/// Evaluates a script which can only query the state of the game, `me()` is the player whose
+/// turn it is. The result is turned into text, whatever type it has.
+pub fn eval_text(source: &str, engine: &Engine) -> Result<String, String> {
+    let view = view(engine, engine.curr_player);
+    let mut script = script_engine();
+    register_queries(&mut script, &view);
+    script.eval::<Dynamic>(source).map(|result| result.to_string()).map_err(|err| err.to_string())
+}
+
+fn register_queries(script: &mut ScriptEngine, view: &Rc<ScriptView>) {
+    let v = view.clone();
+    script.register_fn("me", move || v.player as i64);
+    let v = view.clone();
+    script.register_fn("players", move || v.currencies.len() as i64);
+    let v = view.clone();
+    script.register_fn("money", move |of: i64| -> ScriptResult<i64> {
+        Ok(v.currencies[check_player(&v, of)?] as i64)
+    });
+    let v = view.clone();
+    script.register_fn("position", move |of: i64| -> ScriptResult<i64> {
+        Ok(v.positions[check_player(&v, of)?] as i64)
+    });
+    let v = view.clone();
+    script.register_fn("is_bankrupt", move |of: i64| -> ScriptResult<bool> {
+        Ok(v.bankrupt[check_player(&v, of)?])
+    });
+    // -1 if the bank owns the property
+    let v = view.clone();
+    script.register_fn("owner", move |property: i64| -> ScriptResult<i64> {
+        let property = to_usize(property, "property")?;
+        match v.owners.get(property) {
+            Some(owner) => Ok(owner.map_or(-1, |owner| owner as i64)),
+            None => Err(format!("there is no property {}", property).into()),
+        }
+    });
+    // formats the amount like everywhere else in the game, e.g. "$1,200"
+    let v = view.clone();
+    script.register_fn("format_money", move |amount: i64| -> ScriptResult<String> {
+        Ok(v.currency.format(to_usize(amount, "amount")?))
+    });
+    let v = view.clone();
+    script.register_fn("owned_by", move |of: i64| -> ScriptResult<i64> {
+        let of = check_player(&v, of)?;
+        Ok(v.owners.iter().filter(|owner| **owner == Some(of)).count() as i64)
+    });
+}
+
+fn register_effects(script: &mut ScriptEngine, view: &Rc<ScriptView>, effects: &Rc<RefCell<Vec<ScriptEffect>>>) {
+    let e = effects.clone();
+    script.register_fn("receive", move |amount: i64| -> ScriptResult<()> {
+        e.borrow_mut().push(ScriptEffect::Receive { amount: to_usize(amount, "amount")? });
+        Ok(())
+    });
+    let e = effects.clone();
+    script.register_fn("pay", move |amount: i64| -> ScriptResult<()> {
+        e.borrow_mut().push(ScriptEffect::Pay { amount: to_usize(amount, "amount")? });
+        Ok(())
+    });
+    let (v, e) = (view.clone(), effects.clone());
+    script.register_fn("transfer", move |from: i64, to: i64, amount: i64| -> ScriptResult<()> {
+        let (from, to) = (check_player(&v, from)?, check_player(&v, to)?);
+        if from == to {
+            return Err("money can't be transferred to the same player".into());
+        }
+        e.borrow_mut().push(ScriptEffect::Transfer { from, to, amount: to_usize(amount, "amount")? });
+        Ok(())
+    });
+    let e = effects.clone();
+    script.register_fn("move_by", move |tiles: i64| {
+        e.borrow_mut().push(ScriptEffect::MoveBy { tiles: tiles as isize });
+    });
+    let e = effects.clone();
+    script.register_fn("move_to", move |tile: i64| -> ScriptResult<()> {
+        let tile = to_usize(tile, "tile")?;
+        if tile >= TILES {
+            return Err(format!("there is no tile {}", tile).into());
+        }
+        e.borrow_mut().push(ScriptEffect::MoveTo { tile });
+        Ok(())
+    });
+    let e = effects.clone();
+    script.register_fn("go_to_jail", move || {
+        e.borrow_mut().push(ScriptEffect::GoToJail);
+    });
+}
@@ -0,0 +1,249 @@
+use std::sync::Arc;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use winit::event::{ModifiersState, VirtualKeyCode};
+use crate::events::GameEvent;
+use crate::net::MAX_CHAT_LEN;
+use crate::palette;
+use crate::render::{ColorSource, Model};
+use crate::ui::{ClickKind, Color, ColorBox, Coloring, Component, HoverMode, InputBox, TextBox, TextOverflow, TextSection, TextStyle};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI};
+use crate::{Game, i18n};
+
+// the chat sits at the bottom left corner of the screen, the messages are listed above the input box
+const CHAT_POS: (f32, f32) = (0.01, 0.01);
+const CHAT_WIDTH: f32 = 0.3;
+const INPUT_HEIGHT: f32 = 0.05;
+const LINE_HEIGHT: f32 = 0.035;
+const VISIBLE_LINES: usize = 6;
+const PADDING: f32 = 0.005;
+const TEXT_SCALE: f32 = 20.0;
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+const TRANSPARENT: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.0,
+};
+
+struct Message {
+    player: Option<usize>,
+    // the sender's name at the time the message was received
+    name: String,
+    text: String,
+}
+
+/// Lets the players of a networked game talk to each other, the messages are sent through the server
+/// which relays them to every client. Older messages can be scrolled back to with page up and page down
+/// while the input box is focused.
+// FIXME: scroll with the mouse wheel once components get the scroll delta
+pub struct Chat {
+    // the length of the event log when the chat looked for new messages the last time
+    seen: usize,
+    messages: Vec<Message>,
+    // how many of the newest messages are hidden below the list
+    scroll: usize,
+    input: InputBox<'static>,
+    texts: Vec<TextBox<'static>>,
+}
+
+impl Chat {
+
+    pub fn new() -> Self {
+        let input = InputBox::new(TextBox::new(
+            CHAT_POS,
+            CHAT_WIDTH,
+            INPUT_HEIGHT,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            // the last run shows the input, the first one is the prompt in front of it
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                .with_run(i18n::tr("chat.prompt"), TextStyle::new(TEXT_SCALE).with_color(WHITE).bold())
+                .with_run(String::new(), TextStyle::new(TEXT_SCALE).with_color(WHITE)),
+        ).with_overflow(TextOverflow::Clip), MAX_CHAT_LEN);
+        Self {
+            seen: 0,
+            messages: vec![],
+            scroll: 0,
+            input,
+            texts: vec![],
+        }
+    }
+
+    fn list_pos() -> (f32, f32) {
+        (CHAT_POS.0, CHAT_POS.1 + INPUT_HEIGHT)
+    }
+
+    fn build_texts(&mut self) {
+        self.texts.clear();
+        let end = self.messages.len() - self.scroll;
+        let start = end.saturating_sub(VISIBLE_LINES);
+        let (x, y) = Self::list_pos();
+        // the newest message is at the bottom, right above the input box
+        for (line, message) in self.messages[start..end].iter().rev().enumerate() {
+            let color = message.player.map_or(WHITE, palette::player_color);
+            self.texts.push(TextBox::new(
+                (x + PADDING, y + PADDING + line as f32 * LINE_HEIGHT),
+                CHAT_WIDTH - PADDING * 2.0,
+                LINE_HEIGHT,
+                Coloring::Color([TRANSPARENT; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                    .with_run(format!("{}: ", message.name), TextStyle::new(TEXT_SCALE).with_color(color).bold())
+                    .with_run(message.text.clone(), TextStyle::new(TEXT_SCALE).with_color(WHITE)),
+            ).with_overflow(TextOverflow::Ellipsis));
+        }
+    }
+
+    fn scroll_by(&mut self, lines: isize) {
+        let max = self.messages.len().saturating_sub(VISIBLE_LINES);
+        self.scroll = (self.scroll as isize + lines).clamp(0, max as isize) as usize;
+        self.build_texts();
+    }
+
+}
+
+impl Component for Chat {
+    fn build_model(&self) -> Model {
+        let (x, y) = Self::list_pos();
+        let list = ColorBox {
+            pos: (x, y),
+            width: CHAT_WIDTH,
+            height: LINE_HEIGHT * VISIBLE_LINES as f32 + PADDING * 2.0,
+            coloring: Coloring::Color([BACKDROP_UI; 4]),
+        };
+        let mut vertices = list.build_model().vertices;
+        vertices.extend(self.input.build_model().vertices);
+        Model {
+            vertices,
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn do_render(&self, game: &Arc<Game>) {
+        for text in self.texts.iter() {
+            text.do_render(game);
+        }
+        self.input.do_render(game);
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        CHAT_POS
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (CHAT_WIDTH, INPUT_HEIGHT + LINE_HEIGHT * VISIBLE_LINES as f32 + PADDING * 2.0)
+    }
+
+    fn on_click(&mut self, game: &Arc<Game>, click_kind: ClickKind, pos: (f32, f32)) {
+        // only the input box reacts to clicks, clicking the messages unfocuses it
+        if pos.1 < CHAT_POS.1 + INPUT_HEIGHT {
+            self.input.on_click(game, click_kind, pos);
+        } else {
+            self.input.on_click_outside(game);
+        }
+    }
+
+    fn on_click_outside(&mut self, game: &Arc<Game>) {
+        self.input.on_click_outside(game);
+    }
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        let received = {
+            let event_log = game.event_log.lock().unwrap();
+            if event_log.len() == self.seen {
+                return false;
+            }
+            let received = event_log[self.seen..].iter().filter_map(|event| match event {
+                GameEvent::ChatReceived { player, text } => Some((*player, text.clone())),
+                _ => None,
+            }).collect::<Vec<_>>();
+            self.seen = event_log.len();
+            received
+        };
+        if received.is_empty() {
+            return false;
+        }
+        let engine = game.engine.lock().unwrap();
+        for (player, text) in received {
+            let name = match player {
+                // the message is relayed right away, so it might arrive before the step which added its sender
+                Some(player) => engine.players.get(player).map_or_else(
+                    || i18n::tr_with("player.numbered", &[("number", &(player + 1).to_string())]),
+                    |sender| sender.display_name(),
+                ),
+                None => i18n::tr("chat.spectator"),
+            };
+            self.messages.push(Message {
+                player,
+                name,
+                text,
+            });
+            // the list stays where it is if older messages are being read
+            if self.scroll > 0 {
+                self.scroll += 1;
+            }
+        }
+        drop(engine);
+        self.build_texts();
+        false
+    }
+
+    fn on_key(&mut self, game: &Arc<Game>, key: VirtualKeyCode, down: bool, modifiers: ModifiersState) -> bool {
+        if !self.input.is_active() {
+            return false;
+        }
+        if down {
+            match key {
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                    let text = self.input.input().trim().to_string();
+                    if !text.is_empty() {
+                        game.chat(text);
+                        self.input.set_input(String::new());
+                    }
+                    // jump back to the newest messages, so the sent one shows up
+                    self.scroll_by(-(self.scroll as isize));
+                }
+                VirtualKeyCode::PageUp => {
+                    self.scroll_by(VISIBLE_LINES as isize);
+                    return true;
+                }
+                VirtualKeyCode::PageDown => {
+                    self.scroll_by(-(VISIBLE_LINES as isize));
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        self.input.on_key(game, key, down, modifiers)
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.input.is_focused()
+    }
+
+    fn set_focused(&mut self, game: &Arc<Game>, focused: bool) {
+        self.input.set_focused(game, focused);
+    }
+
+    fn on_char(&mut self, game: &Arc<Game>, received: char) -> bool {
+        self.input.on_char(game, received)
+    }
+}
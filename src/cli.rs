@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+use std::process;
+use crate::settings::Settings;
+
+const USAGE: &str = "Usage: schul-monopoly [options]
+
+Options:
+    --config <dir>          directory the config files are stored in (default: ./config/)
+    --fullscreen            start in fullscreen mode
+    --windowed              start in windowed mode
+    --size <width>x<height> size of the window
+    --seed <seed>           seed for the game's random number generator
+    --daily                 play today's challenge, which is the same game for everybody
+    --lang <language>       language of the user interface
+    --server                host a game without opening a window
+    --connect <host>        join the game hosted by the given server
+    --port <port>           port the game is hosted on (default: 7878)
+    --load <save>           load a saved game
+    --simulate <games>      simulate games between computer players and print statistics about them
+    --bench <frames>        render the board along a scripted camera path and print frame times
+    --help                  print this message";
+
+// values which are None weren't passed and don't override the settings
+#[derive(Default)]
+pub struct Args {
+    pub config_dir: Option<PathBuf>,
+    pub fullscreen: Option<bool>,
+    pub window_size: Option<(u32, u32)>,
+    pub seed: Option<u64>,
+    pub daily: bool,
+    pub language: Option<String>,
+    pub server: bool,
+    pub connect: Option<String>,
+    pub port: Option<u16>,
+    pub load: Option<PathBuf>,
+    // the number of games to simulate without opening a window
+    pub simulate: Option<usize>,
+    // the number of frames to measure in the benchmark
+    pub bench: Option<usize>,
+}
+
+impl Args {
+
+    pub fn apply_to(&self, settings: &mut Settings) {
+        if let Some(fullscreen) = self.fullscreen {
+            settings.window.fullscreen = fullscreen;
+        }
+        if let Some(size) = self.window_size {
+            settings.window.size = Some(size);
+        }
+        if let Some(language) = &self.language {
+            settings.language = language.clone();
+        }
+    }
+
+}
+
+// prints the error along with the usage and exits
+fn invalid(err: &str) -> ! {
+    println!("{}\n\n{}", err, USAGE);
+    process::exit(1);
+}
+
+fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> String {
+    args.next().unwrap_or_else(|| invalid(&format!("Missing value for {}", flag)))
+}
+
+pub fn parse_args() -> Args {
+    let mut ret = Args::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => ret.config_dir = Some(PathBuf::from(value(&mut args, &arg))),
+            "--fullscreen" => ret.fullscreen = Some(true),
+            "--windowed" => ret.fullscreen = Some(false),
+            "--size" => {
+                let size = value(&mut args, &arg);
+                let parsed = size.split_once('x')
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)));
+                match parsed {
+                    Some(size) => ret.window_size = Some(size),
+                    None => invalid(&format!("Invalid window size \"{}\"", size)),
+                }
+            }
+            "--seed" => {
+                let seed = value(&mut args, &arg);
+                ret.seed = Some(seed.parse().unwrap_or_else(|_| invalid(&format!("Invalid seed \"{}\"", seed))));
+            }
+            "--daily" => ret.daily = true,
+            "--lang" => ret.language = Some(value(&mut args, &arg)),
+            "--server" => ret.server = true,
+            "--connect" => ret.connect = Some(value(&mut args, &arg)),
+            "--port" => {
+                let port = value(&mut args, &arg);
+                ret.port = Some(port.parse().unwrap_or_else(|_| invalid(&format!("Invalid port \"{}\"", port))));
+            }
+            "--load" => ret.load = Some(PathBuf::from(value(&mut args, &arg))),
+            "--simulate" => {
+                let games = value(&mut args, &arg);
+                ret.simulate = Some(games.parse().unwrap_or_else(|_| invalid(&format!("Invalid number of games \"{}\"", games))));
+            }
+            "--bench" => {
+                let frames = value(&mut args, &arg);
+                ret.bench = Some(frames.parse().unwrap_or_else(|_| invalid(&format!("Invalid number of frames \"{}\"", frames))));
+            }
+            "--help" => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            _ => invalid(&format!("Unknown argument \"{}\"", arg)),
+        }
+    }
+    if ret.daily && ret.seed.is_some() {
+        invalid("The daily challenge has a seed of its own, so --seed can't be used along with --daily");
+    }
+    ret
+}
@@ -0,0 +1,54 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::action_cards;
+use crate::board::Board;
+use crate::engine::Engine;
+use crate::profile::{self, DailyResult};
+use crate::rules::Rules;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+// the day of the challenge, counted in days since the unix epoch (UTC), so it changes at the same time for everybody
+pub fn today() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() / SECONDS_PER_DAY
+}
+
+// consecutive days shouldn't result in similar seeds, so the day gets mixed (splitmix64)
+pub fn seed(day: u64) -> u64 {
+    let mut seed = day.wrapping_add(0x9E3779B97F4A7C15);
+    seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D049BB133111EB);
+    seed ^ (seed >> 31)
+}
+
+/// The engine for the day's challenge. Everybody plays the same game on the same day,
+/// so the default board, cards and rules are used instead of the local configs and mods.
+pub fn engine(day: u64) -> Engine {
+    Engine::new(Board::default(), action_cards::default_cards(), Rules::default(), Some(seed(day)))
+}
+
+// the day as year-month-day, see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn date(day: u64) -> String {
+    let days = day as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day_of_month)
+}
+
+// adds the result of the finished challenge to the player's profile
+pub fn record(day: u64, engine: &Engine) {
+    let mut profile = profile::load_profile();
+    profile.daily_results.push(DailyResult {
+        date: date(day),
+        seed: engine.seed,
+        turns: engine.turns,
+        winner: engine.winner(),
+        net_worths: (0..engine.players.len()).map(|player| engine.net_worth(player)).collect(),
+    });
+    profile::save_profile(&profile);
+}
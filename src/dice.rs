@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// rolling this many doubles in a row sends the player to jail
+pub const MAX_DOUBLES: usize = 3;
+
+/// The two dice which are rolled every turn. They keep track of the doubles the current
+/// player rolled in a row, as a double lets them roll again.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Dice {
+    // the result of the last roll, None until the first roll of the game
+    pub last: Option<(u8, u8)>,
+    // the doubles the current player rolled in a row during their turn
+    pub doubles: usize,
+    // rolls which are used instead of random ones until they're used up
+    #[serde(skip)]
+    scripted: VecDeque<(u8, u8)>,
+}
+
+impl Dice {
+
+    pub fn roll(&mut self, rng: &mut impl Rng) -> (u8, u8) {
+        let dice = self.scripted.pop_front().unwrap_or_else(|| (rng.gen_range(1..=6), rng.gen_range(1..=6)));
+        self.last = Some(dice);
+        dice
+    }
+
+    // the next rolls of the dice are taken from the script, so tests can play out specific situations
+    #[cfg(test)]
+    pub fn script(&mut self, rolls: impl IntoIterator<Item = (u8, u8)>) {
+        self.scripted.extend(rolls);
+    }
+
+    // the eyes of the last roll, used to calculate the rent of special properties
+    pub fn sum(&self) -> usize {
+        self.last.map_or(0, |(first, second)| (first + second) as usize)
+    }
+
+    pub fn is_double(&self) -> bool {
+        self.last.map_or(false, |(first, second)| first == second)
+    }
+
+    // counts the last roll if it was a double, returns whether the player rolled too many of them
+    pub fn count_double(&mut self) -> bool {
+        if !self.is_double() {
+            self.doubles = 0;
+            return false;
+        }
+        self.doubles += 1;
+        self.doubles >= MAX_DOUBLES
+    }
+
+    // the turn passes on to the next player
+    pub fn reset(&mut self) {
+        self.doubles = 0;
+    }
+
+}
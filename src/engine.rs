@@ -0,0 +1,1646 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::mem;
+use std::mem::MaybeUninit;
+use std::time::Duration;
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use crate::action_cards::{Action, ActionCard, CardStack, NearestKind};
+use crate::board::{Board, Tile, TILES};
+use crate::card_script::{self, ScriptEffect};
+use crate::dice::Dice;
+use crate::events::{CommandError, GameCommand, GameEvent, Immunity, Trade};
+use crate::player::Player;
+use crate::property::{DefinedProperty, PropertyType, MAX_HOUSES, PROPERTIES};
+use crate::rules::{PropertySetup, Rules};
+
+pub const INITIAL_CURRENCY: usize = 400; // TODO: make this configurable!
+// the amount a player receives when passing the start tile
+const START_BONUS: usize = 40;
+// the number of throws a player has to roll doubles before they have to pay their way out of jail
+pub const JAIL_THROWS: usize = 3;
+pub const JAIL_FINE: usize = 10;
+
+// the part of the game the engine is in, it decides which commands are accepted and whose turn it is
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Phase {
+    // players may still join the game
+    Lobby,
+    // the players pick their properties before the first turn, see `PropertySetup::Drafted`
+    Drafting,
+    // the current player has to roll the dice
+    Rolling,
+    // the current player landed on a property nobody owns and has to decide whether to buy it
+    Buying {
+        property: usize,
+    },
+    // the current player may build houses and offer trades before ending their turn
+    Acting,
+    // the current player landed on an income tax tile and chooses between paying the flat amount or the percentage
+    ChoosingTax {
+        flat: usize,
+        percentage: usize,
+    },
+    // a building is auctioned off because of a shortage or a property because its owner went bankrupt to the bank,
+    // afterwards the current player continues with the phase the auction interrupted
+    Auction,
+    Finished,
+}
+
+// what a player may do at a given point of the game, every action stands for the commands which carry it out
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TurnAction {
+    Draft,
+    Roll,
+    // paying the fine or using a jail free card before rolling
+    LeaveJail,
+    // buying or declining the property the player landed on
+    Buy,
+    PayTax,
+    // bidding or passing
+    Bid,
+    // building and selling houses as well as (un)mortgaging properties
+    Manage,
+    OfferTrade,
+    AnswerTrade,
+    EndTurn,
+}
+
+// an immunity which was traded to the holder, it ends once the property changes its owner
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RentImmunity {
+    pub holder: usize,
+    pub property: usize,
+    pub landings: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum AuctionLot {
+    // the bank ran short of buildings, the highest bidder gets to build the next one
+    Building {
+        // whether a hotel is auctioned instead of a house
+        hotel: bool,
+    },
+    // the property belonged to a player who went bankrupt to the bank
+    Property {
+        property: usize,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Auction {
+    pub lot: AuctionLot,
+    pub bidders: Vec<usize>,
+    pub passed: Vec<usize>,
+    // the highest bidder along with the property they want to build on (or the auctioned property) and their bid
+    pub highest: Option<(usize, usize, usize)>,
+    // the phase the auction interrupted, the game continues with it once the auction is over
+    #[serde(default = "Auction::default_resume")]
+    pub resume: Phase,
+}
+
+impl Auction {
+
+    // games saved before the interrupted phase was stored always continued with acting
+    fn default_resume() -> Phase {
+        Phase::Acting
+    }
+
+    // the bidders who may still raise the bid
+    pub fn active_bidders(&self) -> impl Iterator<Item = usize> + '_ {
+        self.bidders.iter().copied().filter(|bidder| !self.passed.contains(bidder))
+    }
+
+}
+
+// the rules of the game, this has no knowledge about rendering or audio, so it can be driven
+// by the UI as well as by the headless simulation. All changes happen through `apply`.
+pub struct Engine {
+    pub board: Board,
+    pub players: Vec<Player>,
+    pub properties: [DefinedProperty; PROPERTIES],
+    pub cards: Vec<ActionCard>,
+    pub card_stacks: [CardStack; 2],
+    pub curr_player: usize,
+    pub phase: Phase,
+    pub pending_trade: Option<Trade>,
+    pub auction: Option<Auction>,
+    // properties of players who went bankrupt to the bank, these are auctioned off one after another
+    pub pending_auctions: Vec<usize>,
+    pub immunities: Vec<RentImmunity>,
+    pub rules: Rules,
+    // the scripts which run when a token lands on a custom tile, indexed by the tile's kind, see `mods::Mods`
+    pub tile_scripts: HashMap<String, String>,
+    // the money which is waiting on the parking tile, only used if the free parking rule is enabled
+    pub parking_pot: usize,
+    // the number of turns that were played so far
+    pub turns: usize,
+    // the number of rounds in which every player who is still in the game had their turn
+    pub rounds: usize,
+    // all randomness of the game has to come from here so games can be reproduced from their seed
+    pub rng: StdRng,
+    pub seed: u64,
+    pub dice: Dice,
+    // the command which is applied for the deciding player once their time ran out, along with the time that's left
+    decision_timer: Option<(GameCommand, Duration)>,
+    // events which weren't picked up by `take_events` yet
+    events: Vec<GameEvent>,
+}
+
+impl Engine {
+
+    // if no seed is provided, a random one is picked, it's kept so the game can be reproduced later on
+    pub fn new(board: Board, cards: Vec<ActionCard>, rules: Rules, seed: Option<u64>) -> Self {
+        let seed = seed.unwrap_or_else(rand::random);
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut properties = MaybeUninit::uninit_array();
+        let mut idx = 0;
+        for tile in board.tiles.iter() {
+            if let Tile::Property { property } = tile {
+                properties[idx].write(DefinedProperty {
+                    frame: property.clone(),
+                    houses: 0,
+                    owner: None,
+                    mortgaged: false,
+                });
+                idx += 1;
+            }
+        }
+
+        let mut first_card_stack = vec![];
+        for _ in 0..(cards.len() / 2) {
+            first_card_stack.push(rng.gen_range(0..(cards.len())));
+        }
+        let mut second_card_stack = vec![];
+        for x in 0..cards.len() {
+            if !first_card_stack.contains(&x) {
+                second_card_stack.push(x);
+            }
+        }
+
+        Self {
+            board,
+            players: vec![],
+            properties: unsafe { MaybeUninit::array_assume_init(properties) },
+            cards,
+            card_stacks: [CardStack::new(first_card_stack), CardStack::new(second_card_stack)],
+            curr_player: 0,
+            phase: Phase::Lobby,
+            pending_trade: None,
+            auction: None,
+            pending_auctions: vec![],
+            immunities: vec![],
+            rules,
+            tile_scripts: HashMap::new(),
+            parking_pot: 0,
+            turns: 0,
+            rounds: 0,
+            rng,
+            seed,
+            dice: Dice::default(),
+            decision_timer: None,
+            events: vec![],
+        }
+    }
+
+    // returns all events that happened since the last call
+    pub fn take_events(&mut self) -> Vec<GameEvent> {
+        mem::take(&mut self.events)
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        self.events.push(event);
+    }
+
+    pub fn remaining_players(&self) -> usize {
+        self.players.iter().filter(|player| !player.bankrupt).count()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.phase == Phase::Finished
+    }
+
+    // if the game was ended by the round limit, the richest of the remaining players wins
+    pub fn winner(&self) -> Option<usize> {
+        if !self.is_finished() {
+            return None;
+        }
+        self.players.iter().filter(|player| !player.bankrupt).max_by_key(|player| self.net_worth(player.id)).map(|player| player.id)
+    }
+
+    // the winner comes first, followed by the others ordered by their net worth, bankrupt players come last
+    pub fn ranking(&self) -> Vec<usize> {
+        let winner = self.winner();
+        let mut players = (0..self.players.len()).collect::<Vec<_>>();
+        players.sort_by_key(|player| (self.players[*player].bankrupt, Some(*player) != winner, Reverse(self.net_worth(*player))));
+        players
+    }
+
+    fn finish(&mut self) {
+        self.phase = Phase::Finished;
+        self.pending_auctions.clear();
+        let winner = self.winner();
+        self.emit(GameEvent::GameFinished {
+            winner,
+        });
+    }
+
+    // validates the command and applies it if it's valid, rejected commands are reported through
+    // a `CommandRejected` event as well
+    pub fn apply(&mut self, command: GameCommand) -> Result<(), CommandError> {
+        let result = self.try_apply(command.clone());
+        match result {
+            Ok(()) => {
+                self.end_bankrupt_turn();
+                self.update_decision_timer();
+            }
+            Err(error) => self.emit(GameEvent::CommandRejected {
+                command,
+                error,
+            }),
+        }
+        result
+    }
+
+    // advances the decision timer, if it ran out the default decision is taken
+    pub fn tick(&mut self, elapsed: Duration) {
+        let expired = match &mut self.decision_timer {
+            Some((_, left)) => {
+                *left = left.saturating_sub(elapsed);
+                left.is_zero()
+            }
+            None => false,
+        };
+        if expired {
+            let (command, _) = self.decision_timer.take().unwrap();
+            // the default decision is taken for whoever had to decide, e.g. the receiver of a trade
+            if let Some(player) = command.player() {
+                self.emit(GameEvent::DecisionTimedOut {
+                    player,
+                });
+            }
+            let _ = self.apply(command);
+        }
+    }
+
+    // what happens if the player who has to decide next doesn't do so in time
+    fn default_decision(&self) -> Option<GameCommand> {
+        if let Some(trade) = &self.pending_trade {
+            return Some(GameCommand::DeclineTrade {
+                player: trade.to,
+            });
+        }
+        match self.phase {
+            Phase::Buying { .. } => Some(GameCommand::DeclineProperty {
+                player: self.curr_player,
+            }),
+            Phase::ChoosingTax { .. } => Some(GameCommand::PayTax {
+                player: self.curr_player,
+                percentage: false,
+            }),
+            Phase::Auction => Some(GameCommand::PassBid {
+                player: self.next_actor(),
+            }),
+            Phase::Drafting => self.properties.iter().position(|property| property.owner.is_none())
+                .map(|property| GameCommand::DraftProperty {
+                    player: self.curr_player,
+                    property,
+                }),
+            Phase::Lobby | Phase::Rolling | Phase::Acting | Phase::Finished => None,
+        }
+    }
+
+    // whether a decision timer is running, ticking the engine doesn't change anything otherwise
+    pub fn is_timed(&self) -> bool {
+        self.decision_timer.is_some()
+    }
+
+    // restarts the timer whenever somebody else has to decide or the decision changed,
+    // restored games give the deciding player the full time again, see `save::SaveGame::restore`
+    pub fn update_decision_timer(&mut self) {
+        let seconds = match self.rules.decision_timer {
+            Some(seconds) => seconds,
+            None => return,
+        };
+        let decision = self.default_decision();
+        if self.decision_timer.as_ref().map(|(command, _)| command) != decision.as_ref() {
+            self.decision_timer = decision.map(|command| (command, Duration::from_secs(seconds)));
+        }
+    }
+
+    fn try_apply(&mut self, command: GameCommand) -> Result<(), CommandError> {
+        match command {
+            GameCommand::AddPlayer { character } => {
+                self.expect_phase(Phase::Lobby)?;
+                self.add_player(character);
+            }
+            GameCommand::StartGame => {
+                self.expect_phase(Phase::Lobby)?;
+                if self.players.is_empty() {
+                    return Err(CommandError::NotEnoughPlayers);
+                }
+                self.emit(GameEvent::GameStarted);
+                if self.rules.free_parking.enabled {
+                    self.parking_pot = self.rules.free_parking.seed;
+                }
+                if self.rules.shuffle_board {
+                    self.shuffle_board();
+                }
+                match self.rules.setup {
+                    PropertySetup::Dealt => {
+                        self.deal_properties();
+                        self.start_turn(0);
+                    }
+                    // with more players than properties nobody would get one
+                    PropertySetup::Drafted if self.draft_size() > 0 => {
+                        self.phase = Phase::Drafting;
+                        self.curr_player = self.draft_picker();
+                    }
+                    PropertySetup::Buying | PropertySetup::Drafted => self.start_turn(0),
+                }
+            }
+            GameCommand::DraftProperty { player, property } => {
+                self.expect_turn(player)?;
+                self.expect_phase(Phase::Drafting)?;
+                if property >= PROPERTIES || self.properties[property].owner.is_some() {
+                    return Err(CommandError::NotAvailable);
+                }
+                self.give_property(player, property);
+                if self.drafted_properties() < self.draft_size() {
+                    self.curr_player = self.draft_picker();
+                } else {
+                    self.start_turn(0);
+                }
+            }
+            GameCommand::RollDice { player } => {
+                self.expect_turn(player)?;
+                self.expect_phase(Phase::Rolling)?;
+                self.roll(player);
+            }
+            GameCommand::BuyProperty { player } => {
+                self.expect_turn(player)?;
+                let property = match self.phase {
+                    Phase::Buying { property } => property,
+                    _ => return Err(CommandError::WrongPhase),
+                };
+                let price = self.properties[property].frame.buy_price;
+                if self.players[player].currency < price {
+                    return Err(CommandError::NotEnoughMoney);
+                }
+                self.pay(player, price);
+                self.properties[property].owner = Some(player);
+                self.players[player].properties.push(property);
+                self.emit(GameEvent::PropertyBought {
+                    player,
+                    property,
+                    price,
+                });
+                self.phase = Phase::Acting;
+            }
+            GameCommand::DeclineProperty { player } => {
+                self.expect_turn(player)?;
+                if !matches!(self.phase, Phase::Buying { .. }) {
+                    return Err(CommandError::WrongPhase);
+                }
+                self.phase = Phase::Acting;
+            }
+            GameCommand::BuildHouse { player, property } => {
+                self.expect_turn(player)?;
+                if self.phase != Phase::Rolling && self.phase != Phase::Acting {
+                    return Err(CommandError::WrongPhase);
+                }
+                self.check_build(player, property)?;
+                let hotel = self.needs_hotel(property);
+                let bidders = self.players_wanting_to_build(hotel);
+                // first come first served only works as long as the bank has enough buildings for everybody
+                if bidders.len() > 1 && bidders.len() > self.buildings_left(hotel) {
+                    self.start_auction(bidders, AuctionLot::Building {
+                        hotel,
+                    }, self.phase);
+                } else {
+                    self.build(player, property, self.house_price(property));
+                }
+            }
+            GameCommand::SellHouse { player, property } => {
+                self.expect_turn(player)?;
+                if self.phase != Phase::Rolling && self.phase != Phase::Acting {
+                    return Err(CommandError::WrongPhase);
+                }
+                self.check_sell(player, property)?;
+                // the hotel gets replaced by the houses it consisted of minus the sold one
+                self.properties[property].houses -= 1;
+                self.receive(player, self.house_price(property) / 2);
+                self.emit(GameEvent::BuildingSold {
+                    player,
+                    property,
+                });
+            }
+            GameCommand::Mortgage { player, property } => {
+                self.expect_turn(player)?;
+                if self.phase != Phase::Rolling && self.phase != Phase::Acting {
+                    return Err(CommandError::WrongPhase);
+                }
+                self.check_mortgage(player, property)?;
+                self.properties[property].mortgaged = true;
+                self.receive(player, self.properties[property].mortgage_value());
+                self.emit(GameEvent::PropertyMortgaged {
+                    player,
+                    property,
+                });
+            }
+            GameCommand::Unmortgage { player, property } => {
+                self.expect_turn(player)?;
+                if self.phase != Phase::Rolling && self.phase != Phase::Acting {
+                    return Err(CommandError::WrongPhase);
+                }
+                if self.properties.get(property).and_then(|property| property.owner) != Some(player) {
+                    return Err(CommandError::NotOwner);
+                }
+                if !self.properties[property].mortgaged {
+                    return Err(CommandError::NotMortgaged);
+                }
+                let price = self.properties[property].unmortgage_price();
+                if self.players[player].currency < price {
+                    return Err(CommandError::NotEnoughMoney);
+                }
+                self.pay(player, price);
+                self.properties[property].mortgaged = false;
+                self.emit(GameEvent::PropertyUnmortgaged {
+                    player,
+                    property,
+                });
+            }
+            GameCommand::Bid { player, property, amount } => {
+                self.expect_phase(Phase::Auction)?;
+                let auction = self.auction.as_ref().unwrap();
+                if !auction.active_bidders().any(|bidder| bidder == player) {
+                    return Err(CommandError::NotBidding);
+                }
+                let min_bid = match auction.lot {
+                    AuctionLot::Building { hotel } => {
+                        self.check_build(player, property)?;
+                        if self.needs_hotel(property) != hotel {
+                            return Err(CommandError::CannotBuild);
+                        }
+                        self.house_price(property)
+                    }
+                    AuctionLot::Property { property: auctioned } => {
+                        if property != auctioned {
+                            return Err(CommandError::NotBidding);
+                        }
+                        1
+                    }
+                };
+                if auction.highest.map_or(false, |(_, _, highest)| amount <= highest) || amount < min_bid {
+                    return Err(CommandError::BidTooLow);
+                }
+                if self.players[player].currency < amount {
+                    return Err(CommandError::NotEnoughMoney);
+                }
+                self.auction.as_mut().unwrap().highest = Some((player, property, amount));
+                self.emit(GameEvent::BidPlaced {
+                    player,
+                    amount,
+                });
+                self.finish_auction_if_decided();
+            }
+            GameCommand::PassBid { player } => {
+                self.expect_phase(Phase::Auction)?;
+                let auction = self.auction.as_mut().unwrap();
+                if !auction.active_bidders().any(|bidder| bidder == player) {
+                    return Err(CommandError::NotBidding);
+                }
+                auction.passed.push(player);
+                self.emit(GameEvent::BidPassed {
+                    player,
+                });
+                self.finish_auction_if_decided();
+            }
+            GameCommand::PayTax { player, percentage: pay_percentage } => {
+                self.expect_turn(player)?;
+                let (flat, percentage) = match self.phase {
+                    Phase::ChoosingTax { flat, percentage } => (flat, percentage),
+                    _ => return Err(CommandError::WrongPhase),
+                };
+                let amount = if pay_percentage {
+                    percentage
+                } else {
+                    flat
+                };
+                self.phase = Phase::Acting;
+                self.pay_fine(player, amount, self.rules.free_parking.collect_taxes);
+            }
+            GameCommand::OfferTrade { trade } => {
+                self.expect_turn(trade.from)?;
+                if self.phase != Phase::Rolling && self.phase != Phase::Acting {
+                    return Err(CommandError::WrongPhase);
+                }
+                if self.pending_trade.is_some() {
+                    return Err(CommandError::InvalidTrade);
+                }
+                self.check_trade(&trade)?;
+                self.pending_trade = Some(trade.clone());
+                self.emit(GameEvent::TradeOffered {
+                    trade,
+                });
+            }
+            GameCommand::AcceptTrade { player } => {
+                let trade = match &self.pending_trade {
+                    Some(trade) if trade.to == player => trade.clone(),
+                    _ => return Err(CommandError::NoPendingTrade),
+                };
+                // the trade may have become invalid since it was offered
+                self.check_trade(&trade)?;
+                self.pending_trade = None;
+                self.execute_trade(&trade);
+                self.emit(GameEvent::TradeAccepted {
+                    trade,
+                });
+            }
+            GameCommand::DeclineTrade { player } => {
+                match &self.pending_trade {
+                    Some(trade) if trade.to == player || trade.from == player => {}
+                    _ => return Err(CommandError::NoPendingTrade),
+                }
+                let trade = self.pending_trade.take().unwrap();
+                self.emit(GameEvent::TradeDeclined {
+                    trade,
+                });
+            }
+            GameCommand::PayBail { player } => {
+                self.expect_turn(player)?;
+                self.expect_phase(Phase::Rolling)?;
+                self.expect_jailed(player)?;
+                if self.players[player].currency < JAIL_FINE {
+                    return Err(CommandError::NotEnoughMoney);
+                }
+                self.pay(player, JAIL_FINE);
+                self.release_from_jail(player);
+            }
+            GameCommand::UseJailFreeCard { player } => {
+                self.expect_turn(player)?;
+                self.expect_phase(Phase::Rolling)?;
+                self.expect_jailed(player)?;
+                if self.players[player].jail_free_cards == 0 {
+                    return Err(CommandError::NoJailFreeCard);
+                }
+                self.players[player].jail_free_cards -= 1;
+                self.release_from_jail(player);
+            }
+            GameCommand::EndTurn { player } => {
+                self.expect_turn(player)?;
+                self.expect_phase(Phase::Acting)?;
+                // a double lets the player roll once more, unless it got them into jail
+                if self.dice.doubles > 0 && !self.players[player].is_jailed() && !self.players[player].bankrupt {
+                    self.phase = Phase::Rolling;
+                    self.emit(GameEvent::RollingAgain {
+                        player,
+                    });
+                } else {
+                    self.next_turn();
+                }
+            }
+            GameCommand::Emote { player, emote } => {
+                // players who went bankrupt are only watching
+                if self.phase == Phase::Lobby || self.players.get(player).map_or(true, |player| player.bankrupt) {
+                    return Err(CommandError::WrongPhase);
+                }
+                self.emit(GameEvent::Emoted {
+                    player,
+                    emote,
+                });
+            }
+            GameCommand::LeaveGame => {
+                self.reset();
+                self.emit(GameEvent::GameLeft);
+            }
+        }
+        Ok(())
+    }
+
+    fn expect_phase(&self, phase: Phase) -> Result<(), CommandError> {
+        if self.phase != phase {
+            return Err(CommandError::WrongPhase);
+        }
+        Ok(())
+    }
+
+    fn expect_jailed(&self, player: usize) -> Result<(), CommandError> {
+        if !self.players[player].is_jailed() {
+            return Err(CommandError::NotJailed);
+        }
+        Ok(())
+    }
+
+    fn expect_turn(&self, player: usize) -> Result<(), CommandError> {
+        if matches!(self.phase, Phase::Lobby | Phase::Finished) {
+            return Err(CommandError::WrongPhase);
+        }
+        if player != self.curr_player {
+            return Err(CommandError::NotYourTurn);
+        }
+        Ok(())
+    }
+
+    fn add_player(&mut self, character: usize) {
+        let len = self.players.len();
+        self.players.push(Player {
+            name: String::new(), // FIXME: implement text fields to enable players to choose names.
+            currency: INITIAL_CURRENCY,
+            id: len,
+            character_id: character,
+            properties: vec![],
+            position: self.board.index.start,
+            jail_free_cards: 0,
+            jail_free_throws: 0,
+            wait: 0,
+            bankrupt: false,
+        });
+        self.emit(GameEvent::PlayerJoined {
+            player: len,
+            character,
+        });
+    }
+
+    // a new game with the same board, cards and rules, which is seeded anew
+    pub fn rematch(&self) -> Engine {
+        let mut engine = Engine::new(self.board.clone(), self.cards.clone(), self.rules.clone(), None);
+        engine.tile_scripts = self.tile_scripts.clone();
+        engine
+    }
+
+    fn reset(&mut self) {
+        self.players.clear();
+        for property in self.properties.iter_mut() {
+            property.owner = None;
+            property.houses = 0;
+            property.mortgaged = false;
+        }
+        self.curr_player = 0;
+        self.phase = Phase::Lobby;
+        self.pending_trade = None;
+        self.auction = None;
+        self.pending_auctions.clear();
+        self.immunities.clear();
+        self.parking_pot = 0;
+        self.turns = 0;
+        self.rounds = 0;
+        self.dice = Dice::default();
+    }
+
+    // only the property tiles swap places, cards which move players to a tile move them to whatever is there now
+    fn shuffle_board(&mut self) {
+        let slots = self.board.tiles.iter().enumerate()
+            .filter(|(_, tile)| matches!(tile, Tile::Property { .. }))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        let mut shuffled = slots.clone();
+        shuffled.shuffle(&mut self.rng);
+        let mut layout: [usize; TILES] = std::array::from_fn(|idx| idx);
+        for (slot, from) in slots.into_iter().zip(shuffled) {
+            layout[slot] = from;
+        }
+        self.board.rearrange(&layout);
+        self.emit(GameEvent::BoardShuffled {
+            layout,
+        });
+    }
+
+    // every player gets the same amount of properties, so a few may stay with the bank
+    fn draft_size(&self) -> usize {
+        PROPERTIES / self.players.len() * self.players.len()
+    }
+
+    fn drafted_properties(&self) -> usize {
+        self.properties.iter().filter(|property| property.owner.is_some()).count()
+    }
+
+    // the order of the picks reverses every round, so the last player of a round picks twice in a row
+    fn draft_picker(&self) -> usize {
+        let picks = self.drafted_properties();
+        let players = self.players.len();
+        let round = picks / players;
+        if round % 2 == 0 {
+            picks % players
+        } else {
+            players - 1 - picks % players
+        }
+    }
+
+    fn deal_properties(&mut self) {
+        let mut properties = (0..PROPERTIES).collect::<Vec<_>>();
+        properties.shuffle(&mut self.rng);
+        let size = self.draft_size();
+        for (idx, property) in properties.into_iter().take(size).enumerate() {
+            self.give_property(idx % self.players.len(), property);
+        }
+    }
+
+    fn give_property(&mut self, player: usize, property: usize) {
+        self.properties[property].owner = Some(player);
+        self.players[player].properties.push(property);
+        self.emit(GameEvent::PropertyReceived {
+            player,
+            property,
+        });
+    }
+
+    fn start_turn(&mut self, player: usize) {
+        self.curr_player = player;
+        self.turns += 1;
+        self.dice.reset();
+        self.phase = Phase::Rolling;
+        self.emit(GameEvent::TurnStarted {
+            player,
+        });
+    }
+
+    // a bankrupt player can't do anything anymore, so their turn ends by itself once the auctions of their properties are over
+    fn end_bankrupt_turn(&mut self) {
+        let bankrupt = self.players.get(self.curr_player).map_or(false, |player| player.bankrupt);
+        if bankrupt && matches!(self.phase, Phase::Rolling | Phase::Acting) {
+            self.next_turn();
+        }
+    }
+
+    // passes the turn on to the next player who isn't bankrupt, players who have to wait are skipped
+    fn next_turn(&mut self) {
+        if let Some(trade) = self.pending_trade.take() {
+            self.emit(GameEvent::TradeDeclined {
+                trade,
+            });
+        }
+        if self.is_finished() {
+            return;
+        }
+        let mut player = self.curr_player;
+        // the number of bankrupt players in a row, after a full pass of them nobody is left to take the turn
+        let mut skipped = 0;
+        loop {
+            if skipped == self.players.len() {
+                self.finish();
+                return;
+            }
+            player = (player + 1) % self.players.len();
+            if self.players[player].bankrupt {
+                skipped += 1;
+                continue;
+            }
+            skipped = 0;
+            if self.players[player].wait > 0 {
+                self.players[player].wait -= 1;
+                continue;
+            }
+            break;
+        }
+        // the turn order wrapped around, so the round is over
+        if player <= self.curr_player {
+            self.rounds += 1;
+            if self.rules.round_limit.map_or(false, |limit| self.rounds >= limit) {
+                self.finish();
+                return;
+            }
+        }
+        self.start_turn(player);
+    }
+
+    // the next rolls of the dice are taken from the script, so tests can play out specific situations
+    #[cfg(test)]
+    pub fn script_rolls(&mut self, rolls: impl IntoIterator<Item = (u8, u8)>) {
+        self.dice.script(rolls);
+    }
+
+    fn roll(&mut self, player: usize) {
+        let dice = self.dice.roll(&mut self.rng);
+        self.emit(GameEvent::DiceRolled {
+            player,
+            dice,
+        });
+        self.phase = Phase::Acting;
+
+        // a jail free card is only used if the player asks for it before rolling
+        if self.players[player].is_jailed() {
+            if dice.0 != dice.1 {
+                self.players[player].jail_free_throws -= 1;
+                if self.players[player].is_jailed() {
+                    return;
+                }
+                if !self.pay(player, JAIL_FINE) {
+                    return;
+                }
+            }
+            self.release_from_jail(player);
+        // doubles which get the player out of jail don't count, as they don't let them roll again
+        } else if self.dice.count_double() {
+            // the token doesn't move, too many doubles in a row send it straight to jail
+            self.dice.reset();
+            self.send_to_jail(player);
+            return;
+        }
+
+        self.move_by(player, self.dice.sum());
+        self.land(player);
+    }
+
+    // moves the player's token forward tile by tile, passing the start tile is rewarded
+    fn move_by(&mut self, player: usize, tiles: usize) {
+        for _ in 0..tiles {
+            let tile = (self.players[player].position + 1) % TILES;
+            self.players[player].position = tile;
+            self.emit(GameEvent::TokenHopped {
+                player,
+                tile,
+            });
+            if tile == self.board.index.start {
+                self.receive(player, START_BONUS);
+            }
+        }
+    }
+
+    fn move_to(&mut self, player: usize, tile: usize) {
+        let position = self.players[player].position;
+        self.move_by(player, (tile + TILES - position) % TILES);
+    }
+
+    // moving backwards never passes the start tile
+    fn move_back(&mut self, player: usize, tiles: usize) {
+        let tile = (self.players[player].position + TILES - tiles % TILES) % TILES;
+        self.players[player].position = tile;
+        self.emit(GameEvent::TokenHopped {
+            player,
+            tile,
+        });
+    }
+
+    fn release_from_jail(&mut self, player: usize) {
+        self.players[player].jail_free_throws = 0;
+        self.emit(GameEvent::ReleasedFromJail {
+            player,
+        });
+    }
+
+    fn send_to_jail(&mut self, player: usize) {
+        self.players[player].position = self.board.index.jail;
+        self.players[player].jail_free_throws = JAIL_THROWS;
+        self.emit(GameEvent::WentToJail {
+            player,
+        });
+    }
+
+    // resolves the effects of the tile the player's token is on
+    fn land(&mut self, player: usize) {
+        let tile = self.players[player].position;
+        self.emit(GameEvent::TokenLanded {
+            player,
+            tile,
+        });
+        match &self.board.tiles[tile] {
+            Tile::Property { property } => {
+                let property = property.id;
+                match self.properties[property].owner {
+                    // the player decides whether to buy it through the next command
+                    None => self.phase = Phase::Buying {
+                        property,
+                    },
+                    Some(owner) if owner != player => {
+                        if self.properties[property].mortgaged {
+                            // no rent is due
+                        } else if self.use_immunity(player, property) {
+                            self.emit(GameEvent::RentWaived {
+                                player,
+                                property,
+                            });
+                        } else {
+                            let rent = self.rent(property, self.dice.sum());
+                            if self.transfer(player, owner, rent) {
+                                self.emit(GameEvent::RentPaid {
+                                    player,
+                                    owner,
+                                    property,
+                                    amount: rent,
+                                });
+                            }
+                        }
+                    }
+                    Some(_) => {}
+                }
+            }
+            Tile::Pay { amount, income_tax: true, .. } => {
+                let flat = *amount;
+                let percentage = self.net_worth(player) * self.rules.income_tax.percentage / 100;
+                // the player decides through the next command
+                self.phase = Phase::ChoosingTax {
+                    flat,
+                    percentage,
+                };
+                self.emit(GameEvent::TaxChoiceOffered {
+                    player,
+                    flat,
+                    percentage,
+                });
+            }
+            Tile::Pay { amount, .. } => {
+                let amount = *amount;
+                self.pay_fine(player, amount, self.rules.free_parking.collect_taxes);
+            }
+            Tile::DrawCard { kind } => {
+                let kind = *kind as usize;
+                let card = self.card_stacks[kind].draw(&mut self.rng);
+                self.emit(GameEvent::CardDrawn {
+                    player,
+                    card,
+                });
+                self.apply_action(player, self.cards[card].action.clone());
+            }
+            Tile::GoToJail { .. } => self.send_to_jail(player),
+            Tile::Parking { .. } => {
+                if self.rules.free_parking.enabled {
+                    // the bank refills the pot right away
+                    let amount = mem::replace(&mut self.parking_pot, self.rules.free_parking.seed);
+                    if amount > 0 {
+                        self.receive(player, amount);
+                        self.emit(GameEvent::ParkingPotWon {
+                            player,
+                            amount,
+                        });
+                    }
+                }
+            }
+            Tile::Custom { kind, .. } => match self.tile_scripts.get(kind) {
+                Some(source) => {
+                    let source = source.clone();
+                    self.run_script(player, &source);
+                }
+                None => {
+                    let error = format!("no mod registered the tile kind {}", kind);
+                    self.emit(GameEvent::ScriptFailed {
+                        player,
+                        error,
+                    });
+                }
+            },
+            Tile::Start { .. } | Tile::Jail { .. } => {}
+        }
+    }
+
+    fn apply_action(&mut self, player: usize, action: Action) {
+        match action {
+            Action::DirectCurrency { amount } => {
+                if amount >= 0 {
+                    self.receive(player, amount as usize);
+                } else {
+                    self.pay_fine(player, amount.unsigned_abs(), self.rules.free_parking.collect_card_fines);
+                }
+            }
+            Action::DistributeCurrency { amount } => {
+                let others = self.players.iter()
+                    .filter(|other| other.id != player && !other.bankrupt)
+                    .map(|other| other.id)
+                    .collect::<Vec<_>>();
+                for other in others {
+                    if amount >= 0 {
+                        self.transfer(other, player, amount as usize);
+                    } else {
+                        self.transfer(player, other, amount.unsigned_abs());
+                    }
+                    if self.players[player].bankrupt {
+                        break;
+                    }
+                }
+            }
+            Action::MoveRelative { amount } => {
+                if amount >= 0 {
+                    self.move_by(player, amount as usize);
+                } else {
+                    self.move_back(player, amount.unsigned_abs());
+                }
+                self.land(player);
+            }
+            Action::MoveAbsolute { tile } => {
+                self.move_to(player, tile);
+                self.land(player);
+            }
+            Action::Wait { rounds } => self.players[player].wait += rounds,
+            Action::GoToJail => self.send_to_jail(player),
+            Action::JailFree => self.players[player].jail_free_cards += 1,
+            Action::MoveToNearest { kind } => {
+                // boards without such a tile leave the player where they are
+                if let Some(tile) = self.nearest_tile(self.players[player].position, kind) {
+                    self.apply_action(player, Action::MoveAbsolute {
+                        tile,
+                    });
+                }
+            }
+            Action::PayPerHouse { house, hotel } => {
+                let (houses, hotels) = self.count_buildings(|property| property.owner == Some(player));
+                self.apply_action(player, Action::DirectCurrency {
+                    amount: -(house * houses as isize + hotel * hotels as isize),
+                });
+            }
+            Action::CollectFromBankPerProperty { amount } => {
+                let properties = self.players[player].properties.len();
+                self.receive(player, amount * properties);
+            }
+            Action::AdvanceToStartIf { within } => {
+                let start = self.board.index.start;
+                let distance = (start + TILES - self.players[player].position) % TILES;
+                if distance > 0 && distance <= within {
+                    self.apply_action(player, Action::MoveAbsolute {
+                        tile: start,
+                    });
+                }
+            }
+            Action::Script { source } => self.run_script(player, &source),
+        }
+    }
+
+    // the first tile ahead of the given one which is of the kind
+    pub fn nearest_tile(&self, from: usize, kind: NearestKind) -> Option<usize> {
+        (1..=TILES).map(|offset| (from + offset) % TILES).find(|tile| kind.matches(&self.board.tiles[*tile]))
+    }
+
+    fn run_script(&mut self, player: usize, source: &str) {
+        match card_script::run(source, self, player) {
+            Ok(effects) => self.apply_script_effects(player, effects),
+            Err(error) => self.emit(GameEvent::ScriptFailed {
+                player,
+                error,
+            }),
+        }
+    }
+
+    fn apply_script_effects(&mut self, player: usize, effects: Vec<ScriptEffect>) {
+        for effect in effects {
+            match effect {
+                ScriptEffect::Receive { amount } => self.receive(player, amount),
+                ScriptEffect::Pay { amount } => self.pay_fine(player, amount, self.rules.free_parking.collect_card_fines),
+                ScriptEffect::Transfer { from, to, amount } => {
+                    // bankrupt players can neither pay nor get paid anymore
+                    if !self.players[from].bankrupt && !self.players[to].bankrupt {
+                        self.transfer(from, to, amount);
+                    }
+                }
+                ScriptEffect::MoveBy { tiles } => {
+                    if tiles >= 0 {
+                        self.move_by(player, tiles as usize);
+                    } else {
+                        self.move_back(player, tiles.unsigned_abs());
+                    }
+                    self.land(player);
+                }
+                ScriptEffect::MoveTo { tile } => {
+                    self.move_to(player, tile);
+                    self.land(player);
+                }
+                ScriptEffect::GoToJail => self.send_to_jail(player),
+            }
+            if self.players[player].bankrupt {
+                break;
+            }
+        }
+    }
+
+    // houses can only be built on properties whose whole group is owned by the player
+    pub fn check_build(&self, player: usize, property: usize) -> Result<(), CommandError> {
+        if self.properties.get(property).and_then(|property| property.owner) != Some(player) {
+            return Err(CommandError::NotOwner);
+        }
+        if !matches!(self.properties[property].frame.ty, PropertyType::Normal { .. }) {
+            return Err(CommandError::CannotBuild);
+        }
+        if !self.owns_group(property)
+            || self.group(property).iter().any(|member| self.properties[*member].mortgaged)
+            || !self.can_build(property) {
+            return Err(CommandError::CannotBuild);
+        }
+        let houses = self.properties[property].houses;
+        if self.group(property).iter().any(|member| self.properties[*member].houses < houses) {
+            return Err(CommandError::UnevenBuilding);
+        }
+        if self.players[player].currency < self.house_price(property) {
+            return Err(CommandError::NotEnoughMoney);
+        }
+        Ok(())
+    }
+
+    // the player's properties which they could build on right now
+    pub fn buildable_properties(&self, player: usize) -> Vec<usize> {
+        self.players[player].properties.iter().copied()
+            .filter(|property| self.check_build(player, *property).is_ok())
+            .collect()
+    }
+
+    // the property along with all other properties of its group
+    pub fn group(&self, property: usize) -> Vec<usize> {
+        let mut group = vec![property];
+        if let PropertyType::Normal { associates, .. } = &self.properties[property].frame.ty {
+            group.extend(associates.iter().flatten());
+        }
+        group
+    }
+
+    // whether the owner of the property holds every property of its group
+    pub fn owns_group(&self, property: usize) -> bool {
+        let owner = self.properties[property].owner;
+        owner.is_some() && self.group(property).iter().all(|member| self.properties[*member].owner == owner)
+    }
+
+    fn owns_unimproved_group(&self, property: usize) -> bool {
+        self.owns_group(property) && self.group(property).iter().all(|member| self.properties[*member].houses == 0)
+    }
+
+    // how many properties of the same type as this one its owner holds, mortgaged ones count as well
+    fn owned_of_type(&self, property: usize) -> usize {
+        let DefinedProperty { owner, frame, .. } = &self.properties[property];
+        self.properties.iter()
+            .filter(|other| other.owner.is_some() && other.owner == *owner && other.frame.ty == frame.ty)
+            .count()
+    }
+
+    // what landing on the property costs with the given roll, mortgaged properties don't charge any rent
+    pub fn rent(&self, property: usize, moves: usize) -> usize {
+        if self.properties[property].mortgaged {
+            return 0;
+        }
+        self.properties[property].calculate_price(moves, self.owns_unimproved_group(property), self.owned_of_type(property))
+    }
+
+    // houses have to be sold evenly across the group and selling a hotel requires the houses it gets broken down into
+    pub fn check_sell(&self, player: usize, property: usize) -> Result<(), CommandError> {
+        if self.properties.get(property).and_then(|property| property.owner) != Some(player) {
+            return Err(CommandError::NotOwner);
+        }
+        let houses = self.properties[property].houses;
+        if houses == 0 {
+            return Err(CommandError::NoHouses);
+        }
+        if self.group(property).iter().any(|member| self.properties[*member].houses > houses) {
+            return Err(CommandError::UnevenSelling);
+        }
+        if houses == MAX_HOUSES && self.buildings_left(false) < MAX_HOUSES - 1 {
+            return Err(CommandError::NoBuildingsLeft);
+        }
+        Ok(())
+    }
+
+    pub fn check_mortgage(&self, player: usize, property: usize) -> Result<(), CommandError> {
+        if self.properties.get(property).and_then(|property| property.owner) != Some(player) {
+            return Err(CommandError::NotOwner);
+        }
+        if self.properties[property].mortgaged {
+            return Err(CommandError::AlreadyMortgaged);
+        }
+        if self.group(property).iter().any(|member| self.properties[*member].houses > 0) {
+            return Err(CommandError::GroupHasBuildings);
+        }
+        Ok(())
+    }
+
+    // a house can only be built if the board defines a rent for it
+    fn can_build(&self, property: usize) -> bool {
+        let property = &self.properties[property];
+        property.houses < MAX_HOUSES && property.frame.rents[property.houses + 1].is_some()
+            && self.buildings_left(self.needs_hotel(property.frame.id)) > 0
+    }
+
+    // the last building on a property is a hotel, which replaces its houses
+    pub fn needs_hotel(&self, property: usize) -> bool {
+        self.properties[property].houses + 1 == MAX_HOUSES
+    }
+
+    // the houses and hotels on the properties which pass the filter
+    fn count_buildings(&self, filter: impl Fn(&DefinedProperty) -> bool) -> (usize, usize) {
+        self.properties.iter().filter(|property| filter(property)).fold((0, 0), |(houses, hotels), property| {
+            if property.houses == MAX_HOUSES {
+                (houses, hotels + 1)
+            } else {
+                (houses + property.houses, hotels)
+            }
+        })
+    }
+
+    pub fn buildings_left(&self, hotel: bool) -> usize {
+        let (houses, hotels) = self.count_buildings(|_| true);
+        if hotel {
+            self.rules.buildings.hotels.saturating_sub(hotels)
+        } else {
+            self.rules.buildings.houses.saturating_sub(houses)
+        }
+    }
+
+    // the players who are able to build a building of the kind right now
+    fn players_wanting_to_build(&self, hotel: bool) -> Vec<usize> {
+        self.players.iter()
+            .filter(|player| !player.bankrupt && player.properties.iter()
+                .any(|property| self.needs_hotel(*property) == hotel && self.check_build(player.id, *property).is_ok()))
+            .map(|player| player.id)
+            .collect()
+    }
+
+    fn build(&mut self, player: usize, property: usize, price: usize) {
+        self.pay(player, price);
+        self.properties[property].houses += 1;
+        self.emit(GameEvent::BuildingPurchased {
+            player,
+            property,
+        });
+    }
+
+    fn start_auction(&mut self, bidders: Vec<usize>, lot: AuctionLot, resume: Phase) {
+        self.auction = Some(Auction {
+            lot,
+            bidders: bidders.clone(),
+            passed: vec![],
+            highest: None,
+            resume,
+        });
+        self.phase = Phase::Auction;
+        match lot {
+            AuctionLot::Building { hotel } => self.emit(GameEvent::BuildingAuctionStarted {
+                bidders,
+                hotel,
+            }),
+            AuctionLot::Property { property } => self.emit(GameEvent::PropertyAuctionStarted {
+                bidders,
+                property,
+            }),
+        }
+    }
+
+    // starts the auction of the next property the bank took from a bankrupt player, every remaining player may bid
+    fn start_pending_auction(&mut self, resume: Phase) -> bool {
+        let property = match self.pending_auctions.pop() {
+            Some(property) => property,
+            None => return false,
+        };
+        let bidders = self.players.iter().filter(|player| !player.bankrupt).map(|player| player.id).collect();
+        self.start_auction(bidders, AuctionLot::Property {
+            property,
+        }, resume);
+        true
+    }
+
+    // the auction is over once nobody is left to outbid the highest bidder
+    fn finish_auction_if_decided(&mut self) {
+        let auction = self.auction.as_ref().unwrap();
+        let active = auction.active_bidders().collect::<Vec<_>>();
+        let decided = match auction.highest {
+            Some((highest, _, _)) => active.iter().all(|bidder| *bidder == highest),
+            None => active.is_empty(),
+        };
+        if !decided {
+            return;
+        }
+        let auction = self.auction.take().unwrap();
+        match (auction.lot, auction.highest) {
+            (AuctionLot::Building { .. }, Some((player, property, price))) => {
+                self.emit(GameEvent::AuctionWon {
+                    player,
+                    property,
+                    price,
+                });
+                self.build(player, property, price);
+            }
+            (AuctionLot::Property { .. }, Some((player, property, price))) => {
+                self.emit(GameEvent::AuctionWon {
+                    player,
+                    property,
+                    price,
+                });
+                self.pay(player, price);
+                self.properties[property].owner = Some(player);
+                self.players[player].properties.push(property);
+            }
+            (AuctionLot::Building { .. }, None) => self.emit(GameEvent::BuildingAuctionCancelled),
+            // the property stays with the bank and can be bought as usual
+            (AuctionLot::Property { property }, None) => self.emit(GameEvent::PropertyAuctionCancelled {
+                property,
+            }),
+        }
+        // e.g. a building auction started before the roll continues with the roll
+        if !self.start_pending_auction(auction.resume) {
+            self.phase = auction.resume;
+        }
+    }
+
+    /// The actions the engine accepts from the player right now, so UI screens can offer exactly those.
+    /// The commands may still be rejected for other reasons, like a lack of money.
+    pub fn legal_actions(&self, player: usize) -> Vec<TurnAction> {
+        let mut actions = vec![];
+        if self.players.get(player).map_or(true, |player| player.bankrupt) {
+            return actions;
+        }
+        if self.pending_trade.as_ref().map_or(false, |trade| trade.to == player) {
+            actions.push(TurnAction::AnswerTrade);
+        }
+        if let Some(auction) = &self.auction {
+            if auction.active_bidders().any(|bidder| bidder == player) {
+                actions.push(TurnAction::Bid);
+            }
+            return actions;
+        }
+        if player != self.curr_player {
+            return actions;
+        }
+        match self.phase {
+            Phase::Drafting => actions.push(TurnAction::Draft),
+            Phase::Rolling => {
+                actions.push(TurnAction::Roll);
+                if self.players[player].is_jailed() {
+                    actions.push(TurnAction::LeaveJail);
+                }
+            }
+            Phase::Buying { .. } => actions.push(TurnAction::Buy),
+            Phase::ChoosingTax { .. } => actions.push(TurnAction::PayTax),
+            Phase::Acting => actions.push(TurnAction::EndTurn),
+            Phase::Lobby | Phase::Auction | Phase::Finished => {}
+        }
+        if matches!(self.phase, Phase::Rolling | Phase::Acting) {
+            actions.push(TurnAction::Manage);
+            if self.pending_trade.is_none() {
+                actions.push(TurnAction::OfferTrade);
+            }
+        }
+        actions
+    }
+
+    // the commands which carry out the action, e.g. buying and declining the property for `TurnAction::Buy`,
+    // bids are for the lowest amount which beats the highest one. Managing properties and offering trades
+    // take more choices than a list of commands can offer, so there are none for them
+    pub fn action_commands(&self, player: usize, action: TurnAction) -> Vec<GameCommand> {
+        match action {
+            TurnAction::Draft => (0..PROPERTIES).filter(|property| self.properties[*property].owner.is_none())
+                .map(|property| GameCommand::DraftProperty {
+                    player,
+                    property,
+                }).collect(),
+            TurnAction::Roll => vec![GameCommand::RollDice {
+                player,
+            }],
+            TurnAction::LeaveJail if self.players[player].jail_free_cards > 0 => vec![GameCommand::UseJailFreeCard {
+                player,
+            }, GameCommand::PayBail {
+                player,
+            }],
+            TurnAction::LeaveJail => vec![GameCommand::PayBail {
+                player,
+            }],
+            TurnAction::Buy => vec![GameCommand::BuyProperty {
+                player,
+            }, GameCommand::DeclineProperty {
+                player,
+            }],
+            TurnAction::PayTax => vec![GameCommand::PayTax {
+                player,
+                percentage: false,
+            }, GameCommand::PayTax {
+                player,
+                percentage: true,
+            }],
+            TurnAction::Bid => {
+                let mut commands = self.lowest_bid(player).into_iter().map(|(property, amount)| GameCommand::Bid {
+                    player,
+                    property,
+                    amount,
+                }).collect::<Vec<_>>();
+                commands.push(GameCommand::PassBid {
+                    player,
+                });
+                commands
+            }
+            TurnAction::AnswerTrade => vec![GameCommand::AcceptTrade {
+                player,
+            }, GameCommand::DeclineTrade {
+                player,
+            }],
+            TurnAction::EndTurn => vec![GameCommand::EndTurn {
+                player,
+            }],
+            TurnAction::Manage | TurnAction::OfferTrade => vec![],
+        }
+    }
+
+    // the property the player can bid on in the current auction along with the lowest bid which beats the highest one,
+    // None if they can't afford it. Buildings are bid for on the first property they could be built on
+    fn lowest_bid(&self, player: usize) -> Option<(usize, usize)> {
+        let auction = self.auction.as_ref()?;
+        let (property, min_bid) = match auction.lot {
+            AuctionLot::Building { hotel } => self.buildable_properties(player).into_iter()
+                .find(|property| self.needs_hotel(*property) == hotel)
+                .map(|property| (property, self.house_price(property)))?,
+            AuctionLot::Property { property } => (property, 1),
+        };
+        let amount = auction.highest.map_or(min_bid, |(_, _, highest)| min_bid.max(highest + 1));
+        Some((property, amount)).filter(|_| self.players[player].currency >= amount)
+    }
+
+    // the player who has to submit the next command, during auctions this isn't necessarily the current player
+    pub fn next_actor(&self) -> usize {
+        match &self.auction {
+            Some(auction) => auction.active_bidders()
+                .find(|bidder| auction.highest.map_or(true, |(highest, _, _)| highest != *bidder))
+                .unwrap_or(self.curr_player),
+            None => self.curr_player,
+        }
+    }
+
+    // the player's money along with the value of their properties and buildings
+    pub fn net_worth(&self, player: usize) -> usize {
+        self.players[player].currency + self.players[player].properties.iter()
+            .map(|property| self.property_value(*property))
+            .sum::<usize>()
+    }
+
+    // what the property is worth including its buildings
+    pub fn property_value(&self, property: usize) -> usize {
+        let value = if self.properties[property].mortgaged {
+            self.properties[property].mortgage_value()
+        } else {
+            self.properties[property].frame.buy_price
+        };
+        value + self.properties[property].houses * self.house_price(property)
+    }
+
+    pub fn house_price(&self, property: usize) -> usize {
+        self.properties[property].frame.buy_price / 2
+    }
+
+    fn receive(&mut self, player: usize, amount: usize) {
+        self.players[player].currency += amount;
+        self.emit(GameEvent::MoneyReceived {
+            player,
+            amount,
+        });
+    }
+
+    // the player pays the bank, returns whether they were able to afford it
+    fn pay(&mut self, player: usize, amount: usize) -> bool {
+        if self.players[player].currency < amount {
+            self.go_bankrupt(player, None);
+            return false;
+        }
+        self.players[player].currency -= amount;
+        self.emit(GameEvent::MoneyPaid {
+            player,
+            amount,
+        });
+        true
+    }
+
+    // a payment to the bank which goes into the parking pot if the free parking rule collects it
+    fn pay_fine(&mut self, player: usize, amount: usize, collect: bool) {
+        if self.pay(player, amount) && collect && self.rules.free_parking.enabled {
+            self.parking_pot += amount;
+        }
+    }
+
+    // if the payer can't afford the whole amount, they go bankrupt to the receiver, returns whether it was paid
+    fn transfer(&mut self, from: usize, to: usize, amount: usize) -> bool {
+        if self.players[from].currency < amount {
+            self.go_bankrupt(from, Some(to));
+            return false;
+        }
+        self.pay(from, amount);
+        self.receive(to, amount);
+        true
+    }
+
+    // consumes one landing of the player's immunity for the property, returns whether they had one
+    fn use_immunity(&mut self, player: usize, property: usize) -> bool {
+        let immunity = match self.immunities.iter_mut().find(|immunity| immunity.holder == player && immunity.property == property) {
+            Some(immunity) => immunity,
+            None => return false,
+        };
+        immunity.landings -= 1;
+        self.immunities.retain(|immunity| immunity.landings > 0);
+        true
+    }
+
+    // both sides have to own what they're offering and properties with houses can't be traded
+    fn check_trade(&self, trade: &Trade) -> Result<(), CommandError> {
+        if trade.from == trade.to || trade.to >= self.players.len() || self.players[trade.to].bankrupt {
+            return Err(CommandError::InvalidTrade);
+        }
+        for (player, properties, money, jail_free_cards, immunities) in [
+            (trade.from, &trade.offered_properties, trade.offered_money, trade.offered_jail_free_cards, &trade.offered_immunities),
+            (trade.to, &trade.requested_properties, trade.requested_money, trade.requested_jail_free_cards, &trade.requested_immunities)] {
+            if self.players[player].currency < money {
+                return Err(CommandError::NotEnoughMoney);
+            }
+            if self.players[player].jail_free_cards < jail_free_cards {
+                return Err(CommandError::InvalidTrade);
+            }
+            for immunity in immunities.iter() {
+                if immunity.landings == 0 {
+                    return Err(CommandError::InvalidTrade);
+                }
+                // a property which changes hands in the same trade can't be promised anymore
+                if self.properties.get(immunity.property).and_then(|property| property.owner) != Some(player)
+                    || properties.contains(&immunity.property) {
+                    return Err(CommandError::NotOwner);
+                }
+            }
+            for property in properties.iter() {
+                match self.properties.get(*property) {
+                    Some(property) if property.owner == Some(player) => {
+                        if property.houses > 0 {
+                            return Err(CommandError::InvalidTrade);
+                        }
+                    }
+                    _ => return Err(CommandError::NotOwner),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_trade(&mut self, trade: &Trade) {
+        if trade.offered_money > 0 {
+            self.transfer(trade.from, trade.to, trade.offered_money);
+        }
+        if trade.requested_money > 0 {
+            self.transfer(trade.to, trade.from, trade.requested_money);
+        }
+        for property in trade.offered_properties.iter() {
+            self.move_property(*property, trade.from, trade.to);
+        }
+        for property in trade.requested_properties.iter() {
+            self.move_property(*property, trade.to, trade.from);
+        }
+        self.players[trade.from].jail_free_cards -= trade.offered_jail_free_cards;
+        self.players[trade.to].jail_free_cards += trade.offered_jail_free_cards;
+        self.players[trade.to].jail_free_cards -= trade.requested_jail_free_cards;
+        self.players[trade.from].jail_free_cards += trade.requested_jail_free_cards;
+        for (holder, immunities) in [(trade.to, &trade.offered_immunities), (trade.from, &trade.requested_immunities)] {
+            for Immunity { property, landings } in immunities.iter().copied() {
+                self.immunities.push(RentImmunity {
+                    holder,
+                    property,
+                    landings,
+                });
+            }
+        }
+    }
+
+    fn move_property(&mut self, property: usize, from: usize, to: usize) {
+        // the new owner didn't promise anything
+        self.immunities.retain(|immunity| immunity.property != property);
+        self.players[from].properties.retain(|owned| *owned != property);
+        self.players[to].properties.push(property);
+        self.properties[property].owner = Some(to);
+    }
+
+    // the buildings are sold back to the bank first. If the player went bankrupt to another player, that player
+    // gets everything, including the mortgaged properties which stay mortgaged. Otherwise the bank auctions the
+    // properties off. If only a single player remains they won the game, a game played alone ends once its player is out
+    fn go_bankrupt(&mut self, player: usize, creditor: Option<usize>) {
+        let properties = mem::take(&mut self.players[player].properties);
+        self.immunities.retain(|immunity| immunity.holder != player && !properties.contains(&immunity.property));
+        let mut assets = mem::take(&mut self.players[player].currency);
+        for property in properties.iter().copied() {
+            assets += self.properties[property].houses * self.house_price(property) / 2;
+            self.properties[property].houses = 0;
+        }
+        self.players[player].bankrupt = true;
+        self.emit(GameEvent::WentBankrupt {
+            player,
+            creditor,
+        });
+        match creditor {
+            Some(creditor) => {
+                for property in properties {
+                    self.properties[property].owner = Some(creditor);
+                    self.players[creditor].properties.push(property);
+                }
+                let jail_free_cards = mem::take(&mut self.players[player].jail_free_cards);
+                self.players[creditor].jail_free_cards += jail_free_cards;
+                if assets > 0 {
+                    self.receive(creditor, assets);
+                }
+            }
+            None => {
+                for property in properties.iter().copied() {
+                    self.properties[property].owner = None;
+                    self.properties[property].mortgaged = false;
+                }
+                self.players[player].jail_free_cards = 0;
+                self.pending_auctions.extend(properties);
+            }
+        }
+        if self.remaining_players() == 0 || (self.remaining_players() == 1 && self.players.len() > 1) {
+            self.finish();
+            return;
+        }
+        if player == self.curr_player {
+            // the rest of the turn is skipped
+            self.phase = Phase::Acting;
+        }
+        if self.auction.is_none() {
+            self.start_pending_auction(self.phase);
+        }
+    }
+
+}
@@ -0,0 +1,242 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::animation::{AnimationClip, AnimationPlayer};
+use crate::render::{Instance, ModeledInstance};
+use crate::tween::{Easing, Tween};
+
+// the game objects which are represented in the world
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum EntityKind {
+    Board,
+    Token {
+        player: usize,
+    },
+    // the idx-th house on the property
+    House {
+        property: usize,
+        idx: usize,
+    },
+    // replaces the houses once the property is fully improved
+    Hotel {
+        property: usize,
+    },
+    Die {
+        idx: usize,
+    },
+    // shows the color group of the property on the tile
+    GroupMarker {
+        tile: usize,
+    },
+    // a strip in the owner's color along the outer edge of an owned tile
+    OwnerMarker {
+        tile: usize,
+    },
+    // tints the tile by how likely the tokens are to land on it, see `probability::landing_probabilities`
+    Heat {
+        tile: usize,
+    },
+    // the speech bubble of the emote the player sent last
+    Emote {
+        player: usize,
+    },
+}
+
+struct Keyframe {
+    target: Instance,
+    duration: Duration,
+    easing: Easing,
+}
+
+// the transition from where the entity was to the current keyframe's target
+struct Animation {
+    from: Instance,
+    to: Instance,
+    tween: Tween,
+}
+
+pub struct Entity {
+    pub model_id: usize,
+    // if the entity is attached to another one, its transform is relative to the parent's
+    parent: Option<EntityKind>,
+    transform: Instance,
+    animation: Option<Animation>,
+    // keyframes which are played once the current animation finished
+    queued: VecDeque<Keyframe>,
+    // clips which are played on top of the entity's transform, like a token's idle animation
+    clips: AnimationPlayer,
+}
+
+impl Entity {
+
+    // where the entity currently is relative to its parent, including the progress of its animation
+    pub fn transform(&self) -> Instance {
+        match &self.animation {
+            Some(animation) => {
+                let progress = animation.tween.progress();
+                Instance {
+                    position: animation.from.position + (animation.to.position - animation.from.position) * progress,
+                    rotation: animation.from.rotation.slerp(animation.to.rotation, progress),
+                }
+            }
+            None => self.transform.clone(),
+        }
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some() || !self.queued.is_empty()
+    }
+
+    fn update(&mut self) {
+        loop {
+            if let Some(animation) = &self.animation {
+                if !animation.tween.is_finished() {
+                    break;
+                }
+                self.transform = animation.to.clone();
+                self.animation = None;
+            }
+            match self.queued.pop_front() {
+                Some(keyframe) => {
+                    self.animation = Some(Animation {
+                        from: self.transform.clone(),
+                        to: keyframe.target,
+                        tween: Tween::new(keyframe.duration, keyframe.easing),
+                    });
+                }
+                None => break,
+            }
+        }
+        self.clips.update();
+    }
+
+}
+
+/// Maps the game's objects to the model instances which represent them, so game logic
+/// only has to say where things should be and never deals with the renderer's instances.
+#[derive(Default)]
+pub struct EntityRegistry {
+    entities: HashMap<EntityKind, Entity>,
+}
+
+impl EntityRegistry {
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // replaces the entity if one of the same kind already exists, its children stay attached
+    pub fn spawn(&mut self, kind: EntityKind, model_id: usize, transform: Instance) {
+        self.insert(kind, None, model_id, transform);
+    }
+
+    // the entity follows its parent whenever the parent moves, so its transform is relative to the parent's
+    pub fn spawn_attached(&mut self, kind: EntityKind, parent: EntityKind, model_id: usize, transform: Instance) {
+        self.insert(kind, Some(parent), model_id, transform);
+    }
+
+    fn insert(&mut self, kind: EntityKind, parent: Option<EntityKind>, model_id: usize, transform: Instance) {
+        self.entities.insert(kind, Entity {
+            model_id,
+            parent,
+            transform,
+            animation: None,
+            queued: VecDeque::new(),
+            clips: AnimationPlayer::default(),
+        });
+    }
+
+    // the entity's children get despawned along with it
+    pub fn despawn(&mut self, kind: EntityKind) {
+        self.entities.remove(&kind);
+        self.remove_orphans();
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&EntityKind) -> bool) {
+        self.entities.retain(|kind, _| f(kind));
+        self.remove_orphans();
+    }
+
+    fn remove_orphans(&mut self) {
+        // removing children may orphan their own children as well
+        loop {
+            let orphans = self.entities.iter()
+                .filter(|(_, entity)| entity.parent.map_or(false, |parent| !self.entities.contains_key(&parent)))
+                .map(|(kind, _)| *kind)
+                .collect::<Vec<_>>();
+            if orphans.is_empty() {
+                return;
+            }
+            for orphan in orphans {
+                self.entities.remove(&orphan);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+    }
+
+    pub fn get(&self, kind: EntityKind) -> Option<&Entity> {
+        self.entities.get(&kind)
+    }
+
+    pub fn contains(&self, kind: EntityKind) -> bool {
+        self.entities.contains_key(&kind)
+    }
+
+    // where the entity currently is in the world, after applying the transforms of all of its parents
+    pub fn world_transform(&self, kind: EntityKind) -> Option<Instance> {
+        let entity = self.entities.get(&kind)?;
+        let transform = entity.transform().then(&entity.clips.pose());
+        Some(match entity.parent.and_then(|parent| self.world_transform(parent)) {
+            Some(parent) => parent.then(&transform),
+            None => transform,
+        })
+    }
+
+    // moves the entity immediately, cancelling all of its animations. The transform is relative
+    // to the entity's parent, just like the targets of its animations
+    pub fn set_transform(&mut self, kind: EntityKind, transform: Instance) {
+        if let Some(entity) = self.entities.get_mut(&kind) {
+            entity.transform = transform;
+            entity.animation = None;
+            entity.queued.clear();
+        }
+    }
+
+    // the animation starts once all animations which were queued before it are finished
+    pub fn animate_to(&mut self, kind: EntityKind, target: Instance, duration: Duration, easing: Easing) {
+        if let Some(entity) = self.entities.get_mut(&kind) {
+            entity.queued.push_back(Keyframe {
+                target,
+                duration,
+                easing,
+            });
+        }
+    }
+
+    // clips play independently of the entity's animations and don't count as animating
+    pub fn is_animating(&self) -> bool {
+        self.entities.values().any(|entity| entity.is_animating())
+    }
+
+    // a clip which doesn't loop plays once and then fades back to the clips played before it
+    pub fn play_clip(&mut self, kind: EntityKind, clip: Arc<AnimationClip>, looping: bool, blend: Duration) {
+        if let Some(entity) = self.entities.get_mut(&kind) {
+            entity.clips.play(clip, looping, blend);
+        }
+    }
+
+    // advances all animations and returns the instances the renderer should draw this frame
+    pub fn instances(&mut self) -> Vec<ModeledInstance> {
+        for entity in self.entities.values_mut() {
+            entity.update();
+        }
+        self.entities.iter().map(|(kind, entity)| ModeledInstance {
+            model_id: entity.model_id,
+            instance: self.world_transform(*kind).unwrap(),
+        }).collect()
+    }
+
+}
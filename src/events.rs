@@ -0,0 +1,426 @@
+use serde::{Deserialize, Serialize};
+use crate::audio::SoundId;
+use crate::board::TILES;
+use crate::player::VoiceLine;
+
+// requests to change the game's state, these are submitted by the UI, the AI and the network
+// and only take effect once the engine validated them
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GameCommand {
+    AddPlayer {
+        character: usize,
+    },
+    StartGame,
+    // only used while the properties are drafted before the first turn
+    DraftProperty {
+        player: usize,
+        property: usize,
+    },
+    RollDice {
+        player: usize,
+    },
+    BuyProperty {
+        player: usize,
+    },
+    DeclineProperty {
+        player: usize,
+    },
+    BuildHouse {
+        player: usize,
+        property: usize,
+    },
+    // hotels are sold by breaking them down into houses first
+    SellHouse {
+        player: usize,
+        property: usize,
+    },
+    Mortgage {
+        player: usize,
+        property: usize,
+    },
+    Unmortgage {
+        player: usize,
+        property: usize,
+    },
+    OfferTrade {
+        trade: Trade,
+    },
+    AcceptTrade {
+        player: usize,
+    },
+    // can be sent by both the player who offered the trade and the one it was offered to
+    DeclineTrade {
+        player: usize,
+    },
+    // a jailed player pays the fine before rolling, so their roll moves them right away
+    PayBail {
+        player: usize,
+    },
+    UseJailFreeCard {
+        player: usize,
+    },
+    EndTurn {
+        player: usize,
+    },
+    // the bid is for building on the given property, so it has to be at least the property's house price,
+    // in auctions of a bankrupt player's properties the bid is for the auctioned property and has to be at least 1
+    Bid {
+        player: usize,
+        property: usize,
+        amount: usize,
+    },
+    PassBid {
+        player: usize,
+    },
+    // pays either the income tax's flat amount or the percentage of the player's net worth
+    PayTax {
+        player: usize,
+        percentage: bool,
+    },
+    // can be sent at any point of the game, it doesn't change the game's state
+    Emote {
+        player: usize,
+        emote: Emote,
+    },
+    LeaveGame,
+}
+
+impl GameCommand {
+
+    // the player the command is sent for, None if it isn't sent for a specific player
+    pub fn player(&self) -> Option<usize> {
+        match self {
+            GameCommand::AddPlayer { .. } | GameCommand::StartGame | GameCommand::LeaveGame => None,
+            GameCommand::OfferTrade { trade } => Some(trade.from),
+            GameCommand::DraftProperty { player, .. }
+            | GameCommand::RollDice { player }
+            | GameCommand::BuyProperty { player }
+            | GameCommand::DeclineProperty { player }
+            | GameCommand::BuildHouse { player, .. }
+            | GameCommand::SellHouse { player, .. }
+            | GameCommand::Mortgage { player, .. }
+            | GameCommand::Unmortgage { player, .. }
+            | GameCommand::AcceptTrade { player }
+            | GameCommand::DeclineTrade { player }
+            | GameCommand::PayBail { player }
+            | GameCommand::UseJailFreeCard { player }
+            | GameCommand::EndTurn { player }
+            | GameCommand::Bid { player, .. }
+            | GameCommand::PassBid { player }
+            | GameCommand::PayTax { player, .. }
+            | GameCommand::Emote { player, .. } => Some(*player),
+        }
+    }
+
+}
+
+// short messages players can show above their token
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Emote {
+    Hello,
+    GoodGame,
+    Oops,
+    Nice,
+    Hurry,
+    Thinking,
+}
+
+impl Emote {
+
+    pub const ALL: [Emote; 6] = [Emote::Hello, Emote::GoodGame, Emote::Oops, Emote::Nice, Emote::Hurry, Emote::Thinking];
+
+    // the translation key of the emote's text
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            Emote::Hello => "emote.hello",
+            Emote::GoodGame => "emote.good_game",
+            Emote::Oops => "emote.oops",
+            Emote::Nice => "emote.nice",
+            Emote::Hurry => "emote.hurry",
+            Emote::Thinking => "emote.thinking",
+        }
+    }
+
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub struct Trade {
+    pub from: usize,
+    pub to: usize,
+    pub offered_properties: Vec<usize>,
+    pub offered_money: usize,
+    pub offered_jail_free_cards: usize,
+    // granted by the offering player on their own properties
+    pub offered_immunities: Vec<Immunity>,
+    pub requested_properties: Vec<usize>,
+    pub requested_money: usize,
+    pub requested_jail_free_cards: usize,
+    // granted by the player the trade is offered to on their own properties
+    pub requested_immunities: Vec<Immunity>,
+}
+
+// the owner of the property promises not to charge rent for the next `landings` times the other player lands on it
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Immunity {
+    pub property: usize,
+    pub landings: usize,
+}
+
+// the reason the engine refused to apply a command
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum CommandError {
+    NotYourTurn,
+    // the command can't be used at this point of the game or turn
+    WrongPhase,
+    NotEnoughPlayers,
+    NotEnoughMoney,
+    NotOwner,
+    CannotBuild,
+    NoPendingTrade,
+    InvalidTrade,
+    // the bank has no buildings of the required kind left
+    NoBuildingsLeft,
+    NotBidding,
+    BidTooLow,
+    NoHouses,
+    // houses have to be built evenly, so only the properties with the fewest houses of their group can get one
+    UnevenBuilding,
+    // houses have to be sold evenly, so only the properties with the most houses of their group can sell one
+    UnevenSelling,
+    // properties can only be mortgaged if their group has no buildings
+    GroupHasBuildings,
+    AlreadyMortgaged,
+    NotMortgaged,
+    // the property was already drafted
+    NotAvailable,
+    NotJailed,
+    NoJailFreeCard,
+}
+
+impl CommandError {
+
+    // the translation key of the message which explains the error to the player
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            CommandError::NotYourTurn => "error.not_your_turn",
+            CommandError::WrongPhase => "error.wrong_phase",
+            CommandError::NotEnoughPlayers => "error.not_enough_players",
+            CommandError::NotEnoughMoney => "error.not_enough_money",
+            CommandError::NotOwner => "error.not_owner",
+            CommandError::CannotBuild => "error.cannot_build",
+            CommandError::NoPendingTrade => "error.no_pending_trade",
+            CommandError::InvalidTrade => "error.invalid_trade",
+            CommandError::NoBuildingsLeft => "error.no_buildings_left",
+            CommandError::NotBidding => "error.not_bidding",
+            CommandError::BidTooLow => "error.bid_too_low",
+            CommandError::NoHouses => "error.no_houses",
+            CommandError::UnevenBuilding => "error.uneven_building",
+            CommandError::UnevenSelling => "error.uneven_selling",
+            CommandError::GroupHasBuildings => "error.group_has_buildings",
+            CommandError::AlreadyMortgaged => "error.already_mortgaged",
+            CommandError::NotMortgaged => "error.not_mortgaged",
+            CommandError::NotAvailable => "error.not_available",
+            CommandError::NotJailed => "error.not_jailed",
+            CommandError::NoJailFreeCard => "error.no_jail_free_card",
+        }
+    }
+
+}
+
+// everything noteworthy that happens during a game, these get recorded in the game's event log
+#[derive(Clone, PartialEq, Debug)]
+pub enum GameEvent {
+    PlayerJoined {
+        player: usize,
+        character: usize,
+    },
+    GameStarted,
+    // the tile at `layout[idx]` was moved to `idx`, this is recorded so replays can restore the layout
+    BoardShuffled {
+        layout: [usize; TILES],
+    },
+    TurnStarted {
+        player: usize,
+    },
+    DiceRolled {
+        player: usize,
+        dice: (u8, u8),
+    },
+    // the player rolled a double, so their turn goes on with another roll
+    RollingAgain {
+        player: usize,
+    },
+    // the player's token moved a single tile further
+    TokenHopped {
+        player: usize,
+        tile: usize,
+    },
+    MoneyPaid {
+        player: usize,
+        amount: usize,
+    },
+    MoneyReceived {
+        player: usize,
+        amount: usize,
+    },
+    // the player's token came to a halt on the tile
+    TokenLanded {
+        player: usize,
+        tile: usize,
+    },
+    CardDrawn {
+        player: usize,
+        card: usize,
+    },
+    ParkingPotWon {
+        player: usize,
+        amount: usize,
+    },
+    // the script of the card the player drew or the custom tile they landed on didn't run through,
+    // none of its effects were applied
+    ScriptFailed {
+        player: usize,
+        error: String,
+    },
+    BuildingPurchased {
+        player: usize,
+        property: usize,
+    },
+    BuildingSold {
+        player: usize,
+        property: usize,
+    },
+    PropertyMortgaged {
+        player: usize,
+        property: usize,
+    },
+    PropertyUnmortgaged {
+        player: usize,
+        property: usize,
+    },
+    WentToJail {
+        player: usize,
+    },
+    // by rolling doubles, paying the fine or using a jail free card
+    ReleasedFromJail {
+        player: usize,
+    },
+    // more players want to build than the bank has buildings left
+    BuildingAuctionStarted {
+        bidders: Vec<usize>,
+        hotel: bool,
+    },
+    BidPlaced {
+        player: usize,
+        amount: usize,
+    },
+    BidPassed {
+        player: usize,
+    },
+    // the player had an immunity for the property
+    RentWaived {
+        player: usize,
+        property: usize,
+    },
+    // only emitted if the player could afford the rent, otherwise they went bankrupt to the owner
+    RentPaid {
+        player: usize,
+        owner: usize,
+        property: usize,
+        amount: usize,
+    },
+    // the player landed on an income tax tile and has to choose what to pay
+    TaxChoiceOffered {
+        player: usize,
+        flat: usize,
+        percentage: usize,
+    },
+    // the player didn't decide in time, so the default decision was taken for them
+    DecisionTimedOut {
+        player: usize,
+    },
+    // the bank auctions off a property of a player who went bankrupt to it
+    PropertyAuctionStarted {
+        bidders: Vec<usize>,
+        property: usize,
+    },
+    // nobody bid, so the building stays in the bank
+    BuildingAuctionCancelled,
+    PropertyAuctionCancelled {
+        property: usize,
+    },
+    AuctionWon {
+        player: usize,
+        property: usize,
+        price: usize,
+    },
+    // the creditor is the player the bankrupt player owed money, if it was the bank there is none
+    WentBankrupt {
+        player: usize,
+        creditor: Option<usize>,
+    },
+    PropertyBought {
+        player: usize,
+        property: usize,
+        price: usize,
+    },
+    // the property was dealt to or drafted by the player for free
+    PropertyReceived {
+        player: usize,
+        property: usize,
+    },
+    TradeOffered {
+        trade: Trade,
+    },
+    TradeAccepted {
+        trade: Trade,
+    },
+    TradeDeclined {
+        trade: Trade,
+    },
+    // the winner is the last player who didn't go bankrupt, or the richest one if the round limit was reached
+    GameFinished {
+        winner: Option<usize>,
+    },
+    GameLeft,
+    Emoted {
+        player: usize,
+        emote: Emote,
+    },
+    CommandRejected {
+        command: GameCommand,
+        error: CommandError,
+    },
+    // only networked games have a chat, the player is `None` for spectators
+    ChatReceived {
+        player: Option<usize>,
+        text: String,
+    },
+}
+
+impl GameEvent {
+
+    pub fn sound(&self) -> Option<SoundId> {
+        Some(match self {
+            GameEvent::DiceRolled { .. } => SoundId::DiceRoll,
+            GameEvent::TokenHopped { .. } => SoundId::TokenHop,
+            GameEvent::MoneyPaid { .. } => SoundId::MoneyPaid,
+            GameEvent::MoneyReceived { .. } => SoundId::MoneyReceived,
+            GameEvent::CardDrawn { .. } => SoundId::CardDraw,
+            GameEvent::BuildingPurchased { .. } => SoundId::BuildingPurchased,
+            GameEvent::WentToJail { .. } => SoundId::GoToJail,
+            _ => return None,
+        })
+    }
+
+    // the voice line the character of the event's player says
+    pub fn voice_line(&self) -> Option<(usize, VoiceLine)> {
+        match self {
+            GameEvent::PlayerJoined { player, .. } => Some((*player, VoiceLine::Select)),
+            GameEvent::WentToJail { player } => Some((*player, VoiceLine::Jail)),
+            GameEvent::AuctionWon { player, .. } => Some((*player, VoiceLine::AuctionWon)),
+            _ => None,
+        }
+    }
+
+}
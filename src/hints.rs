@@ -0,0 +1,27 @@
+use crate::ai::Hint;
+use crate::engine::Engine;
+use crate::i18n;
+
+// the hints are shown by the dialogs of the decisions they're about, see `ActionBar` and `TaxChoice`
+pub fn hint_text(hint: &Hint, engine: &Engine) -> String {
+    let property_name = |property: usize| engine.properties[property].frame.name.clone();
+    match hint {
+        Hint::Buy { property, buy } => {
+            let key = if *buy { "hint.buy" } else { "hint.decline" };
+            i18n::tr_with(key, &[("property", &property_name(*property))])
+        }
+        Hint::Bid { property, max: Some(max) } => i18n::tr_with("hint.bid", &[
+            ("property", &property_name(*property)),
+            ("amount", &engine.rules.currency.format(*max)),
+        ]),
+        Hint::Bid { property, max: None } => i18n::tr_with("hint.pass", &[("property", &property_name(*property))]),
+        Hint::Tax { percentage } => {
+            let key = if *percentage { "hint.tax_percentage" } else { "hint.tax_flat" };
+            i18n::tr(key)
+        }
+        Hint::Trade { accept } => {
+            let key = if *accept { "hint.accept_trade" } else { "hint.decline_trade" };
+            i18n::tr(key)
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+pub const SHADER_DIR: &str = "./src/";
+const RESOURCE_DIR: &str = "./resources/";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+pub enum Change {
+    Shader,
+    Texture(PathBuf),
+}
+
+// polls the shaders and resources for changes, this is only meant to be used in debug builds
+pub struct Watcher {
+    changes: Receiver<Change>,
+}
+
+impl Watcher {
+
+    pub fn start() -> Self {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            watch(sender);
+        });
+        Self {
+            changes: receiver,
+        }
+    }
+
+    // all changes which happened since the last call
+    pub fn changes(&self) -> Vec<Change> {
+        self.changes.try_iter().collect()
+    }
+
+}
+
+fn watch(sender: Sender<Change>) {
+    let mut shaders = modification_times(SHADER_DIR, "wgsl");
+    let mut textures = modification_times(RESOURCE_DIR, "");
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let curr_shaders = modification_times(SHADER_DIR, "wgsl");
+        if curr_shaders != shaders {
+            shaders = curr_shaders;
+            if sender.send(Change::Shader).is_err() {
+                return;
+            }
+        }
+        let curr_textures = modification_times(RESOURCE_DIR, "");
+        for (path, modified) in curr_textures.iter() {
+            if textures.get(path) != Some(modified) {
+                if sender.send(Change::Texture(path.clone())).is_err() {
+                    return;
+                }
+            }
+        }
+        textures = curr_textures;
+    }
+}
+
+// an empty extension matches all files
+fn modification_times(dir: &str, extension: &str) -> HashMap<PathBuf, SystemTime> {
+    let mut ret = HashMap::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return ret,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !extension.is_empty() && path.extension().map_or(true, |ext| ext != extension) {
+            continue;
+        }
+        if let Ok(modified) = entry.metadata().and_then(|metadata| metadata.modified()) {
+            ret.insert(path, modified);
+        }
+    }
+    ret
+}
+
+// debug builds prefer the shader on disk over the embedded one, so edits show up after a reload
+pub fn shader_source(file: &str, embedded: &'static str) -> String {
+    if cfg!(debug_assertions) {
+        if let Ok(src) = fs::read_to_string(Path::new(SHADER_DIR).join(file)) {
+            return src;
+        }
+    }
+    embedded.to_string()
+}
@@ -0,0 +1,213 @@
+use std::sync::Arc;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::palette;
+use crate::render::{ColorSource, Model, TexTy};
+use crate::ui::{ClickKind, Color, ColorBox, Coloring, Component, HoverMode, Tex, TextBox, TextSection, TextStyle};
+use crate::utils::{DARK_GRAY_UI, LIGHT_GRAY_UI};
+use crate::{Game, i18n};
+
+// the rows are stacked at the top left corner of the screen, one per player
+pub const ROW_WIDTH: f32 = 0.24;
+pub const ROW_HEIGHT: f32 = 0.06;
+const HUD_MARGIN: f32 = 0.01;
+// the gap between the rows, the active row's frame is drawn into it
+const ROW_GAP: f32 = 0.004;
+// the player's color is shown as a stripe at the left edge of their row
+const STRIPE_WIDTH: f32 = 0.006;
+// roughly square on common aspect ratios
+const PORTRAIT_WIDTH: f32 = 0.028;
+const PADDING: f32 = 0.005;
+const NAME_SCALE: f32 = 22.0;
+const DETAIL_SCALE: f32 = 18.0;
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+const TRANSPARENT: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.0,
+};
+
+// the bottom left corner of the player's row
+pub fn row_pos(player: usize) -> (f32, f32) {
+    (HUD_MARGIN, 1.0 - HUD_MARGIN - (player + 1) as f32 * (ROW_HEIGHT + ROW_GAP))
+}
+
+/// The character's portrait at the start of the player's row, it's a separate component
+/// as it's drawn from the character's texture instead of the HUD's colors.
+pub fn portrait(game: &Arc<Game>, player: usize, character: usize) -> ColorBox {
+    let character = game.characters.iter().find(|other| other.id == character).unwrap();
+    let buf = game.assets.get().unwrap().character_textures[&character.id].clone();
+    let tex = game.renderer.cached_texture(&character.model_path, || Ok(buf.as_ref().clone())).unwrap();
+    let (x, y) = row_pos(player);
+    ColorBox {
+        pos: (x + STRIPE_WIDTH + PADDING, y + PADDING),
+        width: PORTRAIT_WIDTH,
+        height: ROW_HEIGHT - PADDING * 2.0,
+        coloring: Coloring::Tex(Tex {
+            ty: TexTy::Simple(tex),
+            grayscale_conv: false,
+        }),
+    }
+}
+
+#[derive(Clone, PartialEq)]
+struct Row {
+    name: String,
+    money: String,
+    properties: String,
+    bankrupt: bool,
+}
+
+// everything the HUD shows, it only gets rebuilt if any of this changes
+#[derive(Clone, PartialEq, Default)]
+struct HudState {
+    rows: Vec<Row>,
+    // the player whose turn it is
+    active: Option<usize>,
+}
+
+/// Lists every player along with their money and the number of properties they own
+/// at the top left corner of the screen, the row of the player whose turn it is gets highlighted.
+pub struct Hud {
+    state: HudState,
+    texts: Vec<TextBox<'static>>,
+}
+
+impl Hud {
+
+    pub fn new() -> Self {
+        Self {
+            state: HudState::default(),
+            texts: vec![],
+        }
+    }
+
+    fn build_texts(&mut self) {
+        self.texts.clear();
+        for (player, row) in self.state.rows.iter().enumerate() {
+            let (x, y) = row_pos(player);
+            let text_x = x + STRIPE_WIDTH + PORTRAIT_WIDTH + PADDING * 2.0;
+            let text_width = ROW_WIDTH - (text_x - x) - PADDING;
+            let color = if row.bankrupt { LIGHT_GRAY_UI } else { WHITE };
+            // the name and the money share the upper half of the row
+            self.texts.push(TextBox::new(
+                (text_x, y + ROW_HEIGHT / 2.0),
+                text_width,
+                ROW_HEIGHT / 2.0,
+                Coloring::Color([TRANSPARENT; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                    .with_run(row.name.clone(), TextStyle::new(NAME_SCALE).with_color(color).bold()),
+            ));
+            self.texts.push(TextBox::new(
+                (text_x, y + ROW_HEIGHT / 2.0),
+                text_width,
+                ROW_HEIGHT / 2.0,
+                Coloring::Color([TRANSPARENT; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Right))
+                    .with_run(row.money.clone(), TextStyle::new(NAME_SCALE).with_color(color)),
+            ));
+            self.texts.push(TextBox::new(
+                (text_x, y),
+                text_width,
+                ROW_HEIGHT / 2.0,
+                Coloring::Color([TRANSPARENT; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                    .with_run(row.properties.clone(), TextStyle::new(DETAIL_SCALE).with_color(color)),
+            ));
+        }
+    }
+
+}
+
+impl Component for Hud {
+    fn build_model(&self) -> Model {
+        let mut boxes = vec![];
+        if let Some(player) = self.state.active {
+            // a frame around the active row, the row is drawn on top of it
+            let (x, y) = row_pos(player);
+            boxes.push(ColorBox {
+                pos: (x - ROW_GAP, y - ROW_GAP),
+                width: ROW_WIDTH + ROW_GAP * 2.0,
+                height: ROW_HEIGHT + ROW_GAP * 2.0,
+                coloring: Coloring::Color([WHITE; 4]),
+            });
+        }
+        for player in 0..self.state.rows.len() {
+            let (x, y) = row_pos(player);
+            boxes.push(ColorBox {
+                pos: (x, y),
+                width: ROW_WIDTH,
+                height: ROW_HEIGHT,
+                coloring: Coloring::Color([DARK_GRAY_UI; 4]),
+            });
+            boxes.push(ColorBox {
+                pos: (x, y),
+                width: STRIPE_WIDTH,
+                height: ROW_HEIGHT,
+                coloring: Coloring::Color([palette::player_color(player); 4]),
+            });
+        }
+        Model {
+            vertices: boxes.into_iter().flat_map(|rect| rect.build_model().vertices).collect(),
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn do_render(&self, game: &Arc<Game>) {
+        for text in self.texts.iter() {
+            text.do_render(game);
+        }
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        row_pos(self.state.rows.len().max(1) - 1)
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (ROW_WIDTH, self.state.rows.len() as f32 * (ROW_HEIGHT + ROW_GAP))
+    }
+
+    fn on_click(&mut self, _game: &Arc<Game>, _click_kind: ClickKind, _pos: (f32, f32)) {}
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        let engine = game.engine.lock().unwrap();
+        let state = HudState {
+            rows: engine.players.iter().map(|player| Row {
+                name: player.display_name(),
+                money: if player.bankrupt {
+                    i18n::tr("hud.bankrupt")
+                } else {
+                    engine.rules.currency.format(player.currency)
+                },
+                properties: i18n::tr_with("hud.properties", &[("count", &player.properties.len().to_string())]),
+                bankrupt: player.bankrupt,
+            }).collect(),
+            active: engine.players.get(engine.curr_player).filter(|player| !player.bankrupt).map(|player| player.id),
+        };
+        drop(engine);
+        if state == self.state {
+            return false;
+        }
+        self.state = state;
+        self.build_texts();
+        true
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::RwLock;
+use crate::utils::config_path;
+use crate::utils::{read_config, write_config};
+
+const LANG_DIR: &str = "lang";
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+// the languages which ship with the game, further ones can be added by putting a file into the lang directory
+const BUILTIN_LANGUAGES: [(&str, &str); 2] = [
+    ("en", include_str!("lang/en.json")),
+    ("de", include_str!("lang/de.json")),
+];
+
+static LANGUAGE: RwLock<Option<Language>> = RwLock::new(None);
+
+struct Language {
+    code: String,
+    strings: HashMap<String, String>,
+    // the builtin strings of the default language, used for keys the language doesn't translate (yet)
+    fallback: HashMap<String, String>,
+}
+
+fn lang_file(code: &str) -> String {
+    format!("{}/{}.json", LANG_DIR, code)
+}
+
+fn load_strings(code: &str) -> Option<HashMap<String, String>> {
+    let buf = read_config(&lang_file(code))?;
+    Some(serde_json::from_str(&*buf).unwrap())
+}
+
+// writes the builtin languages which aren't present yet, existing files may have been edited and are kept
+fn write_builtin_languages() {
+    for (code, strings) in BUILTIN_LANGUAGES {
+        if read_config(&lang_file(code)).is_none() {
+            write_config(&lang_file(code), strings);
+        }
+    }
+}
+
+/// Switches the language all strings are translated to, falls back to the default
+/// language if there is no file for the requested one.
+pub fn load_language(code: &str) {
+    write_builtin_languages();
+    // files written by older versions may lack newer keys, so the fallback always comes from the builtin strings
+    let fallback: HashMap<String, String> = serde_json::from_str(BUILTIN_LANGUAGES[0].1).unwrap();
+    let (code, strings) = match load_strings(code) {
+        Some(strings) => (code, strings),
+        None => {
+            println!("There is no language file for \"{}\", using \"{}\" instead", code, DEFAULT_LANGUAGE);
+            (DEFAULT_LANGUAGE, fallback.clone())
+        }
+    };
+    *LANGUAGE.write().unwrap() = Some(Language {
+        code: code.to_string(),
+        strings,
+        fallback,
+    });
+}
+
+// the codes of all languages which have a file in the lang directory
+pub fn available_languages() -> Vec<String> {
+    let mut languages = fs::read_dir(config_path(LANG_DIR)).unwrap()
+        .filter_map(|entry| {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(false, |ext| ext == "json") {
+                path.file_stem().map(|stem| stem.to_string_lossy().into_owned())
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    languages.sort();
+    languages
+}
+
+pub fn current_language() -> String {
+    LANGUAGE.read().unwrap().as_ref().map_or(DEFAULT_LANGUAGE.to_string(), |language| language.code.clone())
+}
+
+/// Translates the key into the current language, the key itself is returned if no language
+/// has a translation for it, so missing strings are easy to spot.
+pub fn tr(key: &str) -> String {
+    let language = LANGUAGE.read().unwrap();
+    language.as_ref()
+        .and_then(|language| language.strings.get(key).or_else(|| language.fallback.get(key)))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+/// How numbers are written in the current language, e.g. "1,234.50" in English
+/// and "1.234,50" in German. Every formatted amount of money goes through this.
+pub struct NumberFormat {
+    pub decimal_separator: String,
+    pub thousands_separator: String,
+    // whether currency symbols are put after the amount
+    pub symbol_after: bool,
+}
+
+impl NumberFormat {
+
+    // groups the digits, the last `decimals` digits are put after the decimal separator
+    pub fn format(&self, value: usize, decimals: usize) -> String {
+        let digits = format!("{:0width$}", value, width = decimals + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - decimals);
+        let mut ret = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                ret.push_str(&self.thousands_separator);
+            }
+            ret.push(digit);
+        }
+        if decimals > 0 {
+            ret.push_str(&self.decimal_separator);
+            ret.push_str(fraction);
+        }
+        ret
+    }
+
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: ".".to_string(),
+            thousands_separator: ",".to_string(),
+            symbol_after: false,
+        }
+    }
+}
+
+// the number format of the current language, the default is used until a language was loaded
+pub fn number_format() -> NumberFormat {
+    let language = LANGUAGE.read().unwrap();
+    let get = |key: &str| language.as_ref()
+        .and_then(|language| language.strings.get(key).or_else(|| language.fallback.get(key)))
+        .cloned();
+    let default = NumberFormat::default();
+    NumberFormat {
+        decimal_separator: get("format.decimal_separator").unwrap_or(default.decimal_separator),
+        thousands_separator: get("format.thousands_separator").unwrap_or(default.thousands_separator),
+        symbol_after: get("format.symbol_after").map_or(default.symbol_after, |after| after == "true"),
+    }
+}
+
+// translates the key and replaces the `{name}` placeholders in the translation with their values
+pub fn tr_with(key: &str, args: &[(&str, &str)]) -> String {
+    let mut text = tr(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
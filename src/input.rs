@@ -0,0 +1,143 @@
+use cgmath::{Deg, Point3};
+use serde::{Deserialize, Serialize};
+use winit::event::{ElementState, MouseButton, VirtualKeyCode};
+use winit::window::{CursorGrabMode, CursorIcon, Window};
+use crate::render::Camera;
+
+// toggles camera mode on and off without having to hold the right mouse button
+const CAMERA_TOGGLE_KEY: VirtualKeyCode = VirtualKeyCode::Tab;
+// switches between director and presentation mode
+const PRESENTATION_TOGGLE_KEY: VirtualKeyCode = VirtualKeyCode::F10;
+
+// who controls the camera
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum CameraMode {
+    // the camera can be moved freely using the camera controller
+    Director,
+    // the camera is locked to a preset which shows the whole board, for classrooms and presentations
+    Presentation,
+}
+
+impl Default for CameraMode {
+    fn default() -> Self {
+        CameraMode::Director
+    }
+}
+
+// the preset the camera is locked to in presentation mode, it looks at the board from the front at an angle
+pub fn presentation_camera() -> Camera {
+    Camera::new(Point3::new(0.0, 9.0, 8.0), Deg(-90.0), Deg(-48.0))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InputMode {
+    // the cursor is free and mouse input goes to the ui
+    Ui,
+    // the cursor is grabbed and mouse movement moves the camera
+    Camera,
+}
+
+// decides whether mouse input goes to the ui or to the camera, so that panning the board
+// doesn't press any buttons and clicking buttons doesn't move the camera.
+pub struct InputArbiter {
+    mode: InputMode,
+    // whether camera mode was entered via the toggle key instead of by holding the right mouse button
+    toggled: bool,
+    camera_mode: CameraMode,
+}
+
+impl InputArbiter {
+
+    pub fn new(camera_mode: CameraMode) -> Self {
+        Self {
+            mode: InputMode::Ui,
+            toggled: false,
+            camera_mode,
+        }
+    }
+
+    #[inline]
+    pub fn mode(&self) -> InputMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn camera_mode(&self) -> CameraMode {
+        self.camera_mode
+    }
+
+    // returns whether the button was consumed, `camera_allowed` should only be true
+    // if there is anything to look at (i.e. the game is the topmost screen)
+    pub fn on_mouse_button(&mut self, window: &Window, button: MouseButton, state: ElementState, camera_allowed: bool) -> bool {
+        if button != MouseButton::Right {
+            return false;
+        }
+        let camera_allowed = camera_allowed && self.camera_mode == CameraMode::Director;
+        match state {
+            ElementState::Pressed if camera_allowed && self.mode == InputMode::Ui => {
+                self.toggled = false;
+                self.set_mode(window, InputMode::Camera);
+            }
+            ElementState::Released if !self.toggled && self.mode == InputMode::Camera => {
+                self.set_mode(window, InputMode::Ui);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    // returns whether the key was consumed
+    pub fn on_key(&mut self, window: &Window, key: VirtualKeyCode, state: ElementState, camera_allowed: bool) -> bool {
+        if key == PRESENTATION_TOGGLE_KEY {
+            if state == ElementState::Released {
+                self.camera_mode = match self.camera_mode {
+                    CameraMode::Director => CameraMode::Presentation,
+                    CameraMode::Presentation => CameraMode::Director,
+                };
+                self.update(window, camera_allowed);
+            }
+            return true;
+        }
+        if key != CAMERA_TOGGLE_KEY {
+            return false;
+        }
+        let camera_allowed = camera_allowed && self.camera_mode == CameraMode::Director;
+        if state == ElementState::Released {
+            match self.mode {
+                InputMode::Ui if camera_allowed => {
+                    self.toggled = true;
+                    self.set_mode(window, InputMode::Camera);
+                }
+                InputMode::Camera => self.set_mode(window, InputMode::Ui),
+                _ => {}
+            }
+        }
+        true
+    }
+
+    // has to be called every frame, so camera mode gets left as soon as e.g. a popup opens
+    pub fn update(&mut self, window: &Window, camera_allowed: bool) {
+        let camera_allowed = camera_allowed && self.camera_mode == CameraMode::Director;
+        if !camera_allowed && self.mode == InputMode::Camera {
+            self.set_mode(window, InputMode::Ui);
+        }
+    }
+
+    fn set_mode(&mut self, window: &Window, mode: InputMode) {
+        self.mode = mode;
+        match mode {
+            InputMode::Ui => {
+                let _ = window.set_cursor_grab(CursorGrabMode::None);
+                // the cursor of whatever gets hovered next takes over once the mouse moves again
+                window.set_cursor_icon(CursorIcon::Default);
+            }
+            InputMode::Camera => {
+                // not every platform supports both grab modes
+                let _ = window.set_cursor_grab(CursorGrabMode::Locked)
+                    .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+                window.set_cursor_icon(CursorIcon::Grabbing);
+            }
+        }
+    }
+
+}
@@ -2,36 +2,49 @@
 #![feature(maybe_uninit_array_assume_init)]
 #![feature(once_cell)]
 
-use std::{fs, mem};
+use std::fs;
 use std::fs::File;
-use std::mem::MaybeUninit;
-use std::ops::{Deref, DerefMut};
+use std::io;
+use std::ops::Deref;
 use std::path::Path;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
 use cgmath::{Deg, Point3, Rad};
 use instant::Instant;
-use rand::Rng;
-use wgpu::{Features, TextureFormat};
+use wgpu::{SurfaceError, TextureFormat};
 use wgpu_biolerless::{DeviceRequirements, StateBuilder};
-use winit::event::{ElementState, Event, MouseButton, WindowEvent};
+use winit::event::{DeviceEvent, ElementState, Event, ModifiersState, MouseButton, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoopBuilder};
-use winit::window::WindowBuilder;
-use crate::action_cards::ActionCard;
+use winit::window::{UserAttentionType, WindowBuilder};
 use crate::atlas::Atlas;
-use crate::board::{Board, Tile};
 use crate::model::Model;
-use crate::player::{Character, load_characters, Player};
-use crate::property::{DefinedProperty, PROPERTIES};
-use crate::render::{Camera, CameraController, ModeledInstance, Renderer};
+use crate::player::{Character, load_characters, VoiceLine};
+use crate::render::{Camera, CameraController, Renderer};
 use crate::screen_sys::ScreenSystem;
-use crate::screens::login;
+use crate::assets::Assets;
+use crate::audio::{Audio, Category, SoundId};
+use crate::events::{GameCommand, GameEvent};
+use crate::settings::{MAX_CAMERA_SENSITIVITY, MIN_CAMERA_SENSITIVITY, Settings};
+use crate::input::{CameraMode, InputArbiter, InputMode};
+use crate::screens::loading::Loading;
+use crate::timestep::{FixedTimestep, FrameLimiter, TICKS_PER_SECOND};
 use crate::ui::ClickKind;
+use crate::engine::Engine;
+use crate::bench::Benchmark;
+use crate::hot_reload::{Change, Watcher};
+use crate::entity::EntityRegistry;
+use crate::mods::Mods;
+use crate::summary::Summary;
+use crate::net::Client;
 
 mod player;
 mod property;
 mod action_cards;
 mod board;
+mod board_layout;
 mod ui;
 mod render;
 mod atlas;
@@ -39,36 +52,141 @@ mod screen_sys;
 mod screens;
 mod utils;
 mod model;
+mod tween;
+mod timestep;
+mod assets;
+mod input;
+mod audio;
+mod events;
+mod settings;
+mod cli;
+mod engine;
+mod ai;
+mod sim;
+mod bench;
+mod hot_reload;
+mod entity;
+mod i18n;
+mod palette;
+mod rules;
+mod text_raster;
+mod animation;
+mod minimap;
+mod money_popups;
+mod bug_report;
+mod card_script;
+mod mods;
+mod mod_panels;
+mod hints;
+mod hud;
+mod action_bar;
+mod chat;
+mod quick_play;
+mod dice;
+mod net;
+mod save;
+mod statistics;
+mod summary;
+mod profile;
+mod daily;
+mod probability;
+#[cfg(test)]
+mod scripted;
+
+const WINDOW_TITLE: &str = "Schul-monopoly";
+// the longest time a single frame may advance the camera by
+const MAX_FRAME_DELTA: Duration = Duration::from_millis(100);
 
 fn main() {
-    if !Path::new("./config/").exists() {
-        fs::create_dir("./config/").unwrap();
+    let args = cli::parse_args();
+    if let Some(config_dir) = args.config_dir.clone() {
+        utils::set_config_dir(config_dir);
+    }
+    if !utils::config_dir().exists() {
+        fs::create_dir_all(utils::config_dir()).unwrap();
+    }
+    if let Some(games) = args.simulate {
+        sim::run(games, args.seed);
+        return;
     }
+    if args.server {
+        net::serve(load_engine(&mods::load_mods(), args.seed), args.port.unwrap_or(net::DEFAULT_PORT));
+        return;
+    }
+    // the clients have to play the server's game, so the connection is established before the game is created
+    let client = match &args.connect {
+        Some(host) => match Client::connect(host, args.port.unwrap_or(net::DEFAULT_PORT)) {
+            Ok(connected) => Some(connected),
+            Err(err) => {
+                println!("Couldn't join the game hosted on {}: {}", host, err);
+                return;
+            }
+        },
+        None => None,
+    };
+    let mut settings = settings::load_settings();
+    args.apply_to(&mut settings);
+    i18n::load_language(&settings.language);
+
     let event_loop = EventLoopBuilder::new().build();
-    let window = WindowBuilder::new()
-        .with_title("Schul-monopoly")
-        .build(&event_loop)
-        .unwrap();
-    let mut req = DeviceRequirements::default();
-    req.features |= Features::PUSH_CONSTANTS;
-    req.limits.max_push_constant_size = 16;
+    let window = settings.window.apply(WindowBuilder::new().with_title(WINDOW_TITLE), event_loop.available_monitors())
+        .build(&event_loop).unwrap();
+    // per model data is passed through uniform buffers, so no optional features are needed
+    let req = DeviceRequirements::default();
     let state = Arc::new(pollster::block_on(
         StateBuilder::new().window(&window).device_requirements(req).build(),
     ).unwrap());
     let renderer = Arc::new(Renderer::new(state.clone(), &window).unwrap());
 
-    let game = Arc::new(Game::new(renderer.clone()));
+    let game = Arc::new(match client {
+        Some((client, engine)) => {
+            let game = Game::new(renderer.clone(), settings, None, None, Some(client));
+            *game.engine.lock().unwrap() = engine;
+            game
+        }
+        None => Game::new(renderer.clone(), settings, args.seed, args.daily.then(daily::today), None),
+    });
+
+    let loaded = match &args.load {
+        Some(path) => match game.load(path) {
+            Ok(()) => true,
+            Err(err) => {
+                println!("Couldn't load the saved game {}, starting a new game: {}", path.display(), err);
+                false
+            }
+        },
+        None => false,
+    };
 
-    game.screen_sys.push_screen(Box::new(login::Login::new(Arc::new(Mutex::new(game.characters.clone())))));
+    let mut benchmark = args.bench.map(Benchmark::new);
+    if benchmark.is_some() || loaded {
+        game.screen_sys.push_screen(Box::new(Loading::skipping_login()));
+    } else {
+        game.screen_sys.push_screen(Box::new(Loading::new()));
+    }
+
+    // shaders and textures get reloaded once they change on disk, so they can be worked on without restarting
+    let watcher = if cfg!(debug_assertions) {
+        Some(Watcher::start())
+    } else {
+        None
+    };
 
     let mut mouse_pos = (0.0, 0.0);
+    let mut modifiers = ModifiersState::empty();
     let mut prev = Instant::now();
+    let mut timestep = FixedTimestep::new(TICKS_PER_SECOND);
+    let mut limiter = FrameLimiter::new();
+    // whether the last frame couldn't be drawn because the gpu ran out of memory
+    let mut out_of_memory = false;
     event_loop.run(move |event, _, control_flow| match event {
         Event::NewEvents(_) => {}
         Event::WindowEvent {
             ref event,
             window_id,
         } if window_id == window.id() => {
+            // all window events are either input or change the window, so the frame has to be drawn again
+            game.request_redraw();
             match event {
                 WindowEvent::Resized(size) => {
                     if !state.resize(*size) {
@@ -80,40 +198,82 @@ fn main() {
                 }
                 WindowEvent::Moved(_) => {}
                 WindowEvent::CloseRequested => {
+                    let mut settings = game.settings.lock().unwrap();
+                    settings.window.update(&window);
+                    settings::save_settings(&settings);
                     *control_flow = ControlFlow::Exit;
                 }
                 WindowEvent::Destroyed => {}
                 WindowEvent::DroppedFile(_) => {}
                 WindowEvent::HoveredFile(_) => {}
                 WindowEvent::HoveredFileCancelled => {}
-                WindowEvent::ReceivedCharacter(_) => {}
-                WindowEvent::Focused(_) => {}
+                WindowEvent::ReceivedCharacter(received) => {
+                    game.screen_sys.receive_char(&game, *received);
+                }
+                WindowEvent::Focused(focused) => {
+                    game.focused.store(*focused, Ordering::Release);
+                    if *focused {
+                        window.set_title(WINDOW_TITLE);
+                        window.request_user_attention(None);
+                    }
+                }
                 WindowEvent::KeyboardInput { input, .. } => {
                     if let Some(keycode) = input.virtual_keycode {
-                        game.camera_controller.lock().unwrap().process_keyboard(keycode, input.state);
+                        let down = input.state == ElementState::Pressed;
+                        if keycode == bug_report::BUG_REPORT_KEY && !down {
+                            match bug_report::export(&game) {
+                                Ok(path) => println!("Exported a bug report to {}", path.display()),
+                                Err(err) => println!("Couldn't export a bug report: {}", err),
+                            }
+                        }
+                        // releases always reach the camera, so it doesn't keep on moving if a screen consumed the key
+                        let consumed = game.screen_sys.press_key(&game, keycode, down, modifiers)
+                            || game.input.lock().unwrap().on_key(&window, keycode, input.state, game.screen_sys.is_current_ingame());
+                        let camera_mode = game.input.lock().unwrap().camera_mode();
+                        game.settings.lock().unwrap().camera_mode = camera_mode;
+                        match camera_mode {
+                            CameraMode::Director => if !consumed || !down {
+                                game.camera_controller.lock().unwrap().process_keyboard(keycode, input.state);
+                            },
+                            // the controller doesn't get any input while the camera is locked to the preset
+                            CameraMode::Presentation => game.camera_controller.lock().unwrap().reset(),
+                        }
                     }
                 }
-                WindowEvent::ModifiersChanged(_) => {}
+                WindowEvent::ModifiersChanged(state) => modifiers = *state,
                 WindowEvent::CursorMoved { position, .. } => {
-                    let (dx, dy) = (position.x - mouse_pos.0, position.y - mouse_pos.1);
-                    game.camera_controller.lock().unwrap().process_mouse(dx, dy);
-                    mouse_pos = (position.x, position.y);
-                    let (width, height) = game.renderer.dimensions.get();
-                    mouse_pos = (position.x / width as f64, 1.0 - position.y / height as f64);
-                    game.screen_sys.on_mouse_hover(&game, mouse_pos);
+                    // the ui only covers the safe area, so positions are relative to it
+                    let (x, y, width, height) = game.renderer.safe_area();
+                    mouse_pos = ((position.x - x as f64) / width as f64, 1.0 - (position.y - y as f64) / height as f64);
+                    if game.input.lock().unwrap().mode() == InputMode::Ui {
+                        window.set_cursor_icon(game.screen_sys.on_mouse_hover(&game, mouse_pos));
+                    }
                 }
                 WindowEvent::CursorEntered { .. } => {}
                 WindowEvent::CursorLeft { .. } => {}
                 WindowEvent::MouseWheel { delta, .. } => {
-                    game.camera_controller.lock().unwrap().process_scroll(delta);
+                    if game.input.lock().unwrap().camera_mode() == CameraMode::Director {
+                        game.camera_controller.lock().unwrap().process_scroll(delta);
+                    }
                 }
                 WindowEvent::MouseInput { button, state, .. } => {
-                    if button == &MouseButton::Left {
+                    let camera_allowed = game.screen_sys.is_current_ingame();
+                    let mode = {
+                        let mut input = game.input.lock().unwrap();
+                        input.on_mouse_button(&window, *button, *state, camera_allowed);
+                        input.mode()
+                    };
+                    if mode == InputMode::Camera {
+                        // buttons don't react to clicks while the camera is being moved, so nothing should stay hovered
+                        game.screen_sys.on_mouse_hover(&game, (-1.0, -1.0));
+                    } else if button == &MouseButton::Left {
                         game.screen_sys.on_mouse_click(&game, mouse_pos, if state == &ElementState::Pressed {
                             ClickKind::PressDown
                         } else {
                             ClickKind::Release
                         });
+                    } else if screen_sys::is_back_button(*button) && state == &ElementState::Released {
+                        game.screen_sys.go_back();
                     }
                 }
                 WindowEvent::TouchpadPressure { .. } => {}
@@ -122,7 +282,10 @@ fn main() {
                 WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
                     if !state.resize(**new_inner_size) {
                         println!("Couldn't resize!");
+                    } else {
+                        game.renderer.dimensions.set(new_inner_size.width, new_inner_size.height);
                     }
+                    game.renderer.resize((new_inner_size.width, new_inner_size.height));
                 }
                 WindowEvent::ThemeChanged(_) => {}
                 WindowEvent::Ime(_) => {}
@@ -132,25 +295,119 @@ fn main() {
                 WindowEvent::TouchpadRotate { .. } => {}
             }
         },
+        Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+            // raw motion keeps on working while the cursor is grabbed
+            if game.input.lock().unwrap().mode() == InputMode::Camera {
+                game.camera_controller.lock().unwrap().process_mouse(delta.0, delta.1);
+                game.request_redraw();
+            }
+        }
         Event::DeviceEvent { .. } => {}
         Event::UserEvent(_) => {}
         Event::Suspended => {}
         Event::Resumed => {}
         Event::MainEventsCleared => {
-            // RedrawRequested will only trigger once, unless we manually
-            // request it.
-            window.request_redraw();
+            // the game logic keeps on running even if no frames are drawn
+            for _ in 0..timestep.advance() {
+                game.tick();
+            }
+            if game.attention_requested.swap(false, Ordering::AcqRel) {
+                window.request_user_attention(Some(UserAttentionType::Informational));
+                window.set_title(&i18n::tr_with("window.your_turn", &[("title", WINDOW_TITLE)]));
+            }
+            if game.window_changed.swap(false, Ordering::AcqRel) {
+                game.settings.lock().unwrap().window.apply_to_window(&window);
+            }
+            if let Some(watcher) = watcher.as_ref() {
+                for change in watcher.changes() {
+                    match change {
+                        Change::Shader => renderer.reload_shaders(),
+                        Change::Texture(path) => renderer.reload_texture(&path),
+                    }
+                    game.request_redraw();
+                }
+            }
+
+            let graphics = game.settings.lock().unwrap().graphics;
+            // the benchmark measures how fast frames can be drawn, so it has to draw all of them without any limit
+            let max_fps = if benchmark.is_some() {
+                None
+            } else {
+                graphics.max_fps
+            };
+            let redraw = benchmark.is_some() || !graphics.redraw_on_demand
+                || game.redraw_requested.load(Ordering::Acquire) || game.is_animating();
+            let next_frame = limiter.next_frame(max_fps);
+            if redraw && Instant::now() >= next_frame {
+                game.redraw_requested.store(false, Ordering::Release);
+                window.request_redraw();
+                *control_flow = ControlFlow::Poll;
+            } else if redraw {
+                *control_flow = ControlFlow::WaitUntil(next_frame.min(timestep.next_tick()));
+            } else {
+                // sleep until the next tick, input wakes the loop up earlier
+                *control_flow = ControlFlow::WaitUntil(timestep.next_tick());
+            }
         }
         Event::RedrawRequested(_) => {
             let now = Instant::now();
-            let curr_delta = now.duration_since(prev);
+            // after the loop slept for a while, the camera mustn't jump by the whole time that passed
+            let curr_delta = now.duration_since(prev).min(MAX_FRAME_DELTA);
             prev = now;
-            // FIXME: perform redraw
+            limiter.frame();
+            game.input.lock().unwrap().update(&window, game.screen_sys.is_current_ingame());
             let models = game.screen_sys.tick(&game, &window);
             let mut camera = game.camera.lock().unwrap();
-            game.camera_controller.lock().unwrap().update_camera(&mut camera, curr_delta);
-            let mut old = mem::replace(game.models.lock().unwrap().deref_mut(), vec![]);
-            renderer.render(models, old, game.atlas.clone(), &camera);
+            if let Some(bench) = benchmark.as_mut() {
+                // the benchmark takes over the camera once the board is shown
+                if game.screen_sys.is_current_ingame() && game.screen_sys.is_current_ready() && !bench.frame(&mut camera) {
+                    bench.report();
+                    benchmark = None;
+                    *control_flow = ControlFlow::Exit;
+                }
+            } else if game.input.lock().unwrap().camera_mode() == CameraMode::Presentation {
+                *camera = input::presentation_camera();
+            } else {
+                game.camera_controller.lock().unwrap().update_camera(&mut camera, curr_delta);
+            }
+            let instances = game.entities.lock().unwrap().instances();
+            // a minimized window has no surface to draw to
+            let size = window.inner_size();
+            if size.width == 0 || size.height == 0 {
+                return;
+            }
+            match renderer.render(models, instances, game.atlas.clone(), &camera) {
+                Ok(()) => out_of_memory = false,
+                // the surface has to be configured again, e.g. after the window was minimized or moved to another gpu
+                Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                    if state.resize(size) {
+                        game.renderer.dimensions.set(size.width, size.height);
+                    }
+                    game.renderer.resize((size.width, size.height));
+                    game.request_redraw();
+                }
+                // the frame simply gets skipped
+                Err(SurfaceError::Timeout) => game.request_redraw(),
+                // the game keeps trying to draw frames with whatever memory it could give back, until the gpu has enough again
+                Err(SurfaceError::OutOfMemory) => {
+                    if !out_of_memory {
+                        println!("The gpu ran out of memory, the game keeps trying to draw");
+                        // the window might get closed before the gpu recovers
+                        if game.is_saved() {
+                            if let Err(err) = game.save(&save::path()) {
+                                println!("Couldn't save the game: {}", err);
+                            }
+                        }
+                        out_of_memory = true;
+                    }
+                    renderer.release_memory();
+                    if state.resize(size) {
+                        game.renderer.dimensions.set(size.width, size.height);
+                    }
+                    game.renderer.resize((size.width, size.height));
+                    game.request_redraw();
+                }
+            }
         }
         Event::RedrawEventsCleared => {}
         Event::LoopDestroyed => {}
@@ -158,106 +415,258 @@ fn main() {
     })
 }
 
-const INITIAL_CURRENCY: usize = 400; // TODO: make this configurable!
-
 pub struct Game {
-    pub players: Mutex<Vec<Player>>,
-    pub properties: [Mutex<DefinedProperty>; PROPERTIES],
-    pub cards: Vec<ActionCard>,
-    pub card_stacks: [Mutex<Vec<usize>>; 2],
-    pub curr_player: AtomicUsize,
-    pub board: Mutex<Board>,
-    pub game_state: Mutex<GameState>,
+    pub engine: Mutex<Engine>,
+    commands: Mutex<Sender<GameCommand>>,
+    command_queue: Mutex<Receiver<GameCommand>>,
+    // set if the game is hosted by a server, the commands are sent to it instead of the local engine
+    client: Mutex<Option<Client>>,
+    subscribers: Mutex<Vec<Sender<GameEvent>>>,
     pub screen_sys: Arc<ScreenSystem>,
     pub renderer: Arc<Renderer>,
     pub atlas: Arc<Atlas>,
     pub characters: Vec<Character>,
-    pub models: Mutex<Vec<ModeledInstance>>,
+    pub entities: Mutex<EntityRegistry>,
     pub camera: Mutex<Camera>,
     pub camera_controller: Mutex<CameraController>,
+    // set by the loading screen once all startup assets are loaded
+    pub assets: OnceLock<Assets>,
+    pub input: Mutex<InputArbiter>,
+    pub audio: Audio,
+    statistics: Mutex<statistics::Recorder>,
+    pub event_log: Mutex<Vec<GameEvent>>,
+    pub settings: Mutex<Settings>,
+    // everything the installed mods registered
+    pub mods: Mods,
+    // the day of the daily challenge if it's being played
+    pub daily: Option<u64>,
+    pub focused: AtomicBool,
+    // set when the window should flash to get the players' attention, handled by the event loop
+    pub attention_requested: AtomicBool,
+    // set when the window settings were changed, the event loop applies them to the window
+    pub window_changed: AtomicBool,
+    // set when something changed which has to be drawn, only used if frames are redrawn on demand
+    redraw_requested: AtomicBool,
 }
 
 impl Game {
 
-    pub fn new(renderer: Arc<Renderer>) -> Self {
-        let board = board::load_board();
-        let mut players = vec![];
-
-        let mut properties = MaybeUninit::uninit_array();
-        let mut idx = 0;
-        for tile in board.tiles.iter() {
-            if let Tile::Property { property } = tile {
-                properties[idx].write(Mutex::new(DefinedProperty {
-                    frame: property.clone(),
-                    houses: 0,
-                    owner: None,
-                }));
-                idx += 1;
-            }
-        }
-        let cards = action_cards::load_cards();
-        let mut first_card_stack = vec![];
-        for _ in 0..(cards.len() / 2) {
-            first_card_stack.push(rand::thread_rng().gen_range(0..(cards.len())));
-        }
-        let mut second_card_stack = vec![];
-        for x in 0..cards.len() {
-            if !first_card_stack.contains(&x) {
-                second_card_stack.push(x);
+    // if no seed is provided, the game is seeded from the system's entropy source,
+    // the seed of the daily challenge is derived from its day instead
+    pub fn new(renderer: Arc<Renderer>, settings: Settings, seed: Option<u64>, daily: Option<u64>, client: Option<Client>) -> Self {
+        let (mods, engine) = match daily {
+            // mods would change the challenge, so they aren't loaded
+            Some(day) => (Mods::default(), daily::engine(day)),
+            None => {
+                let mods = mods::load_mods();
+                let engine = load_engine(&mods, seed);
+                (mods, engine)
             }
-        }
+        };
+        let (commands, command_queue) = channel();
+
+        let audio = Audio::new(audio::load_audio_manifest());
+        settings.audio.apply(&audio);
+        // the music keeps on looping for as long as the window is open, muting only silences it
+        audio.play_looped(SoundId::BackgroundMusic);
+        settings.accessibility.apply(&renderer);
+        settings.graphics.apply(&renderer);
 
         let atlas = Arc::new(Atlas::new(renderer.state.clone(), (1024, 1024), TextureFormat::Rgba8Unorm));
         let camera = Mutex::new(Camera::new(Point3::new(0.0, 0.0, 0.0), Rad::from(Deg(45.0)), Rad::from(Deg(45.0))));
 
         Self {
-            players: Mutex::new(players),
-            properties: unsafe { MaybeUninit::array_assume_init(properties) },
-            cards,
-            card_stacks: [Mutex::new(first_card_stack), Mutex::new(second_card_stack)],
-            curr_player: AtomicUsize::new(0),
-            board: Mutex::new(board),
-            game_state: Mutex::new(GameState::Login),
+            engine: Mutex::new(engine),
+            commands: Mutex::new(commands),
+            command_queue: Mutex::new(command_queue),
+            client: Mutex::new(client),
+            subscribers: Mutex::new(vec![]),
             screen_sys: Arc::new(ScreenSystem::new()),
             renderer,
             atlas,
             characters: load_characters(),
-            models: Mutex::new(vec![]),
+            entities: Mutex::new(EntityRegistry::new()),
             camera,
-            camera_controller: Mutex::new(CameraController::new(0.2, 0.05/*0.5*/)),
+            camera_controller: Mutex::new(CameraController::new(0.2, settings.camera_sensitivity.clamp(MIN_CAMERA_SENSITIVITY, MAX_CAMERA_SENSITIVITY))),
+            assets: OnceLock::new(),
+            input: Mutex::new(InputArbiter::new(settings.camera_mode)),
+            audio,
+            statistics: Mutex::new(statistics::Recorder::new()),
+            event_log: Mutex::new(vec![]),
+            settings: Mutex::new(settings),
+            mods,
+            daily,
+            focused: AtomicBool::new(true),
+            attention_requested: AtomicBool::new(false),
+            window_changed: AtomicBool::new(false),
+            redraw_requested: AtomicBool::new(true),
         }
     }
 
-    pub fn tick(&self) {
-        let curr_player = self.curr_player.load(Ordering::Acquire);
-        let players = self.players.lock().unwrap().len();
-        if players != 0 {
-            self.curr_player.store((curr_player + 1) % players, Ordering::Release);
+    // runs TICKS_PER_SECOND times a second, regardless of the frame rate
+    pub fn tick(self: &Arc<Self>) {
+        self.process_commands();
+        self.screen_sys.fixed_tick(self);
+    }
+
+    // makes sure the next frame gets drawn, even if frames are only redrawn on demand
+    pub fn request_redraw(&self) {
+        self.redraw_requested.store(true, Ordering::Release);
+    }
+
+    // whether something is moving on its own, so every frame has to be drawn
+    pub fn is_animating(&self) -> bool {
+        self.screen_sys.is_animating()
+            || self.entities.lock().unwrap().is_animating()
+            || self.camera_controller.lock().unwrap().is_moving()
+    }
+
+    // queues the command, it gets validated and applied by the engine during the next tick
+    pub fn submit(&self, command: GameCommand) {
+        let mut client = self.client.lock().unwrap();
+        if let Some(connected) = client.as_ref() {
+            // leaving a networked game disconnects from the server, the local engine is reset afterwards
+            if command != GameCommand::LeaveGame {
+                connected.submit(command);
+                return;
+            }
+            *client = None;
+        }
+        self.commands.lock().unwrap().send(command).unwrap();
+    }
+
+    // chat messages are sent to the server, they show up once it relayed them to everybody
+    pub fn chat(&self, text: String) {
+        if let Some(client) = self.client.lock().unwrap().as_ref() {
+            client.chat(text);
+        }
+    }
+
+    pub fn is_networked(&self) -> bool {
+        self.client.lock().unwrap().is_some()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        save::save(&self.engine.lock().unwrap(), path)
+    }
+
+    // the daily challenge has to be played in one go and networked games are saved by nobody
+    // FIXME: let the server save networked games
+    fn is_saved(&self) -> bool {
+        self.daily.is_none() && !self.is_networked()
+    }
+
+    // replaces the current game, this is only meant to be used before the game was started
+    pub fn load(&self, path: &Path) -> io::Result<()> {
+        *self.engine.lock().unwrap() = save::load(path)?;
+        Ok(())
+    }
+
+    // whether the player is controlled from this machine, which all players are unless the game is networked
+    pub fn is_local(&self, player: usize) -> bool {
+        self.client.lock().unwrap().as_ref().map_or(true, |client| client.is_local(player))
+    }
+
+    // the player who sits in front of this machine, that's whoever's turn it is unless the game is networked
+    pub fn local_player(&self) -> Option<usize> {
+        let networked = self.client.lock().unwrap().as_ref().map(|client| client.first_player());
+        networked.unwrap_or_else(|| Some(self.engine.lock().unwrap().curr_player))
+    }
+
+    // every event emitted from now on is sent to the returned receiver as well
+    pub fn subscribe(&self) -> Receiver<GameEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    fn process_commands(&self) {
+        let events = {
+            let mut engine = self.engine.lock().unwrap();
+            // the server's engine ticks for the clients, the local engine only replays its steps
+            let received = match self.client.lock().unwrap().as_ref() {
+                Some(client) => client.sync(&mut engine),
+                None => {
+                    for command in self.command_queue.lock().unwrap().try_iter() {
+                        // rejected commands are reported to the subscribers through an event
+                        let _ = engine.apply(command);
+                    }
+                    engine.tick(Duration::from_secs(1) / TICKS_PER_SECOND);
+                    vec![]
+                }
+            };
+            let mut events = engine.take_events();
+            events.extend(received);
+            events
+        };
+        for event in events {
+            self.emit(event);
         }
+    }
 
+    // records the event in the event log, plays its sound effect and passes it on to the subscribers
+    fn emit(&self, event: GameEvent) {
+        if let GameEvent::TurnStarted { .. } = event {
+            if self.is_saved() {
+                if let Err(err) = self.save(&save::path()) {
+                    println!("Couldn't save the game: {}", err);
+                }
+            }
+            // call the players back to the window if they're doing something else
+            if !self.focused.load(Ordering::Acquire) {
+                self.audio.play(SoundId::TurnChime);
+                self.attention_requested.store(true, Ordering::Release);
+            }
+        }
+        if let Some(sound) = event.sound() {
+            self.audio.play(sound);
+        }
+        if let Some((player, line)) = event.voice_line() {
+            let character = self.engine.lock().unwrap().players.get(player).map(|player| player.character_id);
+            if let Some(character) = character {
+                self.play_voice_line(character, line);
+            }
+        }
+        // subscribers which dropped their receiver aren't interested anymore
+        self.subscribers.lock().unwrap().retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        if let GameEvent::GameFinished { .. } = event {
+            // finished games can't be continued
+            if self.is_saved() {
+                let _ = fs::remove_file(save::path());
+            }
+            if let Some(day) = self.daily {
+                daily::record(day, &self.engine.lock().unwrap());
+            }
+            // drawing the board's image takes a while, so it's done on another thread
+            if let Some(summary) = Summary::new(self) {
+                let renderer = self.renderer.clone();
+                thread::spawn(move || match summary.export(&renderer) {
+                    Ok(path) => println!("Saved a summary of the game to {}", path.display()),
+                    Err(err) => println!("Couldn't save a summary of the game: {}", err),
+                });
+            }
+        }
+        if self.settings.lock().unwrap().collect_statistics {
+            self.statistics.lock().unwrap().on_event(&event, &self.engine.lock().unwrap());
+        }
+        self.event_log.lock().unwrap().push(event);
+        self.request_redraw();
     }
 
-    pub fn add_player(&self, char_id: usize) {
-        let mut players = self.players.lock().unwrap();
-        let len = players.len();
-        players.push(Player {
-            name: String::new(), // FIXME: implement text fields to enable players to choose names.
-            currency: INITIAL_CURRENCY,
-            id: len,
-            character_id: char_id,
-            properties: vec![],
-            position: self.board.lock().unwrap().index.start,
-            jail_free_cards: 0,
-            jail_free_throws: 0,
-            wait: 0,
-        });
+    pub fn play_voice_line(&self, char_id: usize, line: VoiceLine) {
+        if let Some(path) = self.characters.iter().find(|character| character.id == char_id)
+            .and_then(|character| character.voice_lines.get(line)) {
+            self.audio.play_file(path.clone(), Category::Voice);
+        }
     }
 
 }
 
-#[derive(Copy, Clone, PartialEq)]
-pub enum GameState {
-    Login,
-    InGame,
-    Finished,
+// the engine of a regular game, the mods' cards are shuffled into the stacks along with the configured ones
+fn load_engine(mods: &Mods, seed: Option<u64>) -> Engine {
+    let mut cards = action_cards::load_cards();
+    cards.extend(mods.cards.iter().cloned());
+    let mut engine = Engine::new(board::load_board(), cards, rules::load_rules(), seed);
+    engine.tile_scripts = mods.tiles.clone();
+    engine
 }
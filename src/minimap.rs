@@ -0,0 +1,171 @@
+use std::sync::Arc;
+use crate::board::{Tile, TILES};
+use crate::board_layout::{tile_position, BOARD_HALF_SIZE};
+use crate::palette;
+use crate::property::{PropertyFrame, PropertyType};
+use crate::render::{ColorSource, Model};
+use crate::ui::{ClickKind, Color, ColorBox, Coloring, Component, HoverMode};
+use crate::utils::{DARK_GRAY_UI, LIGHT_GRAY_UI};
+use crate::Game;
+
+// the width of the minimap relative to the screen's width, it's always square
+const MINIMAP_SIZE: f32 = 0.18;
+const MINIMAP_MARGIN: f32 = 0.01;
+// the corners take up one cell each, so every side is one cell longer than its tiles
+const CELLS: usize = TILES / 4 + 1;
+// relative to the size of a cell
+const OWNER_SIZE: f32 = 0.5;
+const TOKEN_SIZE: f32 = 0.35;
+const HIGHLIGHT_WIDTH: f32 = 0.12;
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+// everything the minimap shows, it only gets rebuilt if any of this changes
+#[derive(Clone, PartialEq, Default)]
+struct MinimapState {
+    // the color group of every tile (if it has one) along with the player owning it
+    tiles: Vec<(Option<Color>, Option<usize>)>,
+    // the tile every player who is still in the game is on
+    tokens: Vec<(usize, usize)>,
+    // the tile of the player whose turn it is
+    active: Option<usize>,
+    // the width divided by the height of the screen
+    aspect: f32,
+}
+
+/// A schematic top down view of the board in the top right corner of the screen, showing who
+/// owns which tile and where the tokens are, so players keep track of the game while zoomed in.
+pub struct Minimap {
+    state: MinimapState,
+}
+
+impl Minimap {
+
+    pub fn new() -> Self {
+        Self {
+            state: MinimapState::default(),
+        }
+    }
+
+    fn height(&self) -> f32 {
+        MINIMAP_SIZE * self.state.aspect
+    }
+
+    fn cell_dims(&self) -> (f32, f32) {
+        (MINIMAP_SIZE / CELLS as f32, self.height() / CELLS as f32)
+    }
+
+    // the bottom left corner of the tile's cell
+    fn cell_pos(&self, tile: usize) -> (f32, f32) {
+        let position = tile_position(tile);
+        // from -1.0..=1.0 to the index of the cell
+        let column = ((position.x / BOARD_HALF_SIZE + 1.0) / 2.0 * (CELLS - 1) as f32).round();
+        let row = ((position.z / BOARD_HALF_SIZE + 1.0) / 2.0 * (CELLS - 1) as f32).round();
+        let (width, height) = self.cell_dims();
+        let (x, y) = self.pos();
+        // looking down at the board, the far edge (negative z) is at the top
+        (x + column * width, y + self.height() - (row + 1.0) * height)
+    }
+
+    // a rect inside the tile's cell, centered on the given point (relative to the cell)
+    fn cell_rect(&self, tile: usize, center: (f32, f32), size: f32, color: Color) -> ColorBox {
+        let (x, y) = self.cell_pos(tile);
+        let (width, height) = self.cell_dims();
+        ColorBox {
+            pos: (x + width * (center.0 - size / 2.0), y + height * (center.1 - size / 2.0)),
+            width: width * size,
+            height: height * size,
+            coloring: Coloring::Color([color; 4]),
+        }
+    }
+
+}
+
+impl Component for Minimap {
+    fn build_model(&self) -> Model {
+        let mut boxes = vec![ColorBox {
+            pos: self.pos(),
+            width: MINIMAP_SIZE,
+            height: self.height(),
+            coloring: Coloring::Color([DARK_GRAY_UI; 4]),
+        }];
+        if let Some(tile) = self.state.active {
+            // a frame around the active tile, the tile is drawn on top of it
+            boxes.push(self.cell_rect(tile, (0.5, 0.5), 1.0 + HIGHLIGHT_WIDTH * 2.0, WHITE));
+        }
+        for (tile, (group, owner)) in self.state.tiles.iter().enumerate() {
+            boxes.push(self.cell_rect(tile, (0.5, 0.5), 0.9, group.unwrap_or(LIGHT_GRAY_UI)));
+            if let Some(owner) = owner {
+                boxes.push(self.cell_rect(tile, (0.5, 0.5), OWNER_SIZE, palette::player_color(*owner)));
+            }
+        }
+        for (player, tile) in self.state.tokens.iter() {
+            // up to 4 tokens fit into the corners of a cell
+            let corner = (
+                0.25 + 0.5 * (player % 2) as f32,
+                0.25 + 0.5 * ((player / 2) % 2) as f32,
+            );
+            boxes.push(self.cell_rect(*tile, corner, TOKEN_SIZE, WHITE));
+            boxes.push(self.cell_rect(*tile, corner, TOKEN_SIZE * 0.7, palette::player_color(*player)));
+        }
+        Model {
+            vertices: boxes.into_iter().flat_map(|rect| rect.build_model().vertices).collect(),
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        (1.0 - MINIMAP_SIZE - MINIMAP_MARGIN, 1.0 - self.height() - MINIMAP_MARGIN * self.state.aspect)
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (MINIMAP_SIZE, self.height())
+    }
+
+    fn on_click(&mut self, _game: &Arc<Game>, _click_kind: ClickKind, _pos: (f32, f32)) {}
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        let colorblind = game.settings.lock().unwrap().accessibility.colorblind;
+        let (width, height) = game.renderer.ui_dimensions();
+        let engine = game.engine.lock().unwrap();
+        let state = MinimapState {
+            tiles: engine.board.tiles.iter().map(|tile| match tile {
+                Tile::Property { property: frame } => {
+                    let group = match frame {
+                        PropertyFrame { ty: PropertyType::Normal { group: Some(group), .. }, .. } => Some(palette::group_color(*group, colorblind)),
+                        _ => None,
+                    };
+                    (group, engine.properties[frame.id].owner)
+                }
+                _ => (None, None),
+            }).collect(),
+            tokens: engine.players.iter().enumerate()
+                .filter(|(_, player)| !player.bankrupt)
+                .map(|(idx, player)| (idx, player.position))
+                .collect(),
+            active: engine.players.get(engine.curr_player).map(|player| player.position),
+            aspect: width as f32 / height.max(1) as f32,
+        };
+        if state == self.state {
+            return false;
+        }
+        self.state = state;
+        true
+    }
+}
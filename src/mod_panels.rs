@@ -0,0 +1,103 @@
+use std::sync::Arc;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::card_script;
+use crate::render::{ColorSource, Model};
+use crate::ui::{ClickKind, Color, Coloring, Component, HoverMode, TextBox, TextSection, TextStyle};
+use crate::utils::DARK_GRAY_UI;
+use crate::Game;
+
+const PANEL_WIDTH: f32 = 0.18;
+const PANEL_HEIGHT: f32 = 0.1;
+const PANEL_MARGIN: f32 = 0.01;
+const TITLE_SCALE: f32 = 24.0;
+const TEXT_SCALE: f32 = 20.0;
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+/// The panels registered by mods, stacked upwards from the bottom right corner of the screen.
+/// Their scripts are evaluated again whenever something happened in the game.
+pub struct ModPanels {
+    // the length of the event log when the texts were evaluated the last time
+    evaluated: Option<usize>,
+    boxes: Vec<TextBox<'static>>,
+}
+
+impl ModPanels {
+
+    pub fn new() -> Self {
+        Self {
+            evaluated: None,
+            boxes: vec![],
+        }
+    }
+
+}
+
+fn panel_box(idx: usize, title: &str, text: String) -> TextBox<'static> {
+    TextBox::new(
+        (1.0 - PANEL_WIDTH - PANEL_MARGIN, PANEL_MARGIN + idx as f32 * (PANEL_HEIGHT + PANEL_MARGIN)),
+        PANEL_WIDTH,
+        PANEL_HEIGHT,
+        Coloring::Color([DARK_GRAY_UI; 4]),
+        TextSection::new(Layout::default_wrap().v_align(VerticalAlign::Top).h_align(HorizontalAlign::Left))
+            .with_run(format!("{}\n", title), TextStyle::new(TITLE_SCALE).with_color(WHITE).bold())
+            .with_run(text, TextStyle::new(TEXT_SCALE).with_color(WHITE)),
+    )
+}
+
+impl Component for ModPanels {
+    fn build_model(&self) -> Model {
+        Model {
+            vertices: self.boxes.iter().flat_map(|text| text.build_model().vertices).collect(),
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn do_render(&self, game: &Arc<Game>) {
+        for text in self.boxes.iter() {
+            text.do_render(game);
+        }
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        (1.0 - PANEL_WIDTH - PANEL_MARGIN, PANEL_MARGIN)
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (PANEL_WIDTH, self.boxes.len() as f32 * (PANEL_HEIGHT + PANEL_MARGIN))
+    }
+
+    fn on_click(&mut self, _game: &Arc<Game>, _click_kind: ClickKind, _pos: (f32, f32)) {}
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        // the game's state only changes through events, so the texts can't have changed otherwise
+        let events = game.event_log.lock().unwrap().len();
+        if self.evaluated == Some(events) {
+            return false;
+        }
+        self.evaluated = Some(events);
+        let engine = game.engine.lock().unwrap();
+        self.boxes = game.mods.panels.iter().enumerate().map(|(idx, panel)| {
+            // showing the error helps whoever is writing the mod
+            let text = card_script::eval_text(&panel.source, &engine).unwrap_or_else(|err| err);
+            panel_box(idx, &panel.title, text)
+        }).collect();
+        true
+    }
+}
@@ -1,6 +1,6 @@
 use std::fs::{read, read_to_string};
 use anyhow::Result;
-use image::{DynamicImage, GenericImageView};
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
 use std::io::{BufReader, Cursor};
 use std::mem::size_of;
 use std::ops::Range;
@@ -15,7 +15,7 @@ use wgpu::{
 use wgpu_biolerless::{State, TextureBuilder};
 use crate::ui::COLOR_UV_OFFSETS;
 
-const RECT_INDICES: [u32/*u16*/; 6] = [
+pub const RECT_INDICES: [u32/*u16*/; 6] = [
     0, // bottom left
     1, // bottom right
     2, // top right
@@ -50,7 +50,7 @@ pub fn rectangle_model(state: &State, pos: (f32, f32), width: f32, height: f32)
         normal: normal.clone(),
     }, ModelTexVertex {
         position: [vertices[3][0], vertices[3][1], 0.0],
-        tex_coords: [COLOR_UV_OFFSETS[4].0, COLOR_UV_OFFSETS[4].1],
+        tex_coords: [COLOR_UV_OFFSETS[3].0, COLOR_UV_OFFSETS[3].1],
         normal: normal.clone(),
     }];
     let vertex_buffer = state.create_buffer(&vertices, BufferUsages::VERTEX);
@@ -64,6 +64,49 @@ pub fn rectangle_model(state: &State, pos: (f32, f32), width: f32, height: f32)
             material: 0,
         }],
         materials: vec![],
+        lods: vec![],
+    }
+}
+
+// a flat square lying on the xz plane, centered around the origin and facing upwards
+pub fn quad_model(state: &State, size: f32) -> Model {
+    plane_model(state, size, size)
+}
+
+// a flat rect lying on the xz plane, centered around the origin and facing upwards,
+// the top of its texture points towards negative z
+pub fn plane_model(state: &State, width: f32, depth: f32) -> Model {
+    let (half_width, half_depth) = (width / 2.0, depth / 2.0);
+    let normal = [0.0, 1.0, 0.0];
+    let vertices = [ModelTexVertex {
+        position: [-half_width, 0.0, half_depth],
+        tex_coords: [0.0, 1.0],
+        normal,
+    }, ModelTexVertex {
+        position: [half_width, 0.0, half_depth],
+        tex_coords: [1.0, 1.0],
+        normal,
+    }, ModelTexVertex {
+        position: [half_width, 0.0, -half_depth],
+        tex_coords: [1.0, 0.0],
+        normal,
+    }, ModelTexVertex {
+        position: [-half_width, 0.0, -half_depth],
+        tex_coords: [0.0, 0.0],
+        normal,
+    }];
+    let vertex_buffer = state.create_buffer(&vertices, BufferUsages::VERTEX);
+    let index_buffer = state.create_buffer(&RECT_INDICES, BufferUsages::INDEX);
+    Model {
+        meshes: vec![Mesh {
+            name: "".to_string(),
+            vertex_buffer,
+            index_buffer,
+            num_elements: RECT_INDICES.len() as u32,
+            material: 0,
+        }],
+        materials: vec![],
+        lods: vec![],
     }
 }
 
@@ -136,9 +179,31 @@ impl Vertex for ModelColorVertex {
 pub struct Model {
     pub meshes: Vec<Mesh>,
     pub materials: Vec<Material>,
+    // simpler variants which are drawn instead of the model from the given distance to the camera on,
+    // sorted by that distance
+    pub lods: Vec<(f32, Model)>,
 }
 
 impl Model {
+
+    pub fn with_lod(mut self, min_distance: f32, lod: Model) -> Self {
+        self.lods.push((min_distance, lod));
+        self.lods.sort_by(|a, b| a.0.total_cmp(&b.0));
+        self
+    }
+
+    // the level of detail to draw at the given distance to the camera, 0 is the model itself
+    pub fn lod_level(&self, distance: f32) -> usize {
+        self.lods.iter().take_while(|(min_distance, _)| distance >= *min_distance).count()
+    }
+
+    pub fn level(&self, level: usize) -> &Model {
+        match level {
+            0 => self,
+            _ => &self.lods[level - 1].1,
+        }
+    }
+
     pub fn load_from(
         file_name: &str,
         state: &State,
@@ -172,22 +237,11 @@ impl Model {
             path.push_str(&m.diffuse_texture);
             let bytes = read(&path)?;
             let diffuse_texture = ContainedTexture::from_bytes(state, &bytes)/*load_texture(&m.diffuse_texture, state).await*/?;
-            let bind_group = state.create_bind_group(
-                layout,
-                &[
-                    BindGroupEntry {
-                        binding: 0,
-                        resource: BindingResource::TextureView(&diffuse_texture.view),
-                    },
-                    BindGroupEntry {
-                        binding: 1,
-                        resource: BindingResource::Sampler(&diffuse_texture.sampler),
-                    },
-                ],
-            );
+            let bind_group = texture_bind_group(state, layout, &diffuse_texture);
 
             materials.push(Material {
                 name: m.name,
+                texture_path: path,
                 diffuse_texture,
                 bind_group,
             })
@@ -225,16 +279,125 @@ impl Model {
             })
             .collect::<Vec<_>>();
 
-        Ok(Self { meshes, materials })
+        Ok(Self { meshes, materials, lods: vec![] })
+    }
+}
+
+// the normal and the two axes spanning each face of the placeholder box, the axes' cross product is the normal
+const BOX_FACES: [([f32; 3], [f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+];
+// the value shown on each of the faces above, opposite faces add up to 7 like on real dice
+const DIE_FACES: [u8; 6] = [3, 4, 1, 6, 5, 2];
+const CHECKERBOARD_SIZE: u32 = 8;
+
+// a cube centered around the origin, its texture has the faces 1 to 6 next to each other, see `palette::die_texture`
+pub fn die_model(state: &State, size: f32) -> Model {
+    let mut vertices = Vec::with_capacity(BOX_FACES.len() * 4);
+    let mut indices = Vec::with_capacity(BOX_FACES.len() * 6);
+    for ((normal, u, v), value) in BOX_FACES.into_iter().zip(DIE_FACES) {
+        let base = vertices.len() as u32;
+        for (su, sv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            vertices.push(ModelTexVertex {
+                position: [0, 1, 2].map(|axis| (normal[axis] * 0.5 + u[axis] * su + v[axis] * sv) * size),
+                tex_coords: [(value - 1) as f32 / 6.0 + (su + 0.5) / 6.0, 0.5 - sv],
+                normal,
+            });
+        }
+        indices.extend([0, 1, 2, 0, 2, 3].map(|idx| base + idx));
+    }
+    Model {
+        meshes: vec![Mesh {
+            name: "die".to_string(),
+            vertex_buffer: state.create_buffer(&vertices, BufferUsages::VERTEX),
+            index_buffer: state.create_buffer(&indices, BufferUsages::INDEX),
+            num_elements: indices.len() as u32,
+            material: 0,
+        }],
+        materials: vec![],
+        lods: vec![],
+    }
+}
+
+// drawn in place of models which are still being loaded, a unit box with a checkerboard texture
+pub fn placeholder_model(state: &State, layout: &BindGroupLayout) -> Model {
+    let mut vertices = Vec::with_capacity(BOX_FACES.len() * 4);
+    let mut indices = Vec::with_capacity(BOX_FACES.len() * 6);
+    for (normal, u, v) in BOX_FACES {
+        let base = vertices.len() as u32;
+        // the same order as COLOR_UV_OFFSETS
+        for (su, sv) in [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)] {
+            vertices.push(ModelTexVertex {
+                position: [0, 1, 2].map(|axis| normal[axis] * 0.5 + u[axis] * su + v[axis] * sv),
+                tex_coords: [su + 0.5, 0.5 - sv],
+                normal,
+            });
+        }
+        indices.extend([0, 1, 2, 0, 2, 3].map(|idx| base + idx));
+    }
+    let checkerboard = RgbaImage::from_fn(CHECKERBOARD_SIZE, CHECKERBOARD_SIZE, |x, y| if (x + y) % 2 == 0 {
+        Rgba([255, 0, 255, 255])
+    } else {
+        Rgba([0, 0, 0, 255])
+    });
+    let diffuse_texture = ContainedTexture::from_image(state, &DynamicImage::ImageRgba8(checkerboard));
+    let bind_group = texture_bind_group(state, layout, &diffuse_texture);
+    Model {
+        meshes: vec![Mesh {
+            name: "placeholder".to_string(),
+            vertex_buffer: state.create_buffer(&vertices, BufferUsages::VERTEX),
+            index_buffer: state.create_buffer(&indices, BufferUsages::INDEX),
+            num_elements: indices.len() as u32,
+            material: 0,
+        }],
+        materials: vec![Material {
+            name: "placeholder".to_string(),
+            texture_path: String::new(),
+            diffuse_texture,
+            bind_group,
+        }],
+        lods: vec![],
     }
 }
 
 pub struct Material {
     pub name: String,
+    // the file the diffuse texture was loaded from
+    pub texture_path: String,
     pub diffuse_texture: ContainedTexture,
     pub bind_group: BindGroup,
 }
 
+impl Material {
+
+    pub fn set_texture(&mut self, state: &State, layout: &BindGroupLayout, texture: ContainedTexture) {
+        self.bind_group = texture_bind_group(state, layout, &texture);
+        self.diffuse_texture = texture;
+    }
+
+}
+
+fn texture_bind_group(state: &State, layout: &BindGroupLayout, texture: &ContainedTexture) -> BindGroup {
+    state.create_bind_group(
+        layout,
+        &[
+            BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(&texture.view),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: BindingResource::Sampler(&texture.sampler),
+            },
+        ],
+    )
+}
+
 pub struct Mesh {
     pub name: String,
     pub vertex_buffer: Buffer,
@@ -6,10 +6,12 @@ struct CameraUniform {
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
-struct PushConstants {
+// every drawn model gets its own slot of the buffer, which is selected via a dynamic offset
+struct ModelUniform {
     color: vec4<f32>,
 }
-var<push_constant> pc: PushConstants;
+@group(1) @binding(0)
+var<uniform> model_data: ModelUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -46,5 +48,5 @@ fn main_vert(
 fn main_frag(
     in: VertexOutput
 ) -> @location(0) vec4<f32> {
-    return pc.color;
+    return model_data.color;
 }
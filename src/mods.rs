@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use serde::Deserialize;
+use crate::action_cards::{Action, ActionCard};
+
+const MODS_DIR: &str = "./mods/";
+// every directory inside the mods directory which contains this file is a mod
+const MANIFEST_FILE: &str = "mod.json";
+
+// the scripts are referred to by their path relative to the mod's directory
+#[derive(Deserialize)]
+struct Manifest {
+    name: String,
+    #[serde(default)]
+    tiles: Vec<TileManifest>,
+    #[serde(default)]
+    cards: Vec<CardManifest>,
+    #[serde(default)]
+    panels: Vec<PanelManifest>,
+}
+
+#[derive(Deserialize)]
+struct TileManifest {
+    // the board config places the tile through this, like `{"Custom": {"name": "Exam", "kind": "exam"}}`
+    kind: String,
+    script: String,
+}
+
+#[derive(Deserialize)]
+struct CardManifest {
+    text: String,
+    script: String,
+}
+
+#[derive(Deserialize)]
+struct PanelManifest {
+    title: String,
+    script: String,
+}
+
+// a box shown during the game, the script evaluates to its text
+#[derive(Clone)]
+pub struct Panel {
+    pub title: String,
+    pub source: String,
+}
+
+/// Everything the mods registered, all behavior is defined through rhai scripts
+/// which have the same API as the scripts of action cards (see `card_script`).
+#[derive(Clone, Default)]
+pub struct Mods {
+    // the scripts which run when a token lands on a custom tile, indexed by the tile's kind
+    pub tiles: HashMap<String, String>,
+    // these get shuffled into the card stacks along with the configured cards
+    pub cards: Vec<ActionCard>,
+    pub panels: Vec<Panel>,
+}
+
+impl Mods {
+
+    pub fn register_tile(&mut self, kind: String, source: String) {
+        if self.tiles.contains_key(&kind) {
+            println!("The tile kind {} was registered by multiple mods, the last one is used", kind);
+        }
+        self.tiles.insert(kind, source);
+    }
+
+    pub fn register_card(&mut self, text: String, source: String) {
+        self.cards.push(ActionCard {
+            text,
+            action: Action::Script { source },
+        });
+    }
+
+    pub fn register_panel(&mut self, title: String, source: String) {
+        self.panels.push(Panel {
+            title,
+            source,
+        });
+    }
+
+}
+
+// a mod which fails to load is skipped, so it can't keep the game from starting
+pub fn load_mods() -> Mods {
+    let mut mods = Mods::default();
+    let Ok(entries) = fs::read_dir(MODS_DIR) else {
+        return mods;
+    };
+    let mut dirs = entries.filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.join(MANIFEST_FILE).is_file())
+        .collect::<Vec<_>>();
+    // later mods override the tiles of earlier ones, so the order has to be the same on every start
+    dirs.sort();
+    for dir in dirs {
+        if let Err(err) = load_mod(&dir, &mut mods) {
+            println!("Couldn't load the mod at {}: {}", dir.display(), err);
+        }
+    }
+    mods
+}
+
+fn load_mod(dir: &Path, mods: &mut Mods) -> anyhow::Result<()> {
+    let manifest: Manifest = serde_json::from_str(&fs::read_to_string(dir.join(MANIFEST_FILE))?)?;
+    let script = |path: &str| fs::read_to_string(dir.join(path));
+    // all scripts are read before anything is registered, so a mod is either loaded completely or not at all
+    let tiles = manifest.tiles.iter()
+        .map(|tile| Ok((tile.kind.clone(), script(&tile.script)?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let cards = manifest.cards.iter()
+        .map(|card| Ok((card.text.clone(), script(&card.script)?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let panels = manifest.panels.iter()
+        .map(|panel| Ok((panel.title.clone(), script(&panel.script)?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    for (kind, source) in tiles {
+        mods.register_tile(kind, source);
+    }
+    for (text, source) in cards {
+        mods.register_card(text, source);
+    }
+    for (title, source) in panels {
+        mods.register_panel(title, source);
+    }
+    println!("Loaded the mod {}", manifest.name);
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use cgmath::{EuclideanSpace, Point3, Vector3};
+use instant::Instant;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::entity::EntityKind;
+use crate::render::{ColorSource, Model};
+use crate::ui::{ClickKind, Color, Coloring, Component, HoverMode, TextBox, TextSection, TextStyle};
+use crate::rules::CurrencyRules;
+use crate::{Game, hud, i18n};
+
+const POPUP_DURATION: Duration = Duration::from_millis(1500);
+// how far the text rises until it's gone, relative to the screen's height
+const POPUP_RISE: f32 = 0.06;
+const POPUP_WIDTH: f32 = 0.2;
+const POPUP_HEIGHT: f32 = 0.05;
+const POPUP_TEXT_SCALE: f32 = 28.0;
+const BILLS_WIDTH: f32 = 0.35;
+const BILLS_TEXT_SCALE: f32 = 18.0;
+// how far above the token the text starts, in world units
+const TOKEN_OFFSET: f32 = 1.0;
+
+const GAIN: Color = Color {
+    r: 0.3,
+    g: 0.85,
+    b: 0.3,
+    a: 1.0,
+};
+
+const LOSS: Color = Color {
+    r: 0.9,
+    g: 0.25,
+    b: 0.25,
+    a: 1.0,
+};
+
+// the amount broken into bills like "1 × $20 + 4 × $1", payments which a single bill covers show the change instead
+pub fn bills_text(currency: &CurrencyRules, amount: usize, paying: bool) -> Option<String> {
+    let bill_text = |(bill, count): &(usize, usize)| i18n::tr_with("bills.count", &[
+        ("count", &count.to_string()),
+        ("bill", &currency.format_shown(*bill)),
+    ]);
+    if paying {
+        if let Some((bill, change)) = currency.change(amount) {
+            return Some(i18n::tr_with("bills.change", &[
+                ("bill", &currency.format_shown(bill)),
+                ("change", &change.iter().map(bill_text).collect::<Vec<_>>().join(" + ")),
+            ]));
+        }
+    }
+    let bills = currency.breakdown(amount)?;
+    if bills.is_empty() {
+        return None;
+    }
+    Some(bills.iter().map(bill_text).collect::<Vec<_>>().join(" + "))
+}
+
+// a change of a player's money like "+50" or "-24"
+#[derive(Clone)]
+pub struct MoneyPopup {
+    player: usize,
+    text: String,
+    bills: Option<String>,
+    gain: bool,
+    since: Instant,
+}
+
+impl MoneyPopup {
+
+    pub fn new(player: usize, amount: usize, currency: &CurrencyRules, gain: bool) -> Self {
+        Self {
+            player,
+            text: format!("{}{}", if gain { "+" } else { "-" }, currency.format(amount)),
+            bills: bills_text(currency, amount, !gain),
+            gain,
+            since: Instant::now(),
+        }
+    }
+
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.since.elapsed() >= POPUP_DURATION
+    }
+
+}
+
+/// Shows the popups which are added to the shared list as text which rises and fades out,
+/// both at the player's row of the HUD and above their token.
+pub struct MoneyPopups {
+    popups: Arc<Mutex<Vec<MoneyPopup>>>,
+    // the texts of the current frame
+    boxes: Vec<TextBox<'static>>,
+}
+
+impl MoneyPopups {
+
+    pub fn new(popups: Arc<Mutex<Vec<MoneyPopup>>>) -> Self {
+        Self {
+            popups,
+            boxes: vec![],
+        }
+    }
+
+}
+
+// the text is centered on the anchor
+fn popup_box(popup: &MoneyPopup, anchor: (f32, f32), progress: f32) -> TextBox<'static> {
+    let color = if popup.gain { GAIN } else { LOSS };
+    TextBox::new(
+        (anchor.0 - POPUP_WIDTH / 2.0, anchor.1 - POPUP_HEIGHT / 2.0 + POPUP_RISE * progress),
+        POPUP_WIDTH,
+        POPUP_HEIGHT,
+        Coloring::Color([Color { a: 0.0, ..color }; 4]),
+        TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+            .with_run(popup.text.clone(), TextStyle::new(POPUP_TEXT_SCALE).with_color(Color { a: 1.0 - progress, ..color }).bold()),
+    )
+}
+
+// the bills are shown right below the text, only at the HUD, so the board doesn't get cluttered
+fn bills_box(popup: &MoneyPopup, bills: &str, anchor: (f32, f32), progress: f32) -> TextBox<'static> {
+    let color = if popup.gain { GAIN } else { LOSS };
+    TextBox::new(
+        (anchor.0 - POPUP_WIDTH / 2.0, anchor.1 - POPUP_HEIGHT * 1.5 + POPUP_RISE * progress),
+        BILLS_WIDTH,
+        POPUP_HEIGHT,
+        Coloring::Color([Color { a: 0.0, ..color }; 4]),
+        TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+            .with_run(bills.to_string(), TextStyle::new(BILLS_TEXT_SCALE).with_color(Color { a: 1.0 - progress, ..color })),
+    )
+}
+
+// right next to the player's row
+fn hud_anchor(player: usize) -> (f32, f32) {
+    let (x, y) = hud::row_pos(player);
+    (x + hud::ROW_WIDTH + POPUP_WIDTH / 2.0, y + hud::ROW_HEIGHT / 2.0)
+}
+
+impl Component for MoneyPopups {
+    // the texts are the only thing that is drawn
+    fn build_model(&self) -> Model {
+        Model {
+            vertices: vec![],
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn do_render(&self, game: &Arc<Game>) {
+        for text in self.boxes.iter() {
+            text.do_render(game);
+        }
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (0.0, 0.0)
+    }
+
+    fn on_click(&mut self, _game: &Arc<Game>, _click_kind: ClickKind, _pos: (f32, f32)) {}
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        self.boxes.clear();
+        let popups = self.popups.lock().unwrap();
+        if popups.is_empty() {
+            return false;
+        }
+        let entities = game.entities.lock().unwrap();
+        let camera = game.camera.lock().unwrap();
+        for popup in popups.iter() {
+            let progress = (popup.since.elapsed().as_secs_f32() / POPUP_DURATION.as_secs_f32()).min(1.0);
+            self.boxes.push(popup_box(popup, hud_anchor(popup.player), progress));
+            if let Some(bills) = &popup.bills {
+                self.boxes.push(bills_box(popup, bills, hud_anchor(popup.player), progress));
+            }
+            let token = entities.world_transform(EntityKind::Token { player: popup.player })
+                .and_then(|token| game.renderer.project(&camera, Point3::from_vec(token.position + Vector3::unit_y() * TOKEN_OFFSET)));
+            if let Some(anchor) = token {
+                self.boxes.push(popup_box(popup, anchor, progress));
+            }
+        }
+        // the texts are queued every frame, so the model never has to be rebuilt
+        false
+    }
+}
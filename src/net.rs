@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+use instant::Instant;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use crate::ai;
+use crate::engine::Engine;
+use crate::events::{CommandError, GameCommand, GameEvent};
+use crate::save::SaveGame;
+use crate::timestep::TICKS_PER_SECOND;
+
+pub const DEFAULT_PORT: u16 = 7878;
+// clients with a different version are refused, as their engines would get out of sync
+const PROTOCOL_VERSION: u32 = 3;
+// longer chat messages get cut off by the server
+pub const MAX_CHAT_LEN: usize = 120;
+
+// messages are serialized as json, one message per line
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ClientMessage {
+    Hello {
+        version: u32,
+    },
+    Command {
+        command: GameCommand,
+    },
+    Chat {
+        text: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum ServerMessage {
+    // the game is the server's engine before the first step, it carries the board, cards, rules and tile scripts
+    // the server was started with. The history contains every step played so far, so the client's engine can catch up
+    Welcome {
+        game: SaveGame,
+        history: Vec<Step>,
+    },
+    Refused {
+        reason: String,
+    },
+    // the player was added through the client's command, so the client controls them from now on
+    Assigned {
+        player: usize,
+    },
+    Step {
+        step: Step,
+    },
+    // only sent to the client who submitted the command
+    Rejected {
+        command: GameCommand,
+        error: CommandError,
+    },
+    // chat messages don't change the game, so they're relayed right away instead of being part of a step
+    Chat {
+        // the first player of the client who sent the message, clients without players are spectators
+        player: Option<usize>,
+        text: String,
+    },
+}
+
+/// One tick of the server's engine. The engine is deterministic for a given seed, so applying the
+/// same steps in the same order keeps the clients' engines in sync with the server's.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Step {
+    // only the commands the server's engine accepted
+    pub commands: Vec<GameCommand>,
+    pub elapsed: Duration,
+}
+
+impl Step {
+
+    pub fn apply(&self, engine: &mut Engine) {
+        for command in self.commands.iter() {
+            if let Err(err) = engine.apply(command.clone()) {
+                println!("The server's command {:?} was rejected locally ({:?}), the game is out of sync", command, err);
+            }
+        }
+        engine.tick(self.elapsed);
+    }
+
+}
+
+fn send(mut stream: &TcpStream, message: &impl Serialize) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).unwrap();
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// passes on the messages read from the stream until the connection is closed or a message is malformed
+fn receive<T: DeserializeOwned>(reader: impl BufRead, mut on_message: impl FnMut(T) -> bool) {
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        match serde_json::from_str(&line) {
+            Ok(message) => if !on_message(message) {
+                return;
+            },
+            Err(err) => {
+                println!("Received a malformed message: {}", err);
+                return;
+            }
+        }
+    }
+}
+
+enum Incoming {
+    Connected {
+        id: usize,
+        stream: TcpStream,
+    },
+    Message {
+        id: usize,
+        message: ClientMessage,
+    },
+    Disconnected {
+        id: usize,
+    },
+}
+
+struct Connection {
+    stream: TcpStream,
+    welcomed: bool,
+    // the players the connection is allowed to send commands for
+    players: Vec<usize>,
+}
+
+/// Hosts the game without opening a window. The server's engine is the authoritative one, it decides
+/// on the order of the commands and rolls the dice, the clients only replay the steps it sends them.
+/// The players of clients who disconnected are played by the server's computer players from then on.
+pub fn serve(engine: Engine, port: u16) {
+    let listener = match TcpListener::bind(("0.0.0.0", port)) {
+        Ok(listener) => listener,
+        Err(err) => {
+            println!("Couldn't listen on port {}: {}", port, err);
+            return;
+        }
+    };
+    // the save's rng is seeded differently than the engine's own one, so the server plays on a restored copy as well
+    let initial = SaveGame::new(&engine);
+    let mut engine = initial.clone().restore().unwrap();
+    println!("Hosting a game with seed {} on port {}", engine.seed, port);
+    let (sender, incoming) = channel();
+    thread::spawn(move || accept(listener, sender));
+
+    let step_len = Duration::from_secs(1) / TICKS_PER_SECOND;
+    let mut connections = HashMap::new();
+    let mut history = vec![];
+    // the players of disconnected clients
+    let mut abandoned = vec![];
+    loop {
+        let start = Instant::now();
+        let mut commands = vec![];
+        for incoming in incoming.try_iter() {
+            match incoming {
+                Incoming::Connected { id, stream } => {
+                    connections.insert(id, Connection {
+                        stream,
+                        welcomed: false,
+                        players: vec![],
+                    });
+                }
+                Incoming::Disconnected { id } => {
+                    if let Some(connection) = connections.remove(&id) {
+                        println!("A client controlling the players {:?} disconnected, they're played by the computer now", connection.players);
+                        abandoned.extend(connection.players);
+                    }
+                }
+                Incoming::Message { id, message } => {
+                    let connection = match connections.get_mut(&id) {
+                        Some(connection) => connection,
+                        None => continue,
+                    };
+                    match message {
+                        ClientMessage::Hello { version } => {
+                            if version != PROTOCOL_VERSION {
+                                let _ = send(&connection.stream, &ServerMessage::Refused {
+                                    reason: format!("the server uses version {} of the protocol, the client uses {}", PROTOCOL_VERSION, version),
+                                });
+                                let _ = connection.stream.shutdown(Shutdown::Both);
+                                connections.remove(&id);
+                                continue;
+                            }
+                            let _ = send(&connection.stream, &ServerMessage::Welcome {
+                                game: initial.clone(),
+                                history: history.clone(),
+                            });
+                            connection.welcomed = true;
+                        }
+                        ClientMessage::Command { command } => {
+                            if !connection.welcomed || !is_allowed(&command, &connection.players) {
+                                continue;
+                            }
+                            let players = engine.players.len();
+                            match engine.apply(command.clone()) {
+                                Ok(()) => {
+                                    if engine.players.len() > players {
+                                        connection.players.push(players);
+                                        let _ = send(&connection.stream, &ServerMessage::Assigned {
+                                            player: players,
+                                        });
+                                    }
+                                    commands.push(command);
+                                }
+                                Err(error) => {
+                                    let _ = send(&connection.stream, &ServerMessage::Rejected {
+                                        command,
+                                        error,
+                                    });
+                                }
+                            }
+                        }
+                        ClientMessage::Chat { text } => {
+                            let text = text.trim().chars().take(MAX_CHAT_LEN).collect::<String>();
+                            if !connection.welcomed || text.is_empty() {
+                                continue;
+                            }
+                            // the sender gets the message as well, so every client shows the messages in the same order
+                            let player = connection.players.first().copied();
+                            for connection in connections.values().filter(|connection| connection.welcomed) {
+                                let _ = send(&connection.stream, &ServerMessage::Chat {
+                                    player,
+                                    text: text.clone(),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // the computer players decide one command per step at most, so the clients can follow their moves
+        let substitute = abandoned.iter().find_map(|player| ai::substitute_command(&engine, *player));
+        if let Some(command) = substitute {
+            match engine.apply(command.clone()) {
+                Ok(()) => commands.push(command),
+                Err(err) => println!("The computer player's command {:?} was rejected ({:?})", command, err),
+            }
+        }
+        // ticks only change the engine's state while a decision timer is running
+        let timed = engine.is_timed();
+        engine.tick(step_len);
+        // the server has no use for the events, the clients get them from their own engines
+        engine.take_events();
+        if !commands.is_empty() || timed {
+            let step = Step {
+                commands,
+                elapsed: step_len,
+            };
+            for connection in connections.values().filter(|connection| connection.welcomed) {
+                let _ = send(&connection.stream, &ServerMessage::Step {
+                    step: step.clone(),
+                });
+            }
+            history.push(step);
+        }
+        thread::sleep(step_len.saturating_sub(start.elapsed()));
+    }
+}
+
+fn accept(listener: TcpListener, sender: Sender<Incoming>) {
+    for (id, stream) in listener.incoming().enumerate() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                println!("Couldn't accept a client: {}", err);
+                continue;
+            }
+        };
+        let _ = stream.set_nodelay(true);
+        let reader = stream.try_clone().unwrap();
+        if sender.send(Incoming::Connected { id, stream }).is_err() {
+            return;
+        }
+        let sender = sender.clone();
+        thread::spawn(move || {
+            receive(BufReader::new(reader), |message| sender.send(Incoming::Message { id, message }).is_ok());
+            let _ = sender.send(Incoming::Disconnected { id });
+        });
+    }
+}
+
+// clients can only act for their own players, leaving would reset the game for everybody
+fn is_allowed(command: &GameCommand, players: &[usize]) -> bool {
+    match command {
+        GameCommand::LeaveGame => false,
+        command => command.player().map_or(true, |player| players.contains(&player)),
+    }
+}
+
+/// The connection to the server of a networked game, the local engine only gets changed
+/// through the steps the server sends.
+pub struct Client {
+    stream: TcpStream,
+    messages: Mutex<Receiver<ServerMessage>>,
+    // the players which are controlled from this machine
+    players: Mutex<Vec<usize>>,
+}
+
+impl Client {
+
+    // blocks until the server welcomed the client, the game has to be played on the returned engine
+    pub fn connect(host: &str, port: u16) -> io::Result<(Self, Engine)> {
+        let stream = TcpStream::connect((host, port))?;
+        stream.set_nodelay(true)?;
+        send(&stream, &ClientMessage::Hello {
+            version: PROTOCOL_VERSION,
+        })?;
+        // the reader is kept for the rest of the connection, as it might've buffered the messages after the welcome already
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let (game, history) = match serde_json::from_str(&line) {
+            Ok(ServerMessage::Welcome { game, history }) => (game, history),
+            Ok(ServerMessage::Refused { reason }) => return Err(io::Error::new(io::ErrorKind::ConnectionRefused, reason)),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "the server didn't welcome the client")),
+        };
+        let engine = game.restore()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the server's board has the wrong size"))?;
+        let (sender, receiver) = channel();
+        // the steps played before the client joined are applied first
+        for step in history {
+            sender.send(ServerMessage::Step { step }).unwrap();
+        }
+        thread::spawn(move || {
+            receive(reader, |message| sender.send(message).is_ok());
+            println!("Lost the connection to the server");
+        });
+        Ok((Self {
+            stream,
+            messages: Mutex::new(receiver),
+            players: Mutex::new(vec![]),
+        }, engine))
+    }
+
+    pub fn submit(&self, command: GameCommand) {
+        if let Err(err) = send(&self.stream, &ClientMessage::Command { command }) {
+            println!("Couldn't send a command to the server: {}", err);
+        }
+    }
+
+    pub fn chat(&self, text: String) {
+        if let Err(err) = send(&self.stream, &ClientMessage::Chat { text }) {
+            println!("Couldn't send a chat message to the server: {}", err);
+        }
+    }
+
+    // applies the steps the server sent since the last call, the client's own rejected
+    // commands and the chat messages are returned as events, as they never reach the local engine
+    pub fn sync(&self, engine: &mut Engine) -> Vec<GameEvent> {
+        let mut events = vec![];
+        for message in self.messages.lock().unwrap().try_iter() {
+            match message {
+                ServerMessage::Step { step } => step.apply(engine),
+                ServerMessage::Assigned { player } => self.players.lock().unwrap().push(player),
+                ServerMessage::Rejected { command, error } => events.push(GameEvent::CommandRejected {
+                    command,
+                    error,
+                }),
+                ServerMessage::Chat { player, text } => events.push(GameEvent::ChatReceived {
+                    player,
+                    text,
+                }),
+                ServerMessage::Welcome { .. } | ServerMessage::Refused { .. } => {}
+            }
+        }
+        events
+    }
+
+    pub fn is_local(&self, player: usize) -> bool {
+        self.players.lock().unwrap().contains(&player)
+    }
+
+    // the player the server assigned to this machine first, None for spectators
+    pub fn first_player(&self) -> Option<usize> {
+        self.players.lock().unwrap().first().copied()
+    }
+
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
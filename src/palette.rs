@@ -0,0 +1,195 @@
+use image::{Rgba, RgbaImage};
+use crate::property::ColorGroup;
+use crate::ui::Color;
+
+// the size of the marker textures in pixels
+const MARKER_SIZE: u32 = 32;
+const DIE_FACE_SIZE: u32 = 64;
+// the size of a single cell of the patterns in pixels
+const CELL: u32 = 8;
+
+// drawn on top of the group's color, so groups can be told apart without relying on hue
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Pattern {
+    Solid,
+    HorizontalStripes,
+    VerticalStripes,
+    DiagonalStripes,
+    Dots,
+    Checker,
+    Grid,
+    Frame,
+}
+
+impl Pattern {
+
+    // whether the pixel is covered by the pattern
+    fn covers(self, x: u32, y: u32) -> bool {
+        match self {
+            Pattern::Solid => false,
+            Pattern::HorizontalStripes => (y / (CELL / 2)) % 2 == 0,
+            Pattern::VerticalStripes => (x / (CELL / 2)) % 2 == 0,
+            Pattern::DiagonalStripes => ((x + y) / (CELL / 2)) % 2 == 0,
+            Pattern::Dots => {
+                let dx = (x % CELL) as f32 - (CELL as f32 - 1.0) / 2.0;
+                let dy = (y % CELL) as f32 - (CELL as f32 - 1.0) / 2.0;
+                dx * dx + dy * dy <= (CELL as f32 / 4.0).powi(2)
+            }
+            Pattern::Checker => (x / CELL + y / CELL) % 2 == 0,
+            Pattern::Grid => x % CELL < 2 || y % CELL < 2,
+            Pattern::Frame => x < CELL / 2 || y < CELL / 2 || x >= MARKER_SIZE - CELL / 2 || y >= MARKER_SIZE - CELL / 2,
+        }
+    }
+
+}
+
+pub fn group_pattern(group: ColorGroup) -> Pattern {
+    match group {
+        ColorGroup::DarkBlue => Pattern::Solid,
+        ColorGroup::LightBlue => Pattern::HorizontalStripes,
+        ColorGroup::Violet => Pattern::VerticalStripes,
+        ColorGroup::Brown => Pattern::DiagonalStripes,
+        ColorGroup::Red => Pattern::Dots,
+        ColorGroup::Yellow => Pattern::Checker,
+        ColorGroup::Green => Pattern::Grid,
+        ColorGroup::OtherBlue => Pattern::Frame,
+    }
+}
+
+// the colorblind palette is based on the Okabe-Ito palette, whose colors stay distinct for all common kinds of color blindness
+pub fn group_color(group: ColorGroup, colorblind: bool) -> Color {
+    let (r, g, b) = if colorblind {
+        match group {
+            ColorGroup::DarkBlue => (0.0, 0.447, 0.698),
+            ColorGroup::LightBlue => (0.337, 0.706, 0.914),
+            ColorGroup::Violet => (0.8, 0.475, 0.655),
+            ColorGroup::Brown => (0.1, 0.1, 0.1),
+            ColorGroup::Red => (0.835, 0.369, 0.0),
+            ColorGroup::Yellow => (0.941, 0.894, 0.259),
+            ColorGroup::Green => (0.0, 0.62, 0.451),
+            ColorGroup::OtherBlue => (0.902, 0.624, 0.0),
+        }
+    } else {
+        match group {
+            ColorGroup::DarkBlue => (0.0, 0.2, 0.6),
+            ColorGroup::LightBlue => (0.529, 0.808, 0.922),
+            ColorGroup::Violet => (0.58, 0.0, 0.827),
+            ColorGroup::Brown => (0.545, 0.271, 0.075),
+            ColorGroup::Red => (0.863, 0.078, 0.235),
+            ColorGroup::Yellow => (1.0, 0.843, 0.0),
+            ColorGroup::Green => (0.133, 0.545, 0.133),
+            ColorGroup::OtherBlue => (0.255, 0.412, 0.882),
+        }
+    };
+    Color {
+        r,
+        g,
+        b,
+        a: 1.0,
+    }
+}
+
+// the colors which tell the players apart on the minimap, taken from the colorblind safe palette as well
+pub fn player_color(player: usize) -> Color {
+    const COLORS: [(f32, f32, f32); 6] = [
+        (0.835, 0.369, 0.0),
+        (0.0, 0.447, 0.698),
+        (0.941, 0.894, 0.259),
+        (0.0, 0.62, 0.451),
+        (0.8, 0.475, 0.655),
+        (0.337, 0.706, 0.914),
+    ];
+    let (r, g, b) = COLORS[player % COLORS.len()];
+    Color {
+        r,
+        g,
+        b,
+        a: 1.0,
+    }
+}
+
+// goes from blue for unlikely tiles over yellow to red for the likeliest ones
+pub fn heat_color(heat: f32) -> Color {
+    const COLD: (f32, f32, f32) = (0.1, 0.3, 0.9);
+    const WARM: (f32, f32, f32) = (1.0, 0.85, 0.1);
+    const HOT: (f32, f32, f32) = (0.9, 0.1, 0.1);
+    let heat = heat.clamp(0.0, 1.0);
+    let (from, to, progress) = if heat < 0.5 {
+        (COLD, WARM, heat * 2.0)
+    } else {
+        (WARM, HOT, heat * 2.0 - 1.0)
+    };
+    Color {
+        r: from.0 + (to.0 - from.0) * progress,
+        g: from.1 + (to.1 - from.1) * progress,
+        b: from.2 + (to.2 - from.2) * progress,
+        a: 1.0,
+    }
+}
+
+pub fn heat_texture(heat: f32) -> RgbaImage {
+    RgbaImage::from_pixel(1, 1, to_rgba(heat_color(heat)))
+}
+
+/// The texture of the marker which shows the group on the group's tiles,
+/// the pattern is only drawn for colorblind players.
+pub fn marker_texture(group: ColorGroup, colorblind: bool) -> RgbaImage {
+    let color = group_color(group, colorblind);
+    let base = to_rgba(color);
+    // the pattern has to contrast with the group's color
+    let luminance = 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b;
+    let ink = if luminance > 0.5 {
+        Rgba([0, 0, 0, 255])
+    } else {
+        Rgba([255, 255, 255, 255])
+    };
+    let pattern = group_pattern(group);
+    RgbaImage::from_fn(MARKER_SIZE, MARKER_SIZE, |x, y| {
+        if colorblind && pattern.covers(x, y) {
+            ink
+        } else {
+            base
+        }
+    })
+}
+
+// the faces 1 to 6 of a die next to each other, with black pips on white
+pub fn die_texture() -> RgbaImage {
+    // the pips' centers on a 3x3 grid for every value
+    const PIPS: [&[(u32, u32)]; 6] = [
+        &[(1, 1)],
+        &[(0, 0), (2, 2)],
+        &[(0, 0), (1, 1), (2, 2)],
+        &[(0, 0), (2, 0), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (1, 1), (0, 2), (2, 2)],
+        &[(0, 0), (2, 0), (0, 1), (2, 1), (0, 2), (2, 2)],
+    ];
+    let cell = DIE_FACE_SIZE / 4;
+    let radius = (DIE_FACE_SIZE / 10) as i64;
+    RgbaImage::from_fn(DIE_FACE_SIZE * 6, DIE_FACE_SIZE, |x, y| {
+        let (face, fx) = ((x / DIE_FACE_SIZE) as usize, x % DIE_FACE_SIZE);
+        let covered = PIPS[face].iter().any(|(px, py)| {
+            let (dx, dy) = (fx as i64 - ((px + 1) * cell) as i64, y as i64 - ((py + 1) * cell) as i64);
+            dx * dx + dy * dy <= radius * radius
+        });
+        if covered {
+            Rgba([20, 20, 20, 255])
+        } else {
+            Rgba([245, 245, 240, 255])
+        }
+    })
+}
+
+// the strip which shows who owns a tile is simply drawn in the owner's color
+pub fn owner_texture(player: usize) -> RgbaImage {
+    RgbaImage::from_pixel(1, 1, to_rgba(player_color(player)))
+}
+
+pub fn to_rgba(color: Color) -> Rgba<u8> {
+    Rgba([
+        (color.r * 255.0) as u8,
+        (color.g * 255.0) as u8,
+        (color.b * 255.0) as u8,
+        (color.a * 255.0) as u8,
+    ])
+}
@@ -1,8 +1,8 @@
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
 use serde::{Deserialize, Serialize};
+use crate::i18n;
+use crate::utils::{read_config, write_config};
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Player {
     pub name: String,
     pub currency: usize,
@@ -13,6 +13,24 @@ pub struct Player {
     pub jail_free_cards: usize,
     pub jail_free_throws: usize,
     pub wait: usize,
+    pub bankrupt: bool,
+}
+
+impl Player {
+
+    // jailed players have throws left to roll doubles before they have to pay the fine
+    pub fn is_jailed(&self) -> bool {
+        self.jail_free_throws > 0
+    }
+
+    // players without a name are numbered
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            return i18n::tr_with("player.numbered", &[("number", &(self.id + 1).to_string())]);
+        }
+        self.name.clone()
+    }
+
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -20,24 +38,55 @@ pub struct Character {
     pub name: String,
     pub id: usize,
     pub model_path: String,
+    #[serde(default)]
+    pub voice_lines: VoiceLines,
+    // a glTF file containing the token's "idle" and "celebrate" animations
+    #[serde(default)]
+    pub animations: Option<String>,
+}
+
+// paths to short voice clips, every one of them is optional
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VoiceLines {
+    pub on_select: Option<String>,
+    pub on_auction_won: Option<String>,
+    pub on_jail: Option<String>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum VoiceLine {
+    Select,
+    AuctionWon,
+    Jail,
+}
+
+impl VoiceLines {
+
+    pub fn get(&self, line: VoiceLine) -> Option<&String> {
+        match line {
+            VoiceLine::Select => self.on_select.as_ref(),
+            VoiceLine::AuctionWon => self.on_auction_won.as_ref(),
+            VoiceLine::Jail => self.on_jail.as_ref(),
+        }
+    }
+
 }
 
-const CHARACTER_PATH: &str = "./config/characters.json";
+const CHARACTER_FILE: &str = "characters.json";
 
 pub fn load_characters() -> Vec<Character> {
-    if Path::new(CHARACTER_PATH).exists() {
-        let mut file = File::open(CHARACTER_PATH).unwrap();
-        let mut buf = String::new();
-        file.read_to_string(&mut buf).unwrap();
+    if let Some(buf) = read_config(CHARACTER_FILE) {
         serde_json::from_str(&*buf).unwrap()
     } else {
-        let mut file = File::create(CHARACTER_PATH).unwrap();
         let characters = vec![Character {
             name: "Test1".to_string(),
             id: 0,
             model_path: "./Test.png".to_string(),
+            voice_lines: VoiceLines::default(),
+            animations: None,
         }];
-        file.write_all(serde_json::to_string(&characters).unwrap().as_ref()).unwrap();
+        write_config(CHARACTER_FILE, &serde_json::to_string(&characters).unwrap());
         characters
     }
 }
@@ -0,0 +1,136 @@
+use crate::action_cards::Action;
+use crate::board::{Tile, TILES};
+use crate::dice::MAX_DOUBLES;
+use crate::engine::{Engine, JAIL_THROWS};
+
+// how far cards which move the token are followed, a card can send the token to another card tile
+const MAX_CARD_CHAIN: usize = 3;
+
+// where a token is at the start of its turn, along with the throws it has left to get out of jail
+#[derive(Copy, Clone)]
+struct State {
+    tile: usize,
+    jail_throws: usize,
+}
+
+impl State {
+
+    fn index(self) -> usize {
+        self.jail_throws * TILES + self.tile
+    }
+
+}
+
+/// The expected number of times a token lands on every tile during the next `turns` turns of
+/// every player who is still in the game, starting from where the tokens are right now.
+/// Each token is followed through a Markov chain, which accounts for the dice, rolling again after doubles,
+/// being sent to jail and the cards which move the token. Cards run by scripts are treated as if they didn't move it.
+pub fn landing_probabilities(engine: &Engine, turns: usize) -> [f32; TILES] {
+    let mut landings = [0.0; TILES];
+    for player in engine.players.iter().filter(|player| !player.bankrupt) {
+        let mut distribution = vec![0.0; TILES * (JAIL_THROWS + 1)];
+        // FIXME: a jail free card is only taken into account for the first turn
+        let jail_throws = if player.jail_free_cards > 0 { 0 } else { player.jail_free_throws };
+        distribution[State { tile: player.position, jail_throws }.index()] = 1.0;
+        for _ in 0..turns {
+            distribution = step(engine, &distribution, &mut landings);
+        }
+    }
+    landings
+}
+
+// advances the distribution by a single turn, the landings of the turn are added up
+fn step(engine: &Engine, distribution: &[f32], landings: &mut [f32; TILES]) -> Vec<f32> {
+    let mut next = vec![0.0; distribution.len()];
+    for jail_throws in 0..=JAIL_THROWS {
+        for tile in 0..TILES {
+            let probability = distribution[State { tile, jail_throws }.index()];
+            if probability == 0.0 {
+                continue;
+            }
+            if jail_throws == 0 {
+                throw(engine, tile, probability, 0, &mut next, landings);
+                continue;
+            }
+            for first in 1..=6 {
+                for second in 1..=6 {
+                    let probability = probability / 36.0;
+                    // in jail only doubles let the token move before the throws are used up
+                    if jail_throws > 1 && first != second {
+                        next[State { tile, jail_throws: jail_throws - 1 }.index()] += probability;
+                        continue;
+                    }
+                    // the double which gets the token out of jail doesn't let it roll again
+                    let mut ends = vec![];
+                    land(engine, (tile + first + second) % TILES, probability, 0, &mut ends, landings);
+                    for (state, probability) in ends {
+                        next[state.index()] += probability;
+                    }
+                }
+            }
+        }
+    }
+    next
+}
+
+// a throw of the dice by a token which isn't in jail, after `doubles` doubles in a row during the turn
+fn throw(engine: &Engine, tile: usize, probability: f32, doubles: usize, next: &mut [f32], landings: &mut [f32; TILES]) {
+    let jail = State { tile: engine.board.index.jail, jail_throws: JAIL_THROWS };
+    for first in 1..=6 {
+        for second in 1..=6 {
+            let probability = probability / 36.0;
+            if first == second && doubles + 1 >= MAX_DOUBLES {
+                next[jail.index()] += probability;
+                continue;
+            }
+            let mut ends = vec![];
+            land(engine, (tile + first + second) % TILES, probability, 0, &mut ends, landings);
+            for (state, probability) in ends {
+                if first == second && state.jail_throws == 0 {
+                    throw(engine, state.tile, probability, doubles + 1, next, landings);
+                } else {
+                    next[state.index()] += probability;
+                }
+            }
+        }
+    }
+}
+
+// the states the token ends up in after landing on the tile are added to `ends`
+fn land(engine: &Engine, tile: usize, probability: f32, chain: usize, ends: &mut Vec<(State, f32)>, landings: &mut [f32; TILES]) {
+    landings[tile] += probability;
+    let jail = State { tile: engine.board.index.jail, jail_throws: JAIL_THROWS };
+    match &engine.board.tiles[tile] {
+        Tile::GoToJail { .. } => ends.push((jail, probability)),
+        Tile::DrawCard { kind } if chain < MAX_CARD_CHAIN => {
+            // the card is drawn from the stack with replacement, so every card is equally likely every time
+            let stack = engine.card_stacks[*kind as usize].cards();
+            let probability = probability / stack.len().max(1) as f32;
+            for card in stack {
+                match engine.cards[*card].action {
+                    Action::MoveRelative { amount } => {
+                        let target = (tile as isize + amount).rem_euclid(TILES as isize) as usize;
+                        land(engine, target, probability, chain + 1, ends, landings);
+                    }
+                    Action::MoveAbsolute { tile: target } => land(engine, target, probability, chain + 1, ends, landings),
+                    Action::MoveToNearest { kind } => match engine.nearest_tile(tile, kind) {
+                        Some(target) => land(engine, target, probability, chain + 1, ends, landings),
+                        None => ends.push((State { tile, jail_throws: 0 }, probability)),
+                    },
+                    Action::AdvanceToStartIf { within } => {
+                        let start = engine.board.index.start;
+                        let distance = (start + TILES - tile) % TILES;
+                        if distance > 0 && distance <= within {
+                            land(engine, start, probability, chain + 1, ends, landings);
+                        } else {
+                            ends.push((State { tile, jail_throws: 0 }, probability));
+                        }
+                    }
+                    Action::GoToJail => ends.push((jail, probability)),
+                    _ => ends.push((State { tile, jail_throws: 0 }, probability)),
+                }
+            }
+        }
+        _ => ends.push((State { tile, jail_throws: 0 }, probability)),
+    }
+}
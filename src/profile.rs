@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use crate::utils::{read_config, write_config};
+
+const PROFILE_FILE: &str = "profile.json";
+
+pub fn load_profile() -> Profile {
+    match read_config(PROFILE_FILE) {
+        Some(buf) => serde_json::from_str(&*buf).unwrap(),
+        None => Profile::default(),
+    }
+}
+
+pub fn save_profile(profile: &Profile) {
+    write_config(PROFILE_FILE, &serde_json::to_string_pretty(profile).unwrap());
+}
+
+// what the player achieved on this installation, missing entries fall back to their defaults
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Profile {
+    // the finished daily challenges, oldest first
+    pub daily_results: Vec<DailyResult>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct DailyResult {
+    // like "2023-03-14", so the results of a class can be compared by date
+    pub date: String,
+    pub seed: u64,
+    pub turns: usize,
+    pub winner: Option<usize>,
+    // indexed by the player
+    pub net_worths: Vec<usize>,
+}
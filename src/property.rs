@@ -3,18 +3,38 @@ use serde::{Deserialize, Serialize};
 pub const PROPERTIES: usize = 28; // 22 normal, 4 stations, 2 special
 pub const MAX_HOUSES: usize = 5;
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct DefinedProperty {
     pub frame: PropertyFrame,
     pub houses: usize,
     pub owner: Option<usize>,
+    // mortgaged properties don't yield any rent
+    pub mortgaged: bool,
 }
 
 impl DefinedProperty {
 
-    pub fn calculate_price(&self, moves: usize) -> usize {
+    pub fn mortgage_value(&self) -> usize {
+        self.frame.buy_price / 2
+    }
+
+    // lifting a mortgage costs its value plus 10% interest
+    pub fn unmortgage_price(&self) -> usize {
+        self.mortgage_value() + self.mortgage_value() / 10
+    }
+
+    // `unimproved_group` is whether the owner holds the property's whole group without any houses on it,
+    // which doubles the base rent, `owned_of_type` is how many stations or special properties the owner holds
+    pub fn calculate_price(&self, moves: usize, unimproved_group: bool, owned_of_type: usize) -> usize {
+        // stations and special properties take their rent from the entry for the number the owner holds, if the board has one
+        let owned_rent = self.frame.rents.get(owned_of_type.max(1) - 1).copied().flatten();
         match &self.frame.ty {
-            PropertyType::Normal { .. } | PropertyType::Station => self.frame.rents[self.houses].unwrap(),
-            PropertyType::Special => self.frame.rents[0].unwrap() * moves,
+            PropertyType::Normal { .. } if unimproved_group => self.frame.rents[0].unwrap() * 2,
+            PropertyType::Normal { .. } => self.frame.rents[self.houses].unwrap(),
+            // otherwise the rent doubles with every further station
+            PropertyType::Station => owned_rent.unwrap_or(self.frame.rents[0].unwrap() << (owned_of_type.max(1) - 1)),
+            // the roll is multiplied by the rent
+            PropertyType::Special => owned_rent.unwrap_or(self.frame.rents[0].unwrap()) * moves,
         }
     }
 
@@ -33,7 +53,37 @@ pub struct PropertyFrame {
 pub enum PropertyType {
     Normal {
         associates: [Option<usize>; 2], // the ids of the associates
+        // boards from older versions don't have groups, their properties simply get no group marker
+        #[serde(default)]
+        group: Option<ColorGroup>,
     },
     Station,
     Special,
 }
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum ColorGroup {
+    DarkBlue,
+    LightBlue,
+    Violet,
+    Brown,
+    Red,
+    Yellow,
+    Green,
+    OtherBlue,
+}
+
+impl ColorGroup {
+
+    pub const ALL: [ColorGroup; 8] = [
+        ColorGroup::DarkBlue,
+        ColorGroup::LightBlue,
+        ColorGroup::Violet,
+        ColorGroup::Brown,
+        ColorGroup::Red,
+        ColorGroup::Yellow,
+        ColorGroup::Green,
+        ColorGroup::OtherBlue,
+    ];
+
+}
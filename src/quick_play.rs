@@ -0,0 +1,38 @@
+use std::mem;
+use crate::board::{Board, Tile};
+use crate::engine::Engine;
+use crate::rules::{PropertySetup, Rules};
+
+// a full game has to fit into a 45 minute lesson
+const ROUNDS: usize = 12;
+// the share of the regular prices properties cost, in percent
+const PRICE_PERCENTAGE: usize = 50;
+
+// the properties are dealt, so the slow early game is skipped, the other house rules are kept
+fn rules(rules: &Rules) -> Rules {
+    Rules {
+        setup: PropertySetup::Dealt,
+        round_limit: Some(ROUNDS),
+        ..rules.clone()
+    }
+}
+
+// rents stay as they are, so the players have to start building right away to keep up
+fn board(board: &Board) -> Board {
+    let mut board = board.clone();
+    for tile in board.tiles.iter_mut() {
+        if let Tile::Property { property } = tile {
+            property.buy_price = property.buy_price * PRICE_PERCENTAGE / 100;
+        }
+    }
+    board
+}
+
+/// Turns the game in the lobby into a quick game, the players who already joined are kept.
+/// It's played on the whole board, as the engine only supports boards with `TILES` tiles.
+pub fn apply(engine: &mut Engine) {
+    let mut quick = Engine::new(board(&engine.board), engine.cards.clone(), rules(&engine.rules), Some(engine.seed));
+    quick.tile_scripts = mem::take(&mut engine.tile_scripts);
+    quick.players = mem::take(&mut engine.players);
+    *engine = quick;
+}
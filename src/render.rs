@@ -2,43 +2,71 @@ use crate::atlas::{Atlas, AtlasAlloc, AtlasId};
 use bytemuck_derive::Pod;
 use bytemuck_derive::Zeroable;
 use std::borrow::Cow;
+use std::fs;
 use std::collections::{HashMap, HashSet};
 use std::mem::size_of;
+use std::path::Path;
 use std::process::abort;
 use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, Weak};
 use std::sync::mpsc::Sender;
+use std::thread;
 use std::time::Duration;
-use cgmath::{Deg, InnerSpace, Matrix4, perspective, Point3, Quaternion, Rad, SquareMatrix, Vector3};
+use cgmath::{Deg, EuclideanSpace, InnerSpace, Matrix4, perspective, Point3, Quaternion, Rad, SquareMatrix, Vector3, Vector4};
+use atomic_float::AtomicF32;
 use dashmap::DashMap;
 use swap_arc::SwapArc;
-use wgpu::{BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer, BufferAddress, BufferBindingType, BufferUsages, Color, ColorTargetState, ColorWrites, DepthStencilState, IndexFormat, LoadOp, Operations, PushConstantRange, RenderPass, RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPipeline, Sampler, SamplerBindingType, ShaderSource, ShaderStages, Texture, TextureDimension, TextureFormat, TextureSampleType, TextureView, TextureViewDescriptor, TextureViewDimension, VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode};
+use wgpu::{BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource, BindingType, BlendState, Buffer, BufferAddress, BufferBinding, BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, ColorWrites, CommandEncoder, DepthStencilState, ErrorFilter, IndexFormat, LoadOp, Maintain, Operations, PresentMode, RenderPass, RenderPassColorAttachment, RenderPassDepthStencilAttachment, RenderPipeline, Sampler, SamplerBindingType, SamplerDescriptor, SurfaceError, ShaderSource, ShaderStages, Texture, TextureDimension, TextureFormat, TextureSampleType, TextureView, TextureViewDescriptor, TextureViewDimension, VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode};
 use wgpu::util::StagingBelt;
 use wgpu_biolerless::{FragmentShaderState, ModuleSrc, PipelineBuilder, RawTextureBuilder, ShaderModuleSources, State, TextureBuilder, VertexShaderState, WindowSize};
-use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder, Section};
+use image::RgbaImage;
+use wgpu_glyph::{ab_glyph, GlyphBrush, GlyphBrushBuilder, GlyphCruncher, OwnedSection, Region, Section};
 use winit::dpi::PhysicalPosition;
 use winit::event::{ElementState, KeyboardInput, MouseScrollDelta, VirtualKeyCode, WindowEvent};
 use winit::window::Window;
-use crate::model::{ModelColorVertex, ModelTexVertex, Vertex as MVV};
+use crate::model::{ContainedTexture, ModelColorVertex, ModelTexVertex, RECT_INDICES, Vertex as MVV};
+use crate::hot_reload;
+use crate::text_raster::TextRasterizer;
 use crate::utils::LIGHT_GRAY_GPU;
 use std::f32::consts::FRAC_PI_2;
 
 const SAFE_FRAC_PI_2: f32 = FRAC_PI_2 - 0.0001;
+// the number of quads the shared ui index buffer can hold initially, it grows when needed
+const INITIAL_QUADS: usize = 256;
 
 pub struct Renderer {
     pub state: Arc<State>,
-    atlas_pipeline: RenderPipeline,
-    tex_ui_pipeline: RenderPipeline,
-    color_ui_pipeline: RenderPipeline,
-    color_model_pipeline: RenderPipeline,
-    tex_model_pipeline: RenderPipeline,
+    atlas_pipeline: SwapArc<RenderPipeline>,
+    tex_ui_pipeline: SwapArc<RenderPipeline>,
+    color_ui_pipeline: SwapArc<RenderPipeline>,
+    color_model_pipeline: SwapArc<RenderPipeline>,
+    tex_model_pipeline: SwapArc<RenderPipeline>,
     tex_bind_group_layout: BindGroupLayout,
     camera_bind_group_layout: BindGroupLayout,
     pub model_bind_group_layout: BindGroupLayout,
+    model_uniform_bind_group_layout: BindGroupLayout,
     pub dimensions: Dimensions,
     glyphs: Mutex<Vec<GlyphInfo>>,
-    models: Mutex<Vec<UploadedModel>>,
+    // the text which was queued since the last text model was taken, along with the glyph brush it's drawn with
+    queued_text: Mutex<Vec<(usize, OwnedSection)>>,
+    // models which were unloaded leave an empty slot behind, so the ids of the other models stay valid
+    models: Mutex<Vec<Option<UploadedModel>>>,
+    // the models and textures which were loaded from files and are still in use, indexed by their paths
+    model_cache: Mutex<HashMap<String, Weak<ModelHandle>>>,
+    texture_cache: Mutex<HashMap<String, Weak<TexTriple>>>,
     depth_tex: SwapArc<TexTriple>,
+    regular_font: ab_glyph::FontArc,
+    // all text gets scaled by this factor, so it stays readable when projected
+    text_scale: AtomicF32,
+    // the aspect ratio the scene and the ui are locked to, they fill the whole surface if this isn't set
+    safe_area: Mutex<Option<f32>>,
+    // the state configures the surface with the present mode it was built with, this one replaces it
+    present_mode: Mutex<PresentMode>,
+    inset: Mutex<Option<Inset>>,
+    pub text_rasterizer: TextRasterizer,
+    // all ui models consist of quads, so they share an index buffer which is large enough for
+    // the biggest batch drawn so far, along with the number of quads it can hold
+    quad_indices: Mutex<(Arc<Buffer>, usize)>,
 }
 
 pub struct GlyphInfo {
@@ -63,9 +91,12 @@ impl Renderer {
         let font = ab_glyph::FontArc::try_from_slice(include_bytes!(
             "PlayfairDisplayRegular.ttf"
         ))?;
+        let text_rasterizer = TextRasterizer::new(include_bytes!("PlayfairDisplayRegular.ttf"));
 
+        // the bold font gets loaded along with the other assets, until then the regular font
+        // is used in its place so that the font ids used by the ui stay valid.
         glyphs.push(GlyphInfo {
-            brush: Mutex::new(GlyphBrushBuilder::using_font(font).build(&state.device(), state.format())),
+            brush: Mutex::new(GlyphBrushBuilder::using_fonts(vec![font.clone(), font.clone()]).build(&state.device(), state.format())),
             format: state.format(),
             staging_belt: Mutex::new(StagingBelt::new(1024)),
         });
@@ -118,27 +149,102 @@ impl Renderer {
             },
         ]);
 
+        let model_uniform_bind_group_layout = state.create_bind_group_layout(&[BindGroupLayoutEntry {
+            binding: 0,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: BufferSize::new(size_of::<ModelUniform>() as u64),
+            },
+            count: None,
+        }]);
+
         let depth_tex = TexTriple::create_depth_texture(&state);
         let (width, height) = window.window_size();
         Ok(Self {
-            atlas_pipeline: Self::atlas_ui_pipeline(&state),
-            tex_ui_pipeline: Self::tex_ui_pipeline(&state),
-            color_ui_pipeline: Self::color_ui_pipeline(&state),
-            color_model_pipeline: Self::color_model_pipeline(&state, &camera_bind_group_layout),
-            tex_model_pipeline: Self::tex_model_pipeline(&state, &model_bind_group_layout, &camera_bind_group_layout),
+            atlas_pipeline: SwapArc::new(Arc::new(Self::atlas_ui_pipeline(&state))),
+            tex_ui_pipeline: SwapArc::new(Arc::new(Self::tex_ui_pipeline(&state))),
+            color_ui_pipeline: SwapArc::new(Arc::new(Self::color_ui_pipeline(&state))),
+            color_model_pipeline: SwapArc::new(Arc::new(Self::color_model_pipeline(&state, &camera_bind_group_layout, &model_uniform_bind_group_layout))),
+            tex_model_pipeline: SwapArc::new(Arc::new(Self::tex_model_pipeline(&state, &model_bind_group_layout, &camera_bind_group_layout))),
             state,
             dimensions: Dimensions::new(width, height),
             glyphs: Mutex::new(glyphs),
+            queued_text: Mutex::new(vec![]),
             tex_bind_group_layout: bgl,
             models: Mutex::new(vec![]),
+            model_cache: Default::default(),
+            texture_cache: Default::default(),
             camera_bind_group_layout,
             model_bind_group_layout,
+            model_uniform_bind_group_layout,
             depth_tex: SwapArc::new(Arc::new(depth_tex)),
+            regular_font: font,
+            text_scale: AtomicF32::new(1.0),
+            safe_area: Mutex::new(None),
+            present_mode: Mutex::new(state.raw_inner_surface_config().present_mode),
+            inset: Mutex::new(None),
+            text_rasterizer,
+            quad_indices: Mutex::new((Arc::new(Self::create_quad_indices(&state, INITIAL_QUADS)), INITIAL_QUADS)),
         })
     }
 
+    /// Adds a placeholder right away and replaces it with the model once it was loaded on a worker
+    /// thread, so loading models (and their textures) never blocks rendering.
+    pub fn add_model_async(
+        self: &Arc<Self>,
+        load: impl FnOnce(&State, &BindGroupLayout) -> anyhow::Result<crate::model::Model> + Send + 'static,
+        coloring: ModelColoring,
+    ) -> usize {
+        let model_id = self.add_model(crate::model::placeholder_model(&self.state, &self.model_bind_group_layout), coloring);
+        let renderer = self.clone();
+        thread::spawn(move || match load(&renderer.state, &renderer.model_bind_group_layout) {
+            Ok(model) => {
+                // the model may have been unloaded while it was still loading
+                if let Some(uploaded) = renderer.models.lock().unwrap()[model_id].as_mut() {
+                    uploaded.model = model;
+                }
+            }
+            // the placeholder stays, which makes the missing model easy to spot
+            Err(err) => println!("Couldn't load model {}: {}", model_id, err),
+        });
+        model_id
+    }
+
     pub fn resize(&self, _size: (u32, u32)) {
         self.depth_tex.store(Arc::new(TexTriple::create_depth_texture(&self.state)));
+        // resizing configured the surface with the state's own present mode again
+        self.configure_surface();
+    }
+
+    pub fn set_vsync(&self, vsync: bool) {
+        *self.present_mode.lock().unwrap() = if vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::AutoNoVsync
+        };
+        self.configure_surface();
+    }
+
+    fn configure_surface(&self) {
+        let present_mode = *self.present_mode.lock().unwrap();
+        let mut config = self.state.raw_inner_surface_config().clone();
+        if config.present_mode != present_mode {
+            config.present_mode = present_mode;
+            self.state.raw_surface().configure(&self.state.device(), &config);
+        }
+    }
+
+    // gives back the gpu memory which gets rebuilt on demand, used when the gpu ran out of memory
+    pub fn release_memory(&self) {
+        // the index buffer grows back to the size the next frames need
+        *self.quad_indices.lock().unwrap() = (Arc::new(Self::create_quad_indices(&self.state, INITIAL_QUADS)), INITIAL_QUADS);
+        for glyph in self.glyphs.lock().unwrap().iter() {
+            glyph.staging_belt.lock().unwrap().recall();
+        }
+        // the resources which were dropped already only get freed once the device was polled
+        self.state.device().poll(Maintain::Wait);
     }
 
     pub fn add_model(&self, model: crate::model::Model, coloring: ModelColoring) -> usize {
@@ -156,48 +262,118 @@ impl Renderer {
                 Some(bg)
             }
         };
-        models.push(UploadedModel {
+        models.push(Some(UploadedModel {
             model,
             coloring,
             bind_group,
-        });
+        }));
         models.len() - 1
     }
 
+    /// Loads the model like `add_model_async` unless the model at the path is loaded already, in which
+    /// case the loaded model is shared. The model gets unloaded once the last handle to it is dropped.
+    pub fn cached_model(
+        self: &Arc<Self>,
+        path: &str,
+        load: impl FnOnce(&State, &BindGroupLayout) -> anyhow::Result<crate::model::Model> + Send + 'static,
+        coloring: ModelColoring,
+    ) -> Arc<ModelHandle> {
+        let mut cache = self.model_cache.lock().unwrap();
+        if let Some(handle) = cache.get(path).and_then(Weak::upgrade) {
+            return handle;
+        }
+        let handle = Arc::new(ModelHandle {
+            id: self.add_model_async(load, coloring),
+            path: path.to_string(),
+            renderer: Arc::downgrade(self),
+        });
+        cache.insert(path.to_string(), Arc::downgrade(&handle));
+        handle
+    }
+
+    fn unload_model(&self, handle: &ModelHandle) {
+        let mut cache = self.model_cache.lock().unwrap();
+        // the model may have been loaded again since the last handle was dropped
+        if cache.get(&handle.path).map_or(false, |cached| cached.strong_count() == 0) {
+            cache.remove(&handle.path);
+        }
+        drop(cache);
+        self.models.lock().unwrap()[handle.id] = None;
+    }
+
+    /// Uploads the image which `load` returns unless the texture at the path is still in use, in which
+    /// case it gets shared. The texture gets unloaded once the last reference to it is dropped.
+    pub fn cached_texture(&self, path: &str, load: impl FnOnce() -> anyhow::Result<RgbaImage>) -> anyhow::Result<Arc<TexTriple>> {
+        let mut cache = self.texture_cache.lock().unwrap();
+        if let Some(texture) = cache.get(path).and_then(Weak::upgrade) {
+            return Ok(texture);
+        }
+        let texture = Arc::new(self.create_texture(&load()?));
+        cache.retain(|_, texture| texture.strong_count() > 0);
+        cache.insert(path.to_string(), Arc::downgrade(&texture));
+        Ok(texture)
+    }
+
+    pub fn create_texture(&self, image: &RgbaImage) -> TexTriple {
+        let tex = self.state.create_texture(TextureBuilder::new().data(image.as_raw())
+            .format(TextureFormat::Rgba8UnormSrgb).texture_dimension(TextureDimension::D2).dimensions(image.dimensions()));
+        let view = tex.create_view(&TextureViewDescriptor::default());
+        TexTriple {
+            tex,
+            view,
+            sampler: self.state.device().create_sampler(&SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Nearest,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            }),
+        }
+    }
+
     pub fn render(
         &self,
         ui_models: Vec<Model>,
         instances: Vec<ModeledInstance>,
         atlas: Arc<Atlas>, /*atlases: Arc<Mutex<Vec<Arc<Atlas>>>>*/
         camera: &Camera,
-    ) {
-        self.state
+    ) -> Result<(), SurfaceError> {
+        let result = self.state
             .render(
                 |view, mut encoder, state| {
                     /*for atlas in atlases.lock().unwrap().iter() {
                         atlas.update(&mut encoder);
                     }*/
                     atlas.update(&mut encoder);
-                    let mut atlas_models: HashMap<AtlasId, Vec<AbsoluteTextureVertex>> = HashMap::new();
-                    let mut color_models = vec![];
-                    let mut texture_models = vec![];
+                    // the ui models are drawn in the order they were submitted in, so that models
+                    // which were submitted later end up above the ones submitted before them.
+                    let mut ui_batches: Vec<(Option<ClipRect>, UiBatch)> = vec![];
                     for model in ui_models {
+                        let clip = model.clip;
                         match model.color_src.clone() { // FIXME: try getting rid of this clone!
                             ColorSource::PerVert => {
-                                color_models.extend(model.vertices.into_iter().map(
+                                let vertices = model.vertices.into_iter().map(
                                     |vert| match vert {
                                         Vertex::Color { pos, color } => ColorVertex { pos, color },
                                         Vertex::Texture { .. } => unreachable!(),
                                     },
-                                ));
+                                );
+                                // consecutive color models can share a single draw call if they are clipped the same way
+                                match ui_batches.last_mut() {
+                                    Some((batch_clip, UiBatch::Color(batch))) if *batch_clip == clip => batch.extend(vertices),
+                                    _ => ui_batches.push((clip, UiBatch::Color(vertices.collect::<Vec<_>>()))),
+                                }
                             }
                             ColorSource::Atlas(atlas) => {
-                                // FIXME: make different atlases work!
+                                // the atlas may have grown since the uvs were calculated, so they get normalized here
+                                let size = atlas.size();
                                 let vertices = model.vertices.into_iter().map(|vert| match vert {
                                     Vertex::Color { .. } => unreachable!(),
                                     Vertex::Texture { pos, alpha, uv, color_scale_factor, grayscale_conv } => {
-                                        AbsoluteTextureVertex { pos, alpha, uv: match uv {
-                                            UvKind::Absolute(abs) => abs,
+                                        RelativeTextureVertex { pos, alpha, uv: match uv {
+                                            UvKind::Absolute(abs) => (abs.0 as f32 / size.0 as f32, abs.1 as f32 / size.1 as f32),
                                             UvKind::Relative(_) => unreachable!(),
                                         }, color_scale_factor,
                                             meta: {
@@ -210,11 +386,10 @@ impl Renderer {
                                         }
                                     }
                                 });
-                                if let Some(mut models) = atlas_models.get_mut(&atlas.id()) {
-                                    models.extend(vertices);
-                                } else {
-                                    atlas_models
-                                        .insert(atlas.id(), vertices.collect::<Vec<AbsoluteTextureVertex>>());
+                                // consecutive models of the same atlas share a draw call as well
+                                match ui_batches.last_mut() {
+                                    Some((batch_clip, UiBatch::Atlas(batch_atlas, batch))) if *batch_clip == clip && batch_atlas.id() == atlas.id() => batch.extend(vertices),
+                                    _ => ui_batches.push((clip, UiBatch::Atlas(atlas, vertices.collect::<Vec<_>>()))),
                                 }
                             }
                             ColorSource::Tex(tex) => {
@@ -236,105 +411,138 @@ impl Renderer {
                                         }
                                     }
                                 });
-                                texture_models.push((tex, vertices.collect::<Vec<_>>()));
+                                // consecutive models of the same texture (like a row of portraits) share a draw call
+                                match ui_batches.last_mut() {
+                                    Some((batch_clip, UiBatch::Tex(batch_tex, batch))) if *batch_clip == clip && Arc::ptr_eq(batch_tex, &tex) => batch.extend(vertices),
+                                    _ => ui_batches.push((clip, UiBatch::Tex(tex, vertices.collect::<Vec<_>>()))),
+                                }
                             }
+                            ColorSource::Text(sections) => match ui_batches.last_mut() {
+                                Some((batch_clip, UiBatch::Text(batch))) if *batch_clip == clip => batch.extend(sections),
+                                _ => ui_batches.push((clip, UiBatch::Text(sections))),
+                            },
                         }
                     }
-                    let color_buffer =
-                        state.create_buffer(color_models.as_slice(), BufferUsages::VERTEX);
 
                     // setup a buffer before creating the render pass in order to help the
                     // compiler understand that the textures are living long enough.
-                    let mut tex_buffer = vec![];
-
-                    for texture_models in texture_models.iter() {
-                        let texture_buffer =
-                            state.create_buffer(texture_models.1.as_slice(), BufferUsages::VERTEX);
-
-                        let bg = state.create_bind_group(&self.tex_bind_group_layout, &[BindGroupEntry {
-                            binding: 0,
-                            resource: BindingResource::TextureView(&texture_models.0.view),
-                        }, BindGroupEntry {
-                            binding: 1,
-                            resource: BindingResource::Sampler(&texture_models.0.sampler),
-                        }]);
-                        tex_buffer.push((texture_buffer, bg));
-                    }
-                    {
-                        let mut texture_models = texture_models.iter();
-                        let mut tex_buffer = tex_buffer.iter();
-                        let attachments = [Some(RenderPassColorAttachment {
-                            view: &view,
-                            resolve_target: None,
-                            ops: Operations {
-                                load: LoadOp::Clear(LIGHT_GRAY_GPU),
-                                store: true,
-                            },
-                        })];
-                        let mut render_pass =
-                            state.create_render_pass(&mut encoder, &attachments, None);
-                        // let buffer = state.create_buffer(atlas_models.as_slice(), BufferUsages::VERTEX);
-                        // render_pass.set_vertex_buffer(0, buffer.slice(..));
-
-                        render_pass.set_vertex_buffer(0, color_buffer.slice(..));
-                        render_pass.set_pipeline(&self.color_ui_pipeline);
-                        render_pass.draw(0..(color_models.len() as u32), 0..1);
-
-                        // println!("tex models: {}", texture_models.len());
-                        render_pass.set_pipeline(&self.tex_ui_pipeline);
-                        for buf in tex_buffer {
-                            let model = texture_models.next().unwrap();
-                            render_pass.set_vertex_buffer(0, buf.0.slice(..));
-                            render_pass.set_bind_group(0, &buf.1, &[]);
-                            render_pass.draw(0..(model.1.len() as u32), 0..1);
+                    // all batches share one vertex buffer per vertex type and every texture only gets a
+                    // single bind group, so each batch only has to remember where its vertices start.
+                    let mut color_vertices = vec![];
+                    let mut tex_vertices = vec![];
+                    let mut bind_groups = HashMap::new();
+                    let mut batch_offsets = vec![];
+
+                    for (_, batch) in ui_batches.iter() {
+                        match batch {
+                            UiBatch::Color(vertices) => {
+                                batch_offsets.push((color_vertices.len() as i32, None));
+                                color_vertices.extend_from_slice(vertices);
+                            }
+                            UiBatch::Tex(tex, vertices) => {
+                                let key = BindGroupKey::Tex(Arc::as_ptr(tex) as usize);
+                                bind_groups.entry(key).or_insert_with(|| self.tex_bind_group(state, tex));
+                                batch_offsets.push((tex_vertices.len() as i32, Some(key)));
+                                tex_vertices.extend_from_slice(vertices);
+                            }
+                            UiBatch::Atlas(atlas, vertices) => {
+                                let key = BindGroupKey::Atlas(atlas.id());
+                                bind_groups.entry(key).or_insert_with(|| atlas.with_texture(|tex| self.tex_bind_group(state, tex)));
+                                batch_offsets.push((tex_vertices.len() as i32, Some(key)));
+                                tex_vertices.extend_from_slice(vertices);
+                            }
+                            UiBatch::Text(_) => batch_offsets.push((0, None)),
                         }
                     }
-
-                    let projection = Projection::new(state.raw_inner_surface_config().width, state.raw_inner_surface_config().height, Deg(90.0/*45.0*/), 0.1, 100.0);
-
-                    let mut camera_uniform = CameraUniform::new();
-                    camera_uniform.update_view_proj(camera, &projection);
-
-                    let camera_buffer = state.create_buffer(
-                        &[camera_uniform],
-                        BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-                    );
-                    let camera_bind_group = state.create_bind_group(
-                        &self.camera_bind_group_layout,
-                        &[BindGroupEntry {
-                            binding: 0,
-                            resource: camera_buffer.as_entire_binding(),
-                        }],
-                    );
+                    let surface_size = (state.raw_inner_surface_config().width, state.raw_inner_surface_config().height);
+                    let safe_area = self.safe_area();
+                    // the bars get drawn last, on top of everything that may have been drawn outside of the safe area
+                    let letterbox = (safe_area != (0, 0, surface_size.0, surface_size.1)).then(|| {
+                        let base_vertex = color_vertices.len() as i32;
+                        color_vertices.extend(letterbox_vertices(surface_size, safe_area));
+                        base_vertex
+                    });
+                    let color_buffer = (!color_vertices.is_empty()).then(|| state.create_buffer(color_vertices.as_slice(), BufferUsages::VERTEX));
+                    let tex_buffer = (!tex_vertices.is_empty()).then(|| state.create_buffer(tex_vertices.as_slice(), BufferUsages::VERTEX));
+
+                    let max_quads = ui_batches.iter().map(|(_, batch)| batch.len() / 4).max().unwrap_or(0).max(LETTERBOX_BARS);
+                    let quad_indices = self.quad_indices(state, max_quads);
+
+                    let projection = self.scene_projection();
+                    let camera_bind_group = self.camera_bind_group(state, camera, &projection);
+                    let inset = self.inset.lock().unwrap();
+                    let inset_bind_group = inset.as_ref().map(|inset| {
+                        self.camera_bind_group(state, &inset.camera, &Projection::new(inset.size.0, inset.size.1, Deg(90.0), 0.1, 100.0))
+                    });
 
                     let mut diff_instances = HashSet::new();
 
                     let models = self.models.lock().unwrap();
-                    let mut instance_buffer = vec![vec![]; models.len()];
+                    // indexed by the model and the level of detail the instances are drawn with
+                    let mut instance_buffer = models.iter()
+                        .map(|model| vec![vec![]; model.as_ref().map_or(0, |model| model.model.lods.len()) + 1])
+                        .collect::<Vec<_>>();
                     for instance in instances.iter() {
-                        instance_buffer[instance.model_id].push(instance.instance.to_raw());
+                        // instances of unloaded models can't be drawn anymore
+                        let model = match models[instance.model_id].as_ref() {
+                            Some(model) => model,
+                            None => continue,
+                        };
+                        let distance = (instance.instance.position - camera.position.to_vec()).magnitude();
+                        let level = model.model.lod_level(distance);
+                        instance_buffer[instance.model_id][level].push(instance.instance.to_raw());
                         diff_instances.insert(instance.model_id);
                     }
 
-                    let mut instance_gpu_buffs = vec![];
-                    for instance in instance_buffer.iter() {
-                        // FIXME: don't actually create empty buffers for models with no instances!
-                        let buf = self.state.create_buffer(instance, BufferUsages::VERTEX);
-                        instance_gpu_buffs.push(buf);
+                    // every drawn model with a direct color gets its own slot in a single uniform buffer,
+                    // the slots are aligned so that they can be bound using dynamic offsets.
+                    let slot_size = (size_of::<ModelUniform>() as u32).max(state.device().limits().min_uniform_buffer_offset_alignment);
+                    let mut model_uniforms = vec![];
+                    let mut model_offsets = HashMap::new();
+                    for model_id in diff_instances.iter() {
+                        if let ModelColoring::Direct(color) = &models[*model_id].as_ref().unwrap().coloring {
+                            model_offsets.insert(*model_id, model_uniforms.len() as u32);
+                            model_uniforms.extend_from_slice(bytemuck::bytes_of(&ModelUniform { color: *color }));
+                            model_uniforms.resize(model_uniforms.len() + (slot_size as usize - size_of::<ModelUniform>()), 0u8);
+                        }
                     }
-
-                    {
-                        let tex = self.depth_tex.load();
+                    let model_uniform_buffer = (!model_uniforms.is_empty()).then(|| state.create_buffer(model_uniforms.as_slice(), BufferUsages::UNIFORM));
+                    let model_uniform_bind_group = model_uniform_buffer.as_ref().map(|buffer| state.create_bind_group(
+                        &self.model_uniform_bind_group_layout,
+                        &[BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::Buffer(BufferBinding {
+                                buffer,
+                                offset: 0,
+                                size: BufferSize::new(size_of::<ModelUniform>() as u64),
+                            }),
+                        }],
+                    ));
+
+                    let instance_gpu_buffs = instance_buffer.iter().map(|levels| levels.iter().map(|instances| {
+                        (!instances.is_empty()).then(|| self.state.create_buffer(instances, BufferUsages::VERTEX))
+                    }).collect::<Vec<_>>()).collect::<Vec<_>>();
+
+                    let depth_tex = self.depth_tex.load();
+                    // the scene gets drawn into the surface and into the inset (if there is one)
+                    // FIXME: the inset uses the levels of detail which were picked for the main camera
+                    let mut passes = vec![(view, &depth_tex.view, &camera_bind_group, safe_area)];
+                    if let (Some(inset), Some(bind_group)) = (inset.as_ref(), inset_bind_group.as_ref()) {
+                        passes.push((&inset.target.view, &inset.depth.view, bind_group, (0, 0, inset.size.0, inset.size.1)));
+                    }
+                    for (target, depth_view, camera_bind_group, viewport) in passes {
+                        let tex_model_pipeline = self.tex_model_pipeline.load();
+                        let color_model_pipeline = self.color_model_pipeline.load();
                         let attachment = Some(RenderPassDepthStencilAttachment {
-                            view: &tex.view,
+                            view: depth_view,
                             depth_ops: Some(Operations { load: LoadOp::Clear(1.0), store: true }),
                             stencil_ops: None,
                         });
                         let attachments = [Some(RenderPassColorAttachment {
-                            view: &view,
+                            view: target,
                             resolve_target: None,
                             ops: Operations {
-                                load: LoadOp::Load,
+                                load: LoadOp::Clear(LIGHT_GRAY_GPU),
                                 store: true,
                             },
                         })];
@@ -343,47 +551,190 @@ impl Renderer {
                         // FIXME: try using the same render pass as for UI!
 
                         // println!("tex models: {}", texture_models.len());
-                        render_pass.set_bind_group(0, &camera_bind_group, &[]); // camera bind group
-                        for model_id in diff_instances.into_iter() {
-                            let model = models.get(model_id).unwrap();
-                            /*match &model.coloring {
-                                ModelColoring::Direct(color) => {
-                                    render_pass.set_pipeline(&self.color_model_pipeline);
-                                    render_pass.set_push_constants(ShaderStages::FRAGMENT, 0, bytemuck::cast_slice(color));
+                        render_pass.set_viewport(viewport.0 as f32, viewport.1 as f32, viewport.2 as f32, viewport.3 as f32, 0.0, 1.0);
+                        render_pass.set_bind_group(0, camera_bind_group, &[]); // camera bind group
+                        for model_id in diff_instances.iter().copied() {
+                            let model = models[model_id].as_ref().unwrap();
+                            match &model.coloring {
+                                ModelColoring::Direct(_) => {
+                                    render_pass.set_pipeline(&color_model_pipeline);
+                                    render_pass.set_bind_group(1, model_uniform_bind_group.as_ref().unwrap(), &[model_offsets[&model_id]]);
                                 }
-                                ModelColoring::Tex(_) => {
-                                    render_pass.set_pipeline(&self.tex_model_pipeline);
-                                    render_pass.set_bind_group(1, model.bind_group.as_ref().unwrap(), &[]); // texture bind group
+                                ModelColoring::Tex(_) => render_pass.set_pipeline(&tex_model_pipeline),
+                            }
+                            for (level, instance_gpu_buf) in instance_gpu_buffs[model_id].iter().enumerate() {
+                                let instance_gpu_buf = match instance_gpu_buf {
+                                    Some(buf) => buf,
+                                    None => continue,
+                                };
+                                let lod = model.model.level(level);
+                                let instance_count = instance_buffer[model_id][level].len() as u32;
+                                for mesh in lod.meshes.iter() {
+                                    println!("idx: {}", model_id);
+                                    println!("drawing mesh {} : {}", instance_count, mesh.num_elements);
+                                    println!("materials: {}", lod.materials.len());
+                                    if let ModelColoring::Tex(_) = &model.coloring {
+                                        render_pass.set_bind_group(1, &lod.materials[mesh.material].bind_group, &[]);
+                                    }
+                                    render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                                    render_pass.set_index_buffer(mesh.index_buffer.slice(..), IndexFormat::Uint32/*IndexFormat::Uint16*/);
+                                    render_pass.set_vertex_buffer(1, instance_gpu_buf.slice(..));
+                                    render_pass.draw_indexed(0..mesh.num_elements, 0, 0..instance_count);
                                 }
-                            }*/
-                            render_pass.set_pipeline(&self.tex_model_pipeline);
-                            for mesh in model.model.meshes.iter() {
-                                println!("idx: {}", model_id);
-                                println!("drawing mesh {} : {}", instance_buffer.get(model_id).unwrap().len(), mesh.num_elements);
-                                println!("materials: {}", model.model.materials.len());
-                                render_pass.set_bind_group(1, &model.model.materials[mesh.material].bind_group, &[]);
-                                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
-                                render_pass.set_index_buffer(mesh.index_buffer.slice(..), IndexFormat::Uint32/*IndexFormat::Uint16*/);
-                                render_pass.set_vertex_buffer(1, instance_gpu_buffs.get(model_id).unwrap().slice(..));
-                                render_pass.draw_indexed(0..mesh.num_elements, 0, 0..(instance_buffer.get(model_id).unwrap().len() as u32));
+                            }
+                        }
+                    }
+
+                    // the ui is drawn after the world, so (partially transparent) ui elements end up above it.
+                    // the glyph brushes draw text in passes of their own, so the ui's pass gets interrupted
+                    // wherever text sits between the ui models and continues in a new pass afterwards
+                    {
+                        let color_ui_pipeline = self.color_ui_pipeline.load();
+                        let tex_ui_pipeline = self.tex_ui_pipeline.load();
+                        let attachments = [Some(RenderPassColorAttachment {
+                            view: &view,
+                            resolve_target: None,
+                            ops: Operations {
+                                load: LoadOp::Load,
+                                store: true,
+                            },
+                        })];
+                        let mut batches = ui_batches.into_iter().zip(batch_offsets.into_iter()).peekable();
+                        loop {
+                            {
+                                let mut render_pass =
+                                    state.create_render_pass(&mut encoder, &attachments, None);
+                                render_pass.set_viewport(safe_area.0 as f32, safe_area.1 as f32, safe_area.2 as f32, safe_area.3 as f32, 0.0, 1.0);
+                                render_pass.set_index_buffer(quad_indices.slice(..), IndexFormat::Uint32);
+                                let mut curr_clip = None;
+                                let mut curr_bind_group = None;
+                                while let Some(((clip, batch), (base_vertex, bind_group))) = batches.next_if(|((_, batch), _)| !matches!(batch, UiBatch::Text(_))) {
+                                    if clip != curr_clip {
+                                        let (x, y, width, height) = match clip {
+                                            Some(clip) => clip.to_scissor(safe_area),
+                                            None => safe_area,
+                                        };
+                                        render_pass.set_scissor_rect(x, y, width, height);
+                                        curr_clip = clip;
+                                    }
+                                    // FIXME: only switch pipelines and vertex buffers when the kind of batch changes
+                                    match batch {
+                                        UiBatch::Color(_) => {
+                                            render_pass.set_pipeline(&color_ui_pipeline);
+                                            render_pass.set_vertex_buffer(0, color_buffer.as_ref().unwrap().slice(..));
+                                        }
+                                        UiBatch::Tex(..) | UiBatch::Atlas(..) => {
+                                            render_pass.set_pipeline(&tex_ui_pipeline);
+                                            render_pass.set_vertex_buffer(0, tex_buffer.as_ref().unwrap().slice(..));
+                                        }
+                                        UiBatch::Text(_) => unreachable!(),
+                                    }
+                                    if let Some(bind_group) = bind_group {
+                                        if curr_bind_group != Some(bind_group) {
+                                            render_pass.set_bind_group(0, &bind_groups[&bind_group], &[]);
+                                            curr_bind_group = Some(bind_group);
+                                        }
+                                    }
+                                    render_pass.draw_indexed(0..(batch.len() / 4 * RECT_INDICES.len()) as u32, base_vertex, 0..1);
+                                }
+                                if batches.peek().is_none() {
+                                    if let Some(base_vertex) = letterbox {
+                                        render_pass.set_viewport(0.0, 0.0, surface_size.0 as f32, surface_size.1 as f32, 0.0, 1.0);
+                                        render_pass.set_scissor_rect(0, 0, surface_size.0, surface_size.1);
+                                        render_pass.set_pipeline(&color_ui_pipeline);
+                                        render_pass.set_vertex_buffer(0, color_buffer.as_ref().unwrap().slice(..));
+                                        render_pass.draw_indexed(0..(LETTERBOX_BARS * RECT_INDICES.len()) as u32, base_vertex, 0..1);
+                                    }
+                                    break;
+                                }
+                            }
+                            if let Some(((clip, UiBatch::Text(sections)), _)) = batches.next() {
+                                // text is laid out relative to the safe area and can't spill into the bars
+                                let (x, y, width, height) = match clip {
+                                    Some(clip) => clip.to_scissor(safe_area),
+                                    None => safe_area,
+                                };
+                                self.draw_text(state, &mut encoder, view, sections, surface_size, safe_area, (x, y, width, height));
                             }
                         }
                     }
 
                     for glyph in self.glyphs.lock().unwrap().iter() {
-                        let mut staging_belt = glyph.staging_belt.lock().unwrap();
-                        let (width, height) = self.dimensions.get();
-                        glyph.brush.lock().unwrap().draw_queued(&state.device(), &mut staging_belt, &mut encoder, view, width, height).unwrap();
-                        staging_belt.finish();
+                        glyph.staging_belt.lock().unwrap().finish();
                     }
                     encoder
                 },
                 &TextureViewDescriptor::default(),
-            )
-            .unwrap();
+            );
         for glyph in self.glyphs.lock().unwrap().iter() {
             glyph.staging_belt.lock().unwrap().recall();
         }
+        result
+    }
+
+    // draws the sections on top of everything that was drawn so far, the staging belts are finished once all text was drawn
+    fn draw_text(&self, state: &State, encoder: &mut CommandEncoder, view: &TextureView, sections: Vec<(usize, OwnedSection)>,
+                 surface_size: (u32, u32), safe_area: (u32, u32, u32, u32), (x, y, width, height): (u32, u32, u32, u32)) {
+        let glyphs = self.glyphs.lock().unwrap();
+        let mut used = HashSet::new();
+        for (glyph_id, section) in sections.iter() {
+            glyphs[*glyph_id].brush.lock().unwrap().queue(section.to_borrowed());
+            used.insert(*glyph_id);
+        }
+        for glyph_id in used {
+            let glyph = &glyphs[glyph_id];
+            glyph.brush.lock().unwrap().draw_queued_with_transform_and_scissoring(
+                &state.device(),
+                &mut glyph.staging_belt.lock().unwrap(),
+                encoder,
+                view,
+                safe_area_projection(surface_size, safe_area),
+                Region { x, y, width, height },
+            ).unwrap();
+        }
+    }
+
+    fn camera_bind_group(&self, state: &State, camera: &Camera, projection: &Projection) -> BindGroup {
+        let mut camera_uniform = CameraUniform::new();
+        camera_uniform.update_view_proj(camera, projection);
+
+        let camera_buffer = state.create_buffer(
+            &[camera_uniform],
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        );
+        state.create_bind_group(
+            &self.camera_bind_group_layout,
+            &[BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        )
+    }
+
+    fn quad_indices(&self, state: &State, quads: usize) -> Arc<Buffer> {
+        let mut quad_indices = self.quad_indices.lock().unwrap();
+        if quad_indices.1 < quads {
+            let capacity = quads.next_power_of_two();
+            *quad_indices = (Arc::new(Self::create_quad_indices(state, capacity)), capacity);
+        }
+        quad_indices.0.clone()
+    }
+
+    fn create_quad_indices(state: &State, quads: usize) -> Buffer {
+        let indices = (0..quads as u32)
+            .flat_map(|quad| RECT_INDICES.map(|idx| quad * 4 + idx))
+            .collect::<Vec<_>>();
+        state.create_buffer(indices.as_slice(), BufferUsages::INDEX)
+    }
+
+    fn tex_bind_group(&self, state: &State, tex: &TexTriple) -> BindGroup {
+        state.create_bind_group(&self.tex_bind_group_layout, &[BindGroupEntry {
+            binding: 0,
+            resource: BindingResource::TextureView(&tex.view),
+        }, BindGroupEntry {
+            binding: 1,
+            resource: BindingResource::Sampler(&tex.sampler),
+        }])
     }
 
     fn color_ui_pipeline(state: &State) -> RenderPipeline {
@@ -396,12 +747,12 @@ impl Renderer {
                 entry_point: "main_frag",
                 targets: &[Some(ColorTargetState {
                     format: state.format(),
-                    blend: Some(BlendState::REPLACE),
+                    blend: Some(BlendState::ALPHA_BLENDING),
                     write_mask: ColorWrites::ALL,
                 })],
             })
             .shader_src(ShaderModuleSources::Single(ModuleSrc::Source(
-                ShaderSource::Wgsl(include_str!("ui_color.wgsl").into()),
+                ShaderSource::Wgsl(hot_reload::shader_source("ui_color.wgsl", include_str!("ui_color.wgsl")).into()),
             )))
             .layout(&state.create_pipeline_layout(&[], &[]))
             .build(state)
@@ -417,12 +768,12 @@ impl Renderer {
                 entry_point: "main_frag",
                 targets: &[Some(ColorTargetState {
                     format: state.format(),
-                    blend: Some(BlendState::REPLACE),
+                    blend: Some(BlendState::ALPHA_BLENDING),
                     write_mask: ColorWrites::ALL,
                 })],
             })
             .shader_src(ShaderModuleSources::Single(ModuleSrc::Source(
-                ShaderSource::Wgsl(include_str!("ui_atlas.wgsl").into()),
+                ShaderSource::Wgsl(hot_reload::shader_source("ui_atlas.wgsl", include_str!("ui_atlas.wgsl")).into()),
             )))
             .layout(&state.create_pipeline_layout(
                 &[&state.create_bind_group_layout(&[
@@ -460,12 +811,12 @@ impl Renderer {
                 entry_point: "main_frag",
                 targets: &[Some(ColorTargetState {
                     format: state.format(),
-                    blend: Some(BlendState::REPLACE),
+                    blend: Some(BlendState::ALPHA_BLENDING),
                     write_mask: ColorWrites::ALL,
                 })],
             })
             .shader_src(ShaderModuleSources::Single(ModuleSrc::Source(
-                ShaderSource::Wgsl(include_str!("ui_tex.wgsl").into()),
+                ShaderSource::Wgsl(hot_reload::shader_source("ui_tex.wgsl", include_str!("ui_tex.wgsl")).into()),
             )))
             .layout(&state.create_pipeline_layout(
                 &[&state.create_bind_group_layout(&[
@@ -508,7 +859,7 @@ impl Renderer {
                 })],
             })
             .shader_src(ShaderModuleSources::Single(ModuleSrc::Source(
-                ShaderSource::Wgsl(include_str!("model_texture.wgsl").into()),
+                ShaderSource::Wgsl(hot_reload::shader_source("model_texture.wgsl", include_str!("model_texture.wgsl")).into()),
             )))
             .layout(&state.create_pipeline_layout(&[camera_layout, bgl], &[]))
             .depth_stencil(DepthStencilState {
@@ -521,7 +872,7 @@ impl Renderer {
             .build(state)
     }
 
-    fn color_model_pipeline(state: &State, camera_layout: &BindGroupLayout) -> RenderPipeline {
+    fn color_model_pipeline(state: &State, camera_layout: &BindGroupLayout, uniform_layout: &BindGroupLayout) -> RenderPipeline {
         PipelineBuilder::new()
             .vertex(VertexShaderState {
                 entry_point: "main_vert",
@@ -536,15 +887,56 @@ impl Renderer {
                 })],
             })
             .shader_src(ShaderModuleSources::Single(ModuleSrc::Source(
-                ShaderSource::Wgsl(include_str!("model_color.wgsl").into()),
+                ShaderSource::Wgsl(hot_reload::shader_source("model_color.wgsl", include_str!("model_color.wgsl")).into()),
             )))
-            .layout(&state.create_pipeline_layout(&[camera_layout], &[PushConstantRange {
-                stages: ShaderStages::FRAGMENT,
-                range: 0..16,
-            }]))
+            .layout(&state.create_pipeline_layout(&[camera_layout, uniform_layout], &[]))
             .build(state)
     }
 
+    // rebuilds all pipelines from the shaders on disk, if any of them is invalid the old ones stay in use
+    pub fn reload_shaders(&self) {
+        let device = self.state.device();
+        device.push_error_scope(ErrorFilter::Validation);
+        let atlas_pipeline = Self::atlas_ui_pipeline(&self.state);
+        let tex_ui_pipeline = Self::tex_ui_pipeline(&self.state);
+        let color_ui_pipeline = Self::color_ui_pipeline(&self.state);
+        let color_model_pipeline = Self::color_model_pipeline(&self.state, &self.camera_bind_group_layout, &self.model_uniform_bind_group_layout);
+        let tex_model_pipeline = Self::tex_model_pipeline(&self.state, &self.model_bind_group_layout, &self.camera_bind_group_layout);
+        if let Some(err) = pollster::block_on(device.pop_error_scope()) {
+            println!("Couldn't reload shaders: {}", err);
+            return;
+        }
+        self.atlas_pipeline.store(Arc::new(atlas_pipeline));
+        self.tex_ui_pipeline.store(Arc::new(tex_ui_pipeline));
+        self.color_ui_pipeline.store(Arc::new(color_ui_pipeline));
+        self.color_model_pipeline.store(Arc::new(color_model_pipeline));
+        self.tex_model_pipeline.store(Arc::new(tex_model_pipeline));
+        println!("Reloaded shaders");
+    }
+
+    // re-uploads the texture of every material which was loaded from the file
+    pub fn reload_texture(&self, path: &Path) {
+        for model in self.models.lock().unwrap().iter_mut().flatten() {
+            let model = &mut model.model;
+            let materials = model.materials.iter_mut()
+                .chain(model.lods.iter_mut().flat_map(|(_, lod)| lod.materials.iter_mut()));
+            for material in materials {
+                if Path::new(&material.texture_path) != path {
+                    continue;
+                }
+                // the file may still be in the middle of being written, it will be reloaded once that's done
+                match fs::read(path).map_err(anyhow::Error::from)
+                    .and_then(|bytes| ContainedTexture::from_bytes(&self.state, &bytes)) {
+                    Ok(texture) => {
+                        material.set_texture(&self.state, &self.model_bind_group_layout, texture);
+                        println!("Reloaded {}", path.display());
+                    }
+                    Err(err) => println!("Couldn't reload {}: {}", path.display(), err),
+                }
+            }
+        }
+    }
+
     pub fn add_glyph(&self, glyph_info: GlyphInfo) -> usize {
         let mut glyphs = self.glyphs.lock().unwrap();
         let len = glyphs.len();
@@ -552,8 +944,183 @@ impl Renderer {
         len
     }
 
+    // the text is drawn once it's taken as a model, see `take_text_model`
     pub fn queue_glyph(&self, glyph_id: usize, section: Section) {
-        self.glyphs.lock().unwrap()[glyph_id].brush.lock().unwrap().queue(section);
+        self.queued_text.lock().unwrap().push((glyph_id, section.to_owned()));
+    }
+
+    // the text queued since the last call, it's drawn at the model's place in the ui's draw order
+    pub fn take_text_model(&self) -> Option<Model> {
+        let sections = std::mem::take(&mut *self.queued_text.lock().unwrap());
+        (!sections.is_empty()).then(|| Model {
+            vertices: vec![],
+            color_src: ColorSource::Text(sections),
+            clip: None,
+        })
+    }
+
+    // glyph brushes can't replace their fonts, so they get rebuilt with the new bold font
+    pub fn set_bold_font(&self, bytes: Vec<u8>) {
+        self.text_rasterizer.set_bold_font(&bytes);
+        let bold_font = match ab_glyph::FontArc::try_from_vec(bytes) {
+            Ok(font) => font,
+            Err(_) => return,
+        };
+        for glyph in self.glyphs.lock().unwrap().iter() {
+            *glyph.brush.lock().unwrap() = GlyphBrushBuilder::using_fonts(vec![self.regular_font.clone(), bold_font.clone()])
+                .build(&self.state.device(), glyph.format);
+        }
+    }
+
+    pub fn text_scale(&self) -> f32 {
+        self.text_scale.load(Ordering::Acquire)
+    }
+
+    pub fn set_text_scale(&self, scale: f32) {
+        self.text_scale.store(scale, Ordering::Release);
+    }
+
+    /// Renders the scene from the camera into a texture of the given size (in pixels) every frame,
+    /// until the inset gets disabled again. The returned texture can be shown as part of the ui.
+    pub fn enable_inset(&self, camera: Camera, size: (u32, u32)) -> Arc<TexTriple> {
+        let target = Arc::new(TexTriple::create_render_target(&self.state, size));
+        *self.inset.lock().unwrap() = Some(Inset {
+            camera,
+            size,
+            target: target.clone(),
+            depth: TexTriple::create_sized_depth_texture(&self.state, size),
+        });
+        target
+    }
+
+    pub fn disable_inset(&self) {
+        *self.inset.lock().unwrap() = None;
+    }
+
+    pub fn set_safe_area(&self, aspect: Option<f32>) {
+        *self.safe_area.lock().unwrap() = aspect;
+    }
+
+    /// The part of the surface the scene and the ui are drawn in as (x, y, width, height) in pixels,
+    /// whose y axis points downwards. The rest of the surface is covered by letterbox bars.
+    pub fn safe_area(&self) -> (u32, u32, u32, u32) {
+        let (width, height) = self.dimensions.get();
+        match *self.safe_area.lock().unwrap() {
+            // the surface is too wide, so there are bars on the left and the right
+            Some(aspect) if width as f32 / height.max(1) as f32 > aspect => {
+                let inner = ((height as f32 * aspect).round() as u32).clamp(1, width.max(1));
+                ((width - inner.min(width)) / 2, 0, inner, height)
+            }
+            Some(aspect) => {
+                let inner = ((width as f32 / aspect).round() as u32).clamp(1, height.max(1));
+                (0, (height - inner.min(height)) / 2, width, inner)
+            }
+            None => (0, 0, width, height),
+        }
+    }
+
+    // all positions and sizes of the ui are relative to these dimensions
+    pub fn ui_dimensions(&self) -> (u32, u32) {
+        let (_, _, width, height) = self.safe_area();
+        (width, height)
+    }
+
+    fn scene_projection(&self) -> Projection {
+        let (width, height) = self.ui_dimensions();
+        Projection::new(width, height, Deg(90.0/*45.0*/), 0.1, 100.0)
+    }
+
+    /// Maps a point in the world onto the ui as it's seen from the camera, the position is relative
+    /// to the ui like the positions of components. Points behind the camera aren't visible.
+    pub fn project(&self, camera: &Camera, point: Point3<f32>) -> Option<(f32, f32)> {
+        let clip = self.scene_projection().calc_matrix() * camera.calc_matrix() * point.to_homogeneous();
+        if clip.w <= 0.0 {
+            return None;
+        }
+        Some(((clip.x / clip.w + 1.0) / 2.0, (clip.y / clip.w + 1.0) / 2.0))
+    }
+
+    /// The inverse of `project`, returns the ray from the camera through the position on the ui
+    /// as its origin on the near plane and its normalized direction.
+    pub fn pick_ray(&self, camera: &Camera, pos: (f32, f32)) -> (Point3<f32>, Vector3<f32>) {
+        let inverse = (self.scene_projection().calc_matrix() * camera.calc_matrix()).invert().unwrap();
+        let (x, y) = (pos.0 * 2.0 - 1.0, pos.1 * 2.0 - 1.0);
+        // the depth of the clip space goes from 0.0 at the near plane to 1.0 at the far plane
+        let near = Point3::from_homogeneous(inverse * Vector4::new(x, y, 0.0, 1.0));
+        let far = Point3::from_homogeneous(inverse * Vector4::new(x, y, 1.0, 1.0));
+        (near, (far - near).normalize())
+    }
+
+    // returns the pixel bounds the section's glyphs would occupy if they were rendered
+    pub fn glyph_bounds(&self, glyph_id: usize, section: Section) -> Option<ab_glyph::Rect> {
+        self.glyphs.lock().unwrap()[glyph_id].brush.lock().unwrap().glyph_bounds(section)
+    }
+}
+
+// a second view of the scene from a fixed camera
+struct Inset {
+    camera: Camera,
+    size: (u32, u32),
+    target: Arc<TexTriple>,
+    depth: TexTriple,
+}
+
+// one bar for every edge of the surface, the ones which aren't needed are simply empty
+const LETTERBOX_BARS: usize = 4;
+
+fn letterbox_vertices((width, height): (u32, u32), (x, y, inner_width, inner_height): (u32, u32, u32, u32)) -> Vec<ColorVertex> {
+    let bars = [
+        (0, 0, x, height),
+        (x + inner_width, 0, width - x - inner_width, height),
+        (0, 0, width, y),
+        (0, y + inner_height, width, height - y - inner_height),
+    ];
+    // pixels to clip space, whose y axis points upwards
+    let to_clip = |px: u32, py: u32| [px as f32 / width as f32 * 2.0 - 1.0, 1.0 - py as f32 / height as f32 * 2.0];
+    bars.into_iter().flat_map(|(x, y, bar_width, bar_height)| [
+        to_clip(x, y + bar_height),
+        to_clip(x + bar_width, y + bar_height),
+        to_clip(x + bar_width, y),
+        to_clip(x, y),
+    ]).map(|pos| ColorVertex {
+        pos,
+        color: [0.0, 0.0, 0.0, 1.0],
+    }).collect()
+}
+
+// like `wgpu_glyph::orthographic_projection`, but pixel coordinates start at the safe area's corner
+fn safe_area_projection((width, height): (u32, u32), (x, y, _, _): (u32, u32, u32, u32)) -> [f32; 16] {
+    let (width, height) = (width as f32, height as f32);
+    [
+        2.0 / width, 0.0, 0.0, 0.0,
+        0.0, -2.0 / height, 0.0, 0.0,
+        0.0, 0.0, 1.0, 0.0,
+        -1.0 + 2.0 * x as f32 / width, 1.0 - 2.0 * y as f32 / height, 0.0, 1.0,
+    ]
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum BindGroupKey {
+    // the address of the texture
+    Tex(usize),
+    Atlas(AtlasId),
+}
+
+enum UiBatch {
+    Color(Vec<ColorVertex>),
+    Tex(Arc<TexTriple>, Vec<RelativeTextureVertex>),
+    Atlas(Arc<Atlas>, Vec<RelativeTextureVertex>),
+    Text(Vec<(usize, OwnedSection)>),
+}
+
+impl UiBatch {
+    fn len(&self) -> usize {
+        match self {
+            UiBatch::Color(vertices) => vertices.len(),
+            UiBatch::Tex(_, vertices) => vertices.len(),
+            UiBatch::Atlas(_, vertices) => vertices.len(),
+            UiBatch::Text(_) => 0,
+        }
     }
 }
 
@@ -567,6 +1134,40 @@ struct UploadedModel {
 pub struct Model {
     pub vertices: Vec<Vertex>,
     pub color_src: ColorSource,
+    pub clip: Option<ClipRect>,
+}
+
+// a rect relative to the screen's dimensions, its position is its bottom left corner
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ClipRect {
+    pub pos: (f32, f32),
+    pub dims: (f32, f32),
+}
+
+impl ClipRect {
+
+    pub fn intersect(&self, other: &ClipRect) -> ClipRect {
+        let min = (self.pos.0.max(other.pos.0), self.pos.1.max(other.pos.1));
+        let max = (
+            (self.pos.0 + self.dims.0).min(other.pos.0 + other.dims.0),
+            (self.pos.1 + self.dims.1).min(other.pos.1 + other.dims.1),
+        );
+        ClipRect {
+            pos: min,
+            dims: ((max.0 - min.0).max(0.0), (max.1 - min.1).max(0.0)),
+        }
+    }
+
+    // converts the rect into a scissor rect (x, y, width, height) in pixels, whose y axis points downwards,
+    // the rect is relative to the area (given in the same form) the ui is drawn in
+    fn to_scissor(&self, (area_x, area_y, width, height): (u32, u32, u32, u32)) -> (u32, u32, u32, u32) {
+        let x = (self.pos.0 * width as f32).clamp(0.0, width as f32);
+        let y = ((1.0 - self.pos.1 - self.dims.1) * height as f32).clamp(0.0, height as f32);
+        let max_x = ((self.pos.0 + self.dims.0) * width as f32).clamp(x, width as f32);
+        let max_y = ((1.0 - self.pos.1) * height as f32).clamp(y, height as f32);
+        (area_x + x as u32, area_y + y as u32, (max_x - x) as u32, (max_y - y) as u32)
+    }
+
 }
 
 #[derive(Clone)]
@@ -574,6 +1175,8 @@ pub enum ColorSource {
     PerVert,
     Atlas(Arc<Atlas>),
     Tex(Arc<TexTriple>),
+    // the model has no vertices, its text is drawn by the glyph brushes instead
+    Text(Vec<(usize, OwnedSection)>),
 }
 
 pub enum TexTy {
@@ -924,6 +1527,29 @@ impl CameraController {
         }
     }
 
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity;
+    }
+
+    // forgets all input, so the camera doesn't keep on moving once the controller takes over again
+    pub fn reset(&mut self) {
+        self.amount_left = 0.0;
+        self.amount_right = 0.0;
+        self.amount_forward = 0.0;
+        self.amount_backward = 0.0;
+        self.amount_up = 0.0;
+        self.amount_down = 0.0;
+        self.rotate_horizontal = 0.0;
+        self.rotate_vertical = 0.0;
+        self.scroll = 0.0;
+    }
+
+    // whether the camera keeps on moving without any further input
+    pub fn is_moving(&self) -> bool {
+        self.amount_left != 0.0 || self.amount_right != 0.0 || self.amount_forward != 0.0
+            || self.amount_backward != 0.0 || self.amount_up != 0.0 || self.amount_down != 0.0
+    }
+
     pub fn process_mouse(&mut self, mouse_dx: f64, mouse_dy: f64) {
         self.rotate_horizontal = mouse_dx as f32;
         self.rotate_vertical = mouse_dy as f32;
@@ -988,12 +1614,41 @@ impl CameraController {
     }
 }
 
+/// A model shared by everything which loaded it from the same path, see `Renderer::cached_model`.
+pub struct ModelHandle {
+    id: usize,
+    path: String,
+    renderer: Weak<Renderer>,
+}
+
+impl ModelHandle {
+
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+}
+
+impl Drop for ModelHandle {
+    fn drop(&mut self) {
+        if let Some(renderer) = self.renderer.upgrade() {
+            renderer.unload_model(self);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct ModeledInstance {
     pub model_id: usize,
     pub instance: Instance,
 }
 
+#[derive(Pod, Zeroable, Copy, Clone)]
+#[repr(C)]
+struct ModelUniform {
+    color: [f32; 4],
+}
+
 pub enum ModelColoring {
     Direct([f32; 4]),
     Tex(Arc<TexTriple>),
@@ -1006,6 +1661,14 @@ pub struct Instance {
 }
 
 impl Instance {
+    // applies a transform which is relative to this one, like the one of a child relative to its parent
+    pub fn then(&self, child: &Instance) -> Instance {
+        Instance {
+            position: self.position + self.rotation * child.position,
+            rotation: self.rotation * child.rotation,
+        }
+    }
+
     fn to_raw(&self) -> InstanceRaw {
         InstanceRaw {
             model: (Matrix4::from_translation(self.position) * Matrix4::from(self.rotation)).into(),
@@ -1061,8 +1724,31 @@ impl TexTriple {
     pub const DEPTH_FORMAT: TextureFormat = TextureFormat::Depth32Float;
 
     pub fn create_depth_texture(state: &State) -> Self {
+        Self::create_sized_depth_texture(state, (state.raw_inner_surface_config().width, state.raw_inner_surface_config().height))
+    }
+
+    // a texture the scene can be drawn into, which can be sampled by the ui afterwards
+    pub fn create_render_target(state: &State, size: (u32, u32)) -> Self {
+        let texture = state.create_raw_texture(RawTextureBuilder::new().texture_dimension(TextureDimension::D2)
+            .format(state.format()).dimensions(size).usages(wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::TEXTURE_BINDING));
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let sampler = state.device().create_sampler(&SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self { tex: texture, view, sampler }
+    }
+
+    pub fn create_sized_depth_texture(state: &State, size: (u32, u32)) -> Self {
         let texture = state.create_raw_texture(RawTextureBuilder::new().texture_dimension(TextureDimension::D2)
-            .format(Self::DEPTH_FORMAT).dimensions((state.raw_inner_surface_config().width, state.raw_inner_surface_config().height)).usages(wgpu::TextureUsages::RENDER_ATTACHMENT
+            .format(Self::DEPTH_FORMAT).dimensions(size).usages(wgpu::TextureUsages::RENDER_ATTACHMENT
             | wgpu::TextureUsages::TEXTURE_BINDING));
 
         let view = texture.create_view(&TextureViewDescriptor::default());
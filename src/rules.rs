@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use crate::i18n;
+use crate::utils::{read_config, write_config};
+
+const RULES_FILE: &str = "rules.json";
+
+pub fn load_rules() -> Rules {
+    match read_config(RULES_FILE) {
+        Some(buf) => {
+            let mut rules: Rules = serde_json::from_str(&*buf).unwrap();
+            // every amount is divided by the denomination when making change, so it can't be 0
+            rules.currency.denomination = rules.currency.denomination.max(1);
+            rules
+        }
+        None => {
+            let rules = Rules::default();
+            write_config(RULES_FILE, &serde_json::to_string(&rules).unwrap());
+            rules
+        }
+    }
+}
+
+// house rules which can be toggled per installation, missing entries fall back to their defaults
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct Rules {
+    pub free_parking: FreeParkingRules,
+    pub buildings: BuildingRules,
+    pub income_tax: IncomeTaxRules,
+    pub currency: CurrencyRules,
+    // the seconds players have for a decision before the default is chosen for them, no limit if absent
+    pub decision_timer: Option<u64>,
+    pub setup: PropertySetup,
+    // shuffles the property tiles at the start of the game, the corners and card tiles stay where they are
+    pub shuffle_board: bool,
+    // the game ends after this many rounds and the richest player wins, no limit if absent
+    pub round_limit: Option<usize>,
+}
+
+// how the properties get to the players, dealing or drafting them skips the slow early game
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Default, Debug)]
+pub enum PropertySetup {
+    // properties are bought by landing on them
+    #[default]
+    Buying,
+    // the properties are shuffled and dealt evenly before the first turn, the rest stays with the bank
+    Dealt,
+    // players pick properties in snake order (1, 2, 3, 3, 2, 1, ...) before the first turn
+    Drafted,
+}
+
+// how amounts of money are shown to the players, the engine itself only deals with plain amounts.
+// the separators and the symbol's placement follow the selected language unless they are set here
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct CurrencyRules {
+    pub symbol: String,
+    // whether the symbol is put after the amount, like "100 €" or "100 points"
+    pub symbol_after: Option<bool>,
+    pub thousands_separator: Option<String>,
+    // every amount is multiplied by this when shown, so boards can use larger denominations without changing their prices
+    pub denomination: usize,
+    // the last digits of the shown amount are decimals, e.g. 150 with 2 decimals is shown as 1.50
+    pub decimals: usize,
+    // the bills payments are broken into, as shown to the players (after the denomination), empty to not show any
+    pub bills: Vec<usize>,
+}
+
+impl CurrencyRules {
+
+    pub fn format(&self, amount: usize) -> String {
+        self.format_shown(amount * self.denomination)
+    }
+
+    // formats an amount which was already multiplied by the denomination, like a bill
+    pub fn format_shown(&self, shown: usize) -> String {
+        let mut format = i18n::number_format();
+        if let Some(separator) = &self.thousands_separator {
+            format.thousands_separator = separator.clone();
+        }
+        let grouped = format.format(shown, self.decimals);
+        if self.symbol_after.unwrap_or(format.symbol_after) {
+            format!("{} {}", grouped, self.symbol)
+        } else {
+            format!("{}{}", self.symbol, grouped)
+        }
+    }
+
+    /// The bills which make up the amount along with how many of each are needed, largest first.
+    /// Returns None if the amount can't be paid exactly with the bills, e.g. if there is no 1 bill.
+    pub fn breakdown(&self, amount: usize) -> Option<Vec<(usize, usize)>> {
+        let mut bills = self.bills.clone();
+        bills.sort_unstable_by(|a, b| b.cmp(a));
+        // taking the largest bill first results in the fewest bills for every set of bills banks actually use
+        let mut rest = amount * self.denomination;
+        let mut ret = vec![];
+        for bill in bills.into_iter().filter(|bill| *bill > 0) {
+            if rest >= bill {
+                ret.push((bill, rest / bill));
+                rest %= bill;
+            }
+        }
+        if rest == 0 { Some(ret) } else { None }
+    }
+
+    /// The smallest single bill which covers the amount along with the change that's given back,
+    /// None if the amount is a bill itself or larger than every bill.
+    pub fn change(&self, amount: usize) -> Option<(usize, Vec<(usize, usize)>)> {
+        let shown = amount * self.denomination;
+        if self.bills.contains(&shown) {
+            return None;
+        }
+        let bill = self.bills.iter().copied().filter(|bill| *bill > shown).min()?;
+        // the change is broken down like any other amount, so it has to be converted back
+        if (bill - shown) % self.denomination != 0 {
+            return None;
+        }
+        let change = self.breakdown((bill - shown) / self.denomination)?;
+        Some((bill, change))
+    }
+
+}
+
+impl Default for CurrencyRules {
+    fn default() -> Self {
+        Self {
+            symbol: "$".to_string(),
+            symbol_after: None,
+            thousands_separator: None,
+            denomination: 1,
+            decimals: 0,
+            bills: vec![500, 100, 50, 20, 10, 5, 1],
+        }
+    }
+}
+
+// players landing on an income tax tile choose between its flat amount and a share of their net worth
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct IncomeTaxRules {
+    pub percentage: usize,
+}
+
+impl Default for IncomeTaxRules {
+    fn default() -> Self {
+        Self {
+            percentage: 10,
+        }
+    }
+}
+
+// the bank only has a limited amount of buildings, if it runs short they get auctioned off
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct BuildingRules {
+    pub houses: usize,
+    pub hotels: usize,
+}
+
+impl Default for BuildingRules {
+    fn default() -> Self {
+        Self {
+            houses: 32,
+            hotels: 12,
+        }
+    }
+}
+
+// money which is paid to the bank is collected in a pot, whoever lands on the parking tile gets it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FreeParkingRules {
+    pub enabled: bool,
+    // the amount the bank puts into the pot at the start of the game and whenever it was emptied
+    pub seed: usize,
+    // whether the fines of action cards go into the pot
+    pub collect_card_fines: bool,
+    // whether the amounts paid on tax tiles go into the pot
+    pub collect_taxes: bool,
+}
+
+impl Default for FreeParkingRules {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seed: 0,
+            collect_card_fines: true,
+            collect_taxes: true,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use crate::action_cards::{ActionCard, CardStack};
+use crate::board::{Board, BoardIndex, Tile};
+use crate::dice::Dice;
+use crate::engine::{Auction, Engine, Phase, RentImmunity};
+use crate::events::Trade;
+use crate::player::Player;
+use crate::property::{DefinedProperty, PROPERTIES};
+use crate::rules::Rules;
+use crate::utils;
+
+const SAVE_FILE: &str = "savegame.json";
+
+// the game is saved at the start of every turn, so it can be continued after the window was closed
+pub fn path() -> PathBuf {
+    utils::config_path(SAVE_FILE)
+}
+
+/// Everything needed to continue a game. The board, cards and rules are saved along with the
+/// game's state, as they might've been changed by the setup or the config since the game started.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    tiles: Vec<Tile>,
+    properties: Vec<DefinedProperty>,
+    players: Vec<Player>,
+    cards: Vec<ActionCard>,
+    card_stacks: [CardStack; 2],
+    curr_player: usize,
+    phase: Phase,
+    pending_trade: Option<Trade>,
+    auction: Option<Auction>,
+    pending_auctions: Vec<usize>,
+    immunities: Vec<RentImmunity>,
+    rules: Rules,
+    tile_scripts: HashMap<String, String>,
+    parking_pot: usize,
+    turns: usize,
+    rounds: usize,
+    dice: Dice,
+    seed: u64,
+    // the state of the engine's rng can't be saved, so the restored game's rng is seeded from it instead
+    rng_seed: u64,
+}
+
+impl SaveGame {
+
+    pub fn new(engine: &Engine) -> Self {
+        Self {
+            tiles: engine.board.tiles.to_vec(),
+            properties: engine.properties.to_vec(),
+            players: engine.players.clone(),
+            cards: engine.cards.clone(),
+            card_stacks: engine.card_stacks.clone(),
+            curr_player: engine.curr_player,
+            phase: engine.phase,
+            pending_trade: engine.pending_trade.clone(),
+            auction: engine.auction.clone(),
+            pending_auctions: engine.pending_auctions.clone(),
+            immunities: engine.immunities.clone(),
+            rules: engine.rules.clone(),
+            tile_scripts: engine.tile_scripts.clone(),
+            parking_pot: engine.parking_pot,
+            turns: engine.turns,
+            rounds: engine.rounds,
+            dice: engine.dice.clone(),
+            seed: engine.seed,
+            // the running game's rng is left untouched, so saving doesn't change its outcome
+            rng_seed: engine.rng.clone().gen(),
+        }
+    }
+
+    // returns None if the save doesn't fit the size of the board
+    pub fn restore(self) -> Option<Engine> {
+        // the engine expects exactly `PROPERTIES` property tiles, so this has to be checked before it's created
+        let property_tiles = self.tiles.iter().filter(|tile| matches!(tile, Tile::Property { .. })).count();
+        if property_tiles != PROPERTIES || self.properties.len() != PROPERTIES {
+            return None;
+        }
+        let tiles = self.tiles.try_into().ok()?;
+        let board = Board {
+            index: BoardIndex::new(&tiles),
+            tiles,
+        };
+        let mut engine = Engine::new(board, self.cards, self.rules, Some(self.seed));
+        engine.properties = self.properties.try_into().ok()?;
+        engine.players = self.players;
+        engine.card_stacks = self.card_stacks;
+        engine.curr_player = self.curr_player;
+        engine.phase = self.phase;
+        engine.pending_trade = self.pending_trade;
+        engine.auction = self.auction;
+        engine.pending_auctions = self.pending_auctions;
+        engine.immunities = self.immunities;
+        engine.tile_scripts = self.tile_scripts;
+        engine.parking_pot = self.parking_pot;
+        engine.turns = self.turns;
+        engine.rounds = self.rounds;
+        engine.dice = self.dice;
+        engine.rng = StdRng::seed_from_u64(self.rng_seed);
+        engine.update_decision_timer();
+        Some(engine)
+    }
+
+}
+
+pub fn save(engine: &Engine, path: &Path) -> io::Result<()> {
+    fs::write(path, serde_json::to_string(&SaveGame::new(engine)).unwrap())
+}
+
+pub fn load(path: &Path) -> io::Result<Engine> {
+    let buf = fs::read_to_string(path)?;
+    let save: SaveGame = serde_json::from_str(&buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    save.restore().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "the saved board has the wrong size"))
+}
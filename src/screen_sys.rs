@@ -12,21 +12,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::render::{Model, Renderer};
+use crate::render::{ColorSource, Model, Renderer, Vertex};
 use crate::screen_sys::ScreenType::Other;
-use crate::ui::{ClickKind, Container};
-use std::sync::atomic::{AtomicIsize, Ordering};
+use crate::tween::{Easing, Tween};
+use crate::ui::{ClickKind, Color, Container};
+use std::any::{Any, TypeId};
+use std::sync::atomic::{AtomicBool, AtomicIsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
 use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+use instant::Instant;
 use winit::dpi::{PhysicalPosition, Position};
-use winit::event::VirtualKeyCode;
-use winit::window::Window;
+use winit::event::{ModifiersState, MouseButton, VirtualKeyCode};
+use winit::window::{CursorIcon, Window};
 use crate::Game;
+use crate::utils::{DARK_GRAY_UI, LIGHT_GRAY_UI};
 
-pub trait Screen: Send + Sync {
+pub trait Screen: Any + Send + Sync {
     // Called once
     fn init(&mut self, _game: &Arc<Game>) {}
     fn deinit(&mut self, _game: &Arc<Game>) {}
 
+    // Heavy work (e.g. loading and decoding assets) which gets executed on a worker thread after `init`,
+    // a loading indicator is shown instead of the screen until the result was passed to `on_loaded`.
+    // If the work fails the screen is popped again, along with every screen above it
+    fn load_async(&self, _game: &Arc<Game>) -> Option<AsyncLoad> {
+        None
+    }
+
+    fn on_loaded(&mut self, _game: &Arc<Game>, _data: Box<dyn Any + Send>) {}
+
     // May be called multiple times
     fn on_active(&mut self, _game: &Arc<Game>);
     fn on_deactive(&mut self, _game: &Arc<Game>);
@@ -34,18 +50,46 @@ pub trait Screen: Send + Sync {
     // Called every frame the screen is active
     fn tick(&mut self, _game: &Arc<Game>);
 
+    // Whether the screen changes on its own and has to be redrawn every frame while it's active,
+    // screens which only change in response to input or game events don't have to override this
+    fn is_animating(&self) -> bool {
+        false
+    }
+
+    // Called at a fixed rate while the screen is active, use this for game logic and
+    // timers that have to behave the same regardless of the frame rate
+    fn fixed_tick(&mut self, _game: &Arc<Game>) {}
+
     // Events
     fn on_scroll(&mut self, _x: f64, _y: f64) {}
 
-    fn on_resize(&mut self, _game: &Arc<Game>) {} // TODO: make non-optional!
+    // Called when the window's size or scale factor changed while the screen was visible,
+    // screens with aspect ratio dependent layouts should rebuild them here
+    fn on_resize(&mut self, game: &Arc<Game>, width: u32, height: u32) {
+        self.container().relayout(game, width, height);
+    }
+
+    // Called after the language was switched, screens which show text have to rebuild it
+    fn on_language_changed(&mut self, _game: &Arc<Game>) {}
 
-    fn on_key_press(&mut self, screen_sys: &Arc<ScreenSystem>, key: VirtualKeyCode, down: bool) {
-        if key == VirtualKeyCode::Escape && !down && self.is_closable() {
-            screen_sys.pop_screen();
+    // Returns whether the key was consumed, unconsumed keys are passed on to the camera
+    fn on_key(&mut self, game: &Arc<Game>, key: VirtualKeyCode, down: bool, modifiers: ModifiersState) -> bool {
+        if self.container().on_key(game, key, down, modifiers) {
+            return true;
         }
+        if is_back_key(key) && !down {
+            return game.screen_sys.go_back();
+        }
+        false
     }
 
-    fn on_char_receive(&mut self, _received: char) {}
+    fn on_char(&mut self, game: &Arc<Game>, received: char) -> bool {
+        self.container().on_char(game, received)
+    }
+
+    // Called when a click missed all of the components, so it was meant for the scene behind the ui,
+    // the position is relative to the ui like the positions of components
+    fn on_scene_click(&mut self, _game: &Arc<Game>, _pos: (f32, f32)) {}
 
     fn is_closable(&self) -> bool {
         false
@@ -55,10 +99,32 @@ pub trait Screen: Send + Sync {
         false
     }
 
+    // Overlays are rendered on top of the screens beneath them instead of replacing them,
+    // clicks which miss all of an overlay's components are passed on to the screen beneath it
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    // Overlays with a backdrop dim everything beneath them with a full-screen quad
+    // of this color and swallow all clicks, making them modal
+    fn backdrop(&self) -> Option<Color> {
+        None
+    }
+
+    // Whether the music gets ducked while this screen is the topmost one
+    fn ducks_audio(&self) -> bool {
+        self.backdrop().is_some()
+    }
+
     fn ty(&self) -> ScreenType {
         Other(String::new())
     }
 
+    // The transition used when this screen gets pushed or popped
+    fn transition(&self) -> Transition {
+        Transition::Fade
+    }
+
     fn container(&self) -> &Arc<Container>;
 
     fn clone_screen(&self) -> Box<dyn Screen>;
@@ -77,12 +143,169 @@ pub enum ScreenType {
     InGame,
 }
 
-#[derive(Clone)]
+pub fn is_back_key(key: VirtualKeyCode) -> bool {
+    matches!(key, VirtualKeyCode::Escape | VirtualKeyCode::NavigateBackward | VirtualKeyCode::WebBack)
+}
+
+// the "back" side buttons found on many mice, their numbering differs between platforms
+pub fn is_back_button(button: MouseButton) -> bool {
+    matches!(button, MouseButton::Other(1) | MouseButton::Other(8))
+}
+
+const TRANSITION_DURATION: Duration = Duration::from_millis(250);
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Transition {
+    None,
+    Fade,
+    // slides in from and out to the given edge
+    Slide(Edge),
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Transition {
+
+    // visibility is 0.0 for a completely hidden screen and 1.0 for a completely visible one,
+    // the ui's dimensions are needed to move text, which is positioned in pixels
+    fn apply(self, models: &mut [Model], visibility: f32, (width, height): (u32, u32)) {
+        match self {
+            Transition::None => {}
+            Transition::Fade => {
+                for model in models.iter_mut() {
+                    for vertex in model.vertices.iter_mut() {
+                        match vertex {
+                            Vertex::Color { color, .. } => color[3] *= visibility,
+                            Vertex::Texture { alpha, .. } => *alpha *= visibility,
+                        }
+                    }
+                    if let ColorSource::Text(sections) = &mut model.color_src {
+                        for text in sections.iter_mut().flat_map(|(_, section)| section.text.iter_mut()) {
+                            text.extra.color[3] *= visibility;
+                        }
+                    }
+                }
+            }
+            Transition::Slide(edge) => {
+                // clip space spans 2 units in each direction
+                let offset = (1.0 - visibility) * 2.0;
+                let (x_offset, y_offset) = match edge {
+                    Edge::Left => (-offset, 0.0),
+                    Edge::Right => (offset, 0.0),
+                    Edge::Top => (0.0, offset),
+                    Edge::Bottom => (0.0, -offset),
+                };
+                for model in models.iter_mut() {
+                    for vertex in model.vertices.iter_mut() {
+                        let pos = match vertex {
+                            Vertex::Color { pos, .. } => pos,
+                            Vertex::Texture { pos, .. } => pos,
+                        };
+                        pos[0] += x_offset;
+                        pos[1] += y_offset;
+                    }
+                    // clip rects are relative to the screen's dimensions, so they only span 1 unit
+                    if let Some(clip) = model.clip.as_mut() {
+                        clip.pos.0 += x_offset / 2.0;
+                        clip.pos.1 += y_offset / 2.0;
+                    }
+                    // the y axis of the text's pixels points downwards
+                    if let ColorSource::Text(sections) = &mut model.color_src {
+                        for (_, section) in sections.iter_mut() {
+                            section.screen_position.0 += x_offset / 2.0 * width as f32;
+                            section.screen_position.1 -= y_offset / 2.0 * height as f32;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+}
+
+fn backdrop_model(color: Color) -> Model {
+    rect_model((0.0, 0.0), (1.0, 1.0), color)
+}
+
+// pos and dims are relative to the screen's dimensions
+fn rect_model(pos: (f32, f32), dims: (f32, f32), color: Color) -> Model {
+    let color = color.into_array();
+    let (x, y) = (pos.0 * 2.0 - 1.0, pos.1 * 2.0 - 1.0);
+    let (width, height) = (dims.0 * 2.0, dims.1 * 2.0);
+    let vertices = [
+        [x, y],
+        [x + width, y],
+        [x + width, y + height],
+        [x, y + height],
+    ];
+    Model {
+        vertices: vertices.into_iter().map(|pos| Vertex::Color { pos, color }).collect(),
+        color_src: ColorSource::PerVert,
+        clip: None,
+    }
+}
+
+struct OutgoingScreen {
+    transition: Transition,
+    tween: Tween,
+    models: Vec<Model>,
+}
+
+pub type AsyncLoad = Box<dyn FnOnce(&Arc<Game>) -> Result<Box<dyn Any + Send>, String> + Send>;
+
+struct Loading {
+    result: Receiver<Result<Box<dyn Any + Send>, String>>,
+    since: Instant,
+}
+
+impl Loading {
+
+    fn start(game: &Arc<Game>, load: AsyncLoad) -> Self {
+        let (sender, result) = channel();
+        let game = game.clone();
+        thread::spawn(move || {
+            // the screen may have been popped already, so the result can be discarded
+            let _ = sender.send(load(&game));
+        });
+        Self {
+            result,
+            since: Instant::now(),
+        }
+    }
+
+}
+
+const LOADING_BAR_WIDTH: f32 = 0.2;
+const LOADING_BAR_HEIGHT: f32 = 0.015;
+const LOADING_BLOCK_WIDTH: f32 = 0.05;
+const LOADING_CYCLE: Duration = Duration::from_millis(1200);
+
+// a small bar at the bottom of the screen with a block bouncing back and forth inside of it
+fn loading_indicator_models(since: Instant) -> Vec<Model> {
+    let cycle = (since.elapsed().as_secs_f32() / LOADING_CYCLE.as_secs_f32()) % 1.0;
+    // move to the right during the first half of the cycle and back during the second one
+    let progress = Easing::EaseInOut.apply(1.0 - (cycle * 2.0 - 1.0).abs());
+    let bar_x = 0.5 - LOADING_BAR_WIDTH / 2.0;
+    let bar_y = 0.1;
+    let block_x = bar_x + (LOADING_BAR_WIDTH - LOADING_BLOCK_WIDTH) * progress;
+    vec![
+        rect_model((bar_x, bar_y), (LOADING_BAR_WIDTH, LOADING_BAR_HEIGHT), DARK_GRAY_UI),
+        rect_model((block_x, bar_y), (LOADING_BLOCK_WIDTH, LOADING_BAR_HEIGHT), LIGHT_GRAY_UI),
+    ]
+}
+
 struct ScreenInfo {
     screen: Arc<Mutex<Box<dyn Screen>>>,
     active: bool,
     last_width: i32,
     last_height: i32,
+    loading: Option<Loading>,
 }
 
 #[derive(Default)]
@@ -90,6 +313,10 @@ pub struct ScreenSystem {
     screens: Arc<RwLock<Vec<ScreenInfo>>>,
     pre_computed_screens: Arc<RwLock<Vec<Box<dyn Screen>>>>,
     lowest_offset: AtomicIsize,
+    ducked: AtomicBool,
+    transition_in: Mutex<Option<(Transition, Tween)>>,
+    outgoing: Mutex<Option<OutgoingScreen>>,
+    language_changed: AtomicBool,
 }
 
 impl ScreenSystem {
@@ -143,6 +370,41 @@ impl ScreenSystem {
         self.push_screen(screen);
     }
 
+    // pops screens until the topmost screen is of type T, returns false and leaves the
+    // stack untouched if there is no screen of type T on it
+    pub fn pop_to<T: Screen>(&self) -> bool {
+        let pos = self.pre_computed_screens
+            .read()
+            .unwrap()
+            .iter()
+            .rposition(|screen| (**screen).type_id() == TypeId::of::<T>());
+        match pos {
+            Some(pos) => {
+                let len = self.pre_computed_screens.read().unwrap().len();
+                for _ in (pos + 1)..len {
+                    self.pop_screen();
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn close_all_overlays(&self) {
+        while self.is_current_overlay() {
+            self.pop_screen();
+        }
+    }
+
+    // pops the topmost screen if it can be closed, returns whether it got popped
+    pub fn go_back(&self) -> bool {
+        if self.is_current_closable() {
+            self.pop_screen();
+            return true;
+        }
+        false
+    }
+
     pub fn is_current_closable(&self) -> bool {
         if let Some(last) = self.pre_computed_screens.clone().read().unwrap().last() {
             return last.is_closable();
@@ -150,6 +412,13 @@ impl ScreenSystem {
         false
     }
 
+    pub fn is_current_overlay(&self) -> bool {
+        if let Some(last) = self.pre_computed_screens.clone().read().unwrap().last() {
+            return last.is_overlay();
+        }
+        false
+    }
+
     pub fn is_current_ingame(&self) -> bool {
         if let Some(last) = self.pre_computed_screens.clone().read().unwrap().last() {
             return last.ty() == ScreenType::InGame;
@@ -157,6 +426,31 @@ impl ScreenSystem {
         false
     }
 
+    // whether the top screen is shown, which isn't the case until it finished loading
+    pub fn is_current_ready(&self) -> bool {
+        if let Some(last) = self.screens.read().unwrap().last() {
+            return last.active && last.loading.is_none();
+        }
+        false
+    }
+
+    // the screens get notified during the next tick, as the screen which switched the language may still be locked
+    pub fn language_changed(&self) {
+        self.language_changed.store(true, Ordering::Release);
+    }
+
+    // whether the screens have to be redrawn every frame, because they're loading or transitioning
+    pub fn is_animating(&self) -> bool {
+        if self.lowest_offset.load(Ordering::Acquire) != -1
+            || self.transition_in.lock().unwrap().is_some()
+            || self.outgoing.lock().unwrap().is_some() {
+            return true;
+        }
+        let screens = self.screens.read().unwrap();
+        screens.iter().any(|screen| screen.loading.is_some())
+            || screens.last().map_or(false, |screen| screen.screen.lock().unwrap().is_animating())
+    }
+
     pub fn is_any_ingame(&self) -> bool {
         for screen in self
             .pre_computed_screens
@@ -180,47 +474,60 @@ impl ScreenSystem {
         Other(String::new())
     }
 
-    pub fn receive_char(&self, received: char) {
+    pub fn receive_char(&self, game: &Arc<Game>, received: char) -> bool {
         if let Some(screen) = self.screens.clone().read().unwrap().last() {
-            screen
+            return screen
                 .screen
                 .clone()
                 .lock()
                 .unwrap()
-                .on_char_receive(received);
+                .on_char(game, received);
         }
+        false
     }
 
-    pub fn press_key(self: &Arc<Self>, key: VirtualKeyCode, down: bool) {
+    pub fn press_key(&self, game: &Arc<Game>, key: VirtualKeyCode, down: bool, modifiers: ModifiersState) -> bool {
         if let Some(screen) = self.screens.clone().read().unwrap().last() {
-            screen
+            return screen
                 .screen
                 .clone()
                 .lock()
                 .unwrap()
-                .on_key_press(self, key, down);
+                .on_key(game, key, down, modifiers);
         }
+        false
     }
 
-    pub fn on_mouse_click(&self, game: &Arc<Game>, pos: (f64, f64), click_kind: ClickKind) {
-        if let Some(screen) = self.screens.clone().read().unwrap().last() {
-            screen
-                .screen
-                .clone()
-                .lock()
-                .unwrap()
-                .container().on_mouse_click(game, pos, click_kind);
+    // returns whether the click was consumed by any screen
+    pub fn on_mouse_click(&self, game: &Arc<Game>, pos: (f64, f64), click_kind: ClickKind) -> bool {
+        for screen in self.screens.clone().read().unwrap().iter().rev() {
+            let mut screen = screen.screen.lock().unwrap();
+            if screen.container().on_mouse_click(game, pos, click_kind) {
+                return true;
+            }
+            if screen.backdrop().is_some() {
+                return true;
+            }
+            if !screen.is_overlay() {
+                if click_kind == ClickKind::Release {
+                    screen.on_scene_click(game, (pos.0 as f32, pos.1 as f32));
+                }
+                return false;
+            }
         }
+        false
     }
 
-    pub fn on_mouse_hover(&self, game: &Arc<Game>, pos: (f64, f64)) {
-        if let Some(screen) = self.screens.clone().read().unwrap().last() {
-            screen
+    // returns the cursor which should be shown at the position
+    pub fn on_mouse_hover(&self, game: &Arc<Game>, pos: (f64, f64)) -> CursorIcon {
+        match self.screens.clone().read().unwrap().last() {
+            Some(screen) => screen
                 .screen
                 .clone()
                 .lock()
                 .unwrap()
-                .container().on_mouse_hover(game, pos);
+                .container().on_mouse_hover(game, pos),
+            None => CursorIcon::Default,
         }
     }
 
@@ -230,6 +537,11 @@ impl ScreenSystem {
         game: &Arc<Game>,
         window: &Window,
     ) -> Vec<Model> {
+        if self.language_changed.swap(false, Ordering::AcqRel) {
+            for screen in self.screens.read().unwrap().iter() {
+                screen.screen.lock().unwrap().on_language_changed(game);
+            }
+        }
         let lowest = self.lowest_offset.load(Ordering::Acquire);
         if lowest != -1 {
             let screens_len = self.screens.read().unwrap().len();
@@ -248,8 +560,17 @@ impl ScreenSystem {
                 false
             };
             if lowest <= screens_len as isize {
-                for _ in 0..(screens_len as isize - lowest) {
+                for i in 0..(screens_len as isize - lowest) {
                     let screen = self.screens.clone().write().unwrap().pop().unwrap();
+                    if i == 0 {
+                        // keep the topmost screen around until it has transitioned out
+                        let inner_screen = screen.screen.lock().unwrap();
+                        *self.outgoing.lock().unwrap() = Some(OutgoingScreen {
+                            transition: inner_screen.transition(),
+                            tween: Tween::new(TRANSITION_DURATION, Easing::EaseIn),
+                            models: inner_screen.container().build_models(game),
+                        });
+                    }
                     if screen.active {
                         screen
                             .screen
@@ -280,6 +601,7 @@ impl ScreenSystem {
                     active: false,
                     last_width: -1,
                     last_height: -1,
+                    loading: None,
                 });
                 let last = screens.get_mut(idx);
                 if let Some(last) = last {
@@ -296,8 +618,17 @@ impl ScreenSystem {
                 let curr_screen = current.screen.clone();
                 let mut curr_screen = curr_screen.lock().unwrap();
                 curr_screen.init(game);
+                // screens which are still loading get activated once they finished loading
+                if let Some(load) = curr_screen.load_async(game) {
+                    current.loading = Some(Loading::start(game, load));
+                    continue;
+                }
                 current.active = true;
                 curr_screen.on_active(game);
+                *self.transition_in.lock().unwrap() = Some((
+                    curr_screen.transition(),
+                    Tween::new(TRANSITION_DURATION, Easing::EaseOut),
+                ));
             }
             self.lowest_offset.store(-1, Ordering::Release);
             if !was_closable {
@@ -311,13 +642,52 @@ impl ScreenSystem {
 
         let len = self.screens.clone().read().unwrap().len();
         if len == 0 {
-            return vec![];
+            if self.ducked.swap(false, Ordering::AcqRel) {
+                game.audio.set_ducked(false);
+            }
+            return self.outgoing_models(game);
         }
         // Update state for screens
         let tmp = self.screens.clone();
         let mut tmp = tmp.write().unwrap();
+        let mut failed = None;
+        for (idx, screen) in tmp.iter_mut().enumerate() {
+            let data = match screen.loading.as_ref().map(|loading| loading.result.try_recv()) {
+                Some(Ok(Ok(data))) => data,
+                Some(Ok(Err(err))) => {
+                    println!("Couldn't load the screen: {}", err);
+                    failed.get_or_insert(idx);
+                    continue;
+                }
+                // the worker panicked before it could report back
+                Some(Err(TryRecvError::Disconnected)) => {
+                    println!("The screen's loader crashed");
+                    failed.get_or_insert(idx);
+                    continue;
+                }
+                _ => continue,
+            };
+            screen.loading = None;
+            let mut inner_screen = screen.screen.lock().unwrap();
+            inner_screen.on_loaded(game, data);
+            *self.transition_in.lock().unwrap() = Some((
+                inner_screen.transition(),
+                Tween::new(TRANSITION_DURATION, Easing::EaseOut),
+            ));
+        }
+        // the screen can't be shown without its data, the pops are applied at the start of the next frame
+        if let Some(idx) = failed {
+            for _ in idx..tmp.len() {
+                self.pop_screen();
+            }
+            return self.outgoing_models(game);
+        }
+        let ducked = tmp.last().unwrap().screen.lock().unwrap().ducks_audio();
+        if self.ducked.swap(ducked, Ordering::AcqRel) != ducked {
+            game.audio.set_ducked(ducked);
+        }
         let current = tmp.last_mut().unwrap();
-        if !current.active {
+        if !current.active && current.loading.is_none() {
             current.active = true;
             current
                 .screen
@@ -326,16 +696,21 @@ impl ScreenSystem {
                 .unwrap()
                 .on_active(game);
         }
+        // the lowest screen that is still visible through all the overlays above it
+        let lowest_visible = tmp
+            .iter()
+            .rposition(|screen| !screen.screen.lock().unwrap().is_overlay())
+            .unwrap_or(0);
+        let current = tmp.last_mut().unwrap();
         let (width, height) = game.renderer.dimensions.get();
         if current.last_width != width as i32 || current.last_height != height as i32 {
             if current.last_width != -1 && current.last_height != -1 {
                 for screen in tmp.iter_mut().enumerate() {
                     let inner_screen = screen.1.screen.clone();
                     let mut inner_screen = inner_screen.lock().unwrap();
-                    if inner_screen.is_tick_always() || screen.0 == len - 1 {
-                        inner_screen.on_resize(game);
+                    if inner_screen.is_tick_always() || screen.0 >= lowest_visible {
+                        inner_screen.on_resize(game, width, height);
                         drop(inner_screen);
-                        let (width, height) = game.renderer.dimensions.get();
                         screen.1.last_width = width as i32;
                         screen.1.last_height = height as i32;
                     }
@@ -348,17 +723,69 @@ impl ScreenSystem {
         }
         let mut models = vec![];
         for screen in tmp.iter_mut().enumerate() {
+            if let Some(loading) = screen.1.loading.as_ref() {
+                if screen.0 == len - 1 {
+                    models.append(&mut loading_indicator_models(loading.since));
+                }
+                continue;
+            }
             let inner_screen = screen.1.screen.clone();
             let mut inner_screen = inner_screen.lock().unwrap();
-            if inner_screen.is_tick_always() || screen.0 == len - 1 {
+            let ticking = inner_screen.is_tick_always() || screen.0 == len - 1;
+            if ticking {
                 inner_screen.tick(game);
-                let mut screen_models = inner_screen.container().build_models(game);
+                inner_screen.container().tick(game);
+            }
+            if ticking || screen.0 >= lowest_visible {
+                let mut screen_models = vec![];
+                if let Some(backdrop) = inner_screen.backdrop() {
+                    screen_models.push(backdrop_model(backdrop));
+                }
+                screen_models.append(&mut inner_screen.container().build_models(game));
+                if screen.0 == len - 1 {
+                    let mut transition_in = self.transition_in.lock().unwrap();
+                    if let Some((transition, tween)) = transition_in.as_ref() {
+                        transition.apply(&mut screen_models, tween.progress(), game.renderer.ui_dimensions());
+                        if tween.is_finished() {
+                            *transition_in = None;
+                        }
+                    }
+                }
                 models.append(&mut screen_models);
             }
         }
+        models.append(&mut self.outgoing_models(game));
         models
     }
 
+    fn outgoing_models(&self, game: &Arc<Game>) -> Vec<Model> {
+        let mut outgoing = self.outgoing.lock().unwrap();
+        if let Some(screen) = outgoing.as_ref() {
+            if screen.tween.is_finished() {
+                *outgoing = None;
+                return vec![];
+            }
+            let mut models = screen.models.clone();
+            screen.transition.apply(&mut models, 1.0 - screen.tween.progress(), game.renderer.ui_dimensions());
+            return models;
+        }
+        vec![]
+    }
+
+    pub fn fixed_tick(&self, game: &Arc<Game>) {
+        let screens = self.screens.read().unwrap();
+        let len = screens.len();
+        for (idx, screen) in screens.iter().enumerate() {
+            if screen.loading.is_some() {
+                continue;
+            }
+            let mut inner_screen = screen.screen.lock().unwrap();
+            if inner_screen.is_tick_always() || idx == len - 1 {
+                inner_screen.fixed_tick(game);
+            }
+        }
+    }
+
     pub fn on_scroll(&self, x: f64, y: f64) {
         if let Some(screen) = self.screens.clone().read().unwrap().last() {
             screen.screen.clone().lock().unwrap().on_scroll(x, y);
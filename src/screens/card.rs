@@ -0,0 +1,191 @@
+use crate::screen_sys::Screen;
+use crate::ui::{ClickKind, Color, Coloring, Component, Container, HoverMode, TextBox, TextSection, TextStyle, Transform};
+use crate::{Game, i18n};
+use crate::render::Model;
+use crate::tween::{Easing, Tween};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use winit::window::CursorIcon;
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI, LIGHT_GRAY_UI};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+const BLACK: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+const FLIP_DURATION: Duration = Duration::from_millis(700);
+const CARD_POS: (f32, f32) = (0.35, 0.25);
+const CARD_DIMS: (f32, f32) = (0.3, 0.5);
+
+// shows the card a player drew, it gets turned around from its back to its front
+#[derive(Clone)]
+pub struct CardReveal {
+    container: Arc<Container>,
+    card: usize,
+    flip: Tween,
+}
+
+impl CardReveal {
+    pub fn new(card: usize) -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+            card,
+            flip: Tween::new(FLIP_DURATION, Easing::EaseInOut),
+        }
+    }
+
+    fn build(&self, game: &Arc<Game>) {
+        let text = {
+            let engine = game.engine.lock().unwrap();
+            engine.cards[self.card].display_text(&engine.rules.currency)
+        };
+        let back = TextBox::new(
+            CARD_POS,
+            CARD_DIMS.0,
+            CARD_DIMS.1,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(i18n::tr("card.title"), TextStyle::new(34.0).with_color(WHITE)),
+        ).with_static_text();
+        let front = TextBox::new(
+            CARD_POS,
+            CARD_DIMS.0,
+            CARD_DIMS.1,
+            Coloring::Color([LIGHT_GRAY_UI; 4]),
+            TextSection::new(Layout::default_wrap().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(text, TextStyle::new(26.0).with_color(BLACK)),
+        ).with_static_text();
+        self.container.add(Arc::new(RwLock::new(Box::new(Card {
+            back,
+            front,
+            flip: self.flip,
+            revealed: false,
+        }))));
+    }
+}
+
+impl Screen for CardReveal {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.build(game);
+    }
+
+    fn on_language_changed(&mut self, game: &Arc<Game>) {
+        self.container.clear();
+        self.build(game);
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, _game: &Arc<Game>) {}
+
+    fn is_animating(&self) -> bool {
+        !self.flip.is_finished()
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
+
+// during the first half of the flip the back gets squashed, during the second half the front gets unfolded
+struct Card {
+    back: TextBox<'static>,
+    front: TextBox<'static>,
+    flip: Tween,
+    // whether the front is shown already
+    revealed: bool,
+}
+
+impl Card {
+
+    fn face(&self) -> &TextBox<'static> {
+        if self.revealed {
+            &self.front
+        } else {
+            &self.back
+        }
+    }
+
+}
+
+impl Component for Card {
+    fn build_model(&self) -> Model {
+        self.face().build_model()
+    }
+
+    fn build_text_model(&self, game: &Arc<Game>) -> Option<Model> {
+        self.face().build_text_model(game)
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        CARD_POS
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        CARD_DIMS
+    }
+
+    fn on_click(&mut self, game: &Arc<Game>, click_kind: ClickKind, _pos: (f32, f32)) {
+        if click_kind == ClickKind::Release && self.flip.is_finished() {
+            game.screen_sys.pop_screen();
+        }
+    }
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        CursorIcon::Hand
+    }
+
+    fn transform(&self) -> Transform {
+        let progress = self.flip.progress();
+        Transform {
+            // never completely 0, so positions can still be transformed back
+            flip: ((progress * 2.0 - 1.0).abs()).max(0.01),
+            ..Transform::IDENTITY
+        }
+    }
+
+    fn tick(&mut self, _game: &Arc<Game>) -> bool {
+        let revealed = self.flip.progress() >= 0.5;
+        if revealed != self.revealed {
+            self.revealed = revealed;
+            return true;
+        }
+        // the transform is applied to the cached model, so only the face switch requires a rebuild
+        false
+    }
+}
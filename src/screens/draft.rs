@@ -0,0 +1,185 @@
+use crate::screen_sys::Screen;
+use crate::ui::{Button, Color, ColorBox, Coloring, Container, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use crate::engine::{Engine, Phase};
+use crate::events::GameCommand;
+use crate::palette;
+use crate::property::{PropertyType, PROPERTIES};
+use std::sync::{Arc, RwLock};
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI, LIGHT_GRAY_UI};
+
+const BLACK: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+const PANEL_POS: (f32, f32) = (0.08, 0.06);
+const PANEL_DIMS: (f32, f32) = (0.84, 0.88);
+const TITLE_HEIGHT: f32 = 0.08;
+const PADDING: f32 = 0.01;
+const COLUMNS: usize = 4;
+const ROWS: usize = (PROPERTIES + COLUMNS - 1) / COLUMNS;
+const TEXT_SCALE: f32 = 20.0;
+
+// the picks which were made so far and who picks next, the grid gets rebuilt if any of these change
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    owners: Vec<Option<usize>>,
+    picker: usize,
+}
+
+/// Lets the players pick their properties one after another while they're drafted before the first turn,
+/// see `PropertySetup::Drafted`. The properties which were already picked are shown with their owner.
+/// It can be closed to look at the board, the draft button of the action bar opens it again.
+#[derive(Clone)]
+pub struct DraftPicker {
+    container: Arc<Container>,
+    snapshot: Option<Snapshot>,
+}
+
+impl DraftPicker {
+    pub fn new() -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+            snapshot: None,
+        }
+    }
+
+    fn snapshot(engine: &Engine) -> Snapshot {
+        Snapshot {
+            owners: engine.properties.iter().map(|property| property.owner).collect(),
+            picker: engine.curr_player,
+        }
+    }
+
+    // the top left cell is the first property
+    fn cell_pos(idx: usize) -> (f32, f32) {
+        let (width, height) = Self::cell_dims();
+        let (column, row) = (idx % COLUMNS, idx / COLUMNS);
+        (
+            PANEL_POS.0 + PADDING + column as f32 * (width + PADDING),
+            PANEL_POS.1 + PANEL_DIMS.1 - TITLE_HEIGHT - (row + 1) as f32 * (height + PADDING),
+        )
+    }
+
+    fn cell_dims() -> (f32, f32) {
+        (
+            (PANEL_DIMS.0 - PADDING * (COLUMNS + 1) as f32) / COLUMNS as f32,
+            (PANEL_DIMS.1 - TITLE_HEIGHT - PADDING * (ROWS + 1) as f32) / ROWS as f32,
+        )
+    }
+
+    fn build(&self, game: &Arc<Game>) {
+        let colorblind = game.settings.lock().unwrap().accessibility.colorblind;
+        let engine = game.engine.lock().unwrap();
+        let picker = engine.curr_player;
+        // the other players of a networked game pick on their own machines
+        let local = game.is_local(picker);
+        self.container.add(Arc::new(RwLock::new(Box::new(ColorBox {
+            pos: PANEL_POS,
+            width: PANEL_DIMS.0,
+            height: PANEL_DIMS.1,
+            coloring: Coloring::Color([DARK_GRAY_UI; 4]),
+        }))));
+        self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+            (PANEL_POS.0, PANEL_POS.1 + PANEL_DIMS.1 - TITLE_HEIGHT),
+            PANEL_DIMS.0,
+            TITLE_HEIGHT,
+            Coloring::Color([palette::player_color(picker); 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(i18n::tr_with("draft.title", &[("player", &engine.players[picker].display_name())]), TextStyle::new(30.0).with_color(BLACK).bold()),
+        ).with_static_text()))));
+        let (width, height) = Self::cell_dims();
+        for (idx, property) in engine.properties.iter().enumerate() {
+            let name = &property.frame.name;
+            let (color, details) = match property.owner {
+                Some(owner) => (palette::player_color(owner), engine.players[owner].display_name()),
+                None => (match &property.frame.ty {
+                    PropertyType::Normal { group: Some(group), .. } => palette::group_color(*group, colorblind),
+                    _ => LIGHT_GRAY_UI,
+                }, engine.rules.currency.format(property.frame.buy_price)),
+            };
+            let text = TextBox::new(
+                Self::cell_pos(idx),
+                width,
+                height,
+                Coloring::Color([color; 4]),
+                TextSection::new(Layout::default_wrap().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                    .with_run(format!("{}\n", name), TextStyle::new(TEXT_SCALE).with_color(BLACK).bold())
+                    .with_run(details, TextStyle::new(TEXT_SCALE).with_color(BLACK)),
+            ).with_static_text();
+            if property.owner.is_some() || !local {
+                self.container.add(Arc::new(RwLock::new(Box::new(text))));
+                continue;
+            }
+            // the screen stays open, it gets rebuilt once the pick went through
+            self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+                text,
+                Arc::new(Box::new(move |_button: &mut Button<'_, ()>, game| {
+                    game.submit(GameCommand::DraftProperty {
+                        player: picker,
+                        property: idx,
+                    });
+                })),
+                None,
+            )))));
+        }
+    }
+}
+
+impl Screen for DraftPicker {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.snapshot = Some(Self::snapshot(&game.engine.lock().unwrap()));
+        self.build(game);
+    }
+
+    fn on_language_changed(&mut self, game: &Arc<Game>) {
+        self.container.clear();
+        self.build(game);
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, game: &Arc<Game>) {
+        let snapshot = {
+            let engine = game.engine.lock().unwrap();
+            if engine.phase != Phase::Drafting {
+                drop(engine);
+                game.screen_sys.pop_screen();
+                return;
+            }
+            Self::snapshot(&engine)
+        };
+        if self.snapshot.as_ref() != Some(&snapshot) {
+            self.snapshot = Some(snapshot);
+            self.container.clear();
+            self.build(game);
+        }
+    }
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
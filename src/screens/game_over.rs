@@ -0,0 +1,194 @@
+use crate::screen_sys::Screen;
+use crate::screens::in_game::InGame;
+use crate::screens::login::Login;
+use crate::ui::{Button, Color, Coloring, Container, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use crate::events::{GameCommand, GameEvent};
+use crate::palette;
+use crate::statistics;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::mpsc::Receiver;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+// the left edge and the width of every column of the results table
+const COLUMNS: [(f32, f32); 5] = [(0.1, 0.26), (0.36, 0.14), (0.5, 0.14), (0.64, 0.13), (0.77, 0.13)];
+const TABLE_TOP: f32 = 0.74;
+const ROW_HEIGHT: f32 = 0.06;
+const TEXT_SCALE: f32 = 24.0;
+
+// shows the players ranked by their net worth along with a few statistics once the game is over
+#[derive(Clone)]
+pub struct GameOver {
+    container: Arc<Container>,
+    events: Option<Arc<Mutex<Receiver<GameEvent>>>>,
+}
+
+impl GameOver {
+    pub fn new() -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+            events: None,
+        }
+    }
+
+    fn text(pos: (f32, f32), width: f32, text: &str, style: TextStyle) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            width,
+            ROW_HEIGHT,
+            Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                .with_run(text, style),
+        ).with_static_text()
+    }
+
+    fn button(pos: (f32, f32), label: &str) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            0.25,
+            0.08,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(label, TextStyle::new(28.0).with_color(WHITE)),
+        ).with_static_text()
+    }
+
+    fn add_row(&self, y: f32, cells: [String; 5], style: TextStyle) {
+        for ((x, width), cell) in COLUMNS.into_iter().zip(cells) {
+            self.container.add(Arc::new(RwLock::new(Box::new(Self::text((x, y), width, &cell, style)))));
+        }
+    }
+
+    fn build(&self, game: &Arc<Game>) {
+        let players = game.engine.lock().unwrap().players.len();
+        let statistics = statistics::player_statistics(&game.event_log.lock().unwrap(), players);
+        let engine = game.engine.lock().unwrap();
+        let title = match engine.winner() {
+            Some(winner) => i18n::tr_with("game_over.winner", &[("player", &engine.players[winner].display_name())]),
+            None => i18n::tr("game_over.title"),
+        };
+        self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+            (0.1, 0.84),
+            0.8,
+            0.1,
+            Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(&title, TextStyle::new(40.0).with_color(WHITE).bold()),
+        ).with_static_text()))));
+        self.add_row(TABLE_TOP, [
+            i18n::tr("game_over.player"),
+            i18n::tr("game_over.net_worth"),
+            i18n::tr("game_over.rent_collected"),
+            i18n::tr("game_over.times_jailed"),
+            i18n::tr("game_over.properties_bought"),
+        ], TextStyle::new(TEXT_SCALE).with_color(WHITE).bold());
+        for (place, player) in engine.ranking().into_iter().enumerate() {
+            let net_worth = if engine.players[player].bankrupt {
+                i18n::tr("hud.bankrupt")
+            } else {
+                engine.rules.currency.format(engine.net_worth(player))
+            };
+            self.add_row(TABLE_TOP - (place + 1) as f32 * ROW_HEIGHT, [
+                format!("{}. {}", place + 1, engine.players[player].display_name()),
+                net_worth,
+                engine.rules.currency.format(statistics[player].rent_collected),
+                statistics[player].times_jailed.to_string(),
+                statistics[player].properties_bought.to_string(),
+            ], TextStyle::new(TEXT_SCALE).with_color(palette::player_color(player)));
+        }
+        // the daily challenge can only be played once and the server decides when networked games start
+        if game.daily.is_none() && !game.is_networked() {
+            self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+                Self::button((0.2, 0.1), &i18n::tr("game_over.rematch")),
+                Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                    let characters = {
+                        let mut engine = game.engine.lock().unwrap();
+                        let characters = engine.players.iter().map(|player| player.character_id).collect::<Vec<_>>();
+                        // the rematch gets a seed of its own, so it can be reproduced from its bug reports
+                        *engine = engine.rematch();
+                        characters
+                    };
+                    for character in characters {
+                        game.submit(GameCommand::AddPlayer {
+                            character,
+                        });
+                    }
+                    // the board is shown once the engine actually started the new game
+                    game.submit(GameCommand::StartGame);
+                })),
+                None,
+            )))));
+        }
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.55, 0.1), &i18n::tr("game_over.back")),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.submit(GameCommand::LeaveGame);
+                game.screen_sys.pop_to::<Login>();
+            })),
+            None,
+        )))));
+    }
+}
+
+impl Screen for GameOver {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.events = Some(Arc::new(Mutex::new(game.subscribe())));
+        self.build(game);
+    }
+
+    fn on_language_changed(&mut self, game: &Arc<Game>) {
+        self.container.clear();
+        self.build(game);
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, game: &Arc<Game>) {
+        let events = match self.events.as_ref() {
+            Some(events) => events.clone(),
+            None => return,
+        };
+        for event in events.lock().unwrap().try_iter() {
+            match event {
+                GameEvent::GameStarted => {
+                    game.screen_sys.pop_to::<Login>();
+                    game.screen_sys.push_screen(Box::new(InGame::new()));
+                }
+                GameEvent::CommandRejected { command: GameCommand::StartGame, error } => println!("Couldn't start the rematch: {}", i18n::tr(error.message_key())),
+                _ => {}
+            }
+        }
+    }
+
+    // the game can't go on, so the players have to pick one of the buttons
+    fn is_closable(&self) -> bool {
+        false
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
@@ -1,59 +1,410 @@
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
-use crate::render::{Instance, ModelColoring, ModeledInstance, Renderer, TexTriple, TexTy};
+use std::path::Path;
+use crate::render::{Camera, Instance, ModelColoring, ModelHandle, ModeledInstance, Renderer, TexTriple, TexTy};
 use crate::screen_sys::Screen;
-use crate::ui::{Button, Color, ColorBox, Coloring, Container, Tex, TextBox, TextSection};
-use crate::{Game, ScreenSystem, ui};
+use crate::ui::{Button, Color, ColorBox, Coloring, Component, Container, Tex, TextBox, TextSection};
+use crate::{Game, i18n, ScreenSystem, ui};
 use std::sync::{Arc, Mutex, RwLock};
-use cgmath::{Deg, InnerSpace, Quaternion, Rotation3, Vector3, Zero};
-use image::{EncodableLayout, GenericImageView};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::Duration;
+use cgmath::{Deg, InnerSpace, Point3, Quaternion, Rad, Rotation3, Vector3, Zero};
+use image::{imageops, EncodableLayout, GenericImageView, Rgba, RgbaImage};
+use instant::Instant;
 use rand::Rng;
 use wgpu::{Sampler, SamplerDescriptor, TextureAspect, TextureDimension, TextureFormat, TextureViewDescriptor};
 use wgpu_biolerless::TextureBuilder;
 use wgpu_glyph::{HorizontalAlign, Layout, Text, VerticalAlign};
+use winit::event::{ModifiersState, VirtualKeyCode};
+use crate::board::Tile;
+use crate::engine::{Engine, Phase, TurnAction};
+use crate::animation::{self, AnimationClip};
+use crate::assets::BOARD_TEXTURE_PATH;
+use crate::board_layout::{self, BOARD_POSITION};
+use crate::entity::EntityKind;
+use crate::events::{Emote, GameCommand, GameEvent};
+use crate::minimap::Minimap;
+use crate::mod_panels::ModPanels;
+use crate::action_bar::ActionBar;
+use crate::chat::Chat;
+use crate::hud::{self, Hud};
+use crate::money_popups::{MoneyPopup, MoneyPopups};
+use crate::palette;
+use crate::probability;
 use crate::player::Character;
+use crate::property::{ColorGroup, PropertyFrame, PropertyType, MAX_HOUSES};
+use crate::screens::card::CardReveal;
+use crate::screens::draft::DraftPicker;
+use crate::screens::game_over::GameOver;
+use crate::screens::pause::Pause;
+use crate::screens::property::PropertyDetails;
+use crate::screens::tax::TaxChoice;
+use crate::text_raster::TextRun;
+use crate::ui::REGULAR_FONT;
+use crate::tween::Easing;
 use crate::utils::DARK_GRAY_UI;
 
+const HOP_DURATION: Duration = Duration::from_millis(150);
+const JAIL_DURATION: Duration = Duration::from_millis(600);
+const DICE_ROLL_DURATION: Duration = Duration::from_millis(800);
+const DICE_TOSS_HEIGHT: f32 = 2.0;
+// the faces the dice show while they're tumbling, before they land on the rolled one
+const DICE_TUMBLES: u32 = 3;
+const DIE_SIZE: f32 = 0.5;
+// tokens and houses on the same tile are spread out so they don't overlap
+const TOKEN_SPACING: f32 = 0.3;
+const HOUSE_SPACING: f32 = 0.25;
+const MARKER_SIZE: f32 = 0.6;
+const OWNER_MARKER_WIDTH: f32 = 0.8;
+const OWNER_MARKER_DEPTH: f32 = 0.15;
+// how far the owner marker is moved from the tile's center towards the board's edge
+const OWNER_MARKER_OFFSET: f32 = 0.4;
+const BOARD_MODEL_PATH: &str = "./resources/cube.obj";
+// a simpler variant of the model which is used for far away instances, it's optional
+const LOW_DETAIL_MODEL_PATH: &str = "./resources/cube_low.obj";
+const LOW_DETAIL_DISTANCE: f32 = 12.0;
+const HOUSE_MODEL_PATH: &str = "./resources/house.obj";
+const HOTEL_MODEL_PATH: &str = "./resources/hotel.obj";
+const HOUSE_COLOR: [f32; 4] = [0.1, 0.6, 0.2, 1.0];
+const HOTEL_COLOR: [f32; 4] = [0.8, 0.1, 0.1, 1.0];
+const IDLE_CLIP: &str = "idle";
+const CELEBRATION_CLIP: &str = "celebrate";
+const CLIP_BLEND: Duration = Duration::from_millis(250);
+// the top down view of the whole board in the bottom right corner, its width is relative to the screen's width
+const INSET_WIDTH: f32 = 0.22;
+const INSET_MARGIN: f32 = 0.01;
+const INSET_HEIGHT: f32 = 9.0;
+// toggles the overlay which tints the tiles by how likely the tokens are to land on them
+const HEATMAP_KEY: VirtualKeyCode = VirtualKeyCode::H;
+// shortcuts for the action bar's buttons
+const ROLL_KEY: VirtualKeyCode = VirtualKeyCode::Space;
+const END_TURN_KEY: VirtualKeyCode = VirtualKeyCode::Return;
+// uses a jail free card if the player has one and pays the bail otherwise
+const LEAVE_JAIL_KEY: VirtualKeyCode = VirtualKeyCode::B;
+// starts typing a chat message in networked games
+const CHAT_KEY: VirtualKeyCode = VirtualKeyCode::T;
+// the overlay covers this many turns of every player
+const HEATMAP_TURNS: usize = 3;
+const HEAT_LEVELS: usize = 8;
+// slightly larger than the group markers, which are drawn on top of it
+const HEAT_SIZE: f32 = 0.9;
+const EMOTE_DURATION: Duration = Duration::from_secs(3);
+// the bubbles float above the tokens, their size is in world units
+const EMOTE_OFFSET: f32 = 1.2;
+const EMOTE_SIZE: f32 = 0.4;
+const EMOTE_TEXT_SCALE: f32 = 48.0;
+const EMOTE_PADDING: u32 = 12;
+// pressing one of these keys sends the emote at the same index
+const EMOTE_KEYS: [VirtualKeyCode; 6] = [
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+];
+
 #[derive(Clone)]
 pub struct InGame {
     container: Arc<Container>,
-    board_id: usize,
+    models: Option<Models>,
+    // whether the group markers which are currently shown are the colorblind ones
+    markers_colorblind: Option<bool>,
+    // the owners of the tiles the owner markers are currently shown for
+    owners: Vec<Option<usize>>,
+    // the number of houses which are currently shown on every property, a hotel counts as `MAX_HOUSES`
+    buildings: Vec<usize>,
+    heatmap: bool,
+    // the tokens' tiles and remaining jail throws the heat of the tiles was calculated for, empty if the heatmap is hidden
+    heat_tokens: Vec<(usize, usize)>,
+    events: Option<Arc<Mutex<Receiver<GameEvent>>>>,
+    // the animation clips which are still being loaded
+    clips: Option<Arc<Mutex<Receiver<Clips>>>>,
+    // when the players' current emotes were sent, indexed by the player
+    emotes: HashMap<usize, Instant>,
+    // the money changes which are currently floating up, they get drawn by the `MoneyPopups` component
+    money_popups: Arc<Mutex<Vec<MoneyPopup>>>,
+    // whether the draft picker was opened, it's only opened on its own once
+    draft_opened: bool,
+    // only networked games have a chat
+    chat: Option<Arc<RwLock<Box<dyn Component>>>>,
+}
+
+// indexed by the character's id and the clip's name
+type Clips = HashMap<usize, HashMap<String, Arc<AnimationClip>>>;
+
+#[derive(Clone)]
+struct Models {
+    // FIXME: use a dedicated model for the tokens, for now they use the board's model
+    board: Arc<ModelHandle>,
+    die: usize,
+    house: Arc<ModelHandle>,
+    hotel: Arc<ModelHandle>,
+    // indexed by the group and whether it's the colorblind variant
+    markers: HashMap<(ColorGroup, bool), usize>,
+    // the owner markers, indexed by the player
+    owners: Vec<usize>,
+    // the tints of the heatmap, from the least to the most likely tiles
+    heat: Vec<usize>,
+    // the animation clips of the tokens, they are empty until they were loaded
+    clips: Clips,
+    // the speech bubbles of the emotes
+    emotes: HashMap<Emote, usize>,
+}
+
+impl Models {
+
+    fn clip(&self, engine: &Engine, player: usize, name: &str) -> Option<Arc<AnimationClip>> {
+        self.clips.get(&engine.players[player].character_id)?.get(name).cloned()
+    }
+
 }
 
 impl InGame {
     pub fn new() -> Self {
         Self {
             container: Arc::new(Container::new()),
-            board_id: 0,
+            models: None,
+            markers_colorblind: None,
+            owners: vec![],
+            buildings: vec![],
+            heatmap: false,
+            heat_tokens: vec![],
+            events: None,
+            clips: None,
+            emotes: HashMap::new(),
+            money_popups: Arc::new(Mutex::new(vec![])),
+            draft_opened: false,
+            chat: None,
         }
     }
 }
 
+// a token without animations simply stands still
+fn load_clips(game: &Arc<Game>) -> Receiver<Clips> {
+    let (sender, receiver) = channel();
+    let characters = game.characters.clone();
+    thread::spawn(move || {
+        let mut clips = HashMap::new();
+        for character in characters.iter() {
+            if let Some(path) = &character.animations {
+                match animation::load_clips(path) {
+                    Ok(character_clips) => {
+                        clips.insert(character.id, character_clips);
+                    }
+                    Err(err) => println!("Couldn't load the animations of {}: {}", character.name, err),
+                }
+            }
+        }
+        // the screen may already be gone
+        let _ = sender.send(clips);
+    });
+    receiver
+}
+
+// the inset is square on screen, so its texture has the same number of pixels along both axes
+// FIXME: the inset gets stretched if the window is resized afterwards
+fn add_inset(game: &Arc<Game>, container: &Arc<Container>) {
+    let (width, height) = game.renderer.ui_dimensions();
+    let size = ((width as f32 * INSET_WIDTH) as u32).max(1);
+    // looking straight down, with the far edge of the board at the top like on the minimap
+    let camera = Camera::new(Point3::new(BOARD_POSITION.x, BOARD_POSITION.y + INSET_HEIGHT, BOARD_POSITION.z), Deg(-90.0), Deg(-89.9));
+    let tex = game.renderer.enable_inset(camera, (size, size));
+    let inset_height = size as f32 / height.max(1) as f32;
+    container.add(Arc::new(RwLock::new(Box::new(ColorBox {
+        pos: (1.0 - INSET_WIDTH - INSET_MARGIN, INSET_MARGIN),
+        width: INSET_WIDTH,
+        height: inset_height,
+        coloring: Coloring::Tex(Tex {
+            ty: TexTy::Simple(tex),
+            grayscale_conv: false,
+        }),
+    }))));
+}
+
+// the emote's text in black on a white bubble
+// FIXME: the bubbles keep the language they were created in
+fn emote_texture(game: &Arc<Game>, emote: Emote) -> RgbaImage {
+    let text = i18n::tr(emote.message_key());
+    let text = game.renderer.text_rasterizer.rasterize(&[TextRun {
+        text: &text,
+        scale: EMOTE_TEXT_SCALE,
+        font: REGULAR_FONT,
+        color: [0.0, 0.0, 0.0, 1.0],
+    }], None);
+    let mut bubble = RgbaImage::from_pixel(text.width() + EMOTE_PADDING * 2, text.height() + EMOTE_PADDING * 2, Rgba([255, 255, 255, 255]));
+    imageops::overlay(&mut bubble, &text, EMOTE_PADDING as i64, EMOTE_PADDING as i64);
+    bubble
+}
+
+// the bubble stands upright above the token and faces the camera, no matter where it's looked at from
+fn emote_transform(token: &Instance, camera: Point3<f32>) -> Instance {
+    let position = Vector3::unit_y() * EMOTE_OFFSET;
+    let to_camera = Vector3::new(camera.x, camera.y, camera.z) - (token.position + position);
+    Instance {
+        position,
+        rotation: Quaternion::from_angle_y(Rad(to_camera.x.atan2(to_camera.z))) * Quaternion::from_angle_x(Deg(90.0)),
+    }
+}
+
+fn create_texture(game: &Arc<Game>, buf: &RgbaImage) -> Arc<TexTriple> {
+    Arc::new(game.renderer.create_texture(buf))
+}
+
+// the transforms of the things on the board are relative to the board
+fn marker_transform(tile: usize) -> Instance {
+    // slightly above the board, so the marker isn't hidden inside of it
+    board_layout::on_tile(tile, Vector3::unit_y() * 0.01)
+}
+
+// below the group markers, so the tint shows around them
+fn heat_transform(tile: usize) -> Instance {
+    board_layout::on_tile(tile, Vector3::unit_y() * 0.005)
+}
+
+// the strip lies along the edge of the board the tile is on
+fn owner_marker_transform(tile: usize) -> Instance {
+    board_layout::on_tile(tile, Vector3::unit_z() * OWNER_MARKER_OFFSET + Vector3::unit_y() * 0.01)
+}
+
+fn token_transform(player: usize, tile: usize) -> Instance {
+    board_layout::on_tile(tile, Vector3::new((player % 2) as f32, 0.0, (player / 2) as f32) * TOKEN_SPACING)
+}
+
+fn house_transform(tile: usize, idx: usize) -> Instance {
+    board_layout::on_tile(tile, Vector3::new(idx as f32 * HOUSE_SPACING, 0.0, -TOKEN_SPACING))
+}
+
+// the hotel takes up the space of the houses it replaces
+fn hotel_transform(tile: usize) -> Instance {
+    board_layout::on_tile(tile, Vector3::new((MAX_HOUSES - 2) as f32 * HOUSE_SPACING / 2.0, 0.0, -TOKEN_SPACING))
+}
+
+fn die_position(idx: usize) -> Vector3<f32> {
+    Vector3::new(idx as f32 - 0.5, 2.0, 0.0)
+}
+
+// the rotation which leaves the face with the value on top
+fn die_rotation(value: u8) -> Quaternion<f32> {
+    match value {
+        2 => Quaternion::from_angle_x(Deg(90.0)),
+        3 => Quaternion::from_angle_z(Deg(90.0)),
+        4 => Quaternion::from_angle_z(Deg(-90.0)),
+        5 => Quaternion::from_angle_x(Deg(-90.0)),
+        6 => Quaternion::from_angle_x(Deg(180.0)),
+        _ => Quaternion::from_angle_x(Deg(0.0)),
+    }
+}
+
+fn property_tile(engine: &Engine, property: usize) -> Option<usize> {
+    engine.board.tiles.iter().position(|tile| matches!(tile, Tile::Property { property: frame } if frame.id == property))
+}
+
 impl Screen for InGame {
     fn init(&mut self, game: &Arc<Game>) {
-        // let mut buf = image::open("./resources/board.jpg").unwrap();
-        let mut buf = image::open("./resources/cube-diffuse.jpg").unwrap();
-        let buf = Arc::new(buf.into_rgba8());
-        let tex = game.renderer.state.create_texture(TextureBuilder::new().data(buf.as_bytes())
-            .format(TextureFormat::Rgba8UnormSrgb).texture_dimension(TextureDimension::D2).dimensions(buf.dimensions()));
-        let view = tex.create_view(&TextureViewDescriptor::default());
-        let tex = Arc::new(TexTriple {
-            tex,
-            view,
-            sampler: game.renderer.state.device().create_sampler(&SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                ..Default::default()
-            }),
+        self.events = Some(Arc::new(Mutex::new(game.subscribe())));
+        let board_texture = game.assets.get().unwrap().board_texture.clone();
+        let tex = game.renderer.cached_texture(BOARD_TEXTURE_PATH, || Ok(board_texture.as_ref().clone())).unwrap();
+        // let board_id = game.renderer.add_model(crate::model::rectangle_model(&game.renderer.state, (0.0, 0.0), 1.0, 1.0), ModelColoring::Tex(tex));
+        // let board_id = game.renderer.add_model(crate::model::Model::load_from("./resources/board.obj", &game.renderer.state, &game.renderer.model_bind_group_layout).unwrap(), ModelColoring::Tex(tex));
+        // loading the board's model takes a while, so a placeholder is shown until it's ready
+        let board = game.renderer.cached_model(BOARD_MODEL_PATH, |state, layout| {
+            let mut board_model = crate::model::Model::load_from(BOARD_MODEL_PATH, state, layout)?;
+            if Path::new(LOW_DETAIL_MODEL_PATH).exists() {
+                board_model = board_model.with_lod(LOW_DETAIL_DISTANCE, crate::model::Model::load_from(LOW_DETAIL_MODEL_PATH, state, layout)?);
+            }
+            Ok(board_model)
+        }, ModelColoring::Tex(tex));
+        let house = game.renderer.cached_model(HOUSE_MODEL_PATH, |state, layout| {
+            crate::model::Model::load_from(HOUSE_MODEL_PATH, state, layout)
+        }, ModelColoring::Direct(HOUSE_COLOR));
+        let hotel = game.renderer.cached_model(HOTEL_MODEL_PATH, |state, layout| {
+            crate::model::Model::load_from(HOTEL_MODEL_PATH, state, layout)
+        }, ModelColoring::Direct(HOTEL_COLOR));
+        // both variants are loaded up front, so the markers can be swapped as soon as the setting changes
+        let mut markers = HashMap::new();
+        for group in ColorGroup::ALL {
+            for colorblind in [false, true] {
+                let tex = create_texture(game, &palette::marker_texture(group, colorblind));
+                let model = game.renderer.add_model(crate::model::quad_model(&game.renderer.state, MARKER_SIZE), ModelColoring::Tex(tex));
+                markers.insert((group, colorblind), model);
+            }
+        }
+        let owners = (0..game.engine.lock().unwrap().players.len()).map(|player| {
+            let tex = create_texture(game, &palette::owner_texture(player));
+            game.renderer.add_model(crate::model::plane_model(&game.renderer.state, OWNER_MARKER_WIDTH, OWNER_MARKER_DEPTH), ModelColoring::Tex(tex))
+        }).collect();
+        let heat = (0..HEAT_LEVELS).map(|level| {
+            let tex = create_texture(game, &palette::heat_texture(level as f32 / (HEAT_LEVELS - 1) as f32));
+            game.renderer.add_model(crate::model::quad_model(&game.renderer.state, HEAT_SIZE), ModelColoring::Tex(tex))
+        }).collect();
+        let mut emotes = HashMap::new();
+        for emote in Emote::ALL {
+            let bubble = emote_texture(game, emote);
+            let width = EMOTE_SIZE * bubble.width() as f32 / bubble.height() as f32;
+            let tex = create_texture(game, &bubble);
+            emotes.insert(emote, game.renderer.add_model(crate::model::plane_model(&game.renderer.state, width, EMOTE_SIZE), ModelColoring::Tex(tex)));
+        }
+        self.clips = Some(Arc::new(Mutex::new(load_clips(game))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Minimap::new()))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Hud::new()))));
+        self.container.add(Arc::new(RwLock::new(Box::new(ActionBar::new()))));
+        for player in game.engine.lock().unwrap().players.iter() {
+            self.container.add(Arc::new(RwLock::new(Box::new(hud::portrait(game, player.id, player.character_id)))));
+        }
+        self.container.add(Arc::new(RwLock::new(Box::new(MoneyPopups::new(self.money_popups.clone())))));
+        if !game.mods.panels.is_empty() {
+            self.container.add(Arc::new(RwLock::new(Box::new(ModPanels::new()))));
+        }
+        if game.is_networked() {
+            let chat: Arc<RwLock<Box<dyn Component>>> = Arc::new(RwLock::new(Box::new(Chat::new())));
+            self.container.add(chat.clone());
+            self.chat = Some(chat);
+        }
+        if game.settings.lock().unwrap().graphics.inset {
+            add_inset(game, &self.container);
+        }
+
+        let mut entities = game.entities.lock().unwrap();
+        entities.spawn(EntityKind::Board, board.id(), Instance { position: BOARD_POSITION, rotation: Quaternion::from_angle_x(Deg(0.0)) });
+        for player in game.engine.lock().unwrap().players.iter().filter(|player| !player.bankrupt) {
+            entities.spawn_attached(EntityKind::Token { player: player.id }, EntityKind::Board, board.id(), token_transform(player.id, player.position));
+        }
+        let die = game.renderer.add_model(crate::model::die_model(&game.renderer.state, DIE_SIZE), ModelColoring::Tex(create_texture(game, &palette::die_texture())));
+        // the dice keep showing the last roll when the game is entered again
+        if let Some(dice) = game.engine.lock().unwrap().dice.last {
+            for (idx, value) in [dice.0, dice.1].into_iter().enumerate() {
+                entities.spawn(EntityKind::Die { idx }, die, Instance { position: die_position(idx), rotation: die_rotation(value) });
+            }
+        }
+        self.models = Some(Models {
+            board,
+            die,
+            house,
+            hotel,
+            markers,
+            owners,
+            heat,
+            clips: HashMap::new(),
+            emotes,
         });
-        // self.board_id = game.renderer.add_model(crate::model::rectangle_model(&game.renderer.state, (0.0, 0.0), 1.0, 1.0), ModelColoring::Tex(tex));
-        // self.board_id = game.renderer.add_model(crate::model::Model::load_from("./resources/board.obj", &game.renderer.state, &game.renderer.model_bind_group_layout).unwrap(), ModelColoring::Tex(tex));
-        self.board_id = game.renderer.add_model(crate::model::Model::load_from("./resources/cube.obj", &game.renderer.state, &game.renderer.model_bind_group_layout).unwrap(), ModelColoring::Tex(tex));
+    }
+
+    fn deinit(&mut self, game: &Arc<Game>) {
+        game.renderer.disable_inset();
+        self.events = None;
+        self.clips = None;
+        self.emotes.clear();
+        self.money_popups.lock().unwrap().clear();
+        self.markers_colorblind = None;
+        self.owners.clear();
+        self.buildings.clear();
+        self.heat_tokens.clear();
+        self.draft_opened = false;
+        game.entities.lock().unwrap().clear();
     }
 
     fn on_active(&mut self, _game: &Arc<Game>) {
@@ -133,46 +484,295 @@ impl Screen for InGame {
     fn on_deactive(&mut self, _game: &Arc<Game>) {}
 
     fn tick(&mut self, game: &Arc<Game>) {
-        game.models.lock().unwrap().push(ModeledInstance {
-            model_id: self.board_id,
-            instance: Instance { position: Vector3::unit_y(), rotation: Quaternion::from_angle_x(Deg(0.0)) },
+        let loaded_clips = self.clips.as_ref().and_then(|clips| clips.lock().unwrap().try_recv().ok());
+        if let Some(clips) = loaded_clips {
+            self.clips = None;
+            if let Some(models) = self.models.as_mut() {
+                models.clips = clips;
+                let engine = game.engine.lock().unwrap();
+                let mut entities = game.entities.lock().unwrap();
+                for player in engine.players.iter().filter(|player| !player.bankrupt) {
+                    if let Some(clip) = models.clip(&engine, player.id, IDLE_CLIP) {
+                        entities.play_clip(EntityKind::Token { player: player.id }, clip, true, CLIP_BLEND);
+                    }
+                }
+            }
+        }
+        let models = match self.models.clone() {
+            Some(models) => models,
+            None => return,
+        };
+        if !self.draft_opened && game.engine.lock().unwrap().phase == Phase::Drafting {
+            self.draft_opened = true;
+            game.screen_sys.push_screen(Box::new(DraftPicker::new()));
+        }
+        let colorblind = game.settings.lock().unwrap().accessibility.colorblind;
+        if self.markers_colorblind != Some(colorblind) {
+            self.markers_colorblind = Some(colorblind);
+            let engine = game.engine.lock().unwrap();
+            let mut entities = game.entities.lock().unwrap();
+            for (tile, kind) in engine.board.tiles.iter().enumerate() {
+                if let Tile::Property { property: PropertyFrame { ty: PropertyType::Normal { group: Some(group), .. }, .. } } = kind {
+                    // spawning replaces the marker with the other variant
+                    entities.spawn_attached(EntityKind::GroupMarker { tile }, EntityKind::Board, models.markers[&(*group, colorblind)], marker_transform(tile));
+                }
+            }
+        }
+        {
+            let engine = game.engine.lock().unwrap();
+            let owners = engine.board.tiles.iter().map(|tile| match tile {
+                Tile::Property { property: frame } => engine.properties[frame.id].owner,
+                _ => None,
+            }).collect::<Vec<_>>();
+            if owners != self.owners {
+                let mut entities = game.entities.lock().unwrap();
+                for (tile, owner) in owners.iter().enumerate() {
+                    if self.owners.get(tile) == Some(owner) {
+                        continue;
+                    }
+                    match owner {
+                        // spawning replaces the marker of the previous owner
+                        Some(owner) => entities.spawn_attached(EntityKind::OwnerMarker { tile }, EntityKind::Board, models.owners[*owner], owner_marker_transform(tile)),
+                        None => entities.despawn(EntityKind::OwnerMarker { tile }),
+                    }
+                }
+                self.owners = owners;
+            }
+            // mortgaged properties can't have any buildings
+            let buildings = engine.properties.iter()
+                .map(|property| if property.mortgaged { 0 } else { property.houses })
+                .collect::<Vec<_>>();
+            if buildings != self.buildings {
+                let mut entities = game.entities.lock().unwrap();
+                for (property, houses) in buildings.iter().copied().enumerate() {
+                    if self.buildings.get(property) == Some(&houses) {
+                        continue;
+                    }
+                    entities.retain(|kind| !matches!(kind, EntityKind::House { property: other, .. } | EntityKind::Hotel { property: other } if *other == property));
+                    let tile = match property_tile(&engine, property) {
+                        Some(tile) => tile,
+                        None => continue,
+                    };
+                    if houses == MAX_HOUSES {
+                        entities.spawn_attached(EntityKind::Hotel { property }, EntityKind::Board, models.hotel.id(), hotel_transform(tile));
+                    } else {
+                        for idx in 0..houses {
+                            entities.spawn_attached(EntityKind::House { property, idx }, EntityKind::Board, models.house.id(), house_transform(tile, idx));
+                        }
+                    }
+                }
+                self.buildings = buildings;
+            }
+            let heat_tokens = if self.heatmap {
+                engine.players.iter()
+                    .filter(|player| !player.bankrupt)
+                    .map(|player| (player.position, player.jail_free_throws))
+                    .collect()
+            } else {
+                vec![]
+            };
+            if heat_tokens != self.heat_tokens {
+                let mut entities = game.entities.lock().unwrap();
+                if self.heatmap {
+                    let landings = probability::landing_probabilities(&engine, HEATMAP_TURNS);
+                    let max = landings.iter().copied().fold(0.0, f32::max).max(f32::EPSILON);
+                    for (tile, landings) in landings.iter().enumerate() {
+                        let level = (landings / max * (HEAT_LEVELS - 1) as f32).round() as usize;
+                        // spawning replaces the previous tint
+                        entities.spawn_attached(EntityKind::Heat { tile }, EntityKind::Board, models.heat[level], heat_transform(tile));
+                    }
+                } else {
+                    entities.retain(|kind| !matches!(kind, EntityKind::Heat { .. }));
+                }
+                self.heat_tokens = heat_tokens;
+            }
+        }
+        let events = match self.events.as_ref() {
+            Some(events) => events.clone(),
+            None => return,
+        };
+        for event in events.lock().unwrap().try_iter() {
+            let engine = game.engine.lock().unwrap();
+            let mut entities = game.entities.lock().unwrap();
+            match event {
+                GameEvent::TokenHopped { player, tile } => {
+                    entities.animate_to(EntityKind::Token { player }, token_transform(player, tile), HOP_DURATION, Easing::EaseInOut);
+                }
+                GameEvent::WentToJail { player } => {
+                    entities.animate_to(EntityKind::Token { player }, token_transform(player, engine.board.index.jail), JAIL_DURATION, Easing::EaseInOut);
+                }
+                GameEvent::DiceRolled { dice, .. } => {
+                    for (idx, value) in [dice.0, dice.1].into_iter().enumerate() {
+                        let kind = EntityKind::Die { idx };
+                        let rest = die_position(idx);
+                        if !entities.contains(kind) {
+                            entities.spawn(kind, models.die, Instance { position: rest, rotation: die_rotation(1) });
+                        }
+                        // toss the die up and let it tumble over a few random faces while it falls back down
+                        let step = DICE_ROLL_DURATION / (DICE_TUMBLES + 1);
+                        for tumble in 0..DICE_TUMBLES {
+                            let height = DICE_TOSS_HEIGHT * (DICE_TUMBLES - tumble) as f32 / DICE_TUMBLES as f32;
+                            entities.animate_to(kind, Instance {
+                                position: rest + Vector3::unit_y() * height,
+                                rotation: Quaternion::from_angle_y(Deg(90.0 * tumble as f32 + 45.0)) * die_rotation(rand::thread_rng().gen_range(1..=6)),
+                            }, step, if tumble == 0 { Easing::EaseOut } else { Easing::EaseInOut });
+                        }
+                        entities.animate_to(kind, Instance { position: rest, rotation: die_rotation(value) }, step, Easing::EaseIn);
+                    }
+                }
+                GameEvent::PropertyBought { player, .. } | GameEvent::AuctionWon { player, .. } => {
+                    if let Some(clip) = models.clip(&engine, player, CELEBRATION_CLIP) {
+                        entities.play_clip(EntityKind::Token { player }, clip, false, CLIP_BLEND);
+                    }
+                }
+                GameEvent::GameFinished { winner } => {
+                    if let Some(player) = winner {
+                        if let Some(clip) = models.clip(&engine, player, CELEBRATION_CLIP) {
+                            entities.play_clip(EntityKind::Token { player }, clip, false, CLIP_BLEND);
+                        }
+                    }
+                    game.screen_sys.push_screen(Box::new(GameOver::new()));
+                }
+                GameEvent::Emoted { player, emote } => {
+                    // a new emote replaces the player's previous one
+                    entities.spawn_attached(EntityKind::Emote { player }, EntityKind::Token { player }, models.emotes[&emote], Instance {
+                        position: Vector3::unit_y() * EMOTE_OFFSET,
+                        rotation: Quaternion::from_angle_x(Deg(90.0)),
+                    });
+                    self.emotes.insert(player, Instant::now());
+                }
+                GameEvent::MoneyPaid { player, amount } => {
+                    self.money_popups.lock().unwrap().push(MoneyPopup::new(player, amount, &engine.rules.currency, false));
+                }
+                GameEvent::MoneyReceived { player, amount } => {
+                    self.money_popups.lock().unwrap().push(MoneyPopup::new(player, amount, &engine.rules.currency, true));
+                }
+                GameEvent::CardDrawn { card, .. } => {
+                    game.screen_sys.push_screen(Box::new(CardReveal::new(card)));
+                }
+                GameEvent::TaxChoiceOffered { player, flat, percentage } => {
+                    game.screen_sys.push_screen(Box::new(TaxChoice::new(player, flat, percentage)));
+                }
+                // rejected commands are shown by the action bar and the property card,
+                // broken scripts are only of interest to the authors of the mods
+                GameEvent::ScriptFailed { error, .. } => println!("script failed: {}", error),
+                // the buildings which were sold back to the bank get removed like all other sold buildings
+                GameEvent::WentBankrupt { player, .. } => {
+                    entities.despawn(EntityKind::Token { player });
+                }
+                _ => {}
+            }
+        }
+        self.money_popups.lock().unwrap().retain(|popup| !popup.is_finished());
+        let camera = game.camera.lock().unwrap().position;
+        let mut entities = game.entities.lock().unwrap();
+        self.emotes.retain(|player, since| {
+            let kind = EntityKind::Emote { player: *player };
+            match entities.world_transform(EntityKind::Token { player: *player }) {
+                Some(token) if since.elapsed() < EMOTE_DURATION => {
+                    entities.set_transform(kind, emote_transform(&token, camera));
+                    true
+                }
+                _ => {
+                    entities.despawn(kind);
+                    false
+                }
+            }
         });
+    }
 
-        let board_id = self.board_id;
-
-        const NUM_INSTANCES_PER_ROW: u32 = 10;
-        const INSTANCE_DISPLACEMENT: cgmath::Vector3<f32> = cgmath::Vector3::new(NUM_INSTANCES_PER_ROW as f32 * 0.5, 0.0, NUM_INSTANCES_PER_ROW as f32 * 0.5);
-        let instances = (0..NUM_INSTANCES_PER_ROW).flat_map(|z| {
-            (0..NUM_INSTANCES_PER_ROW).map(move |x| {
-                let position = cgmath::Vector3 { x: x as f32, y: 0.0, z: z as f32 } - INSTANCE_DISPLACEMENT;
+    // emotes and money popups disappear on their own
+    fn is_animating(&self) -> bool {
+        !self.emotes.is_empty() || !self.money_popups.lock().unwrap().is_empty()
+    }
 
-                let rotation = if position.is_zero() {
-                    // this is needed so an object at (0, 0, 0) won't get scaled to zero
-                    // as Quaternions can effect scale if they're not created correctly
-                    cgmath::Quaternion::from_axis_angle(cgmath::Vector3::unit_z(), cgmath::Deg(0.0))
-                } else {
-                    cgmath::Quaternion::from_axis_angle(position.normalize(), cgmath::Deg(45.0))
+    fn on_key(&mut self, game: &Arc<Game>, key: VirtualKeyCode, down: bool, modifiers: ModifiersState) -> bool {
+        if self.container.on_key(game, key, down, modifiers) {
+            return true;
+        }
+        // the chat is focused on release, so the key's character isn't typed into it
+        if let Some(chat) = self.chat.as_ref().filter(|_| key == CHAT_KEY) {
+            if !down {
+                self.container.focus(game, chat);
+            }
+            return true;
+        }
+        if let Some(idx) = EMOTE_KEYS.iter().position(|emote_key| *emote_key == key) {
+            if !down {
+                // spectators of a networked game have nobody to emote for
+                if let Some(player) = game.local_player() {
+                    game.submit(GameCommand::Emote {
+                        player,
+                        emote: Emote::ALL[idx],
+                    });
+                }
+            }
+            return true;
+        }
+        if key == ROLL_KEY || key == END_TURN_KEY || key == LEAVE_JAIL_KEY {
+            if !down {
+                let (player, actions, jail_free_cards) = {
+                    let engine = game.engine.lock().unwrap();
+                    let player = engine.curr_player;
+                    (player, engine.legal_actions(player), engine.players[player].jail_free_cards)
                 };
-
-                ModeledInstance {
-                    model_id: board_id,
-                    instance: Instance {
-                        position, rotation,
-                    },
+                // the other players of a networked game act on their own machines
+                if !game.is_local(player) {
+                    return true;
+                } else if key == ROLL_KEY && actions.contains(&TurnAction::Roll) {
+                    game.submit(GameCommand::RollDice {
+                        player,
+                    });
+                } else if key == END_TURN_KEY && actions.contains(&TurnAction::EndTurn) {
+                    game.submit(GameCommand::EndTurn {
+                        player,
+                    });
+                } else if key == LEAVE_JAIL_KEY && actions.contains(&TurnAction::LeaveJail) {
+                    game.submit(if jail_free_cards > 0 {
+                        GameCommand::UseJailFreeCard {
+                            player,
+                        }
+                    } else {
+                        GameCommand::PayBail {
+                            player,
+                        }
+                    });
                 }
-            })
-        }).collect::<Vec<_>>();
-        game.models.lock().unwrap().extend(instances.into_iter());
+            }
+            return true;
+        }
+        if key == HEATMAP_KEY {
+            if !down {
+                self.heatmap = !self.heatmap;
+            }
+            return true;
+        }
+        if key == VirtualKeyCode::Escape && !down {
+            game.screen_sys.push_screen(Box::new(Pause::new()));
+            return true;
+        }
+        false
+    }
 
-        println!("adding model!");
+    // clicking a property's tile shows its card
+    fn on_scene_click(&mut self, game: &Arc<Game>, pos: (f32, f32)) {
+        let (origin, direction) = game.renderer.pick_ray(&game.camera.lock().unwrap(), pos);
+        let tile = match board_layout::pick_tile(origin, direction) {
+            Some(tile) => tile,
+            None => return,
+        };
+        if let Tile::Property { property: frame } = &game.engine.lock().unwrap().board.tiles[tile] {
+            game.screen_sys.push_screen(Box::new(PropertyDetails::new(frame.id)));
+        }
     }
 
     fn is_closable(&self) -> bool {
         false
     }
 
+    // the board has to keep on being rendered beneath overlays like the pause menu
     fn is_tick_always(&self) -> bool {
-        false
+        true
     }
 
     fn container(&self) -> &Arc<Container> {
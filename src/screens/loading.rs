@@ -0,0 +1,151 @@
+use crate::assets::{self, LoadEvent};
+use crate::screen_sys::Screen;
+use crate::screens::in_game::InGame;
+use crate::screens::login::Login;
+use crate::ui::{Color, ColorBox, Coloring, Container, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, RwLock};
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{DARK_GRAY_UI, LIGHT_GRAY_UI};
+
+const BAR_POS: (f32, f32) = (0.3, 0.45);
+const BAR_WIDTH: f32 = 0.4;
+const BAR_HEIGHT: f32 = 0.03;
+
+const ERROR_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.45,
+    b: 0.4,
+    a: 1.0,
+};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+// shown at startup while the assets are loaded, replaces itself with the login screen afterwards
+#[derive(Clone)]
+pub struct Loading {
+    container: Arc<Container>,
+    events: Option<Arc<Mutex<Receiver<LoadEvent>>>>,
+    // go straight to the board instead of the login screen
+    skip_login: bool,
+}
+
+impl Loading {
+    pub fn new() -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+            events: None,
+            skip_login: false,
+        }
+    }
+
+    pub fn skipping_login() -> Self {
+        Self {
+            skip_login: true,
+            ..Self::new()
+        }
+    }
+
+    fn show_progress(&self, step: &str, done: usize, total: usize) {
+        self.container.clear();
+        self.container.add(Arc::new(RwLock::new(Box::new(ColorBox {
+            pos: BAR_POS,
+            width: BAR_WIDTH,
+            height: BAR_HEIGHT,
+            coloring: Coloring::Color([DARK_GRAY_UI; 4]),
+        }))));
+        let progress = done as f32 / total.max(1) as f32;
+        if progress > 0.0 {
+            self.container.add(Arc::new(RwLock::new(Box::new(ColorBox {
+                pos: BAR_POS,
+                width: BAR_WIDTH * progress,
+                height: BAR_HEIGHT,
+                coloring: Coloring::Color([LIGHT_GRAY_UI; 4]),
+            }))));
+        }
+        self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+            (BAR_POS.0, BAR_POS.1 + BAR_HEIGHT),
+            BAR_WIDTH,
+            0.05,
+            Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(i18n::tr_with("loading.progress", &[
+                    ("step", &i18n::tr(step)),
+                    ("done", &done.to_string()),
+                    ("total", &total.to_string()),
+                ]), TextStyle::new(24.0).with_color(WHITE)),
+        )))));
+    }
+
+    // the game can't be played without its assets, so the error stays until the window is closed
+    fn show_error(&self, error: &str) {
+        self.container.clear();
+        self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+            (0.1, BAR_POS.1),
+            0.8,
+            0.1,
+            Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+            TextSection::new(Layout::default_wrap().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(i18n::tr_with("loading.failed", &[("error", error)]), TextStyle::new(24.0).with_color(ERROR_COLOR)),
+        )))));
+    }
+}
+
+impl Screen for Loading {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.events = Some(Arc::new(Mutex::new(assets::load_startup_assets(game))));
+        self.show_progress("loading.assets", 0, 1);
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    // the progress bar has to follow the loader
+    fn is_animating(&self) -> bool {
+        self.events.is_some()
+    }
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, game: &Arc<Game>) {
+        let events = match self.events.as_ref() {
+            Some(events) => events.clone(),
+            None => return,
+        };
+        for event in events.lock().unwrap().try_iter() {
+            match event {
+                LoadEvent::Progress { step, done, total } => self.show_progress(step, done, total),
+                LoadEvent::Finished(assets) => {
+                    let _ = game.assets.set(assets);
+                    self.events = None;
+                    if self.skip_login {
+                        game.screen_sys.replace_screen(Box::new(InGame::new()));
+                    } else {
+                        game.screen_sys.replace_screen(Box::new(Login::new(Arc::new(Mutex::new(game.characters.clone())))));
+                    }
+                    return;
+                }
+                LoadEvent::Failed(err) => {
+                    println!("Couldn't load the assets: {}", err);
+                    self.show_error(&err);
+                    self.events = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
@@ -4,22 +4,36 @@ use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 use std::path::Path;
 use crate::render::{Renderer, TexTriple, TexTy};
 use crate::screen_sys::Screen;
-use crate::ui::{Button, Color, ColorBox, Coloring, Container, Tex, TextBox, TextSection};
-use crate::{Game, GameState, ScreenSystem, ui};
+use crate::ui::{Button, Color, ColorBox, Coloring, Container, Tex, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n, ScreenSystem, ui};
 use std::sync::{Arc, Mutex, RwLock};
+use std::sync::mpsc::Receiver;
 use image::{EncodableLayout, GenericImageView, RgbaImage};
 use rand::Rng;
 use wgpu::{Sampler, SamplerDescriptor, TextureAspect, TextureDimension, TextureFormat, TextureViewDescriptor};
 use wgpu_biolerless::TextureBuilder;
 use wgpu_glyph::{HorizontalAlign, Layout, Text, VerticalAlign};
+use crate::engine::Phase;
+use crate::events::{GameCommand, GameEvent};
 use crate::player::Character;
 use crate::screens::in_game::InGame;
+use crate::screens::settings::Settings;
+use crate::assets::PLAY_BUTTON_PATH;
+use crate::{quick_play, save};
 use crate::utils::DARK_GRAY_UI;
 
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
 #[derive(Clone)]
 pub struct Login {
     container: Arc<Container>,
     chars: Arc<Mutex<Vec<Character>>>,
+    events: Option<Arc<Mutex<Receiver<GameEvent>>>>,
 }
 
 impl Login {
@@ -27,40 +41,32 @@ impl Login {
         Self {
             container: Arc::new(Container::new()),
             chars,
+            events: None,
         }
     }
 }
 
 impl Screen for Login {
     fn on_active(&mut self, game: &Arc<Game>) {
+        // only listen while the screen is shown, so events don't pile up during the game
+        self.events = Some(Arc::new(Mutex::new(game.subscribe())));
+        // the screen gets activated again whenever an overlay like the settings was closed
+        self.container.clear();
+        let joined = game.engine.lock().unwrap().players.iter().map(|player| player.character_id).collect::<Vec<_>>();
         let entry_offset = 1.0 / (self.chars.lock().unwrap().len() + 3) as f32;
         for char in self.chars.lock().unwrap().iter().enumerate() {
-            println!("path: {}", char.1.model_path);
-            let mut buf = image::open(Path::new(&char.1.model_path).canonicalize().unwrap()).unwrap();
-            let buf = Arc::new(buf.into_rgba8());
-            let tex = game.renderer.state.create_texture(TextureBuilder::new().data(buf.as_bytes())
-                .format(TextureFormat::Rgba8UnormSrgb).texture_dimension(TextureDimension::D2).dimensions(buf.dimensions()));
-            let view = tex.create_view(&TextureViewDescriptor::default());
+            let buf = game.assets.get().unwrap().character_textures.get(&char.1.id).unwrap().clone();
+            // characters may share their portraits
+            let tex = game.renderer.cached_texture(&char.1.model_path, || Ok(buf.as_ref().clone())).unwrap();
             self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
                 TextBox::new(
                     (((char.0 + 1) as f32 * entry_offset), 1.0 - entry_offset * 1.5),
                     0.1,
                     0.2,
+                    // the characters which already joined are grayed out
                     Coloring::Tex(Tex {
-                        ty: TexTy::Simple(Arc::new(TexTriple {
-                            tex,
-                            view,
-                            sampler: game.renderer.state.device().create_sampler(&SamplerDescriptor {
-                                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                                mag_filter: wgpu::FilterMode::Linear,
-                                min_filter: wgpu::FilterMode::Nearest,
-                                mipmap_filter: wgpu::FilterMode::Nearest,
-                                ..Default::default()
-                            }),
-                        })),
-                        grayscale_conv: false,
+                        ty: TexTy::Simple(tex),
+                        grayscale_conv: joined.contains(&char.1.id),
                     }),
                     TextSection {
                         layout: Layout::default_single_line().v_align(VerticalAlign::Bottom).h_align(HorizontalAlign::Left),
@@ -71,7 +77,9 @@ impl Screen for Login {
                 Arc::new(Box::new(|button: &mut Button<'_, (Arc<RgbaImage>, usize)>, game| {
                     if let Coloring::Tex(tex) = &mut button.inner_box.coloring {
                         if !tex.grayscale_conv {
-                            game.add_player(button.data.as_mut().unwrap().1);
+                            game.submit(GameCommand::AddPlayer {
+                                character: button.data.as_mut().unwrap().1,
+                            });
                         }
                         tex.grayscale_conv = true;
                     }
@@ -79,30 +87,15 @@ impl Screen for Login {
                 Some((buf, char.1.id))
             )))));
         }
-        let mut buf = image::open("./resources/play-button_3.jpg").unwrap();
-        let buf = Arc::new(buf.into_rgba8());
-        let tex = game.renderer.state.create_texture(TextureBuilder::new().data(buf.as_bytes())
-            .format(TextureFormat::Rgba8UnormSrgb).texture_dimension(TextureDimension::D2).dimensions(buf.dimensions()));
-        let view = tex.create_view(&TextureViewDescriptor::default());
+        let buf = game.assets.get().unwrap().play_button.clone();
+        let tex = game.renderer.cached_texture(PLAY_BUTTON_PATH, || Ok(buf.as_ref().clone())).unwrap();
         self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
             TextBox::new(
                 (0.35, entry_offset * 1.5),
                 0.3,
                 buf.height() as f32 / (buf.width() as f32 / 0.3),
                 Coloring::Tex(Tex {
-                    ty: TexTy::Simple(Arc::new(TexTriple {
-                        tex,
-                        view,
-                        sampler: game.renderer.state.device().create_sampler(&SamplerDescriptor {
-                            address_mode_u: wgpu::AddressMode::ClampToEdge,
-                            address_mode_v: wgpu::AddressMode::ClampToEdge,
-                            address_mode_w: wgpu::AddressMode::ClampToEdge,
-                            mag_filter: wgpu::FilterMode::Linear,
-                            min_filter: wgpu::FilterMode::Nearest,
-                            mipmap_filter: wgpu::FilterMode::Nearest,
-                            ..Default::default()
-                        }),
-                    })),
+                    ty: TexTy::Simple(tex),
                     grayscale_conv: false,
                 }),
                 TextSection {
@@ -113,17 +106,86 @@ impl Screen for Login {
             ),
             Arc::new(Box::new(|button: &mut Button<'_, Arc<RgbaImage>>, game| {
                 println!("start game!");
-                *game.game_state.lock().unwrap() = GameState::InGame;
-                game.screen_sys.push_screen(Box::new(InGame::new()));
-
+                // the board is shown once the engine actually started the game
+                game.submit(GameCommand::StartGame);
             })),
             Some(buf)
         )))));
+        // the daily challenge has to be the same game for everybody, and the server decides on the rules of networked games
+        if game.daily.is_none() && !game.is_networked() {
+            self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+                TextBox::new(
+                    (0.7, entry_offset * 1.5),
+                    0.2,
+                    0.08,
+                    Coloring::Color([DARK_GRAY_UI; 4]),
+                    TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                        .with_run(&i18n::tr("login.quick_play"), TextStyle::new(28.0).with_color(WHITE)),
+                ).with_static_text(),
+                Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                    {
+                        let mut engine = game.engine.lock().unwrap();
+                        if engine.phase == Phase::Lobby {
+                            quick_play::apply(&mut engine);
+                        }
+                    }
+                    game.submit(GameCommand::StartGame);
+                })),
+                None,
+            )))));
+        }
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            TextBox::new(
+                (0.78, 0.9),
+                0.2,
+                0.08,
+                Coloring::Color([DARK_GRAY_UI; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                    .with_run(&i18n::tr("login.settings"), TextStyle::new(28.0).with_color(WHITE)),
+            ).with_static_text(),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.screen_sys.push_screen(Box::new(Settings::new()));
+            })),
+            None,
+        )))));
+        if game.daily.is_none() && !game.is_networked() && save::path().exists() {
+            self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+                TextBox::new(
+                    (0.1, entry_offset * 1.5),
+                    0.2,
+                    0.08,
+                    Coloring::Color([DARK_GRAY_UI; 4]),
+                    TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                        .with_run(&i18n::tr("login.continue"), TextStyle::new(28.0).with_color(WHITE)),
+                ).with_static_text(),
+                Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                    match game.load(&save::path()) {
+                        Ok(()) => game.screen_sys.push_screen(Box::new(InGame::new())),
+                        Err(err) => println!("Couldn't continue the saved game: {}", err),
+                    }
+                })),
+                None,
+            )))));
+        }
     }
 
-    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+    fn on_deactive(&mut self, _game: &Arc<Game>) {
+        self.events = None;
+    }
 
-    fn tick(&mut self, _game: &Arc<Game>) {}
+    fn tick(&mut self, game: &Arc<Game>) {
+        let events = match self.events.as_ref() {
+            Some(events) => events.clone(),
+            None => return,
+        };
+        for event in events.lock().unwrap().try_iter() {
+            match event {
+                GameEvent::GameStarted => game.screen_sys.push_screen(Box::new(InGame::new())),
+                GameEvent::CommandRejected { command: GameCommand::StartGame, error } => println!("Couldn't start the game: {}", i18n::tr(error.message_key())),
+                _ => {}
+            }
+        }
+    }
 
     fn is_closable(&self) -> bool {
         false
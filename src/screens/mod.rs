@@ -1,2 +1,10 @@
 pub mod login;
-mod in_game;
\ No newline at end of file
+pub mod loading;
+mod card;
+pub mod draft;
+mod game_over;
+mod in_game;
+mod pause;
+mod property;
+mod settings;
+mod tax;
\ No newline at end of file
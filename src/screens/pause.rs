@@ -0,0 +1,103 @@
+use crate::screen_sys::Screen;
+use crate::screens::login::Login;
+use crate::screens::settings::Settings;
+use crate::ui::{Button, Color, Coloring, Container, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use crate::events::GameCommand;
+use std::sync::{Arc, RwLock};
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+#[derive(Clone)]
+pub struct Pause {
+    container: Arc<Container>,
+}
+
+impl Pause {
+    pub fn new() -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+        }
+    }
+
+    fn button(pos: (f32, f32), label: &str) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            0.3,
+            0.1,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(label, TextStyle::new(30.0).with_color(WHITE)),
+        ).with_static_text()
+    }
+
+    fn build(&self) {
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.35, 0.6), &i18n::tr("pause.resume")),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.screen_sys.pop_screen();
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.35, 0.45), &i18n::tr("pause.settings")),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.screen_sys.push_screen(Box::new(Settings::new()));
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.35, 0.3), &i18n::tr("pause.leave")),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.submit(GameCommand::LeaveGame);
+                game.screen_sys.pop_to::<Login>();
+            })),
+            None,
+        )))));
+    }
+}
+
+impl Screen for Pause {
+    fn init(&mut self, _game: &Arc<Game>) {
+        self.build();
+    }
+
+    fn on_language_changed(&mut self, _game: &Arc<Game>) {
+        self.container.clear();
+        self.build();
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, _game: &Arc<Game>) {}
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
@@ -0,0 +1,320 @@
+use crate::screen_sys::Screen;
+use crate::ui::{Button, Color, ColorBox, Coloring, Container, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use crate::engine::{Engine, Phase};
+use crate::events::{CommandError, GameCommand, GameEvent};
+use crate::palette;
+use crate::property::{PropertyType, MAX_HOUSES};
+use std::sync::{Arc, RwLock};
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI, LIGHT_GRAY_UI};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+const BLACK: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+const ERROR_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.45,
+    b: 0.4,
+    a: 1.0,
+};
+
+const PANEL_POS: (f32, f32) = (0.3, 0.08);
+const PANEL_DIMS: (f32, f32) = (0.4, 0.84);
+const TITLE_HEIGHT: f32 = 0.08;
+const LINE_HEIGHT: f32 = 0.045;
+const PADDING: f32 = 0.02;
+const BUTTON_HEIGHT: f32 = 0.06;
+const TEXT_SCALE: f32 = 22.0;
+
+// the rent table, the owner and the mortgage state of a property, it gets rebuilt if any of these change
+#[derive(Clone, PartialEq)]
+struct Snapshot {
+    houses: usize,
+    owner: Option<usize>,
+    mortgaged: bool,
+    // buildings and mortgages can only be changed by the player whose turn it is
+    curr_player: usize,
+    phase: Phase,
+}
+
+// shows the card of the property on the tile which was clicked, its owner can build on it and mortgage it from here
+#[derive(Clone)]
+pub struct PropertyDetails {
+    container: Arc<Container>,
+    property: usize,
+    snapshot: Option<Snapshot>,
+    // the length of the event log when the card looked for rejected commands the last time
+    seen: usize,
+    // why the last command submitted from the card was rejected, it's shown until the property changes
+    error: Option<CommandError>,
+}
+
+impl PropertyDetails {
+    pub fn new(property: usize) -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+            property,
+            snapshot: None,
+            seen: 0,
+            error: None,
+        }
+    }
+
+    // whether the command was submitted from the card
+    fn is_own_command(&self, command: &GameCommand) -> bool {
+        match command {
+            GameCommand::BuildHouse { property, .. }
+            | GameCommand::SellHouse { property, .. }
+            | GameCommand::Mortgage { property, .. }
+            | GameCommand::Unmortgage { property, .. } => *property == self.property,
+            _ => false,
+        }
+    }
+
+    fn snapshot(&self, engine: &Engine) -> Snapshot {
+        let property = &engine.properties[self.property];
+        Snapshot {
+            houses: property.houses,
+            owner: property.owner,
+            mortgaged: property.mortgaged,
+            curr_player: engine.curr_player,
+            phase: engine.phase,
+        }
+    }
+
+    // the label of every row of the rent table along with its rent
+    fn rent_rows(engine: &Engine, property: usize) -> Vec<(String, String)> {
+        let property = &engine.properties[property];
+        let currency = &engine.rules.currency;
+        // stations and special properties have a row for every number of them the owner might hold
+        let of_type = engine.properties.iter().filter(|other| other.frame.ty == property.frame.ty).count();
+        match &property.frame.ty {
+            PropertyType::Normal { .. } => property.frame.rents.iter().enumerate().filter_map(|(houses, rent)| {
+                let label = match houses {
+                    0 => i18n::tr("property.rent"),
+                    1 => i18n::tr("property.rent_house"),
+                    MAX_HOUSES => i18n::tr("property.rent_hotel"),
+                    houses => i18n::tr_with("property.rent_houses", &[("count", &houses.to_string())]),
+                };
+                rent.map(|rent| (label, currency.format(rent)))
+            }).collect(),
+            PropertyType::Station => (1..=of_type).map(|owned| (
+                i18n::tr_with("property.rent_stations", &[("count", &owned.to_string())]),
+                currency.format(property.calculate_price(0, false, owned)),
+            )).collect(),
+            // the rent depends on the roll, so the factor is shown instead
+            PropertyType::Special => (1..=of_type).map(|owned| (
+                i18n::tr_with("property.rent_specials", &[("count", &owned.to_string())]),
+                i18n::tr_with("property.roll_factor", &[("factor", &property.calculate_price(1, false, owned).to_string())]),
+            )).collect(),
+        }
+    }
+
+    fn line(pos: (f32, f32), label: &str, value: &str, bold: bool) -> [TextBox<'static>; 2] {
+        let width = PANEL_DIMS.0 - PADDING * 2.0;
+        let style = |text_style: TextStyle| if bold { text_style.bold() } else { text_style };
+        [
+            TextBox::new(
+                pos,
+                width,
+                LINE_HEIGHT,
+                Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                    .with_run(label, style(TextStyle::new(TEXT_SCALE).with_color(WHITE))),
+            ).with_static_text(),
+            TextBox::new(
+                pos,
+                width,
+                LINE_HEIGHT,
+                Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+                TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Right))
+                    .with_run(value, style(TextStyle::new(TEXT_SCALE).with_color(WHITE))),
+            ).with_static_text(),
+        ]
+    }
+
+    fn button(pos: (f32, f32), label: &str) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            (PANEL_DIMS.0 - PADDING * 3.0) / 2.0,
+            BUTTON_HEIGHT,
+            Coloring::Color([LIGHT_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(label, TextStyle::new(TEXT_SCALE).with_color(BLACK)),
+        ).with_static_text()
+    }
+
+    fn build(&self, game: &Arc<Game>) {
+        let colorblind = game.settings.lock().unwrap().accessibility.colorblind;
+        let engine = game.engine.lock().unwrap();
+        let property = &engine.properties[self.property];
+        let currency = &engine.rules.currency;
+        self.container.add(Arc::new(RwLock::new(Box::new(ColorBox {
+            pos: PANEL_POS,
+            width: PANEL_DIMS.0,
+            height: PANEL_DIMS.1,
+            coloring: Coloring::Color([DARK_GRAY_UI; 4]),
+        }))));
+        // the title is shown on the color of the property's group like on the physical cards
+        let title_color = match &property.frame.ty {
+            PropertyType::Normal { group: Some(group), .. } => palette::group_color(*group, colorblind),
+            _ => LIGHT_GRAY_UI,
+        };
+        self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+            (PANEL_POS.0, PANEL_POS.1 + PANEL_DIMS.1 - TITLE_HEIGHT),
+            PANEL_DIMS.0,
+            TITLE_HEIGHT,
+            Coloring::Color([title_color; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(&property.frame.name, TextStyle::new(30.0).with_color(BLACK).bold()),
+        ).with_static_text()))));
+
+        let owner = property.owner.map_or_else(|| i18n::tr("property.unowned"), |owner| engine.players[owner].display_name());
+        // the rent which is currently charged is highlighted
+        let current_row = match property.frame.ty {
+            PropertyType::Normal { .. } if property.owner.is_some() && !property.mortgaged => Some(property.houses),
+            _ => None,
+        };
+        let mut lines = vec![(i18n::tr("property.price"), currency.format(property.frame.buy_price), false)];
+        lines.extend(Self::rent_rows(&engine, self.property).into_iter().enumerate()
+            .map(|(row, (label, value))| (label, value, current_row == Some(row))));
+        lines.push((i18n::tr("property.owner"), owner, false));
+        lines.push(if property.mortgaged {
+            (i18n::tr("property.mortgaged"), currency.format(property.unmortgage_price()), false)
+        } else {
+            (i18n::tr("property.mortgage_value"), currency.format(property.mortgage_value()), false)
+        });
+        for (idx, (label, value, bold)) in lines.iter().enumerate() {
+            let pos = (PANEL_POS.0 + PADDING, PANEL_POS.1 + PANEL_DIMS.1 - TITLE_HEIGHT - PADDING - (idx + 1) as f32 * LINE_HEIGHT);
+            for text in Self::line(pos, label, value, *bold) {
+                self.container.add(Arc::new(RwLock::new(Box::new(text))));
+            }
+        }
+
+        let mut buttons = vec![];
+        let own_turn = property.owner == Some(engine.curr_player) && game.is_local(engine.curr_player)
+            && matches!(engine.phase, Phase::Rolling | Phase::Acting);
+        if own_turn {
+            let player = engine.curr_player;
+            let property_id = self.property;
+            if matches!(property.frame.ty, PropertyType::Normal { .. }) && !property.mortgaged && property.houses < MAX_HOUSES {
+                buttons.push((
+                    i18n::tr_with("property.build", &[("amount", &currency.format(engine.house_price(property_id)))]),
+                    GameCommand::BuildHouse { player, property: property_id },
+                ));
+            }
+            if property.houses > 0 {
+                buttons.push((i18n::tr("property.sell"), GameCommand::SellHouse { player, property: property_id }));
+            } else if property.mortgaged {
+                buttons.push((i18n::tr("property.lift_mortgage"), GameCommand::Unmortgage { player, property: property_id }));
+            } else {
+                buttons.push((i18n::tr("property.take_mortgage"), GameCommand::Mortgage { player, property: property_id }));
+            }
+        }
+        if let Some(error) = self.error {
+            self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+                (PANEL_POS.0 + PADDING, PANEL_POS.1 + PADDING * 3.0 + BUTTON_HEIGHT * 2.0),
+                PANEL_DIMS.0 - PADDING * 2.0,
+                LINE_HEIGHT * 2.0,
+                Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+                TextSection::new(Layout::default_wrap().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                    .with_run(i18n::tr(error.message_key()), TextStyle::new(TEXT_SCALE).with_color(ERROR_COLOR)),
+            ).with_static_text()))));
+        }
+        let button_width = (PANEL_DIMS.0 - PADDING * 3.0) / 2.0;
+        for (idx, (label, command)) in buttons.into_iter().enumerate() {
+            let pos = (PANEL_POS.0 + PADDING + (idx % 2) as f32 * (button_width + PADDING), PANEL_POS.1 + PADDING * 2.0 + BUTTON_HEIGHT);
+            // the screen stays open, it gets rebuilt once the command changed the property
+            self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+                Self::button(pos, &label),
+                Arc::new(Box::new(move |_button: &mut Button<'_, ()>, game| {
+                    game.submit(command.clone());
+                })),
+                None,
+            )))));
+        }
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((PANEL_POS.0 + PADDING, PANEL_POS.1 + PADDING), &i18n::tr("property.close")),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.screen_sys.pop_screen();
+            })),
+            None,
+        )))));
+    }
+}
+
+impl Screen for PropertyDetails {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.snapshot = Some(self.snapshot(&game.engine.lock().unwrap()));
+        self.seen = game.event_log.lock().unwrap().len();
+        self.build(game);
+    }
+
+    fn on_language_changed(&mut self, game: &Arc<Game>) {
+        self.container.clear();
+        self.build(game);
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, game: &Arc<Game>) {
+        let snapshot = self.snapshot(&game.engine.lock().unwrap());
+        let mut changed = false;
+        if self.snapshot.as_ref() != Some(&snapshot) {
+            self.snapshot = Some(snapshot);
+            self.error = None;
+            changed = true;
+        }
+        {
+            let event_log = game.event_log.lock().unwrap();
+            for event in event_log[self.seen..].iter() {
+                if let GameEvent::CommandRejected { command, error } = event {
+                    if self.is_own_command(command) {
+                        self.error = Some(*error);
+                        changed = true;
+                    }
+                }
+            }
+            self.seen = event_log.len();
+        }
+        if changed {
+            self.container.clear();
+            self.build(game);
+        }
+    }
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
@@ -0,0 +1,299 @@
+use crate::screen_sys::Screen;
+use crate::settings::{self, MAX_CAMERA_SENSITIVITY, MAX_TEXT_SCALE, MIN_CAMERA_SENSITIVITY, MIN_TEXT_SCALE};
+use crate::ui::{Button, Color, Coloring, Container, Slider, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use std::sync::{Arc, RwLock};
+use std::sync::atomic::Ordering;
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+const LABEL_X: f32 = 0.25;
+const SLIDER_X: f32 = 0.45;
+const ROW_HEIGHT: f32 = 0.08;
+const SLIDER_STEP: f32 = 0.075;
+// the toggles are laid out in two columns below the sliders
+const COLUMNS: [f32; 2] = [0.18, 0.52];
+const BUTTON_STEP: f32 = 0.1;
+
+// the window sizes the resolution button cycles through
+const RESOLUTIONS: [(u32, u32); 5] = [(1280, 720), (1600, 900), (1920, 1080), (2560, 1440), (3840, 2160)];
+// the frame rate limits the frame limit button cycles through
+const FPS_LIMITS: [Option<u32>; 5] = [None, Some(30), Some(60), Some(120), Some(144)];
+
+#[derive(Clone)]
+pub struct Settings {
+    container: Arc<Container>,
+}
+
+impl Settings {
+    pub fn new() -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+        }
+    }
+
+    fn label(pos: (f32, f32), width: f32, text: &str) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            width,
+            ROW_HEIGHT,
+            Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Left))
+                .with_run(text, TextStyle::new(26.0).with_color(WHITE)),
+        ).with_static_text()
+    }
+
+    fn button(pos: (f32, f32), text: &str) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            0.3,
+            ROW_HEIGHT,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(text, TextStyle::new(26.0).with_color(WHITE)),
+        ).with_static_text()
+    }
+
+    fn add_slider(&self, y: f32, label: &str, value: f32, on_change: fn(&mut settings::Settings, f32)) {
+        self.container.add(Arc::new(RwLock::new(Box::new(Self::label((LABEL_X, y), 0.2, label)))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Slider::new(
+            (SLIDER_X, y + ROW_HEIGHT / 3.0),
+            0.3,
+            ROW_HEIGHT / 3.0,
+            value,
+            Arc::new(Box::new(move |value: f32, game: &Arc<Game>| {
+                let mut settings = game.settings.lock().unwrap();
+                on_change(&mut settings, value);
+                settings.audio.apply(&game.audio);
+                settings.accessibility.apply(&game.renderer);
+                game.camera_controller.lock().unwrap().set_sensitivity(settings.camera_sensitivity);
+            })),
+        )))));
+    }
+
+    // the position of the toggle button in the given slot, the slots are filled row by row
+    fn slot(idx: usize) -> (f32, f32) {
+        (COLUMNS[idx % COLUMNS.len()], 0.42 - (idx / COLUMNS.len()) as f32 * BUTTON_STEP)
+    }
+
+    fn build(&self, game: &Arc<Game>) {
+        let audio = game.settings.lock().unwrap().audio;
+        let slider_y = |row: usize| 0.9 - row as f32 * SLIDER_STEP;
+        self.add_slider(slider_y(0), &i18n::tr("settings.master"), audio.master, |settings, value| settings.audio.master = value);
+        self.add_slider(slider_y(1), &i18n::tr("settings.music"), audio.music, |settings, value| settings.audio.music = value);
+        self.add_slider(slider_y(2), &i18n::tr("settings.effects"), audio.sfx, |settings, value| settings.audio.sfx = value);
+        self.add_slider(slider_y(3), &i18n::tr("settings.voices"), audio.voice, |settings, value| settings.audio.voice = value);
+        let accessibility = game.settings.lock().unwrap().accessibility;
+        // the slider covers the range from the smallest to the largest text scale
+        self.add_slider(slider_y(4), &i18n::tr("settings.text_size"),
+            (accessibility.text_scale - MIN_TEXT_SCALE) / (MAX_TEXT_SCALE - MIN_TEXT_SCALE),
+            |settings, value| settings.accessibility.text_scale = MIN_TEXT_SCALE + value * (MAX_TEXT_SCALE - MIN_TEXT_SCALE));
+        let camera_sensitivity = game.settings.lock().unwrap().camera_sensitivity;
+        self.add_slider(slider_y(5), &i18n::tr("settings.camera_sensitivity"),
+            (camera_sensitivity - MIN_CAMERA_SENSITIVITY) / (MAX_CAMERA_SENSITIVITY - MIN_CAMERA_SENSITIVITY),
+            |settings, value| settings.camera_sensitivity = MIN_CAMERA_SENSITIVITY + value * (MAX_CAMERA_SENSITIVITY - MIN_CAMERA_SENSITIVITY));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(0), &mute_text(audio.muted)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                settings.audio.muted = !settings.audio.muted;
+                settings.audio.apply(&game.audio);
+                button.inner_box.text.texts[0] = mute_text(settings.audio.muted);
+            })),
+            None,
+        )))));
+        let graphics = game.settings.lock().unwrap().graphics;
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(1), &power_saving_text(graphics.redraw_on_demand)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                settings.graphics.redraw_on_demand = !settings.graphics.redraw_on_demand;
+                button.inner_box.text.texts[0] = power_saving_text(settings.graphics.redraw_on_demand);
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(2), &colorblind_text(accessibility.colorblind)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                settings.accessibility.colorblind = !settings.accessibility.colorblind;
+                button.inner_box.text.texts[0] = colorblind_text(settings.accessibility.colorblind);
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(3), &language_text()),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                // cycle through all available languages
+                let languages = i18n::available_languages();
+                let curr = languages.iter().position(|language| *language == i18n::current_language()).unwrap_or(0);
+                let next = languages[(curr + 1) % languages.len()].clone();
+                i18n::load_language(&next);
+                game.settings.lock().unwrap().language = next;
+                game.screen_sys.language_changed();
+            })),
+            None,
+        )))));
+        let window = game.settings.lock().unwrap().window.clone();
+        // the window itself is changed by the event loop, as the screens have no access to it
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(4), &fullscreen_text(window.fullscreen)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                settings.window.fullscreen = !settings.window.fullscreen;
+                button.inner_box.text.texts[0] = fullscreen_text(settings.window.fullscreen);
+                game.window_changed.store(true, Ordering::Release);
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(5), &resolution_text(window.size)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                // sizes which aren't one of the presets continue with the smallest preset
+                let next = settings.window.size.and_then(|size| RESOLUTIONS.iter().position(|resolution| *resolution == size))
+                    .map_or(0, |curr| (curr + 1) % RESOLUTIONS.len());
+                settings.window.size = Some(RESOLUTIONS[next]);
+                button.inner_box.text.texts[0] = resolution_text(settings.window.size);
+                game.window_changed.store(true, Ordering::Release);
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(6), &max_fps_text(graphics.max_fps)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                let curr = FPS_LIMITS.iter().position(|limit| *limit == settings.graphics.max_fps).unwrap_or(0);
+                settings.graphics.max_fps = FPS_LIMITS[(curr + 1) % FPS_LIMITS.len()];
+                button.inner_box.text.texts[0] = max_fps_text(settings.graphics.max_fps);
+            })),
+            None,
+        )))));
+        // the surface is configured with the new present mode right away
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button(Self::slot(7), &vsync_text(graphics.vsync)),
+            Arc::new(Box::new(|button: &mut Button<'_, ()>, game| {
+                let mut settings = game.settings.lock().unwrap();
+                settings.graphics.vsync = !settings.graphics.vsync;
+                settings.graphics.apply(&game.renderer);
+                button.inner_box.text.texts[0] = vsync_text(settings.graphics.vsync);
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.35, 0.02), &i18n::tr("settings.back")),
+            Arc::new(Box::new(|_button: &mut Button<'_, ()>, game| {
+                game.screen_sys.pop_screen();
+            })),
+            None,
+        )))));
+    }
+}
+
+fn mute_text(muted: bool) -> String {
+    if muted {
+        i18n::tr("settings.sound_off")
+    } else {
+        i18n::tr("settings.sound_on")
+    }
+}
+
+fn power_saving_text(on_demand: bool) -> String {
+    if on_demand {
+        i18n::tr("settings.power_saving_on")
+    } else {
+        i18n::tr("settings.power_saving_off")
+    }
+}
+
+fn colorblind_text(colorblind: bool) -> String {
+    if colorblind {
+        i18n::tr("settings.colorblind_on")
+    } else {
+        i18n::tr("settings.colorblind_off")
+    }
+}
+
+fn fullscreen_text(fullscreen: bool) -> String {
+    if fullscreen {
+        i18n::tr("settings.fullscreen_on")
+    } else {
+        i18n::tr("settings.fullscreen_off")
+    }
+}
+
+fn resolution_text(size: Option<(u32, u32)>) -> String {
+    match size {
+        Some((width, height)) => i18n::tr_with("settings.resolution", &[("width", &width.to_string()), ("height", &height.to_string())]),
+        None => i18n::tr("settings.resolution_default"),
+    }
+}
+
+fn vsync_text(vsync: bool) -> String {
+    if vsync {
+        i18n::tr("settings.vsync_on")
+    } else {
+        i18n::tr("settings.vsync_off")
+    }
+}
+
+fn max_fps_text(max_fps: Option<u32>) -> String {
+    match max_fps {
+        Some(fps) => i18n::tr_with("settings.max_fps", &[("fps", &fps.to_string())]),
+        None => i18n::tr("settings.max_fps_off"),
+    }
+}
+
+fn language_text() -> String {
+    i18n::tr_with("settings.language", &[("language", &i18n::tr("language.name"))])
+}
+
+impl Screen for Settings {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.build(game);
+    }
+
+    fn on_language_changed(&mut self, game: &Arc<Game>) {
+        self.container.clear();
+        self.build(game);
+    }
+
+    // the settings are only written to disk once the screen gets closed, so dragging sliders stays cheap
+    fn deinit(&mut self, game: &Arc<Game>) {
+        settings::save_settings(&game.settings.lock().unwrap());
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    fn tick(&mut self, _game: &Arc<Game>) {}
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
@@ -0,0 +1,156 @@
+use crate::screen_sys::Screen;
+use crate::ui::{Button, Color, Coloring, Container, TextBox, TextSection, TextStyle};
+use crate::{Game, i18n};
+use crate::ai;
+use crate::hints;
+use crate::money_popups;
+use crate::engine::Phase;
+use crate::events::GameCommand;
+use std::sync::{Arc, RwLock};
+use wgpu_glyph::{HorizontalAlign, Layout, VerticalAlign};
+use crate::utils::{BACKDROP_UI, DARK_GRAY_UI};
+
+const WHITE: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+
+// asks the player who landed on an income tax tile which of the two amounts they want to pay
+#[derive(Clone)]
+pub struct TaxChoice {
+    container: Arc<Container>,
+    player: usize,
+    flat: usize,
+    percentage: usize,
+}
+
+impl TaxChoice {
+    pub fn new(player: usize, flat: usize, percentage: usize) -> Self {
+        Self {
+            container: Arc::new(Container::new()),
+            player,
+            flat,
+            percentage,
+        }
+    }
+
+    fn button(pos: (f32, f32), label: &str) -> TextBox<'static> {
+        TextBox::new(
+            pos,
+            0.3,
+            0.1,
+            Coloring::Color([DARK_GRAY_UI; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(label, TextStyle::new(30.0).with_color(WHITE)),
+        ).with_static_text()
+    }
+
+    fn build(&self, game: &Arc<Game>) {
+        let player = self.player;
+        let rules = game.engine.lock().unwrap().rules.clone();
+        let rate = rules.income_tax.percentage;
+        self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+            (0.2, 0.6),
+            0.6,
+            0.1,
+            Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+            TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                .with_run(&i18n::tr("tax.title"), TextStyle::new(34.0).with_color(WHITE)),
+        ).with_static_text()))));
+        // the other players of a networked game get their hints on their own machines
+        if game.settings.lock().unwrap().hints && game.is_local(player) {
+            let engine = game.engine.lock().unwrap();
+            if let Some(hint) = ai::hint(&engine, player) {
+                self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+                    (0.2, 0.24),
+                    0.6,
+                    0.08,
+                    Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+                    TextSection::new(Layout::default_single_line().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                        .with_run(&hints::hint_text(&hint, &engine), TextStyle::new(24.0).with_color(WHITE)),
+                ).with_static_text()))));
+            }
+        }
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.15, 0.4), &i18n::tr_with("tax.flat", &[("amount", &rules.currency.format(self.flat))])),
+            Arc::new(Box::new(move |_button: &mut Button<'_, ()>, game| {
+                game.submit(GameCommand::PayTax {
+                    player,
+                    percentage: false,
+                });
+                game.screen_sys.pop_screen();
+            })),
+            None,
+        )))));
+        self.container.add(Arc::new(RwLock::new(Box::new(Button::new(
+            Self::button((0.55, 0.4), &i18n::tr_with("tax.percentage", &[("rate", &rate.to_string()), ("amount", &rules.currency.format(self.percentage))])),
+            Arc::new(Box::new(move |_button: &mut Button<'_, ()>, game| {
+                game.submit(GameCommand::PayTax {
+                    player,
+                    percentage: true,
+                });
+                game.screen_sys.pop_screen();
+            })),
+            None,
+        )))));
+        // shows how each amount is paid with bills, like on the physical board
+        for (x, amount) in [(0.15, self.flat), (0.55, self.percentage)] {
+            if let Some(bills) = money_popups::bills_text(&rules.currency, amount, true) {
+                self.container.add(Arc::new(RwLock::new(Box::new(TextBox::new(
+                    (x, 0.34),
+                    0.3,
+                    0.05,
+                    Coloring::Color([Color { a: 0.0, ..WHITE }; 4]),
+                    TextSection::new(Layout::default_wrap().v_align(VerticalAlign::Center).h_align(HorizontalAlign::Center))
+                        .with_run(&bills, TextStyle::new(18.0).with_color(WHITE)),
+                ).with_static_text()))));
+            }
+        }
+    }
+}
+
+impl Screen for TaxChoice {
+    fn init(&mut self, game: &Arc<Game>) {
+        self.build(game);
+    }
+
+    fn on_language_changed(&mut self, game: &Arc<Game>) {
+        self.container.clear();
+        self.build(game);
+    }
+
+    fn on_active(&mut self, _game: &Arc<Game>) {}
+
+    fn on_deactive(&mut self, _game: &Arc<Game>) {}
+
+    // the decision may have been taken for the player because their time ran out
+    fn tick(&mut self, game: &Arc<Game>) {
+        if !matches!(game.engine.lock().unwrap().phase, Phase::ChoosingTax { .. }) {
+            game.screen_sys.pop_screen();
+        }
+    }
+
+    // the tax has to be paid before the turn can go on
+    fn is_closable(&self) -> bool {
+        false
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn backdrop(&self) -> Option<Color> {
+        Some(BACKDROP_UI)
+    }
+
+    fn container(&self) -> &Arc<Container> {
+        &self.container
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+
+}
@@ -0,0 +1,1158 @@
+use crate::action_cards::{self, Action, ActionCard, CardStack};
+use crate::board::{Board, Tile, TileKind, TILES};
+use crate::engine::{Engine, TurnAction};
+use crate::events::{CommandError, GameCommand};
+use crate::property::MAX_HOUSES;
+use crate::rules::Rules;
+
+/// Drives an engine with scripted dice rolls and decisions, so the rules can be tested without a
+/// window and without depending on the luck of the dice. It uses the default board and cards,
+/// whose prices and rents are all 0 until a test sets them.
+pub struct ScriptedGame {
+    pub engine: Engine,
+}
+
+impl ScriptedGame {
+
+    // the game is started right away
+    pub fn new(players: usize, rules: Rules) -> Self {
+        let mut engine = Engine::new(Board::default(), action_cards::default_cards(), rules, Some(0));
+        for _ in 0..players {
+            engine.apply(GameCommand::AddPlayer {
+                character: 0,
+            }).unwrap();
+        }
+        engine.apply(GameCommand::StartGame).unwrap();
+        Self {
+            engine,
+        }
+    }
+
+    pub fn set_prices(&mut self, property: usize, buy_price: usize, rents: [Option<usize>; 1 + MAX_HOUSES]) {
+        let frame = &mut self.engine.properties[property].frame;
+        frame.buy_price = buy_price;
+        frame.rents = rents;
+    }
+
+    pub fn give(&mut self, player: usize, property: usize) {
+        self.engine.properties[property].owner = Some(player);
+        self.engine.players[player].properties.push(property);
+    }
+
+    // the token is moved without landing on the tile
+    pub fn place(&mut self, player: usize, tile: usize) {
+        self.engine.players[player].position = tile;
+    }
+
+    pub fn set_currency(&mut self, player: usize, currency: usize) {
+        self.engine.players[player].currency = currency;
+    }
+
+    pub fn currency(&self, player: usize) -> usize {
+        self.engine.players[player].currency
+    }
+
+    // the first tile of the kind
+    pub fn tile(&self, kind: TileKind) -> usize {
+        self.engine.board.tiles.iter().position(|tile| tile.kind() == kind).unwrap()
+    }
+
+    // the tile the property is on
+    pub fn property_tile(&self, property: usize) -> usize {
+        self.engine.board.tiles.iter()
+            .position(|tile| matches!(tile, Tile::Property { property: frame } if frame.id == property))
+            .unwrap()
+    }
+
+    pub fn apply(&mut self, command: GameCommand) -> Result<(), CommandError> {
+        self.engine.apply(command)
+    }
+
+    pub fn roll(&mut self, player: usize, dice: (u8, u8)) -> Result<(), CommandError> {
+        self.engine.script_rolls([dice]);
+        self.apply(GameCommand::RollDice {
+            player,
+        })
+    }
+
+    pub fn buy(&mut self, player: usize) -> Result<(), CommandError> {
+        self.apply(GameCommand::BuyProperty {
+            player,
+        })
+    }
+
+    pub fn decline(&mut self, player: usize) -> Result<(), CommandError> {
+        self.apply(GameCommand::DeclineProperty {
+            player,
+        })
+    }
+
+    pub fn bid(&mut self, player: usize, property: usize, amount: usize) -> Result<(), CommandError> {
+        self.apply(GameCommand::Bid {
+            player,
+            property,
+            amount,
+        })
+    }
+
+    pub fn pass(&mut self, player: usize) -> Result<(), CommandError> {
+        self.apply(GameCommand::PassBid {
+            player,
+        })
+    }
+
+    pub fn end_turn(&mut self, player: usize) -> Result<(), CommandError> {
+        self.apply(GameCommand::EndTurn {
+            player,
+        })
+    }
+
+    // takes the action the way a human player does through the HUD, `choice` picks one of its commands
+    pub fn act(&mut self, player: usize, action: TurnAction, choice: usize) -> Result<(), CommandError> {
+        assert!(self.engine.legal_actions(player).contains(&action), "{:?} isn't legal for player {}", action, player);
+        let command = self.engine.action_commands(player, action).swap_remove(choice);
+        self.apply(command)
+    }
+
+    // every card which is drawn from now on has the action
+    pub fn rig_cards(&mut self, action: Action) {
+        self.engine.cards = vec![ActionCard {
+            text: String::new(),
+            action,
+        }];
+        self.engine.card_stacks = [CardStack::new(vec![0]), CardStack::new(vec![0])];
+    }
+
+    // the player rolls onto a card tile without passing the start tile
+    pub fn draw_card(&mut self, player: usize) {
+        let tile = (3..TILES).find(|tile| self.engine.board.tiles[*tile].kind() == TileKind::DrawCard).unwrap();
+        self.place(player, tile - 3);
+        self.roll(player, (1, 2)).unwrap();
+    }
+
+    // the player's turn passes without anything happening, they're just visiting the jail
+    pub fn skip_turn(&mut self, player: usize) {
+        self.place(player, self.tile(TileKind::Jail) - 3);
+        self.roll(player, (1, 2)).unwrap();
+        self.end_turn(player).unwrap();
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Phase, INITIAL_CURRENCY, JAIL_FINE};
+    use crate::events::{Emote, GameEvent, Immunity, Trade};
+    use crate::action_cards::NearestKind;
+    use crate::ai;
+    use crate::property::PROPERTIES;
+    use crate::rules::{FreeParkingRules, PropertySetup};
+    use crate::save::SaveGame;
+    use crate::statistics::{self, PlayerStatistics};
+    use std::time::Duration;
+
+    // rents for a property without houses, with 1-4 houses and with a hotel
+    const RENTS: [Option<usize>; 1 + MAX_HOUSES] = [Some(4), Some(20), Some(60), Some(180), Some(320), Some(450)];
+    // the dark blue group, the first property is on tile 1 and the second one on tile 3
+    const DARK_BLUE: [usize; 2] = [0, 1];
+    const STATION: usize = 2;
+    const OTHER_STATION: usize = 10;
+    const SPECIAL: usize = 7;
+    // a group of three properties
+    const LIGHT_BLUE: usize = 3;
+
+    fn game(players: usize) -> ScriptedGame {
+        let mut game = ScriptedGame::new(players, Rules::default());
+        for property in DARK_BLUE {
+            game.set_prices(property, 60, RENTS);
+        }
+        game
+    }
+
+    // the player lands on the go to jail tile and ends their turn
+    fn jail(game: &mut ScriptedGame, player: usize) {
+        game.place(player, game.tile(TileKind::GoToJail) - 3);
+        game.roll(player, (1, 2)).unwrap();
+        game.end_turn(player).unwrap();
+    }
+
+    #[test]
+    fn scripted_rolls_move_the_token() {
+        let mut game = game(2);
+        game.roll(0, (2, 1)).unwrap();
+        assert_eq!(game.engine.players[0].position, game.property_tile(DARK_BLUE[1]));
+        assert_eq!(game.engine.phase, Phase::Buying { property: DARK_BLUE[1] });
+    }
+
+    #[test]
+    fn only_the_current_player_may_roll_once() {
+        let mut game = game(2);
+        assert_eq!(game.roll(1, (1, 2)), Err(CommandError::NotYourTurn));
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.roll(0, (1, 2)), Err(CommandError::WrongPhase));
+    }
+
+    #[test]
+    fn legal_actions_follow_the_phase() {
+        let mut game = game(2);
+        assert_eq!(game.engine.legal_actions(0), vec![TurnAction::Roll, TurnAction::Manage, TurnAction::OfferTrade]);
+        assert_eq!(game.engine.legal_actions(1), vec![]);
+        game.roll(0, (2, 1)).unwrap();
+        assert_eq!(game.engine.legal_actions(0), vec![TurnAction::Buy]);
+        game.decline(0).unwrap();
+        assert_eq!(game.engine.legal_actions(0), vec![TurnAction::EndTurn, TurnAction::Manage, TurnAction::OfferTrade]);
+    }
+
+    #[test]
+    fn computer_players_take_over_the_decisions_of_abandoned_players() {
+        let mut game = game(2);
+        assert_eq!(ai::substitute_command(&game.engine, 1), None);
+        game.engine.script_rolls([(2, 1)]);
+        let roll = ai::substitute_command(&game.engine, 0).unwrap();
+        assert_eq!(roll, GameCommand::RollDice { player: 0 });
+        game.apply(roll).unwrap();
+        game.decline(0).unwrap();
+        game.apply(GameCommand::OfferTrade {
+            trade: Trade {
+                from: 0,
+                to: 1,
+                offered_money: 100,
+                ..Trade::default()
+            },
+        }).unwrap();
+        // only the answer to the trade is awaited, a gift is accepted
+        assert_eq!(ai::substitute_command(&game.engine, 0), None);
+        assert_eq!(ai::substitute_command(&game.engine, 1), Some(GameCommand::AcceptTrade { player: 1 }));
+    }
+
+    #[test]
+    fn trades_are_declined_for_the_receiver_once_their_time_is_up() {
+        let mut game = ScriptedGame::new(2, Rules {
+            decision_timer: Some(30),
+            ..Rules::default()
+        });
+        game.roll(0, (2, 1)).unwrap();
+        game.decline(0).unwrap();
+        game.apply(GameCommand::OfferTrade {
+            trade: Trade {
+                from: 0,
+                to: 1,
+                offered_money: 100,
+                ..Trade::default()
+            },
+        }).unwrap();
+        game.engine.take_events();
+        game.engine.tick(Duration::from_secs(29));
+        assert!(game.engine.pending_trade.is_some());
+        game.engine.tick(Duration::from_secs(1));
+        assert!(game.engine.pending_trade.is_none());
+        let events = game.engine.take_events();
+        assert!(events.contains(&GameEvent::DecisionTimedOut { player: 1 }));
+        assert!(events.iter().any(|event| matches!(event, GameEvent::TradeDeclined { .. })));
+        assert_eq!(game.currency(1), INITIAL_CURRENCY);
+    }
+
+    #[test]
+    fn jail_free_cards_are_traded_for_the_fine_they_save() {
+        let mut game = game(2);
+        game.engine.players[0].jail_free_cards = 1;
+        game.roll(0, (2, 1)).unwrap();
+        game.decline(0).unwrap();
+        let offer = |requested_money| GameCommand::OfferTrade {
+            trade: Trade {
+                from: 0,
+                to: 1,
+                offered_jail_free_cards: 1,
+                requested_money,
+                ..Trade::default()
+            },
+        };
+        game.apply(offer(JAIL_FINE)).unwrap();
+        assert_eq!(ai::substitute_command(&game.engine, 1), Some(GameCommand::DeclineTrade { player: 1 }));
+        game.apply(GameCommand::DeclineTrade { player: 1 }).unwrap();
+        game.apply(offer(JAIL_FINE - 1)).unwrap();
+        assert_eq!(ai::substitute_command(&game.engine, 1), Some(GameCommand::AcceptTrade { player: 1 }));
+        game.apply(GameCommand::AcceptTrade { player: 1 }).unwrap();
+        assert_eq!(game.engine.players[0].jail_free_cards, 0);
+        assert_eq!(game.engine.players[1].jail_free_cards, 1);
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - (JAIL_FINE - 1));
+    }
+
+    #[test]
+    fn immunities_are_traded_for_the_rent_they_waive_and_used_up_by_landing() {
+        let mut game = game(2);
+        game.give(0, DARK_BLUE[1]);
+        game.roll(0, (2, 1)).unwrap();
+        // two landings on the dark blue property waive a rent of 4 each
+        game.apply(GameCommand::OfferTrade {
+            trade: Trade {
+                from: 0,
+                to: 1,
+                offered_immunities: vec![Immunity { property: DARK_BLUE[1], landings: 2 }],
+                requested_money: 7,
+                ..Trade::default()
+            },
+        }).unwrap();
+        assert_eq!(ai::substitute_command(&game.engine, 1), Some(GameCommand::AcceptTrade { player: 1 }));
+        game.apply(GameCommand::AcceptTrade { player: 1 }).unwrap();
+        game.end_turn(0).unwrap();
+
+        game.place(1, 0);
+        game.roll(1, (1, 2)).unwrap();
+        assert!(game.engine.take_events().contains(&GameEvent::RentWaived { player: 1, property: DARK_BLUE[1] }));
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 7);
+        assert_eq!(game.engine.immunities.len(), 1);
+        assert_eq!(game.engine.immunities[0].landings, 1);
+        game.end_turn(1).unwrap();
+        game.skip_turn(0);
+
+        game.place(1, 0);
+        game.roll(1, (1, 2)).unwrap();
+        assert!(game.engine.immunities.is_empty());
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 7);
+        game.end_turn(1).unwrap();
+        game.skip_turn(0);
+
+        // the immunity is used up, so the rent is due again
+        game.place(1, 0);
+        game.roll(1, (1, 2)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 7 - RENTS[0].unwrap());
+    }
+
+    #[test]
+    fn immunities_end_when_the_property_changes_hands() {
+        let mut game = game(3);
+        game.give(0, DARK_BLUE[1]);
+        game.roll(0, (2, 1)).unwrap();
+        game.apply(GameCommand::OfferTrade {
+            trade: Trade {
+                from: 0,
+                to: 1,
+                offered_immunities: vec![Immunity { property: DARK_BLUE[1], landings: 3 }],
+                ..Trade::default()
+            },
+        }).unwrap();
+        game.apply(GameCommand::AcceptTrade { player: 1 }).unwrap();
+        assert_eq!(game.engine.immunities.len(), 1);
+        // the new owner didn't promise anything
+        game.apply(GameCommand::OfferTrade {
+            trade: Trade {
+                from: 0,
+                to: 2,
+                offered_properties: vec![DARK_BLUE[1]],
+                ..Trade::default()
+            },
+        }).unwrap();
+        game.apply(GameCommand::AcceptTrade { player: 2 }).unwrap();
+        assert!(game.engine.immunities.is_empty());
+    }
+
+    #[test]
+    fn a_human_turn_is_played_through_the_legal_actions() {
+        let mut game = game(2);
+        game.engine.players[0].jail_free_cards = 1;
+        jail(&mut game, 0);
+        game.skip_turn(1);
+
+        // the jail free card comes first, paying the fine second
+        game.act(0, TurnAction::LeaveJail, 0).unwrap();
+        assert!(!game.engine.players[0].is_jailed());
+        assert_eq!(game.engine.players[0].jail_free_cards, 0);
+        game.place(0, game.property_tile(DARK_BLUE[1]) - 3);
+        game.engine.script_rolls([(2, 1)]);
+        game.act(0, TurnAction::Roll, 0).unwrap();
+        assert_eq!(game.engine.phase, Phase::Buying { property: DARK_BLUE[1] });
+        game.act(0, TurnAction::Buy, 0).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[1]].owner, Some(0));
+        game.act(0, TurnAction::EndTurn, 0).unwrap();
+
+        game.place(1, (game.property_tile(DARK_BLUE[0]) + TILES - 3) % TILES);
+        game.engine.script_rolls([(2, 1)]);
+        game.act(1, TurnAction::Roll, 0).unwrap();
+        game.act(1, TurnAction::Buy, 1).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].owner, None);
+        game.apply(GameCommand::OfferTrade {
+            trade: Trade {
+                from: 1,
+                to: 0,
+                offered_money: 100,
+                requested_properties: vec![DARK_BLUE[1]],
+                ..Trade::default()
+            },
+        }).unwrap();
+        game.act(0, TurnAction::AnswerTrade, 0).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[1]].owner, Some(1));
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 60 + 100);
+        game.act(1, TurnAction::EndTurn, 0).unwrap();
+        assert_eq!(game.engine.curr_player, 0);
+    }
+
+    #[test]
+    fn auctions_are_played_through_the_legal_actions() {
+        let mut game = game(2);
+        let light_blue = shortage(&mut game);
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        // the lowest bid is the house's price, afterwards it has to be raised by one
+        game.act(0, TurnAction::Bid, 0).unwrap();
+        assert_eq!(game.engine.auction.as_ref().unwrap().highest, Some((0, DARK_BLUE[0], game.engine.house_price(DARK_BLUE[0]))));
+        game.act(1, TurnAction::Bid, 0).unwrap();
+        let price = game.engine.house_price(light_blue[0]).max(game.engine.house_price(DARK_BLUE[0]) + 1);
+        assert_eq!(game.engine.auction.as_ref().unwrap().highest, Some((1, light_blue[0], price)));
+        // passing is always the last choice
+        game.act(0, TurnAction::Bid, 1).unwrap();
+        assert_eq!(game.engine.properties[light_blue[0]].houses, 1);
+        assert_eq!(game.engine.legal_actions(0), vec![TurnAction::Roll, TurnAction::Manage, TurnAction::OfferTrade]);
+    }
+
+    #[test]
+    fn players_may_emote_during_the_turns_of_others() {
+        let mut game = game(2);
+        game.engine.take_events();
+        game.apply(GameCommand::Emote { player: 1, emote: Emote::Hello }).unwrap();
+        assert_eq!(game.engine.take_events(), vec![GameEvent::Emoted { player: 1, emote: Emote::Hello }]);
+    }
+
+    #[test]
+    fn buying_and_declining() {
+        let mut game = game(2);
+        game.roll(0, (2, 1)).unwrap();
+        game.buy(0).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[1]].owner, Some(0));
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 60);
+        game.end_turn(0).unwrap();
+
+        game.roll(1, (2, 3)).unwrap();
+        game.decline(1).unwrap();
+        assert_eq!(game.engine.properties[STATION].owner, None);
+        assert_eq!(game.engine.phase, Phase::Acting);
+    }
+
+    #[test]
+    fn buying_requires_enough_money() {
+        let mut game = game(2);
+        game.set_currency(0, 59);
+        game.roll(0, (2, 1)).unwrap();
+        assert_eq!(game.buy(0), Err(CommandError::NotEnoughMoney));
+        assert_eq!(game.engine.properties[DARK_BLUE[1]].owner, None);
+    }
+
+    #[test]
+    fn rent_is_paid_to_the_owner() {
+        let mut game = game(2);
+        game.give(0, DARK_BLUE[1]);
+        game.skip_turn(0);
+        game.roll(1, (2, 1)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 4);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 4);
+    }
+
+    #[test]
+    fn owning_the_whole_group_doubles_the_rent() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        game.skip_turn(0);
+        game.roll(1, (2, 1)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 8);
+    }
+
+    #[test]
+    fn houses_raise_the_rent() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[1] }).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[1]].houses, 1);
+        // houses cost half of the property's price
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 30);
+        game.skip_turn(0);
+        game.roll(1, (2, 1)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 20);
+    }
+
+    #[test]
+    fn houses_are_built_evenly() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[1] }).unwrap();
+        assert_eq!(game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[1] }), Err(CommandError::UnevenBuilding));
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[1] }).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[1]].houses, 2);
+    }
+
+    #[test]
+    fn houses_require_the_whole_group() {
+        let mut game = game(2);
+        game.give(0, DARK_BLUE[1]);
+        assert_eq!(game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[1] }), Err(CommandError::CannotBuild));
+    }
+
+    // both players own a whole group they can build on while the bank has a single house left
+    fn shortage(game: &mut ScriptedGame) -> Vec<usize> {
+        let light_blue = game.engine.group(LIGHT_BLUE);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        for property in light_blue.iter().copied() {
+            game.set_prices(property, 100, RENTS);
+            game.give(1, property);
+        }
+        game.engine.rules.buildings.houses = 1;
+        light_blue
+    }
+
+    #[test]
+    fn houses_are_built_right_away_while_the_bank_has_enough() {
+        let mut game = game(2);
+        shortage(&mut game);
+        game.engine.rules.buildings.houses = 2;
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].houses, 1);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+    }
+
+    #[test]
+    fn a_shortage_auctions_the_last_house() {
+        let mut game = game(2);
+        shortage(&mut game);
+        let price = game.engine.house_price(DARK_BLUE[0]);
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        assert_eq!(game.engine.phase, Phase::Auction);
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].houses, 0);
+        assert_eq!(game.bid(0, DARK_BLUE[0], price - 1), Err(CommandError::BidTooLow));
+        game.bid(0, DARK_BLUE[0], price + 5).unwrap();
+        game.pass(1).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].houses, 1);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - price - 5);
+        assert_eq!(game.engine.buildings_left(false), 0);
+    }
+
+    #[test]
+    fn the_other_player_may_win_the_shortage_auction() {
+        let mut game = game(2);
+        let light_blue = shortage(&mut game);
+        let price = game.engine.house_price(light_blue[0]);
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        game.pass(0).unwrap();
+        game.bid(1, light_blue[0], price).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].houses, 0);
+        assert_eq!(game.engine.properties[light_blue[0]].houses, 1);
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - price);
+    }
+
+    #[test]
+    fn a_shortage_auction_before_the_roll_continues_with_the_roll() {
+        let mut game = game(2);
+        shortage(&mut game);
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        game.pass(0).unwrap();
+        game.pass(1).unwrap();
+        assert!(game.engine.take_events().iter().any(|event| matches!(event, GameEvent::BuildingAuctionCancelled)));
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].houses, 0);
+        // the turn can't be ended without rolling
+        assert_eq!(game.engine.phase, Phase::Rolling);
+        assert_eq!(game.end_turn(0), Err(CommandError::WrongPhase));
+        game.roll(0, (2, 1)).unwrap();
+        game.end_turn(0).unwrap();
+        assert_eq!(game.engine.curr_player, 1);
+    }
+
+    #[test]
+    fn a_shortage_auction_after_the_roll_continues_with_acting() {
+        let mut game = game(2);
+        shortage(&mut game);
+        game.skip_turn(0);
+        game.skip_turn(1);
+        game.place(0, game.tile(TileKind::Jail) - 3);
+        game.roll(0, (1, 2)).unwrap();
+        game.apply(GameCommand::BuildHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        game.pass(1).unwrap();
+        game.pass(0).unwrap();
+        assert_eq!(game.engine.phase, Phase::Acting);
+        game.end_turn(0).unwrap();
+    }
+
+    #[test]
+    fn station_rent_doubles_with_every_station() {
+        let mut game = game(2);
+        for station in [STATION, OTHER_STATION] {
+            game.set_prices(station, 200, [Some(25), None, None, None, None, None]);
+            game.give(0, station);
+        }
+        game.skip_turn(0);
+        game.place(1, game.property_tile(STATION) - 5);
+        game.roll(1, (2, 3)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 50);
+    }
+
+    #[test]
+    fn special_rent_scales_with_the_roll() {
+        let mut game = game(2);
+        game.set_prices(SPECIAL, 150, [Some(2), None, None, None, None, None]);
+        game.give(0, SPECIAL);
+        game.skip_turn(0);
+        game.place(1, game.property_tile(SPECIAL) - 9);
+        game.roll(1, (4, 5)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 18);
+    }
+
+    #[test]
+    fn mortgaged_properties_yield_no_rent() {
+        let mut game = game(2);
+        game.give(0, DARK_BLUE[1]);
+        game.apply(GameCommand::Mortgage { player: 0, property: DARK_BLUE[1] }).unwrap();
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 30);
+        game.skip_turn(0);
+        game.roll(1, (2, 1)).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY);
+    }
+
+    #[test]
+    fn passing_the_start_tile_is_rewarded() {
+        let mut game = game(2);
+        game.place(0, 38);
+        game.roll(0, (2, 1)).unwrap();
+        assert_eq!(game.engine.players[0].position, 1);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 40);
+    }
+
+    #[test]
+    fn the_go_to_jail_tile_sends_to_jail() {
+        let mut game = game(2);
+        jail(&mut game, 0);
+        assert_eq!(game.engine.players[0].position, game.tile(TileKind::Jail));
+        // the start bonus isn't paid on the way to jail
+        assert_eq!(game.currency(0), INITIAL_CURRENCY);
+    }
+
+    #[test]
+    fn doubles_release_from_jail() {
+        let mut game = game(2);
+        let jail_tile = game.tile(TileKind::Jail);
+        jail(&mut game, 0);
+        game.skip_turn(1);
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.engine.players[0].position, jail_tile);
+        game.end_turn(0).unwrap();
+        game.skip_turn(1);
+        game.roll(0, (3, 3)).unwrap();
+        assert_eq!(game.engine.players[0].position, jail_tile + 6);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY);
+    }
+
+    #[test]
+    fn doubles_let_the_player_roll_again() {
+        let mut game = game(2);
+        game.place(0, game.tile(TileKind::Jail) - 2);
+        game.roll(0, (1, 1)).unwrap();
+        game.end_turn(0).unwrap();
+        assert_eq!(game.engine.curr_player, 0);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+    }
+
+    #[test]
+    fn three_doubles_in_a_row_send_the_player_to_jail() {
+        let mut game = game(2);
+        let jail_tile = game.tile(TileKind::Jail);
+        for _ in 0..2 {
+            game.place(0, jail_tile - 2);
+            game.roll(0, (1, 1)).unwrap();
+            game.end_turn(0).unwrap();
+        }
+        game.place(0, jail_tile - 4);
+        game.roll(0, (2, 2)).unwrap();
+        assert_eq!(game.engine.players[0].position, jail_tile);
+        assert!(game.engine.players[0].jail_free_throws > 0);
+        game.end_turn(0).unwrap();
+        assert_eq!(game.engine.curr_player, 1);
+    }
+
+    #[test]
+    fn the_fine_is_paid_after_three_failed_rolls() {
+        let mut game = game(2);
+        let jail_tile = game.tile(TileKind::Jail);
+        jail(&mut game, 0);
+        game.skip_turn(1);
+        for _ in 0..2 {
+            game.roll(0, (1, 2)).unwrap();
+            assert_eq!(game.engine.players[0].position, jail_tile);
+            game.end_turn(0).unwrap();
+            game.skip_turn(1);
+        }
+        game.roll(0, (1, 3)).unwrap();
+        assert_eq!(game.engine.players[0].position, jail_tile + 4);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 10);
+    }
+
+    #[test]
+    fn paying_bail_lets_the_roll_move_the_token() {
+        let mut game = game(2);
+        let jail_tile = game.tile(TileKind::Jail);
+        jail(&mut game, 0);
+        game.skip_turn(1);
+        game.apply(GameCommand::PayBail { player: 0 }).unwrap();
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 10);
+        assert_eq!(game.apply(GameCommand::PayBail { player: 0 }), Err(CommandError::NotJailed));
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.engine.players[0].position, jail_tile + 3);
+    }
+
+    #[test]
+    fn jail_free_cards_can_be_used_before_rolling() {
+        let mut game = game(2);
+        jail(&mut game, 0);
+        game.skip_turn(1);
+        assert_eq!(game.apply(GameCommand::UseJailFreeCard { player: 0 }), Err(CommandError::NoJailFreeCard));
+        game.engine.players[0].jail_free_cards = 1;
+        game.apply(GameCommand::UseJailFreeCard { player: 0 }).unwrap();
+        assert!(!game.engine.players[0].is_jailed());
+        assert_eq!(game.engine.players[0].jail_free_cards, 0);
+    }
+
+    #[test]
+    fn rolling_in_jail_keeps_the_jail_free_card() {
+        let mut game = game(2);
+        let jail_tile = game.tile(TileKind::Jail);
+        game.engine.players[0].jail_free_cards = 1;
+        jail(&mut game, 0);
+        game.skip_turn(1);
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.engine.players[0].position, jail_tile);
+        assert!(game.engine.players[0].is_jailed());
+        assert_eq!(game.engine.players[0].jail_free_cards, 1);
+    }
+
+    #[test]
+    fn cards_moving_past_the_start_tile_pay_the_bonus() {
+        let mut game = game(2);
+        game.rig_cards(Action::MoveAbsolute { tile: 1 });
+        game.draw_card(0);
+        assert_eq!(game.engine.players[0].position, 1);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 40);
+        assert_eq!(game.engine.phase, Phase::Buying { property: DARK_BLUE[0] });
+    }
+
+    #[test]
+    fn distributed_currency_is_collected_from_every_other_player() {
+        let mut game = game(3);
+        game.rig_cards(Action::DistributeCurrency { amount: 10 });
+        game.draw_card(0);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 20);
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 10);
+        assert_eq!(game.currency(2), INITIAL_CURRENCY - 10);
+    }
+
+    #[test]
+    fn waiting_players_skip_their_turns() {
+        let mut game = game(2);
+        game.rig_cards(Action::Wait { rounds: 1 });
+        game.draw_card(0);
+        game.end_turn(0).unwrap();
+        game.skip_turn(1);
+        assert_eq!(game.engine.curr_player, 1);
+        game.skip_turn(1);
+        assert_eq!(game.engine.curr_player, 0);
+    }
+
+    #[test]
+    fn cards_move_to_the_nearest_station() {
+        let mut game = game(2);
+        game.rig_cards(Action::MoveToNearest { kind: NearestKind::Station });
+        game.draw_card(0);
+        assert_eq!(game.engine.players[0].position, game.property_tile(OTHER_STATION));
+        assert_eq!(game.currency(0), INITIAL_CURRENCY);
+    }
+
+    #[test]
+    fn repairs_are_paid_per_house_and_hotel() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        game.engine.properties[DARK_BLUE[0]].houses = 2;
+        game.engine.properties[DARK_BLUE[1]].houses = MAX_HOUSES;
+        game.rig_cards(Action::PayPerHouse { house: 25, hotel: 100 });
+        game.draw_card(0);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 150);
+    }
+
+    #[test]
+    fn the_bank_pays_for_every_property() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        game.rig_cards(Action::CollectFromBankPerProperty { amount: 10 });
+        game.draw_card(0);
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 20);
+    }
+
+    #[test]
+    fn advancing_to_the_start_tile_depends_on_the_distance() {
+        let mut game = game(2);
+        game.rig_cards(Action::AdvanceToStartIf { within: 10 });
+        game.draw_card(0);
+        let card_tile = game.engine.players[0].position;
+        assert_ne!(card_tile, game.tile(TileKind::Start));
+        game.end_turn(0).unwrap();
+        game.rig_cards(Action::AdvanceToStartIf { within: TILES });
+        game.draw_card(1);
+        assert_eq!(game.engine.players[1].position, game.tile(TileKind::Start));
+        assert_eq!(game.currency(1), INITIAL_CURRENCY + 40);
+    }
+
+    #[test]
+    fn bankruptcy_to_a_player_ends_a_two_player_game() {
+        let mut game = game(2);
+        game.give(0, DARK_BLUE[1]);
+        game.give(1, DARK_BLUE[0]);
+        game.skip_turn(0);
+        game.set_currency(1, 3);
+        game.roll(1, (2, 1)).unwrap();
+        assert!(game.engine.players[1].bankrupt);
+        // the creditor gets the money that was left along with the properties
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 3);
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].owner, Some(0));
+        assert_eq!(game.engine.phase, Phase::Finished);
+        assert_eq!(game.engine.winner(), Some(0));
+    }
+
+    #[test]
+    fn bankruptcy_ends_a_game_played_alone() {
+        let mut game = game(1);
+        let tax = game.tile(TileKind::Pay);
+        game.engine.board.tiles[tax] = Tile::Pay {
+            name: "Tax".to_string(),
+            amount: 500,
+            income_tax: false,
+        };
+        // the only player keeps on taking their turns
+        game.skip_turn(0);
+        assert_eq!(game.engine.curr_player, 0);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+        game.set_currency(0, 10);
+        game.place(0, tax - 3);
+        game.roll(0, (1, 2)).unwrap();
+        assert!(game.engine.players[0].bankrupt);
+        assert_eq!(game.engine.phase, Phase::Finished);
+        assert_eq!(game.engine.winner(), None);
+    }
+
+    #[test]
+    fn bankruptcy_to_the_bank_auctions_the_properties() {
+        let mut game = game(3);
+        let tax = game.engine.board.tiles.iter()
+            .position(|tile| matches!(tile, Tile::Pay { income_tax: false, .. }))
+            .unwrap();
+        game.engine.board.tiles[tax] = Tile::Pay {
+            name: "Tax".to_string(),
+            amount: 500,
+            income_tax: false,
+        };
+        game.give(0, DARK_BLUE[0]);
+        game.place(0, tax - 2);
+        game.roll(0, (1, 1)).unwrap();
+        assert!(game.engine.players[0].bankrupt);
+        assert_eq!(game.engine.phase, Phase::Auction);
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].owner, None);
+
+        assert_eq!(game.bid(1, DARK_BLUE[0], 0), Err(CommandError::BidTooLow));
+        game.bid(1, DARK_BLUE[0], 10).unwrap();
+        game.bid(2, DARK_BLUE[0], 15).unwrap();
+        assert_eq!(game.bid(1, DARK_BLUE[0], 15), Err(CommandError::BidTooLow));
+        game.pass(1).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].owner, Some(2));
+        assert_eq!(game.currency(2), INITIAL_CURRENCY - 15);
+        // the game goes on with the next player who is still in it
+        assert_eq!(game.engine.curr_player, 1);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+    }
+
+    #[test]
+    fn the_turn_of_a_bankrupt_player_ends_by_itself() {
+        let mut game = game(3);
+        game.give(1, DARK_BLUE[1]);
+        game.set_currency(0, 3);
+        game.roll(0, (2, 1)).unwrap();
+        assert!(game.engine.players[0].bankrupt);
+        assert!(game.engine.legal_actions(0).is_empty());
+        // nothing had to be auctioned, so the next player is up right away
+        assert_eq!(game.engine.curr_player, 1);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+        assert_eq!(game.end_turn(0), Err(CommandError::NotYourTurn));
+    }
+
+    #[test]
+    fn auctions_without_bids_leave_the_property_with_the_bank() {
+        let mut game = game(3);
+        let tax = game.tile(TileKind::Pay);
+        game.engine.board.tiles[tax] = Tile::Pay {
+            name: "Tax".to_string(),
+            amount: 500,
+            income_tax: true,
+        };
+        game.give(0, DARK_BLUE[0]);
+        game.roll(0, (2, 2)).unwrap();
+        game.apply(GameCommand::PayTax { player: 0, percentage: false }).unwrap();
+        assert!(game.engine.players[0].bankrupt);
+        game.pass(1).unwrap();
+        game.pass(2).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].owner, None);
+        assert!(game.engine.take_events().iter().any(|event| matches!(event, GameEvent::PropertyAuctionCancelled { .. })));
+    }
+
+    #[test]
+    fn saved_games_continue_where_they_left_off() {
+        let mut game = game(2);
+        game.give(1, DARK_BLUE[1]);
+        game.engine.players[0].jail_free_cards = 1;
+        game.roll(0, (1, 2)).unwrap();
+        let save = serde_json::to_string(&SaveGame::new(&game.engine)).unwrap();
+        let mut restored = ScriptedGame {
+            engine: serde_json::from_str::<SaveGame>(&save).unwrap().restore().unwrap(),
+        };
+        assert_eq!(restored.engine.phase, game.engine.phase);
+        assert_eq!(restored.engine.players[0].position, game.engine.players[0].position);
+        assert_eq!(restored.currency(0), INITIAL_CURRENCY - 4);
+        assert_eq!(restored.engine.players[0].jail_free_cards, 1);
+        assert_eq!(restored.engine.properties[DARK_BLUE[1]].owner, Some(1));
+        restored.end_turn(0).unwrap();
+        restored.skip_turn(1);
+        assert_eq!(restored.engine.curr_player, 0);
+    }
+
+    #[test]
+    fn saves_with_missing_properties_are_rejected() {
+        let game = game(2);
+        let mut save = serde_json::to_value(&SaveGame::new(&game.engine)).unwrap();
+        // the board loses a property tile while the saved properties are kept, so they no longer match
+        let start = save["tiles"][0].clone();
+        save["tiles"][1] = start;
+        assert!(serde_json::from_value::<SaveGame>(save).unwrap().restore().is_none());
+    }
+
+    #[test]
+    fn rematches_are_played_on_a_new_seed_with_the_same_rules() {
+        let mut game = game(2);
+        game.engine.rules.buildings.houses = 7;
+        game.engine.tile_scripts.insert("portal".to_string(), String::new());
+        game.roll(0, (2, 1)).unwrap();
+        let rematch = game.engine.rematch();
+        assert_ne!(rematch.seed, game.engine.seed);
+        assert_eq!(rematch.phase, Phase::Lobby);
+        assert!(rematch.players.is_empty());
+        assert_eq!(rematch.rules.buildings.houses, 7);
+        assert!(rematch.tile_scripts.contains_key("portal"));
+        assert_eq!(rematch.properties.len(), game.engine.properties.len());
+    }
+
+    #[test]
+    fn players_are_ranked_by_their_net_worth() {
+        let mut game = game(3);
+        game.set_currency(0, 100);
+        game.set_currency(1, 300);
+        game.set_currency(2, 200);
+        game.give(0, DARK_BLUE[0]);
+        assert_eq!(game.engine.ranking(), vec![1, 2, 0]);
+        game.set_prices(DARK_BLUE[0], 250, RENTS);
+        assert_eq!(game.engine.ranking(), vec![0, 1, 2]);
+        game.engine.players[0].bankrupt = true;
+        assert_eq!(game.engine.ranking(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn statistics_count_rent_jail_and_purchases() {
+        let mut game = game(3);
+        game.roll(0, (2, 1)).unwrap();
+        game.buy(0).unwrap();
+        game.end_turn(0).unwrap();
+        jail(&mut game, 1);
+        // from the start tile onto the property player 0 bought
+        game.place(2, game.property_tile(DARK_BLUE[1]) - 3);
+        game.roll(2, (1, 2)).unwrap();
+        let statistics = statistics::player_statistics(&game.engine.take_events(), 3);
+        assert_eq!(statistics, vec![
+            PlayerStatistics { rent_collected: 4, times_jailed: 0, properties_bought: 1 },
+            PlayerStatistics { rent_collected: 0, times_jailed: 1, properties_bought: 0 },
+            PlayerStatistics::default(),
+        ]);
+    }
+
+    #[test]
+    fn the_parking_pot_is_seeded_and_only_collects_what_the_rules_allow() {
+        let mut game = ScriptedGame::new(2, Rules {
+            free_parking: FreeParkingRules {
+                enabled: true,
+                seed: 50,
+                collect_card_fines: false,
+                collect_taxes: true,
+            },
+            ..Rules::default()
+        });
+        assert_eq!(game.engine.parking_pot, 50);
+        let tax = game.tile(TileKind::Pay);
+        game.engine.board.tiles[tax] = Tile::Pay {
+            name: "Tax".to_string(),
+            amount: 100,
+            income_tax: false,
+        };
+        game.place(0, tax - 3);
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.engine.parking_pot, 150);
+        game.end_turn(0).unwrap();
+
+        // card fines go to the bank
+        game.rig_cards(Action::DirectCurrency { amount: -30 });
+        game.draw_card(1);
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 30);
+        assert_eq!(game.engine.parking_pot, 150);
+        game.end_turn(1).unwrap();
+
+        // the bank refills the pot once it's won
+        game.place(0, game.tile(TileKind::Parking) - 3);
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - 100 + 150);
+        assert_eq!(game.engine.parking_pot, 50);
+    }
+
+    #[test]
+    fn houses_are_sold_evenly_before_the_group_can_be_mortgaged() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+        }
+        game.engine.properties[DARK_BLUE[0]].houses = 2;
+        game.engine.properties[DARK_BLUE[1]].houses = 1;
+        assert_eq!(game.apply(GameCommand::SellHouse { player: 0, property: DARK_BLUE[1] }), Err(CommandError::UnevenSelling));
+        assert_eq!(game.apply(GameCommand::Mortgage { player: 0, property: DARK_BLUE[1] }), Err(CommandError::GroupHasBuildings));
+        game.apply(GameCommand::SellHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        // houses are sold for half of their price
+        assert_eq!(game.currency(0), INITIAL_CURRENCY + 15);
+        game.apply(GameCommand::SellHouse { player: 0, property: DARK_BLUE[1] }).unwrap();
+        assert_eq!(game.apply(GameCommand::Mortgage { player: 0, property: DARK_BLUE[1] }), Err(CommandError::GroupHasBuildings));
+        game.apply(GameCommand::SellHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        game.apply(GameCommand::Mortgage { player: 0, property: DARK_BLUE[1] }).unwrap();
+        assert!(game.engine.properties[DARK_BLUE[1]].mortgaged);
+    }
+
+    #[test]
+    fn hotels_are_broken_down_into_houses_when_sold() {
+        let mut game = game(2);
+        for property in DARK_BLUE {
+            game.give(0, property);
+            game.engine.properties[property].houses = MAX_HOUSES;
+        }
+        // the bank needs the houses the hotel is broken down into
+        game.engine.rules.buildings.houses = MAX_HOUSES - 2;
+        assert_eq!(game.apply(GameCommand::SellHouse { player: 0, property: DARK_BLUE[0] }), Err(CommandError::NoBuildingsLeft));
+        game.engine.rules.buildings.houses = MAX_HOUSES - 1;
+        game.apply(GameCommand::SellHouse { player: 0, property: DARK_BLUE[0] }).unwrap();
+        assert_eq!(game.engine.properties[DARK_BLUE[0]].houses, MAX_HOUSES - 1);
+        assert_eq!(game.engine.buildings_left(false), 0);
+    }
+
+    #[test]
+    fn income_tax_is_paid_as_the_flat_amount_or_a_share_of_the_net_worth() {
+        let mut game = game(2);
+        let tax = game.tile(TileKind::Pay);
+        game.engine.board.tiles[tax] = Tile::Pay {
+            name: "Tax".to_string(),
+            amount: 60,
+            income_tax: true,
+        };
+        game.place(0, tax - 3);
+        game.roll(0, (1, 2)).unwrap();
+        assert_eq!(game.engine.phase, Phase::ChoosingTax { flat: 60, percentage: INITIAL_CURRENCY / 10 });
+        assert_eq!(game.end_turn(0), Err(CommandError::WrongPhase));
+        game.apply(GameCommand::PayTax { player: 0, percentage: true }).unwrap();
+        assert_eq!(game.currency(0), INITIAL_CURRENCY - INITIAL_CURRENCY / 10);
+        game.end_turn(0).unwrap();
+
+        // the properties count towards the net worth as well
+        game.set_prices(STATION, 400, RENTS);
+        game.give(1, STATION);
+        game.place(1, tax - 3);
+        game.roll(1, (1, 2)).unwrap();
+        let percentage = game.engine.net_worth(1) / 10;
+        assert!(percentage > INITIAL_CURRENCY / 10);
+        assert_eq!(game.engine.phase, Phase::ChoosingTax { flat: 60, percentage });
+        game.apply(GameCommand::PayTax { player: 1, percentage: false }).unwrap();
+        assert_eq!(game.currency(1), INITIAL_CURRENCY - 60);
+    }
+
+    #[test]
+    fn dealt_properties_are_split_evenly_before_the_first_turn() {
+        let game = ScriptedGame::new(3, Rules {
+            setup: PropertySetup::Dealt,
+            ..Rules::default()
+        });
+        for player in 0..3 {
+            assert_eq!(game.engine.players[player].properties.len(), PROPERTIES / 3);
+        }
+        // the property which is left over stays with the bank
+        assert_eq!(game.engine.properties.iter().filter(|property| property.owner.is_none()).count(), PROPERTIES % 3);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+        assert_eq!(game.engine.curr_player, 0);
+    }
+
+    #[test]
+    fn properties_are_drafted_in_snake_order() {
+        let mut game = ScriptedGame::new(3, Rules {
+            setup: PropertySetup::Drafted,
+            ..Rules::default()
+        });
+        assert_eq!(game.engine.phase, Phase::Drafting);
+        let mut pickers = vec![];
+        for property in 0..PROPERTIES / 3 * 3 {
+            let player = game.engine.curr_player;
+            if property == 0 {
+                assert_eq!(game.apply(GameCommand::DraftProperty { player: 1, property }), Err(CommandError::NotYourTurn));
+            } else {
+                assert_eq!(game.apply(GameCommand::DraftProperty { player, property: 0 }), Err(CommandError::NotAvailable));
+            }
+            game.apply(GameCommand::DraftProperty { player, property }).unwrap();
+            pickers.push(player);
+        }
+        assert_eq!(pickers[..9], [0, 1, 2, 2, 1, 0, 0, 1, 2]);
+        assert_eq!(game.engine.properties[PROPERTIES - 1].owner, None);
+        assert_eq!(game.engine.phase, Phase::Rolling);
+        assert_eq!(game.engine.curr_player, 0);
+    }
+
+    #[test]
+    fn shuffled_boards_keep_the_other_tiles_in_place_and_can_be_replayed() {
+        let mut game = ScriptedGame::new(2, Rules {
+            shuffle_board: true,
+            ..Rules::default()
+        });
+        let ids = |board: &Board| board.tiles.iter().map(|tile| match tile {
+            Tile::Property { property } => Some(property.id),
+            _ => None,
+        }).collect::<Vec<_>>();
+        let default = Board::default();
+        for (tile, default) in game.engine.board.tiles.iter().zip(default.tiles.iter()) {
+            assert_eq!(tile.kind(), default.kind());
+        }
+        assert_ne!(ids(&game.engine.board), ids(&default));
+        let mut shuffled = ids(&game.engine.board).into_iter().flatten().collect::<Vec<_>>();
+        shuffled.sort_unstable();
+        assert_eq!(shuffled, (0..PROPERTIES).collect::<Vec<_>>());
+
+        // the layout is recorded, so the board can be rebuilt from it
+        let layout = game.engine.take_events().into_iter().find_map(|event| match event {
+            GameEvent::BoardShuffled { layout } => Some(layout),
+            _ => None,
+        }).unwrap();
+        let mut replayed = Board::default();
+        replayed.rearrange(&layout);
+        assert_eq!(ids(&replayed), ids(&game.engine.board));
+        let restored = SaveGame::new(&game.engine).restore().unwrap();
+        assert_eq!(ids(&restored.board), ids(&game.engine.board));
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::MonitorHandle;
+use winit::window::{Fullscreen, Window, WindowBuilder};
+use crate::audio::{Audio, Category};
+use crate::input::CameraMode;
+use crate::render::Renderer;
+use crate::utils::{read_config, write_config};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+pub const MIN_CAMERA_SENSITIVITY: f32 = 0.01;
+pub const MAX_CAMERA_SENSITIVITY: f32 = 0.2;
+
+pub fn load_settings() -> Settings {
+    match read_config(SETTINGS_FILE) {
+        Some(buf) => serde_json::from_str(&*buf).unwrap(),
+        None => {
+            let settings = Settings::default();
+            save_settings(&settings);
+            settings
+        }
+    }
+}
+
+pub fn save_settings(settings: &Settings) {
+    write_config(SETTINGS_FILE, &serde_json::to_string(settings).unwrap());
+}
+
+// missing entries fall back to their defaults, so settings files of older versions keep on working
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub audio: AudioSettings,
+    pub window: WindowSettings,
+    pub graphics: GraphicsSettings,
+    pub accessibility: AccessibilitySettings,
+    pub language: String,
+    pub camera_mode: CameraMode,
+    // adds anonymous statistics about every finished game to a file in the config directory
+    pub collect_statistics: bool,
+    // suggests what a computer player would do whenever a decision has to be taken
+    pub hints: bool,
+    // how fast the camera turns while the mouse is moved, between MIN_CAMERA_SENSITIVITY and MAX_CAMERA_SENSITIVITY
+    pub camera_sensitivity: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            audio: AudioSettings::default(),
+            window: WindowSettings::default(),
+            graphics: GraphicsSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            language: "en".to_string(),
+            camera_mode: CameraMode::default(),
+            collect_statistics: false,
+            hints: false,
+            camera_sensitivity: 0.05,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WindowSettings {
+    pub fullscreen: bool,
+    // the platform's default size is used if this isn't set
+    pub size: Option<(u32, u32)>,
+    // the outer position of the window in desktop coordinates
+    pub position: Option<(i32, i32)>,
+    // the name of the monitor the window was last on
+    pub monitor: Option<String>,
+}
+
+impl WindowSettings {
+
+    pub fn apply(&self, mut window: WindowBuilder, monitors: impl Iterator<Item = MonitorHandle>) -> WindowBuilder {
+        let monitors = monitors.collect::<Vec<_>>();
+        // the monitor may have been unplugged since the last session, in that case the platform picks one
+        let monitor = self.monitor.as_ref()
+            .and_then(|name| monitors.iter().find(|monitor| monitor.name().as_ref() == Some(name)).cloned());
+        if self.fullscreen {
+            window = window.with_fullscreen(Some(Fullscreen::Borderless(monitor)));
+        }
+        if let Some((width, height)) = self.size {
+            window = window.with_inner_size(PhysicalSize::new(width, height));
+        }
+        if let Some((x, y)) = self.position {
+            // don't restore positions which would leave the window off screen
+            if monitors.iter().any(|monitor| contains(monitor, x, y)) {
+                window = window.with_position(PhysicalPosition::new(x, y));
+            }
+        }
+        window
+    }
+
+    // applies the settings to the window which is already open, e.g. after they were changed in the settings screen
+    pub fn apply_to_window(&self, window: &Window) {
+        if self.fullscreen {
+            window.set_fullscreen(Some(Fullscreen::Borderless(window.current_monitor())));
+        } else {
+            window.set_fullscreen(None);
+            if let Some((width, height)) = self.size {
+                window.set_inner_size(PhysicalSize::new(width, height));
+            }
+        }
+    }
+
+    // remembers where the window currently is, so it can be restored in the next session
+    pub fn update(&mut self, window: &Window) {
+        self.fullscreen = window.fullscreen().is_some();
+        self.monitor = window.current_monitor().and_then(|monitor| monitor.name());
+        // the geometry of a fullscreen window isn't the one the window should have once it leaves fullscreen again
+        if !self.fullscreen {
+            let size = window.inner_size();
+            self.size = Some((size.width, size.height));
+            self.position = window.outer_position().ok().map(|position| (position.x, position.y));
+        }
+    }
+
+}
+
+fn contains(monitor: &MonitorHandle, x: i32, y: i32) -> bool {
+    let position = monitor.position();
+    let size = monitor.size();
+    x >= position.x && y >= position.y
+        && x < position.x + size.width as i32 && y < position.y + size.height as i32
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    // frames aren't drawn more often than this, no limit is applied if this isn't set
+    pub max_fps: Option<u32>,
+    // waits for the display's refresh before presenting a frame, which prevents tearing
+    pub vsync: bool,
+    // only draw frames if something changed instead of drawing them all the time, this saves a lot of power
+    pub redraw_on_demand: bool,
+    // the scene and the ui are locked to this aspect ratio (width, height) and the rest of the
+    // window is filled with letterbox bars, they are stretched across the whole window if this isn't set
+    pub safe_area: Option<(u32, u32)>,
+    // shows the whole board from above in a corner of the screen, next to the regular camera
+    pub inset: bool,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            max_fps: None,
+            vsync: true,
+            redraw_on_demand: true,
+            safe_area: None,
+            inset: false,
+        }
+    }
+}
+
+impl GraphicsSettings {
+
+    pub fn apply(&self, renderer: &Renderer) {
+        renderer.set_safe_area(self.safe_area.map(|(width, height)| width as f32 / height.max(1) as f32));
+        renderer.set_vsync(self.vsync);
+    }
+
+}
+
+pub const MIN_TEXT_SCALE: f32 = 0.75;
+pub const MAX_TEXT_SCALE: f32 = 2.0;
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct AccessibilitySettings {
+    // use a colorblind safe palette for the color groups and mark each group with its own pattern
+    pub colorblind: bool,
+    // applied to all text, between MIN_TEXT_SCALE and MAX_TEXT_SCALE
+    pub text_scale: f32,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            colorblind: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+impl AccessibilitySettings {
+
+    pub fn apply(&self, renderer: &Renderer) {
+        renderer.set_text_scale(self.text_scale.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE));
+    }
+
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+    pub voice: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            music: 0.5,
+            sfx: 1.0,
+            voice: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+
+    pub fn apply(&self, audio: &Audio) {
+        audio.set_master_volume(self.master);
+        audio.set_volume(Category::Music, self.music);
+        audio.set_volume(Category::Sfx, self.sfx);
+        audio.set_volume(Category::Voice, self.voice);
+        audio.set_muted(self.muted);
+    }
+
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use crate::action_cards;
+use crate::ai::{self, Strategy};
+use crate::board::{self, Board, CardKind, Tile, TILES};
+use crate::engine::Engine;
+use crate::events::{GameCommand, GameEvent};
+use crate::mods;
+use crate::rules;
+
+// games which take longer than this are counted as draws
+const MAX_TURNS: usize = 5000;
+
+struct Stats {
+    games: usize,
+    draws: usize,
+    total_turns: usize,
+    landings: [usize; TILES],
+    // indexed by the strategy's name
+    wins: HashMap<&'static str, usize>,
+}
+
+/// Simulates `games` games between computer controlled players without opening a window
+/// and prints statistics about them.
+pub fn run(games: usize, seed: Option<u64>) {
+    let board = board::load_board();
+    let mods = mods::load_mods();
+    let mut cards = action_cards::load_cards();
+    cards.extend(mods.cards.iter().cloned());
+    let rules = rules::load_rules();
+    let strategies = ai::strategies();
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Simulating {} games with seed {}...", games, seed);
+
+    let mut stats = Stats {
+        games,
+        draws: 0,
+        total_turns: 0,
+        landings: [0; TILES],
+        wins: HashMap::new(),
+    };
+    for game in 0..games {
+        // rotate the seats so no strategy profits from always going first
+        let seats = (0..strategies.len())
+            .map(|seat| &*strategies[(game + seat) % strategies.len()])
+            .collect::<Vec<_>>();
+        let mut engine = Engine::new(board.clone(), cards.clone(), rules.clone(), Some(seed.wrapping_add(game as u64)));
+        engine.tile_scripts = mods.tiles.clone();
+        simulate(&mut engine, &seats, &mut stats);
+    }
+    print_stats(&stats, &board, &strategies);
+}
+
+fn simulate(engine: &mut Engine, seats: &[&dyn Strategy], stats: &mut Stats) {
+    for _ in seats {
+        engine.apply(GameCommand::AddPlayer {
+            character: 0,
+        }).unwrap();
+    }
+    engine.apply(GameCommand::StartGame).unwrap();
+    while !engine.is_finished() && engine.turns < MAX_TURNS {
+        let player = engine.next_actor();
+        let command = ai::next_command(seats[player], engine, player);
+        engine.apply(command).unwrap();
+        for event in engine.take_events() {
+            if let GameEvent::TokenLanded { tile, .. } = event {
+                stats.landings[tile] += 1;
+            }
+        }
+    }
+    stats.total_turns += engine.turns;
+    match engine.winner() {
+        Some(winner) => *stats.wins.entry(seats[winner].name()).or_insert(0) += 1,
+        None => stats.draws += 1,
+    }
+}
+
+fn print_stats(stats: &Stats, board: &Board, strategies: &[Box<dyn Strategy>]) {
+    if stats.games == 0 {
+        return;
+    }
+    println!("Average game length: {:.1} turns", stats.total_turns as f64 / stats.games as f64);
+    println!("Draws (more than {} turns): {}", MAX_TURNS, stats.draws);
+    println!("Win rates:");
+    for strategy in strategies.iter() {
+        let wins = stats.wins.get(strategy.name()).copied().unwrap_or(0);
+        println!("  {:<10} {:>6.2}%", strategy.name(), wins as f64 / stats.games as f64 * 100.0);
+    }
+    println!("Landing frequencies:");
+    let total = stats.landings.iter().sum::<usize>().max(1);
+    for (idx, landings) in stats.landings.iter().enumerate() {
+        println!("  {:>2} {:<20} {:>6.2}%", idx, tile_name(&board.tiles[idx]), *landings as f64 / total as f64 * 100.0);
+    }
+}
+
+fn tile_name(tile: &Tile) -> &str {
+    match tile {
+        Tile::Parking { name } | Tile::Start { name } | Tile::Jail { name } | Tile::GoToJail { name }
+        | Tile::Pay { name, .. } | Tile::Custom { name, .. } => name,
+        Tile::Property { property } => &property.name,
+        Tile::DrawCard { kind: CardKind::Chance } => "Chance",
+        Tile::DrawCard { kind: CardKind::Community } => "Community",
+    }
+}
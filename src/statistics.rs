@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+use crate::board::TILES;
+use crate::engine::Engine;
+use crate::events::GameEvent;
+use crate::rules::Rules;
+use crate::utils::{read_config, write_config};
+
+// teachers can hand this file in, so the defaults of the school boards can be balanced
+const STATISTICS_FILE: &str = "statistics.json";
+
+/// Statistics about all games that were finished on this installation. They are anonymous,
+/// neither names nor seeds are kept, only totals.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Statistics {
+    pub games: usize,
+    pub total_turns: usize,
+    pub shortest_game: Option<usize>,
+    pub longest_game: Option<usize>,
+    pub total_seconds: u64,
+    // how many games were played with each rule option, like "setup: Drafted"
+    pub rule_options: BTreeMap<String, usize>,
+    // how often a token came to a halt on every tile, indexed by the tile's position
+    pub landings: Vec<usize>,
+}
+
+pub fn load_statistics() -> Statistics {
+    read_config(STATISTICS_FILE)
+        .and_then(|buf| serde_json::from_str(&buf).ok())
+        .unwrap_or_default()
+}
+
+fn rule_options(rules: &Rules) -> Vec<String> {
+    let mut options = vec![
+        format!("setup: {:?}", rules.setup),
+        format!("income tax: {}%", rules.income_tax.percentage),
+    ];
+    if rules.free_parking.enabled {
+        options.push("free parking".to_string());
+    }
+    if rules.shuffle_board {
+        options.push("shuffled board".to_string());
+    }
+    if let Some(rounds) = rules.round_limit {
+        options.push(format!("round limit: {}", rounds));
+    }
+    if let Some(seconds) = rules.decision_timer {
+        options.push(format!("decision timer: {}s", seconds));
+    }
+    options
+}
+
+/// What a single player did during a game, it's shown once the game is over.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct PlayerStatistics {
+    pub rent_collected: usize,
+    pub times_jailed: usize,
+    // from the bank, either directly or at an auction
+    pub properties_bought: usize,
+}
+
+// only the events since the last game was started or left are counted, so the log may contain earlier games,
+// games which were continued from a save only count what happened since they were continued
+pub fn player_statistics(events: &[GameEvent], players: usize) -> Vec<PlayerStatistics> {
+    let start = events.iter()
+        .rposition(|event| matches!(event, GameEvent::GameStarted | GameEvent::GameLeft))
+        .map_or(0, |idx| idx + 1);
+    let mut statistics = vec![PlayerStatistics::default(); players];
+    // the bank auctions off both properties and buildings
+    let mut property_auction = false;
+    for event in events[start..].iter() {
+        match event {
+            GameEvent::RentPaid { owner, amount, .. } => statistics[*owner].rent_collected += amount,
+            GameEvent::WentToJail { player } => statistics[*player].times_jailed += 1,
+            GameEvent::PropertyBought { player, .. } => statistics[*player].properties_bought += 1,
+            GameEvent::PropertyAuctionStarted { .. } => property_auction = true,
+            GameEvent::BuildingAuctionStarted { .. } => property_auction = false,
+            GameEvent::AuctionWon { player, .. } if property_auction => statistics[*player].properties_bought += 1,
+            _ => {}
+        }
+    }
+    statistics
+}
+
+/// Follows the events of the current game and adds it to the statistics file once it's finished,
+/// games which are left before that aren't counted.
+pub struct Recorder {
+    started: Option<Instant>,
+    landings: [usize; TILES],
+}
+
+impl Recorder {
+
+    pub fn new() -> Self {
+        Self {
+            started: None,
+            landings: [0; TILES],
+        }
+    }
+
+    pub fn on_event(&mut self, event: &GameEvent, engine: &Engine) {
+        match event {
+            GameEvent::GameStarted => {
+                self.started = Some(Instant::now());
+                self.landings = [0; TILES];
+            }
+            GameEvent::TokenLanded { tile, .. } => self.landings[*tile] += 1,
+            GameEvent::GameFinished { .. } => {
+                if let Some(started) = self.started.take() {
+                    self.record(engine, started.elapsed().as_secs());
+                }
+            }
+            GameEvent::GameLeft => self.started = None,
+            _ => {}
+        }
+    }
+
+    fn record(&self, engine: &Engine, seconds: u64) {
+        let mut statistics = load_statistics();
+        statistics.games += 1;
+        statistics.total_turns += engine.turns;
+        statistics.shortest_game = Some(statistics.shortest_game.map_or(engine.turns, |turns| turns.min(engine.turns)));
+        statistics.longest_game = Some(statistics.longest_game.map_or(engine.turns, |turns| turns.max(engine.turns)));
+        statistics.total_seconds += seconds;
+        for option in rule_options(&engine.rules) {
+            *statistics.rule_options.entry(option).or_default() += 1;
+        }
+        statistics.landings.resize(TILES, 0);
+        for (total, landings) in statistics.landings.iter_mut().zip(self.landings.iter()) {
+            *total += landings;
+        }
+        write_config(STATISTICS_FILE, &serde_json::to_string_pretty(&statistics).unwrap());
+    }
+
+}
@@ -0,0 +1,204 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use fontdue::layout::HorizontalAlign;
+use image::{imageops, Rgba, RgbaImage};
+use crate::board::{Tile, TILES};
+use crate::engine::Engine;
+use crate::board_layout::{tile_position, BOARD_HALF_SIZE};
+use crate::palette;
+use crate::property::MAX_HOUSES;
+use crate::render::Renderer;
+use crate::text_raster::TextRun;
+use crate::ui::{BOLD_FONT, REGULAR_FONT};
+use crate::{Game, i18n};
+
+const SUMMARY_DIR: &str = "./summaries/";
+// the corners take up one cell each, so every side is one cell longer than its tiles
+const CELLS: usize = TILES / 4 + 1;
+// relative to the size of a cell
+const OWNER_FRAME: f32 = 0.08;
+const BUILDINGS_TEXT_SCALE: f32 = 0.3;
+// relative to the size of the board image
+const TABLE_WIDTH: f32 = 0.6;
+const TABLE_TEXT_SCALE: f32 = 0.03;
+const TABLE_PADDING: u32 = 16;
+
+// the state of a property's tile at the end of the game
+struct PropertyState {
+    tile: usize,
+    owner: Option<usize>,
+    houses: usize,
+    mortgaged: bool,
+}
+
+// a row of the results table
+struct Standing {
+    player: usize,
+    net_worth: String,
+    properties: usize,
+    bankrupt: bool,
+}
+
+/// Everything that is shown in the summary of a finished game. It's taken right away,
+/// so the image can be drawn on another thread while the game moves on.
+pub struct Summary {
+    board_texture: Arc<RgbaImage>,
+    properties: Vec<PropertyState>,
+    standings: Vec<Standing>,
+    turns: usize,
+}
+
+impl Summary {
+
+    // the board's texture isn't available before the assets were loaded
+    pub fn new(game: &Game) -> Option<Self> {
+        let board_texture = game.assets.get()?.board_texture.clone();
+        let engine = game.engine.lock().unwrap();
+        let properties = engine.board.tiles.iter().enumerate().filter_map(|(tile, kind)| match kind {
+            Tile::Property { property: frame } => {
+                let property = &engine.properties[frame.id];
+                Some(PropertyState {
+                    tile,
+                    owner: property.owner,
+                    houses: property.houses,
+                    mortgaged: property.mortgaged,
+                })
+            }
+            _ => None,
+        }).collect();
+        Some(Self {
+            board_texture,
+            properties,
+            standings: standings(&engine),
+            turns: engine.turns,
+        })
+    }
+
+    /// Saves the summary as text along with an image of the board which shows who owned what,
+    /// returns the path of the image.
+    pub fn export(&self, renderer: &Renderer) -> anyhow::Result<PathBuf> {
+        fs::create_dir_all(SUMMARY_DIR)?;
+        let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let path = PathBuf::from(SUMMARY_DIR).join(format!("summary-{}", time));
+        fs::write(path.with_extension("txt"), self.text())?;
+        let image_path = path.with_extension("png");
+        self.draw(renderer).save(&image_path)?;
+        Ok(image_path)
+    }
+
+    fn text(&self) -> String {
+        let mut ret = String::new();
+        writeln!(ret, "{}", i18n::tr_with("summary.turns", &[("turns", &self.turns.to_string())])).unwrap();
+        for (place, standing) in self.standings.iter().enumerate() {
+            writeln!(ret, "{}", standing_text(place, standing)).unwrap();
+        }
+        ret
+    }
+
+    fn draw(&self, renderer: &Renderer) -> RgbaImage {
+        let mut image = self.board_texture.as_ref().clone();
+        let cell = (image.width() / CELLS as u32, image.height() / CELLS as u32);
+        for property in self.properties.iter() {
+            let (x, y) = cell_pos(property.tile, image.dimensions());
+            if property.mortgaged {
+                fill(&mut image, (x, y), cell, Rgba([0, 0, 0, 128]));
+            }
+            if let Some(owner) = property.owner {
+                let color = palette::to_rgba(palette::player_color(owner));
+                let frame = ((cell.0.min(cell.1) as f32 * OWNER_FRAME) as u32).max(1);
+                fill(&mut image, (x, y), (cell.0, frame), color);
+                fill(&mut image, (x, y + cell.1 - frame), (cell.0, frame), color);
+                fill(&mut image, (x, y), (frame, cell.1), color);
+                fill(&mut image, (x + cell.0 - frame, y), (frame, cell.1), color);
+            }
+            if property.houses > 0 {
+                let text = if property.houses == MAX_HOUSES {
+                    i18n::tr("summary.hotel")
+                } else {
+                    property.houses.to_string()
+                };
+                let text = renderer.text_rasterizer.rasterize(&[TextRun {
+                    text: &text,
+                    scale: cell.1 as f32 * BUILDINGS_TEXT_SCALE,
+                    font: BOLD_FONT,
+                    color: [0.0, 0.0, 0.0, 1.0],
+                }], None);
+                let pos = (x + cell.0.saturating_sub(text.width()) / 2, y + cell.1.saturating_sub(text.height()) / 2);
+                fill(&mut image, pos, text.dimensions(), Rgba([255, 255, 255, 200]));
+                imageops::overlay(&mut image, &text, pos.0 as i64, pos.1 as i64);
+            }
+        }
+        // the results go into the empty middle of the board
+        let scale = image.height() as f32 * TABLE_TEXT_SCALE;
+        let title = i18n::tr("summary.title");
+        let rows = self.standings.iter().enumerate()
+            .map(|(place, standing)| format!("\n{}", standing_text(place, standing)))
+            .collect::<String>();
+        let table = renderer.text_rasterizer.rasterize(&[TextRun {
+            text: &title,
+            scale: scale * 1.3,
+            font: BOLD_FONT,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }, TextRun {
+            text: &rows,
+            scale,
+            font: REGULAR_FONT,
+            color: [1.0, 1.0, 1.0, 1.0],
+        }], Some((image.width() as f32 * TABLE_WIDTH, HorizontalAlign::Left)));
+        let size = (table.width() + TABLE_PADDING * 2, table.height() + TABLE_PADDING * 2);
+        let pos = (image.width().saturating_sub(size.0) / 2, image.height().saturating_sub(size.1) / 2);
+        fill(&mut image, pos, size, Rgba([0, 0, 0, 200]));
+        imageops::overlay(&mut image, &table, (pos.0 + TABLE_PADDING) as i64, (pos.1 + TABLE_PADDING) as i64);
+        image
+    }
+
+}
+
+fn standings(engine: &Engine) -> Vec<Standing> {
+    engine.ranking().into_iter().map(|player| Standing {
+        player,
+        net_worth: engine.rules.currency.format(engine.net_worth(player)),
+        properties: engine.properties.iter().filter(|property| property.owner == Some(player)).count(),
+        bankrupt: engine.players[player].bankrupt,
+    }).collect()
+}
+
+fn standing_text(place: usize, standing: &Standing) -> String {
+    let key = if standing.bankrupt {
+        "summary.bankrupt"
+    } else {
+        "summary.standing"
+    };
+    // FIXME: use the players' names once they can choose them
+    i18n::tr_with(key, &[
+        ("place", &(place + 1).to_string()),
+        ("player", &(standing.player + 1).to_string()),
+        ("net_worth", &standing.net_worth),
+        ("properties", &standing.properties.to_string()),
+    ])
+}
+
+// the top left corner of the tile's cell in pixels, the far edge of the board (negative z) is at the top like on the minimap
+fn cell_pos(tile: usize, (width, height): (u32, u32)) -> (u32, u32) {
+    let position = tile_position(tile);
+    // from -1.0..=1.0 to the index of the cell
+    let column = ((position.x / BOARD_HALF_SIZE + 1.0) / 2.0 * (CELLS - 1) as f32).round() as u32;
+    let row = ((position.z / BOARD_HALF_SIZE + 1.0) / 2.0 * (CELLS - 1) as f32).round() as u32;
+    (column * (width / CELLS as u32), row * (height / CELLS as u32))
+}
+
+// blends the color over the rect, the parts outside of the image are skipped
+fn fill(image: &mut RgbaImage, (x, y): (u32, u32), (width, height): (u32, u32), color: Rgba<u8>) {
+    let alpha = color.0[3] as f32 / 255.0;
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            let pixel = image.get_pixel_mut(px, py);
+            for channel in 0..3 {
+                pixel.0[channel] = (pixel.0[channel] as f32 * (1.0 - alpha) + color.0[channel] as f32 * alpha) as u8;
+            }
+        }
+    }
+}
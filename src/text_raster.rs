@@ -0,0 +1,90 @@
+use std::sync::RwLock;
+use fontdue::{Font, FontSettings};
+use fontdue::layout::{CoordinateSystem, HorizontalAlign, Layout, LayoutSettings, TextStyle};
+use image::{Rgba, RgbaImage};
+use wgpu_glyph::FontId;
+
+// a run of text along with its scale, font and color, like the ones of a `TextSection`
+pub struct TextRun<'a> {
+    pub text: &'a str,
+    pub scale: f32,
+    pub font: FontId,
+    pub color: [f32; 4],
+}
+
+/// Rasterizes text into images on the cpu, so text which doesn't change can be drawn as a
+/// textured quad instead of being laid out by the glyph brush every frame.
+pub struct TextRasterizer {
+    // indexed by the font ids the glyph brush uses, see `REGULAR_FONT` and `BOLD_FONT`
+    fonts: RwLock<Vec<Font>>,
+}
+
+impl TextRasterizer {
+
+    pub fn new(regular_font: &[u8]) -> Self {
+        let font = Font::from_bytes(regular_font, FontSettings::default()).unwrap();
+        Self {
+            fonts: RwLock::new(vec![font.clone(), font]),
+        }
+    }
+
+    pub fn set_bold_font(&self, bold_font: &[u8]) {
+        if let Ok(font) = Font::from_bytes(bold_font, FontSettings::default()) {
+            self.fonts.write().unwrap()[1] = font;
+        }
+    }
+
+    /// Lays out the runs next to one another. If a max width is given, the text gets wrapped
+    /// and aligned inside of it and the image is exactly as wide, otherwise the image is as
+    /// small as the text.
+    pub fn rasterize(&self, runs: &[TextRun<'_>], max_width: Option<(f32, HorizontalAlign)>) -> RgbaImage {
+        let fonts = self.fonts.read().unwrap();
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+        layout.reset(&LayoutSettings {
+            max_width: max_width.map(|(width, _)| width),
+            horizontal_align: max_width.map_or(HorizontalAlign::Left, |(_, align)| align),
+            ..LayoutSettings::default()
+        });
+        for run in runs {
+            layout.append(fonts.as_slice(), &TextStyle::with_user_data(run.text, run.scale, run.font.0, run.color));
+        }
+        let (min_x, max_x) = match max_width {
+            Some((width, _)) => (0, width.ceil() as i32),
+            None => layout.glyphs().iter().fold((0, 0), |(min, max), glyph| {
+                (min.min(glyph.x.floor() as i32), max.max((glyph.x + glyph.width as f32).ceil() as i32))
+            }),
+        };
+        let width = (max_x - min_x).max(1) as u32;
+        let height = (layout.height().ceil() as u32).max(1);
+        let mut image = RgbaImage::new(width, height);
+        for glyph in layout.glyphs() {
+            if glyph.width == 0 || glyph.height == 0 {
+                continue;
+            }
+            let (_, coverage) = fonts[glyph.font_index].rasterize_config(glyph.key);
+            let color = glyph.user_data;
+            for gy in 0..glyph.height {
+                for gx in 0..glyph.width {
+                    let x = glyph.x.round() as i32 + gx as i32 - min_x;
+                    let y = glyph.y.round() as i32 + gy as i32;
+                    if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                        continue;
+                    }
+                    let alpha = coverage[gy * glyph.width + gx] as f32 / 255.0 * color[3];
+                    let pixel = image.get_pixel_mut(x as u32, y as u32);
+                    // overlapping glyphs keep the more opaque pixel
+                    if alpha * 255.0 > pixel.0[3] as f32 {
+                        *pixel = Rgba([
+                            (color[0] * 255.0) as u8,
+                            (color[1] * 255.0) as u8,
+                            (color[2] * 255.0) as u8,
+                            (alpha * 255.0) as u8,
+                        ]);
+                    }
+                }
+            }
+        }
+        image
+    }
+
+}
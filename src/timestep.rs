@@ -0,0 +1,77 @@
+use std::time::Duration;
+use instant::Instant;
+
+// the rate at which game logic gets updated, independent of the frame rate
+pub const TICKS_PER_SECOND: u32 = 20;
+
+// if the game falls behind by more ticks than this (e.g. after the window was dragged around),
+// the remaining time is dropped instead of trying to catch up all at once.
+const MAX_CATCH_UP_TICKS: u32 = 5;
+
+pub struct FixedTimestep {
+    step: Duration,
+    accumulator: Duration,
+    last: Instant,
+}
+
+impl FixedTimestep {
+
+    pub fn new(ticks_per_second: u32) -> Self {
+        Self {
+            step: Duration::from_secs(1) / ticks_per_second,
+            accumulator: Duration::ZERO,
+            last: Instant::now(),
+        }
+    }
+
+    /// Advances the timestep by the time that passed since the last call and returns
+    /// how many logic ticks have to be run to catch up.
+    pub fn advance(&mut self) -> u32 {
+        let now = Instant::now();
+        self.accumulator += now.duration_since(self.last);
+        self.last = now;
+        let mut ticks = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            ticks += 1;
+            if ticks == MAX_CATCH_UP_TICKS {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+        }
+        ticks
+    }
+
+    /// The point in time at which the next logic tick is due.
+    pub fn next_tick(&self) -> Instant {
+        self.last + self.step.saturating_sub(self.accumulator)
+    }
+
+}
+
+// keeps frames from being drawn more often than the configured frame rate
+pub struct FrameLimiter {
+    last_frame: Instant,
+}
+
+impl FrameLimiter {
+
+    pub fn new() -> Self {
+        Self {
+            last_frame: Instant::now(),
+        }
+    }
+
+    /// The earliest point in time at which the next frame may be drawn.
+    pub fn next_frame(&self, max_fps: Option<u32>) -> Instant {
+        match max_fps {
+            Some(max_fps) if max_fps > 0 => self.last_frame + Duration::from_secs(1) / max_fps,
+            _ => self.last_frame,
+        }
+    }
+
+    pub fn frame(&mut self) {
+        self.last_frame = Instant::now();
+    }
+
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+use instant::Instant;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+
+    // maps linear progress in [0, 1] onto the eased progress
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tween {
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Tween {
+
+    pub fn new(duration: Duration, easing: Easing) -> Self {
+        Self {
+            start: Instant::now(),
+            duration,
+            easing,
+        }
+    }
+
+    pub fn raw_progress(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.start.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    #[inline]
+    pub fn progress(&self) -> f32 {
+        self.easing.apply(self.raw_progress())
+    }
+
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.start.elapsed() >= self.duration
+    }
+
+    #[inline]
+    pub fn lerp(&self, from: f32, to: f32) -> f32 {
+        from + (to - from) * self.progress()
+    }
+
+}
@@ -1,13 +1,20 @@
-use crate::atlas::UV;
-use crate::render::{ColorSource, Model, TexTriple, TexTy, UvKind, Vertex};
+use crate::atlas::{AtlasAlloc, UV};
+use crate::audio::SoundId;
+use crate::render::{ClipRect, ColorSource, Model, TexTriple, TexTy, UvKind, Vertex};
 use crate::screen_sys::ScreenSystem;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 use atomic_float::AtomicF64;
+use instant::Instant;
 use fontdue::{Font, FontSettings};
 use wgpu::{Sampler, Texture, TextureView};
-use wgpu_glyph::{BuiltInLineBreaker, Extra, Layout, Section, Text};
+use wgpu_glyph::{BuiltInLineBreaker, Extra, FontId, HorizontalAlign, Layout, Section, Text, VerticalAlign};
+use winit::event::{ModifiersState, VirtualKeyCode};
+use winit::window::CursorIcon;
 use crate::{Game, Renderer};
+use crate::text_raster::TextRun;
+use crate::utils::{DARK_GRAY_UI, LIGHT_GRAY_UI};
 
 pub trait Component: Send + Sync {
     fn build_model(&self) -> Model;
@@ -16,6 +23,11 @@ pub trait Component: Send + Sync {
 
     fn do_render(&self, _game: &Arc<Game>) {}
 
+    // text which was rasterized into the atlas, it's drawn right above the component's model
+    fn build_text_model(&self, _game: &Arc<Game>) -> Option<Model> {
+        None
+    }
+
     fn pos(&self) -> (f32, f32);
 
     fn dims(&self) -> (f32, f32);
@@ -29,14 +41,119 @@ pub trait Component: Send + Sync {
     fn on_hover(&mut self, game: &Arc<Game>, mode: HoverMode, pos: (f32, f32));
 
     fn is_hovered(&self) -> Option<HoverMode>;
+
+    // the cursor which is shown while the component is hovered
+    fn cursor(&self) -> CursorIcon {
+        CursorIcon::Default
+    }
+
+    // the transform gets applied around the component's center after its model was built,
+    // positions passed to the component's handlers are already transformed back into its local space.
+    fn transform(&self) -> Transform {
+        Transform::IDENTITY
+    }
+
+    // called every frame the component's screen is ticked, returns whether the component's
+    // model has to be rebuilt.
+    fn tick(&mut self, _game: &Arc<Game>) -> bool {
+        false
+    }
+
+    // keyboard input is offered to the components of the top screen front to back,
+    // returns whether the input was consumed, so it doesn't get passed on any further.
+    fn on_key(&mut self, _game: &Arc<Game>, _key: VirtualKeyCode, _down: bool, _modifiers: ModifiersState) -> bool {
+        false
+    }
+
+    fn on_char(&mut self, _game: &Arc<Game>, _received: char) -> bool {
+        false
+    }
+
+    // components which take keyboard input (e.g. input boxes) can be focused by clicking them
+    // or by cycling through them with the focus key, see `Container::cycle_focus`
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    fn is_focused(&self) -> bool {
+        false
+    }
+
+    fn set_focused(&mut self, _game: &Arc<Game>, _focused: bool) {}
+
+    // called when the window got resized, the component's model gets rebuilt afterwards
+    fn relayout(&mut self, _game: &Arc<Game>, _width: u32, _height: u32) {}
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Transform {
+    // counter clockwise rotation in radians
+    pub rotation: f32,
+    pub scale: f32,
+    // an additional horizontal scale, shrinking it to 0 and back looks like turning the component around
+    pub flip: f32,
+}
+
+impl Transform {
+
+    pub const IDENTITY: Self = Self {
+        rotation: 0.0,
+        scale: 1.0,
+        flip: 1.0,
+    };
+
+    #[inline]
+    pub fn is_identity(&self) -> bool {
+        *self == Self::IDENTITY
+    }
+
+    // transforms a point from the component's local space into screen space, all positions are
+    // relative to the screen and the aspect ratio (width / height) is used to rotate inside the
+    // (square) pixel space instead of the (stretched) relative space.
+    pub fn apply(&self, center: (f32, f32), point: (f32, f32), aspect: f32) -> (f32, f32) {
+        let (sin, cos) = self.rotation.sin_cos();
+        let x = (point.0 - center.0) * aspect * self.flip;
+        let y = point.1 - center.1;
+        let rotated = ((x * cos - y * sin) * self.scale, (x * sin + y * cos) * self.scale);
+        (center.0 + rotated.0 / aspect, center.1 + rotated.1)
+    }
+
+    // the inverse of `apply`
+    pub fn invert(&self, center: (f32, f32), point: (f32, f32), aspect: f32) -> (f32, f32) {
+        let (sin, cos) = (-self.rotation).sin_cos();
+        let x = (point.0 - center.0) * aspect / self.scale;
+        let y = (point.1 - center.1) / self.scale;
+        let rotated = (x * cos - y * sin, x * sin + y * cos);
+        (center.0 + rotated.0 / aspect / self.flip, center.1 + rotated.1)
+    }
+
+    fn apply_to_model(&self, model: &mut Model, center: (f32, f32), aspect: f32) {
+        for vert in model.vertices.iter_mut() {
+            let pos = match vert {
+                Vertex::Color { pos, .. } => pos,
+                Vertex::Texture { pos, .. } => pos,
+            };
+            // convert from clip space into relative screen space and back
+            let transformed = self.apply(center, ((pos[0] + 1.0) / 2.0, (pos[1] + 1.0) / 2.0), aspect);
+            *pos = [transformed.0 * 2.0 - 1.0, transformed.1 * 2.0 - 1.0];
+        }
+    }
+
 }
 
 #[derive(Copy, Clone, PartialEq)]
 pub enum ClickKind {
     PressDown,
     Release,
+    // sent after the release of the second click of a double click
+    Double,
 }
 
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+const DOUBLE_CLICK_DISTANCE: f64 = 0.01;
+// moves the focus to the next focusable component of the container
+const FOCUS_KEY: VirtualKeyCode = VirtualKeyCode::Tab;
+
 #[derive(Copy, Clone, PartialEq)]
 pub enum HoverMode {
     Enter,
@@ -65,11 +182,41 @@ impl HoverMode {
 
 pub struct UIComponent {
     inner: Arc<InnerUIComponent>,
+    // components with a higher layer get rendered above and receive input before components with a lower one
+    layer: i32,
 }
 
 impl UIComponent {
-    pub fn build_model(&self) -> Model {
-        self.inner.build_model()
+    pub fn build_model(&self, aspect: f32) -> Model {
+        let mut model = self.inner.build_model();
+        // the transform is applied on top of the cached model, so animating it doesn't require a rebuild
+        let inner = self.inner.inner.read().unwrap();
+        let transform = inner.transform();
+        if !transform.is_identity() {
+            transform.apply_to_model(&mut model, center(inner.pos(), inner.dims()), aspect);
+        }
+        model
+    }
+
+    pub fn build_text_model(&self, game: &Arc<Game>, aspect: f32) -> Option<Model> {
+        let inner = self.inner.inner.read().unwrap();
+        let mut model = inner.build_text_model(game)?;
+        let transform = inner.transform();
+        if !transform.is_identity() {
+            transform.apply_to_model(&mut model, center(inner.pos(), inner.dims()), aspect);
+        }
+        Some(model)
+    }
+
+    // maps a position from screen space into the component's local (untransformed) space
+    pub fn local_pos(&self, pos: (f32, f32), aspect: f32) -> (f32, f32) {
+        let inner = self.inner.inner.read().unwrap();
+        let transform = inner.transform();
+        if transform.is_identity() {
+            pos
+        } else {
+            transform.invert(center(inner.pos(), inner.dims()), pos, aspect)
+        }
     }
 
     pub fn on_click(&self, game: &Arc<Game>, click_kind: ClickKind, pos: (f32, f32)) {
@@ -91,7 +238,52 @@ impl UIComponent {
         self.inner.inner.read().unwrap().is_hovered()
     }
 
-    pub fn is_inbounds(&self, pos: (f32, f32)) -> bool {
+    pub fn cursor(&self) -> CursorIcon {
+        self.inner.inner.read().unwrap().cursor()
+    }
+
+    pub fn tick(&self, game: &Arc<Game>) {
+        if self.inner.inner.write().unwrap().tick(game) {
+            self.inner.make_dirty();
+        }
+    }
+
+    pub fn relayout(&self, game: &Arc<Game>, width: u32, height: u32) {
+        self.inner.inner.write().unwrap().relayout(game, width, height);
+        self.inner.make_dirty();
+    }
+
+    pub fn on_key(&self, game: &Arc<Game>, key: VirtualKeyCode, down: bool, modifiers: ModifiersState) -> bool {
+        let consumed = self.inner.inner.write().unwrap().on_key(game, key, down, modifiers);
+        if consumed {
+            self.inner.make_dirty();
+        }
+        consumed
+    }
+
+    pub fn on_char(&self, game: &Arc<Game>, received: char) -> bool {
+        let consumed = self.inner.inner.write().unwrap().on_char(game, received);
+        if consumed {
+            self.inner.make_dirty();
+        }
+        consumed
+    }
+
+    pub fn is_focusable(&self) -> bool {
+        self.inner.inner.read().unwrap().is_focusable()
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.inner.inner.read().unwrap().is_focused()
+    }
+
+    pub fn set_focused(&self, game: &Arc<Game>, focused: bool) {
+        self.inner.inner.write().unwrap().set_focused(game, focused);
+        self.inner.make_dirty();
+    }
+
+    pub fn is_inbounds(&self, pos: (f32, f32), aspect: f32) -> bool {
+        let pos = self.local_pos(pos, aspect);
         let inner = self.inner.inner.read().unwrap();
         let dims = inner.dims();
         let inner_pos = inner.pos();
@@ -99,6 +291,16 @@ impl UIComponent {
     }
 }
 
+#[inline]
+fn center(pos: (f32, f32), dims: (f32, f32)) -> (f32, f32) {
+    (pos.0 + dims.0 / 2.0, pos.1 + dims.1 / 2.0)
+}
+
+fn aspect_ratio(game: &Arc<Game>) -> f32 {
+    let (width, height) = game.renderer.ui_dimensions();
+    width as f32 / height.max(1) as f32
+}
+
 pub fn is_inbounds(dims: (f32, f32), pos: (f32, f32), test: (f32, f32)) -> bool {
     // println!("pos: {:?}", pos);
     let bounds = (pos.0 + dims.0, pos.1 + dims.1);
@@ -107,13 +309,12 @@ pub fn is_inbounds(dims: (f32, f32), pos: (f32, f32), test: (f32, f32)) -> bool
     (test.0 >= pos.0 && test.1 >= pos.1) && (test.0 <= bounds.0 && test.1 <= bounds.1)
 }
 
-pub const COLOR_UV_OFFSETS: [(f32, f32); 6] = [
+// ui quads consist of 4 vertices in this order, the renderer draws them using `RECT_INDICES`
+pub const COLOR_UV_OFFSETS: [(f32, f32); 4] = [
     (0.0, 1.0), // bottom left
     (1.0, 1.0), // bottom right
     (1.0, 0.0), // top right
-    (0.0, 1.0), // bottom left
     (0.0, 0.0), // top left
-    (1.0, 0.0), // top right
 ];
 
 pub struct InnerUIComponent {
@@ -138,7 +339,7 @@ impl InnerUIComponent {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -197,6 +398,9 @@ impl Default for ScrollData {
 pub struct Container {
     components: RwLock<Vec<UIComponent>>,
     scroll_data: ScrollData, // FIXME: use this for scroll sliders
+    last_click: Mutex<Option<(Instant, (f64, f64))>>,
+    // if set, the children of this container don't get rendered outside of this rect
+    clip: RwLock<Option<ClipRect>>,
 }
 
 impl Container {
@@ -205,47 +409,180 @@ impl Container {
     }
 
     pub fn add(self: &Arc<Self>, component: Arc<RwLock<Box<dyn Component>>>) {
+        self.add_layered(component, 0);
+    }
+
+    pub fn add_layered(self: &Arc<Self>, component: Arc<RwLock<Box<dyn Component>>>, layer: i32) {
         let model = component.read().unwrap().build_model();
-        self.components.write().unwrap().push(UIComponent {
+        let mut components = self.components.write().unwrap();
+        // keep the components sorted by their layer, components on the same layer
+        // keep their insertion order.
+        let idx = components.iter().position(|component| component.layer > layer).unwrap_or(components.len());
+        components.insert(idx, UIComponent {
             inner: Arc::new(InnerUIComponent {
                 inner: component,
                 precomputed_model: Mutex::new(model),
                 dirty: AtomicBool::new(false),
             }),
+            layer,
         });
     }
 
+    pub fn clear(&self) {
+        self.components.write().unwrap().clear();
+    }
+
+    pub fn set_clip(&self, clip: Option<ClipRect>) {
+        *self.clip.write().unwrap() = clip;
+    }
+
+    pub fn tick(&self, game: &Arc<Game>) {
+        for component in self.components.read().unwrap().iter() {
+            component.tick(game);
+        }
+    }
+
+    pub fn relayout(&self, game: &Arc<Game>, width: u32, height: u32) {
+        for component in self.components.read().unwrap().iter() {
+            component.relayout(game, width, height);
+        }
+    }
+
+    // the models are returned back to front
     pub fn build_models(&self, game: &Arc<Game>) -> Vec<Model> {
+        let aspect = aspect_ratio(game);
+        let clip = *self.clip.read().unwrap();
         let mut models = vec![];
         for component in self.components.read().unwrap().iter() {
-            models.push(component.build_model());
+            let mut model = component.build_model(aspect);
+            if let Some(clip) = clip {
+                model.clip = Some(model.clip.map_or(clip, |own| own.intersect(&clip)));
+            }
+            models.push(model);
+            if let Some(mut model) = component.build_text_model(game, aspect) {
+                if let Some(clip) = clip {
+                    model.clip = Some(model.clip.map_or(clip, |own| own.intersect(&clip)));
+                }
+                models.push(model);
+            }
+            // the text the component queued is drawn right above its models
             component.inner.inner.read().unwrap().do_render(game);
+            if let Some(mut model) = game.renderer.take_text_model() {
+                model.clip = clip;
+                models.push(model);
+            }
         }
         models
     }
 
-    pub fn on_mouse_click(&self, game: &Arc<Game>, pos: (f64, f64), click_kind: ClickKind) {
+    // hit testing happens front to back, so components on higher layers get the click first
+    // returns whether any component was hit
+    pub fn on_mouse_click(&self, game: &Arc<Game>, pos: (f64, f64), click_kind: ClickKind) -> bool {
+        let hit = self.dispatch_click(game, pos, click_kind);
+        if click_kind == ClickKind::Release && self.is_double_click(pos) {
+            self.dispatch_click(game, pos, ClickKind::Double);
+        }
+        hit
+    }
+
+    fn is_double_click(&self, pos: (f64, f64)) -> bool {
+        let now = Instant::now();
+        let mut last_click = self.last_click.lock().unwrap();
+        if let Some((time, last_pos)) = last_click.take() {
+            let distance = ((pos.0 - last_pos.0).powi(2) + (pos.1 - last_pos.1).powi(2)).sqrt();
+            if now.duration_since(time) <= DOUBLE_CLICK_TIME && distance <= DOUBLE_CLICK_DISTANCE {
+                // don't remember this click, so a triple click doesn't result in 2 double clicks
+                return true;
+            }
+        }
+        *last_click = Some((now, pos));
+        false
+    }
+
+    fn dispatch_click(&self, game: &Arc<Game>, pos: (f64, f64), click_kind: ClickKind) -> bool {
+        let aspect = aspect_ratio(game);
         let mut found = false;
-        for component in self.components.read().unwrap().iter() {
-            if !found && component.is_inbounds((pos.0 as f32, pos.1 as f32)) { // FIXME: switch to using f64 instead!
-                component.on_click(game, click_kind, (pos.0 as f32, pos.1 as f32));
+        for component in self.components.read().unwrap().iter().rev() {
+            if !found && component.is_inbounds((pos.0 as f32, pos.1 as f32), aspect) { // FIXME: switch to using f64 instead!
+                component.on_click(game, click_kind, component.local_pos((pos.0 as f32, pos.1 as f32), aspect));
                 found = true;
             } else {
                 component.on_click_outside(game);
             }
         }
+        found
     }
 
-    pub fn on_mouse_hover(&self, game: &Arc<Game>, pos: (f64, f64)) {
-        let mut found = false;
-        for component in self.components.read().unwrap().iter() {
-            if !found && component.is_inbounds((pos.0 as f32, pos.1 as f32)) { // FIXME: switch to using f64 instead!
-                component.on_hover(game, HoverMode::Enter, (pos.0 as f32, pos.1 as f32));
-                found = true;
+    // the focused component gets the keys first, then they're offered to the other components front to back
+    pub fn on_key(&self, game: &Arc<Game>, key: VirtualKeyCode, down: bool, modifiers: ModifiersState) -> bool {
+        if key == FOCUS_KEY {
+            if down {
+                return self.cycle_focus(game, modifiers.shift());
+            }
+            // the release belongs to the press which moved the focus
+            return self.components.read().unwrap().iter().any(|component| component.is_focusable());
+        }
+        let components = self.components.read().unwrap();
+        if let Some(focused) = components.iter().find(|component| component.is_focused()) {
+            if focused.on_key(game, key, down, modifiers) {
+                return true;
+            }
+        }
+        components.iter().rev().any(|component| !component.is_focused() && component.on_key(game, key, down, modifiers))
+    }
+
+    // moves the focus to the next focusable component in insertion order, or to the previous one if `backwards`
+    // is set (shift is held), wrapping around at the ends. returns whether there was any component to focus.
+    pub fn cycle_focus(&self, game: &Arc<Game>, backwards: bool) -> bool {
+        let components = self.components.read().unwrap();
+        let focusable = components.iter().filter(|component| component.is_focusable()).collect::<Vec<_>>();
+        if focusable.is_empty() {
+            return false;
+        }
+        let len = focusable.len();
+        let next = match focusable.iter().position(|component| component.is_focused()) {
+            Some(idx) if backwards => (idx + len - 1) % len,
+            Some(idx) => (idx + 1) % len,
+            None if backwards => len - 1,
+            None => 0,
+        };
+        for (idx, component) in focusable.iter().enumerate() {
+            if idx != next && component.is_focused() {
+                component.set_focused(game, false);
+            }
+        }
+        focusable[next].set_focused(game, true);
+        true
+    }
+
+    // focuses the component, e.g. the first input box of a screen once it opened
+    pub fn focus(&self, game: &Arc<Game>, component: &Arc<RwLock<Box<dyn Component>>>) {
+        for other in self.components.read().unwrap().iter() {
+            let focused = Arc::ptr_eq(&other.inner.inner, component);
+            if focused != other.is_focused() {
+                other.set_focused(game, focused);
+            }
+        }
+    }
+
+    pub fn on_char(&self, game: &Arc<Game>, received: char) -> bool {
+        self.components.read().unwrap().iter().rev().any(|component| component.on_char(game, received))
+    }
+
+    // returns the cursor of the hovered component
+    pub fn on_mouse_hover(&self, game: &Arc<Game>, pos: (f64, f64)) -> CursorIcon {
+        let aspect = aspect_ratio(game);
+        let mut cursor = None;
+        for component in self.components.read().unwrap().iter().rev() {
+            let local_pos = component.local_pos((pos.0 as f32, pos.1 as f32), aspect);
+            if cursor.is_none() && component.is_inbounds((pos.0 as f32, pos.1 as f32), aspect) { // FIXME: switch to using f64 instead!
+                component.on_hover(game, HoverMode::Enter, local_pos);
+                cursor = Some(component.cursor());
             } else if component.is_hovered() == Some(HoverMode::Enter) {
-                component.on_hover(game, HoverMode::Exit, (pos.0 as f32, pos.1 as f32));
+                component.on_hover(game, HoverMode::Exit, local_pos);
             }
         }
+        cursor.unwrap_or(CursorIcon::Default)
     }
 }
 
@@ -255,20 +592,49 @@ pub struct Button<'a, T = ()> {
     pub on_click: Arc<Box<dyn Fn(&mut Button<'a, T>, &Arc<Game>) + Send + Sync>>,
     hovered: bool,
     pressed: bool,
+    repeat: Option<Repeat>,
+    pressed_since: Instant,
+    last_repeat: Instant,
+}
+
+// buttons with a repeat fire once when they get pressed down and then keep on firing
+// every `interval` after being held for `delay`.
+#[derive(Copy, Clone)]
+pub struct Repeat {
+    pub delay: Duration,
+    pub interval: Duration,
 }
 
 impl<'a, T> Button<'a, T> {
 
     pub fn new(inner_box: TextBox<'a>, on_click: Arc<Box<dyn Fn(&mut Button<'a, T>, &Arc<Game>) + Send + Sync>>, data: Option<T>) -> Self {
+        let now = Instant::now();
         Self {
             inner_box,
             data,
             on_click,
             hovered: false,
             pressed: false,
+            repeat: None,
+            pressed_since: now,
+            last_repeat: now,
         }
     }
 
+    pub fn with_repeat(mut self, delay: Duration, interval: Duration) -> Self {
+        self.repeat = Some(Repeat {
+            delay,
+            interval,
+        });
+        self
+    }
+
+    fn fire(&mut self, game: &Arc<Game>) {
+        game.audio.play(SoundId::ButtonClick);
+        let func = self.on_click.clone();
+        func(self, game);
+    }
+
 }
 
 impl<T: Send + Sync> Component for Button<'_, T> {
@@ -299,6 +665,7 @@ impl<T: Send + Sync> Component for Button<'_, T> {
         Model {
             vertices,
             color_src: base_model.color_src,
+            clip: base_model.clip,
         }
     }
 
@@ -306,6 +673,10 @@ impl<T: Send + Sync> Component for Button<'_, T> {
         self.inner_box.do_render(game)
     }
 
+    fn build_text_model(&self, game: &Arc<Game>) -> Option<Model> {
+        self.inner_box.build_text_model(game)
+    }
+
     fn pos(&self) -> (f32, f32) {
         self.inner_box.pos()
     }
@@ -319,16 +690,25 @@ impl<T: Send + Sync> Component for Button<'_, T> {
             self.pressed = false;
             // only perform the actual click if we are inbounds in case the user changes their mind and doesn't
             // want to click anymore.
-            if is_inbounds(self.dims(), self.pos(), pos) {
-                let func = self.on_click.clone();
-                func(self, game);
+            // repeating buttons already fired when they got pressed down.
+            if self.repeat.is_none() && is_inbounds(self.dims(), self.pos(), pos) {
+                self.fire(game);
             }
-        } else {
+        } else if click_kind == ClickKind::PressDown {
             self.pressed = true;
+            if self.repeat.is_some() {
+                let now = Instant::now();
+                self.pressed_since = now;
+                self.last_repeat = now;
+                self.fire(game);
+            }
         }
     }
 
-    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {
+        // the mouse was either pressed or released somewhere else, so we can't be held down anymore
+        self.pressed = false;
+    }
 
     fn on_scroll(&mut self, _game: &Arc<Game>) {}
 
@@ -343,13 +723,35 @@ impl<T: Send + Sync> Component for Button<'_, T> {
             Some(HoverMode::Exit)
         }
     }
+
+    fn cursor(&self) -> CursorIcon {
+        CursorIcon::Hand
+    }
+
+    fn transform(&self) -> Transform {
+        self.inner_box.transform
+    }
+
+    fn tick(&mut self, game: &Arc<Game>) -> bool {
+        if let Some(repeat) = self.repeat {
+            // only repeat as long as the cursor stays on the button
+            if self.pressed && self.hovered {
+                let now = Instant::now();
+                if now.duration_since(self.pressed_since) >= repeat.delay && now.duration_since(self.last_repeat) >= repeat.interval {
+                    self.last_repeat = now;
+                    self.fire(game);
+                }
+            }
+        }
+        false
+    }
 }
 
 pub struct ColorBox {
     pub pos: (f32, f32),
     pub width: f32,
     pub height: f32,
-    pub coloring: Coloring<6>,
+    pub coloring: Coloring<4>,
 }
 
 impl Component for ColorBox {
@@ -362,16 +764,11 @@ impl Component for ColorBox {
                 2.0 * self.width - 1.0 + x_off,
                 2.0 * self.height - 1.0 + y_off,
             ], // top right
-            [-1.0 + x_off, -1.0 + y_off], // bottom left
             [-1.0 + x_off, 2.0 * self.height - 1.0 + y_off], // top left
-            [
-                2.0 * self.width - 1.0 + x_off,
-                2.0 * self.height - 1.0 + y_off,
-            ], // top right
         ];
         let vertices = match &self.coloring {
             Coloring::Color(colors) => {
-                let mut ret = Vec::with_capacity(6);
+                let mut ret = Vec::with_capacity(4);
                 for (i, pos) in vertices.into_iter().enumerate() {
                     ret.push(Vertex::Color {
                         pos,
@@ -381,13 +778,13 @@ impl Component for ColorBox {
                 ret
             }
             Coloring::Tex(tex) => {
-                let mut ret = Vec::with_capacity(6);
+                let mut ret = Vec::with_capacity(4);
                 for (idx, pos) in vertices.into_iter().enumerate() {
                     ret.push(Vertex::Texture {
                         pos,
                         alpha: 1.0, // FIXME: make this actually parameterized!
                         uv: match &tex.ty {
-                            TexTy::Atlas(atlas) => UvKind::Absolute(atlas.uv().into_tuple()),
+                            TexTy::Atlas(atlas) => UvKind::Absolute(atlas.uv_at(COLOR_UV_OFFSETS[idx])),
                             TexTy::Simple(_) => UvKind::Relative(COLOR_UV_OFFSETS[idx]),
                         },
                         color_scale_factor: 1.0,
@@ -406,6 +803,245 @@ impl Component for ColorBox {
                     TexTy::Simple(tex) => ColorSource::Tex(tex.clone()),
                 },
             },
+            clip: None,
+        }
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        self.pos
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn on_click(&mut self, _game: &Arc<Game>, _click_kind: ClickKind, _pos: (f32, f32)) {}
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {}
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+}
+
+// a horizontal slider for values in [0, 1], the value can be changed by clicking or dragging
+pub struct Slider {
+    pub pos: (f32, f32),
+    pub width: f32,
+    pub height: f32,
+    pub track_color: Color,
+    pub fill_color: Color,
+    value: f32,
+    dragging: bool,
+    hovered: bool,
+    on_change: Arc<Box<dyn Fn(f32, &Arc<Game>) + Send + Sync>>,
+}
+
+const SLIDER_KNOB_WIDTH: f32 = 0.01;
+
+impl Slider {
+
+    pub fn new(pos: (f32, f32), width: f32, height: f32, value: f32, on_change: Arc<Box<dyn Fn(f32, &Arc<Game>) + Send + Sync>>) -> Self {
+        Self {
+            pos,
+            width,
+            height,
+            track_color: DARK_GRAY_UI,
+            fill_color: LIGHT_GRAY_UI,
+            value: value.clamp(0.0, 1.0),
+            dragging: false,
+            hovered: false,
+            on_change,
+        }
+    }
+
+    #[inline]
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    fn update_value(&mut self, game: &Arc<Game>, x: f32) {
+        let value = ((x - self.pos.0) / self.width).clamp(0.0, 1.0);
+        if value != self.value {
+            self.value = value;
+            let func = self.on_change.clone();
+            func(value, game);
+        }
+    }
+
+}
+
+impl Component for Slider {
+    fn build_model(&self) -> Model {
+        let track = ColorBox {
+            pos: self.pos,
+            width: self.width,
+            height: self.height,
+            coloring: Coloring::Color([self.track_color; 4]),
+        };
+        let fill = ColorBox {
+            pos: self.pos,
+            width: self.width * self.value,
+            height: self.height,
+            coloring: Coloring::Color([self.fill_color; 4]),
+        };
+        let knob_color = if self.hovered || self.dragging {
+            self.fill_color
+        } else {
+            self.fill_color.scale(0.8)
+        };
+        let knob = ColorBox {
+            pos: (self.pos.0 + self.width * self.value - SLIDER_KNOB_WIDTH / 2.0, self.pos.1),
+            width: SLIDER_KNOB_WIDTH,
+            height: self.height,
+            coloring: Coloring::Color([knob_color; 4]),
+        };
+        let mut vertices = track.build_model().vertices;
+        vertices.extend(fill.build_model().vertices);
+        vertices.extend(knob.build_model().vertices);
+        Model {
+            vertices,
+            color_src: ColorSource::PerVert,
+            clip: None,
+        }
+    }
+
+    fn pos(&self) -> (f32, f32) {
+        self.pos
+    }
+
+    fn dims(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn on_click(&mut self, game: &Arc<Game>, click_kind: ClickKind, pos: (f32, f32)) {
+        match click_kind {
+            ClickKind::PressDown => {
+                self.dragging = true;
+                self.update_value(game, pos.0);
+            }
+            ClickKind::Release => self.dragging = false,
+            ClickKind::Double => {}
+        }
+    }
+
+    fn on_click_outside(&mut self, _game: &Arc<Game>) {
+        self.dragging = false;
+    }
+
+    fn on_scroll(&mut self, _game: &Arc<Game>) {}
+
+    fn on_hover(&mut self, game: &Arc<Game>, mode: HoverMode, pos: (f32, f32)) {
+        self.hovered = mode == HoverMode::Enter;
+        // follow the cursor while dragging
+        if self.dragging {
+            self.update_value(game, pos.0);
+        }
+    }
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        if self.hovered {
+            Some(HoverMode::Enter)
+        } else {
+            Some(HoverMode::Exit)
+        }
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        if self.dragging {
+            CursorIcon::Grabbing
+        } else {
+            CursorIcon::Hand
+        }
+    }
+}
+
+// A panel background that stretches a (small) bordered texture to arbitrary sizes by splitting
+// it into 9 slices, the corners keep their size, the edges get stretched along one axis
+// and the center gets stretched along both axes.
+pub struct NineSlice {
+    pub pos: (f32, f32),
+    pub width: f32,
+    pub height: f32,
+    pub tex: Arc<TexTriple>,
+    // the size of the border inside the texture in pixels
+    pub tex_border: u32,
+    // the size of the border on screen (relative to the screen's dimensions)
+    pub border: (f32, f32),
+    pub grayscale_conv: bool,
+}
+
+impl NineSlice {
+
+    pub fn new(pos: (f32, f32), width: f32, height: f32, tex: Arc<TexTriple>, tex_border: u32, border: (f32, f32)) -> Self {
+        Self {
+            pos,
+            width,
+            height,
+            tex,
+            tex_border,
+            border,
+            grayscale_conv: false,
+        }
+    }
+
+}
+
+impl Component for NineSlice {
+    fn build_model(&self) -> Model {
+        let tex_size = self.tex.tex.size();
+        // make sure the borders never overlap, even if the panel is smaller than both borders combined
+        let border_x = self.border.0.min(self.width / 2.0);
+        let border_y = self.border.1.min(self.height / 2.0);
+        let uv_border_x = (self.tex_border as f32 / tex_size.width as f32).min(0.5);
+        let uv_border_y = (self.tex_border as f32 / tex_size.height as f32).min(0.5);
+
+        let xs = [
+            self.pos.0,
+            self.pos.0 + border_x,
+            self.pos.0 + self.width - border_x,
+            self.pos.0 + self.width,
+        ];
+        // from bottom to top
+        let ys = [
+            self.pos.1,
+            self.pos.1 + border_y,
+            self.pos.1 + self.height - border_y,
+            self.pos.1 + self.height,
+        ];
+        let us = [0.0, uv_border_x, 1.0 - uv_border_x, 1.0];
+        // the texture's v axis points downwards, so the bottom of the panel maps to v = 1.0
+        let vs = [1.0, 1.0 - uv_border_y, uv_border_y, 0.0];
+
+        let mut vertices = Vec::with_capacity(9 * 4);
+        for row in 0..3 {
+            for column in 0..3 {
+                // the same order as COLOR_UV_OFFSETS
+                let corners = [
+                    (column, row), // bottom left
+                    (column + 1, row), // bottom right
+                    (column + 1, row + 1), // top right
+                    (column, row + 1), // top left
+                ];
+                for (x, y) in corners {
+                    vertices.push(Vertex::Texture {
+                        pos: [2.0 * xs[x] - 1.0, 2.0 * ys[y] - 1.0],
+                        alpha: 1.0,
+                        uv: UvKind::Relative((us[x], vs[y])),
+                        color_scale_factor: 1.0,
+                        grayscale_conv: self.grayscale_conv,
+                    });
+                }
+            }
+        }
+        Model {
+            vertices,
+            color_src: ColorSource::Tex(self.tex.clone()),
+            clip: None,
         }
     }
 
@@ -434,22 +1070,160 @@ pub struct TextBox<'a> {
     pub pos: (f32, f32),
     pub width: f32,
     pub height: f32,
-    pub coloring: Coloring<6>,
+    pub coloring: Coloring<4>,
     pub text: TextSection<'a>,
+    pub overflow: TextOverflow,
+    pub transform: Transform,
+    // static text is rasterized into the atlas once instead of being laid out every frame
+    static_text: bool,
+    rasterized: Mutex<Option<RasterizedText>>,
+}
+
+struct RasterizedText {
+    // what the text was rasterized from, if any of these change it has to be rasterized again
+    texts: Vec<String>,
+    text_scale: f32,
+    max_width: Option<u32>,
+    alloc: Arc<AtlasAlloc>,
+    path: String,
 }
 
+impl Drop for RasterizedText {
+    fn drop(&mut self) {
+        self.alloc.atlas().dealloc(self.path.clone());
+    }
+}
+
+static RASTERIZED_TEXTS: AtomicUsize = AtomicUsize::new(0);
+
+// determines what happens to text that doesn't fit into its box vertically,
+// horizontally the text always gets wrapped or clipped depending on the layout.
+#[derive(Copy, Clone, PartialEq)]
+pub enum TextOverflow {
+    Visible,
+    Clip,
+    Ellipsis,
+}
+
+const ELLIPSIS: &str = "…";
+
 impl<'a> TextBox<'a> {
 
-    pub fn new(pos: (f32, f32), width: f32, height: f32, coloring: Coloring<6>, text: TextSection<'a>) -> Self {
+    pub fn new(pos: (f32, f32), width: f32, height: f32, coloring: Coloring<4>, text: TextSection<'a>) -> Self {
         Self {
             pos,
             width,
             height,
             coloring,
             text,
+            overflow: TextOverflow::Clip,
+            transform: Transform::IDENTITY,
+            static_text: false,
+            rasterized: Mutex::new(None),
         }
     }
 
+    /// Rasterizes the text into the atlas, which is a lot cheaper to draw as long as the
+    /// text doesn't change often (like the captions of buttons). The text gets clipped
+    /// instead of being ellipsized.
+    pub fn with_static_text(mut self) -> Self {
+        self.static_text = true;
+        self
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: TextOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// The height (relative to the screen's height) the text needs when it is laid out
+    /// inside this box without being clipped, this can be used to lay out boxes below this one.
+    pub fn text_height(&self, renderer: &Renderer) -> f32 {
+        let (width, height) = renderer.ui_dimensions();
+        self.measure(renderer, &self.text.texts, self.width * width as f32) / height as f32
+    }
+
+    fn measure(&self, renderer: &Renderer, texts: &[String], bounds_width: f32) -> f32 {
+        let text_scale = renderer.text_scale();
+        let section = Section {
+            screen_position: (0.0, 0.0),
+            bounds: (bounds_width, f32::INFINITY),
+            layout: self.text.layout,
+            text: self.text.text.iter().zip(texts.iter()).map(|(text, content)| {
+                let mut text = text.with_text(content.as_str());
+                text.scale.x *= text_scale;
+                text.scale.y *= text_scale;
+                text
+            }).collect::<Vec<_>>(),
+        };
+        renderer.glyph_bounds(0, section).map_or(0.0, |bounds| bounds.height())
+    }
+
+    // the point the text gets aligned to in pixels, glyphs use a y axis that points downwards.
+    fn text_anchor(&self, (width, height): (u32, u32)) -> (f32, f32) {
+        let (h_align, v_align) = match self.text.layout {
+            Layout::SingleLine { h_align, v_align, .. } => (h_align, v_align),
+            Layout::Wrap { h_align, v_align, .. } => (h_align, v_align),
+        };
+        let x = match h_align {
+            HorizontalAlign::Left => self.pos.0,
+            HorizontalAlign::Center => self.pos.0 + self.width / 2.0,
+            HorizontalAlign::Right => self.pos.0 + self.width,
+        };
+        let top = 1.0 - (self.pos.1 + self.height);
+        let y = match v_align {
+            VerticalAlign::Top => top,
+            VerticalAlign::Center => top + self.height / 2.0,
+            VerticalAlign::Bottom => top + self.height,
+        };
+        (x * width as f32, y * height as f32)
+    }
+
+    // truncates the text so that it fits into the box including a trailing ellipsis,
+    // returns None if the text fits without being truncated.
+    fn ellipsize(&self, renderer: &Renderer, bounds: (f32, f32)) -> Option<Vec<String>> {
+        if self.measure(renderer, &self.text.texts, bounds.0) <= bounds.1 {
+            return None;
+        }
+        let total_chars = self.text.texts.iter().map(|text| text.chars().count()).sum::<usize>();
+        // FIXME: cache the result of this search as long as neither the text nor the dimensions change
+        let mut low = 0;
+        let mut high = total_chars;
+        while low < high {
+            let mid = (low + high + 1) / 2;
+            if self.measure(renderer, &Self::truncate_texts(&self.text.texts, mid), bounds.0) <= bounds.1 {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        Some(Self::truncate_texts(&self.text.texts, low))
+    }
+
+    fn truncate_texts(texts: &[String], chars: usize) -> Vec<String> {
+        let mut remaining = chars;
+        let mut ellipsis_placed = false;
+        texts.iter().map(|text| {
+            if ellipsis_placed {
+                return String::new();
+            }
+            let len = text.chars().count();
+            if len < remaining {
+                remaining -= len;
+                return text.clone();
+            }
+            ellipsis_placed = true;
+            let mut truncated = text.chars().take(remaining).collect::<String>().trim_end().to_string();
+            truncated.push_str(ELLIPSIS);
+            truncated
+        }).collect()
+    }
+
 }
 
 impl Component for TextBox<'_> {
@@ -462,16 +1236,11 @@ impl Component for TextBox<'_> {
                 2.0 * self.width - 1.0 + x_off,
                 2.0 * self.height - 1.0 + y_off,
             ],
-            [-1.0 + x_off, -1.0 + y_off],
             [-1.0 + x_off, 2.0 * self.height - 1.0 + y_off],
-            [
-                2.0 * self.width - 1.0 + x_off,
-                2.0 * self.height - 1.0 + y_off,
-            ],
         ];
         let vertices = match &self.coloring {
             Coloring::Color(colors) => {
-                let mut ret = Vec::with_capacity(6);
+                let mut ret = Vec::with_capacity(4);
                 for (i, pos) in vertices.into_iter().enumerate() {
                     ret.push(Vertex::Color {
                         pos,
@@ -481,13 +1250,13 @@ impl Component for TextBox<'_> {
                 ret
             }
             Coloring::Tex(tex) => {
-                let mut ret = Vec::with_capacity(6);
+                let mut ret = Vec::with_capacity(4);
                 for (idx, pos) in vertices.into_iter().enumerate() {
                     ret.push(Vertex::Texture {
                         pos,
                         alpha: 1.0, // FIXME: make this actually parameterized!
                         uv: match &tex.ty {
-                            TexTy::Atlas(atlas) => UvKind::Absolute(atlas.uv().into_tuple()),
+                            TexTy::Atlas(atlas) => UvKind::Absolute(atlas.uv_at(COLOR_UV_OFFSETS[idx])),
                             TexTy::Simple(_) => UvKind::Relative(COLOR_UV_OFFSETS[idx]),
                         },
                         color_scale_factor: 1.0,
@@ -506,21 +1275,134 @@ impl Component for TextBox<'_> {
                     TexTy::Simple(tex) => ColorSource::Tex(tex.clone()),
                 },
             },
+            clip: None,
         }
     }
 
     fn do_render(&self, game: &Arc<Game>) {
-        let (width, height) = game.renderer.dimensions.get();
+        if self.static_text {
+            return;
+        }
+        let (width, height) = game.renderer.ui_dimensions();
+        let bounds = (self.width * width as f32, self.height * height as f32);
+        let truncated = if self.overflow == TextOverflow::Ellipsis {
+            self.ellipsize(&game.renderer, bounds)
+        } else {
+            None
+        };
+        let texts = truncated.as_ref().unwrap_or(&self.text.texts);
+        // FIXME: glyphs can't be rotated yet, so only the anchor follows the rotation
+        let anchor = self.text_anchor((width, height));
+        let anchor = self.transform.apply(
+            center(self.pos, self.dims()),
+            (anchor.0 / width as f32, 1.0 - anchor.1 / height as f32),
+            width as f32 / height.max(1) as f32,
+        );
+        let scale = self.transform.scale;
+        let text_scale = game.renderer.text_scale();
         game.renderer.queue_glyph(0, Section {
-            screen_position: (self.pos.0 * width as f32/*(self.pos.0 - 1.0) / 2.0*/, /*0.0*/(1.0 - self.pos.1/* - self.height*/) * height as f32/*(self.pos.1 - 1.0) / 2.0*/),
-            bounds: (self.width * width as f32, self.height * height as f32),
+            screen_position: (anchor.0 * width as f32, (1.0 - anchor.1) * height as f32),
+            bounds: if self.overflow == TextOverflow::Visible {
+                (bounds.0 * scale, f32::INFINITY)
+            } else {
+                // glyphs outside of the bounds get clipped
+                (bounds.0 * scale, bounds.1 * scale)
+            },
             layout: self.text.layout,
             text: self.text.text.iter().enumerate().map(|txt| {
-                txt.1.with_text(&*self.text.texts[txt.0])
+                let mut text = txt.1.with_text(&*texts[txt.0]);
+                text.scale.x *= scale * text_scale;
+                text.scale.y *= scale * text_scale;
+                text
             }).collect::<Vec<_>>(),
         });
     }
 
+    fn build_text_model(&self, game: &Arc<Game>) -> Option<Model> {
+        if !self.static_text {
+            return None;
+        }
+        let (width, height) = game.renderer.ui_dimensions();
+        let text_scale = game.renderer.text_scale();
+        let (h_align, v_align, wrap) = match self.text.layout {
+            Layout::SingleLine { h_align, v_align, .. } => (h_align, v_align, false),
+            Layout::Wrap { h_align, v_align, .. } => (h_align, v_align, true),
+        };
+        // wrapped text fills the whole width of the box, so it has to be rasterized again if that changes
+        let max_width = wrap.then(|| (self.width * width as f32) as u32);
+        let mut rasterized = self.rasterized.lock().unwrap();
+        let outdated = rasterized.as_ref().map_or(true, |rasterized| rasterized.texts != self.text.texts
+            || rasterized.text_scale != text_scale || rasterized.max_width != max_width);
+        if outdated {
+            let runs = self.text.text.iter().zip(self.text.texts.iter()).map(|(text, content)| TextRun {
+                text: content.as_str(),
+                scale: text.scale.y * text_scale,
+                font: text.font_id,
+                color: text.extra.color,
+            }).collect::<Vec<_>>();
+            let align = match h_align {
+                HorizontalAlign::Left => fontdue::layout::HorizontalAlign::Left,
+                HorizontalAlign::Center => fontdue::layout::HorizontalAlign::Center,
+                HorizontalAlign::Right => fontdue::layout::HorizontalAlign::Right,
+            };
+            let image = game.renderer.text_rasterizer.rasterize(&runs, max_width.map(|max_width| (max_width as f32, align)));
+            let path = format!("text/{}", RASTERIZED_TEXTS.fetch_add(1, Ordering::Relaxed));
+            let alloc = game.atlas.alloc(path.clone(), image.dimensions(), image.as_raw());
+            // replacing the old text frees its space in the atlas
+            *rasterized = Some(RasterizedText {
+                texts: self.text.texts.clone(),
+                text_scale,
+                max_width,
+                alloc,
+                path,
+            });
+        }
+        let alloc = rasterized.as_ref().unwrap().alloc.clone();
+        let (text_width, text_height) = alloc.size();
+        let (text_width, text_height) = (text_width as f32 / width as f32, text_height as f32 / height as f32);
+        let anchor = self.text_anchor((width, height));
+        let anchor = (anchor.0 / width as f32, 1.0 - anchor.1 / height as f32);
+        let x = if wrap {
+            self.pos.0
+        } else {
+            match h_align {
+                HorizontalAlign::Left => anchor.0,
+                HorizontalAlign::Center => anchor.0 - text_width / 2.0,
+                HorizontalAlign::Right => anchor.0 - text_width,
+            }
+        };
+        let y = match v_align {
+            VerticalAlign::Top => anchor.1 - text_height,
+            VerticalAlign::Center => anchor.1 - text_height / 2.0,
+            VerticalAlign::Bottom => anchor.1,
+        };
+        let (min, max) = ((2.0 * x - 1.0, 2.0 * y - 1.0), (2.0 * (x + text_width) - 1.0, 2.0 * (y + text_height) - 1.0));
+        let vertices = [
+            [min.0, min.1],
+            [max.0, min.1],
+            [max.0, max.1],
+            [min.0, max.1],
+        ];
+        Some(Model {
+            vertices: vertices.into_iter().enumerate().map(|(idx, pos)| Vertex::Texture {
+                pos,
+                alpha: 1.0,
+                uv: UvKind::Absolute(alloc.uv_at(COLOR_UV_OFFSETS[idx])),
+                color_scale_factor: 1.0,
+                grayscale_conv: false,
+            }).collect(),
+            color_src: ColorSource::Atlas(alloc.atlas().clone()),
+            clip: if self.overflow == TextOverflow::Visible {
+                None
+            } else {
+                Some(ClipRect {
+                    pos: self.pos,
+                    dims: (self.width, self.height),
+                })
+            },
+        })
+    }
+
     fn pos(&self) -> (f32, f32) {
         self.pos
     }
@@ -542,6 +1424,10 @@ impl Component for TextBox<'_> {
     fn is_hovered(&self) -> Option<HoverMode> {
         None
     }
+
+    fn transform(&self) -> Transform {
+        self.transform
+    }
 }
 
 pub struct TextSection<'a, X = Extra> {
@@ -552,6 +1438,77 @@ pub struct TextSection<'a, X = Extra> {
     pub texts: Vec</*Arc<*/String/*>*/>,
 }
 
+impl<'a> TextSection<'a> {
+
+    pub fn new(layout: Layout<BuiltInLineBreaker>) -> Self {
+        Self {
+            layout,
+            text: vec![],
+            texts: vec![],
+        }
+    }
+
+    /// Appends a run of text with its own style, runs are laid out next to one another,
+    /// so this can be used to emphasize single words inside a paragraph.
+    pub fn push_run(&mut self, text: impl Into<String>, style: TextStyle) {
+        self.text.push(Text::default()
+            .with_scale(style.scale)
+            .with_color(style.color.into_array())
+            .with_font_id(style.font));
+        self.texts.push(text.into());
+    }
+
+    pub fn with_run(mut self, text: impl Into<String>, style: TextStyle) -> Self {
+        self.push_run(text, style);
+        self
+    }
+
+}
+
+// these have to match the order in which the fonts get passed to the glyph brush
+pub const REGULAR_FONT: FontId = FontId(0);
+pub const BOLD_FONT: FontId = FontId(1);
+
+#[derive(Copy, Clone)]
+pub struct TextStyle {
+    pub color: Color,
+    pub scale: f32,
+    pub font: FontId,
+}
+
+impl TextStyle {
+
+    pub const fn new(scale: f32) -> Self {
+        Self {
+            color: Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+            scale,
+            font: REGULAR_FONT,
+        }
+    }
+
+    pub const fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub const fn bold(mut self) -> Self {
+        self.font = BOLD_FONT;
+        self
+    }
+
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self::new(16.0)
+    }
+}
+
 /*
 impl<'a, X> TextSection<'a, X> {
 
@@ -563,43 +1520,65 @@ impl<'a, X> TextSection<'a, X> {
 
 }*/
 
-/*
 pub struct InputBox<'a> {
+    // the last text run of the inner box is used to display the input
     pub inner_box: TextBox<'a>,
+    input: String,
+    max_len: usize,
     active: bool,
 }
 
-impl Component for InputBox<'_> {
-    fn build_model(&self) -> Model {
-        // FIXME: handle inner active!
-        let base_model = self.inner_box.build_model();
-        let mut vertices = base_model.vertices;
-        let color_src = base_model.color_src;
+const CARET: char = '|';
 
-        let tmp = self.inner_box.text
+impl<'a> InputBox<'a> {
 
-        let new_vertices = [
-            [-1.0 + x_off, -1.0 + y_off],
-            [2.0 * self.width - 1.0 + x_off, -1.0 + y_off],
-            [
-                2.0 * self.width - 1.0 + x_off,
-                2.0 * self.height - 1.0 + y_off,
-            ],
-            [-1.0 + x_off, -1.0 + y_off],
-            [-1.0 + x_off, 2.0 * self.height - 1.0 + y_off],
-            [
-                2.0 * self.width - 1.0 + x_off,
-                2.0 * self.height - 1.0 + y_off,
-            ],
-        ];
-        wgpu_glyph::GlyphCruncher::fonts()
+    pub fn new(mut inner_box: TextBox<'a>, max_len: usize) -> Self {
+        if inner_box.text.texts.is_empty() {
+            inner_box.text.push_run(String::new(), TextStyle::default());
+        }
+        let mut ret = Self {
+            inner_box,
+            input: String::new(),
+            max_len,
+            active: false,
+        };
+        ret.update_text();
+        ret
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
 
-        vertices.push();
+    pub fn set_input(&mut self, input: impl Into<String>) {
+        self.input = input.into();
+        self.update_text();
+    }
 
-        Model {
-            vertices,
-            color_src,
-        }
+    #[inline]
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.active = active;
+        self.update_text();
+    }
+
+    fn update_text(&mut self) {
+        let text = if self.active {
+            format!("{}{}", self.input, CARET)
+        } else {
+            self.input.clone()
+        };
+        *self.inner_box.text.texts.last_mut().unwrap() = text;
+    }
+
+}
+
+impl Component for InputBox<'_> {
+    fn build_model(&self) -> Model {
+        self.inner_box.build_model()
     }
 
     fn do_render(&self, game: &Arc<Game>) {
@@ -614,15 +1593,75 @@ impl Component for InputBox<'_> {
         (self.inner_box.width, self.inner_box.height)
     }
 
-    fn on_click(&mut self, _game: &Arc<Game>, _click_kind: ClickKind) {
-        self.active = true;
+    fn on_click(&mut self, _game: &Arc<Game>, click_kind: ClickKind, _pos: (f32, f32)) {
+        if click_kind == ClickKind::PressDown && !self.active {
+            self.set_active(true);
+        }
     }
 
     fn on_click_outside(&mut self, _game: &Arc<Game>) {
-        self.active = false;
+        if self.active {
+            self.set_active(false);
+        }
     }
 
     fn on_scroll(&mut self, _game: &Arc<Game>) {}
 
-    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode) {}
-}*/
+    fn on_hover(&mut self, _game: &Arc<Game>, _mode: HoverMode, _pos: (f32, f32)) {}
+
+    fn is_hovered(&self) -> Option<HoverMode> {
+        None
+    }
+
+    fn cursor(&self) -> CursorIcon {
+        CursorIcon::Text
+    }
+
+    fn transform(&self) -> Transform {
+        self.inner_box.transform
+    }
+
+    fn on_key(&mut self, _game: &Arc<Game>, key: VirtualKeyCode, down: bool, _modifiers: ModifiersState) -> bool {
+        if !self.active {
+            return false;
+        }
+        if down {
+            match key {
+                VirtualKeyCode::Back => {
+                    self.input.pop();
+                    self.update_text();
+                }
+                VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter | VirtualKeyCode::Escape => {
+                    self.set_active(false);
+                }
+                _ => {}
+            }
+        }
+        // an active input box swallows all keys, so typing doesn't trigger any hotkeys
+        true
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn is_focused(&self) -> bool {
+        self.active
+    }
+
+    fn set_focused(&mut self, _game: &Arc<Game>, focused: bool) {
+        self.set_active(focused);
+    }
+
+    fn on_char(&mut self, _game: &Arc<Game>, received: char) -> bool {
+        if !self.active {
+            return false;
+        }
+        // control characters (backspace, enter, etc.) are handled in on_key
+        if !received.is_control() && self.input.chars().count() < self.max_len {
+            self.input.push(received);
+            self.update_text();
+        }
+        true
+    }
+}
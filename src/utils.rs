@@ -1,5 +1,39 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
 use crate::ui;
 
+const DEFAULT_CONFIG_DIR: &str = "./config/";
+
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+// has to be called before any config file is accessed, otherwise the default directory stays in use
+pub fn set_config_dir(dir: PathBuf) {
+    let _ = CONFIG_DIR.set(dir);
+}
+
+pub fn config_dir() -> &'static PathBuf {
+    CONFIG_DIR.get_or_init(|| PathBuf::from(DEFAULT_CONFIG_DIR))
+}
+
+pub fn config_path(file: &str) -> PathBuf {
+    config_dir().join(file)
+}
+
+// the contents of the config file, if there is one
+pub fn read_config(file: &str) -> Option<String> {
+    fs::read_to_string(config_path(file)).ok()
+}
+
+// creates the directories the file is in, if they don't exist yet
+pub fn write_config(file: &str, contents: &str) {
+    let path = config_path(file);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).unwrap();
+    }
+    fs::write(path, contents).unwrap();
+}
+
 pub const LIGHT_GRAY_GPU: wgpu::Color = wgpu::Color {
     r: 0.384,
     g: 0.396,
@@ -12,4 +46,18 @@ pub const DARK_GRAY_UI: ui::Color = ui::Color {
     g: 0.239,
     b: 0.278,
     a: 1.0,
-};
\ No newline at end of file
+};
+
+pub const LIGHT_GRAY_UI: ui::Color = ui::Color {
+    r: 0.6,
+    g: 0.612,
+    b: 0.635,
+    a: 1.0,
+};
+
+pub const BACKDROP_UI: ui::Color = ui::Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.5,
+};